@@ -4,11 +4,13 @@ use screeps_clockwork::bench_support::{
     astar_multiroom_distance_map, astar_portal_multiroom_distance_map, base_heuristic_with_range,
     bfs_multiroom_distance_map, bfs_portal_multiroom_distance_map,
     closest_portal_heuristic_cached_with_range, dijkstra_multiroom_distance_map,
-    dijkstra_portal_multiroom_distance_map, multiroom_flow_field, multiroom_mono_flow_field,
-    path_to_multiroom_distance_map_origin, path_to_multiroom_flow_field_origin,
-    path_to_multiroom_mono_flow_field_origin, DirectionOrder, MultiroomDistanceMap,
-    MultiroomFlowField, MultiroomMonoFlowField,
+    dijkstra_portal_multiroom_distance_map, hierarchical_multiroom_path, multiroom_flow_field,
+    multiroom_mono_flow_field, path_to_multiroom_distance_map_origin,
+    path_to_multiroom_flow_field_origin, path_to_multiroom_mono_flow_field_origin, DirectionOrder,
+    ExitGraph, IncrementalDijkstra, MultiroomDistanceMap, MultiroomFlowField,
+    MultiroomMonoFlowField,
 };
+use std::hint;
 
 mod fixtures;
 
@@ -64,6 +66,31 @@ fn bench_bfs(c: &mut Criterion) {
     group.finish();
 }
 
+/// The other searches built on the shared open list, on the scenario whose path costs
+/// run far past its bucket window.
+fn bench_high_cost_searches(c: &mut Criterion) {
+    let scenario = fixtures::high_cost_multiroom_scenario();
+    let mut group = c.benchmark_group("distance_map/high_cost");
+
+    group.bench_with_input(
+        BenchmarkId::new("incremental_dijkstra", scenario.name),
+        &scenario,
+        |bench, scenario| {
+            bench.iter(|| hint::black_box(run_incremental_dijkstra(scenario)));
+        },
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("hierarchical", scenario.name),
+        &scenario,
+        |bench, scenario| {
+            bench.iter(|| hint::black_box(run_hierarchical(scenario)));
+        },
+    );
+
+    group.finish();
+}
+
 fn bench_portal_variants(c: &mut Criterion) {
     let scenarios = fixtures::portal_distance_map_scenarios();
     let mut group = c.benchmark_group("distance_map/portal_variants");
@@ -248,6 +275,34 @@ fn run_astar(scenario: &DistanceMapScenario) -> usize {
     result.ops()
 }
 
+fn run_incremental_dijkstra(scenario: &DistanceMapScenario) -> usize {
+    let search = hint::black_box(IncrementalDijkstra::new(
+        vec![hint::black_box(scenario.start)],
+        |room| scenario.cost_matrix(room),
+        scenario.max_rooms,
+        scenario.max_path_cost,
+    ));
+    search.ops()
+}
+
+fn run_hierarchical(scenario: &DistanceMapScenario) -> usize {
+    let targets = scenario.targets();
+    let heuristic = base_heuristic_with_range(&targets);
+    // A fresh exit graph each time, so every run pays for the room searches.
+    let mut exit_graph = ExitGraph::new();
+    let result = hint::black_box(hierarchical_multiroom_path(
+        &mut exit_graph,
+        hint::black_box(scenario.start),
+        scenario.target,
+        scenario.target_range,
+        |room| scenario.cost_matrix(room),
+        scenario.max_rooms,
+        scenario.max_ops,
+        heuristic,
+    ));
+    result.ops()
+}
+
 fn run_bfs(scenario: &DistanceMapScenario) -> usize {
     let targets = scenario.targets();
     let result = black_box(bfs_multiroom_distance_map(
@@ -364,6 +419,7 @@ criterion_group!(
     benches,
     bench_dijkstra_vs_astar,
     bench_bfs,
+    bench_high_cost_searches,
     bench_portal_variants,
     bench_astar_vs_portal_astar,
     bench_flow_fields,
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 
 use screeps::{Position, RoomCoordinate, RoomName, RoomXY};
-use screeps_clockwork::bench_support::{ClockworkCostMatrix, PortalIndex};
+use screeps_clockwork::bench_support::{ClockworkCostMatrix, Goal, PortalIndex};
 
 const ROOM_SIZE: usize = 50;
 const ROOM_AREA: usize = ROOM_SIZE * ROOM_SIZE;
 const PLAIN_COST: u8 = 1;
 const SWAMP_COST: u8 = 5;
+const HIGH_COST: u8 = 254;
 const WALL_COST: u8 = 255;
 
 pub struct DistanceMapScenario {
@@ -35,8 +36,8 @@ pub struct PortalDistanceMapScenario {
 }
 
 impl DistanceMapScenario {
-    pub fn targets(&self) -> Vec<(Position, usize)> {
-        vec![(self.target, self.target_range)]
+    pub fn targets(&self) -> Vec<Goal> {
+        vec![Goal::within(self.target, self.target_range)]
     }
 
     pub fn cost_matrix(&self, room: RoomName) -> Option<ClockworkCostMatrix> {
@@ -48,8 +49,8 @@ impl DistanceMapScenario {
 }
 
 impl PortalDistanceMapScenario {
-    pub fn targets(&self) -> Vec<(Position, usize)> {
-        vec![(self.target, self.target_range)]
+    pub fn targets(&self) -> Vec<Goal> {
+        vec![Goal::within(self.target, self.target_range)]
     }
 
     pub fn cost_matrix(&self, room: RoomName) -> Option<ClockworkCostMatrix> {
@@ -67,6 +68,7 @@ pub fn distance_map_scenarios() -> Vec<DistanceMapScenario> {
         realistic_w1n1_scenario(),
         realistic_w8n8_scenario(),
         private_server_sector_scenario(),
+        high_cost_multiroom_scenario(),
     ]
 }
 
@@ -170,6 +172,26 @@ fn private_server_sector_scenario() -> DistanceMapScenario {
     }
 }
 
+/// Every tile costs 254, so path costs run into the tens of thousands: far more keys
+/// than the open list keeps buckets for. Only the four rooms between the start and
+/// the target are open, so the searches can't spend their rooms elsewhere.
+pub fn high_cost_multiroom_scenario() -> DistanceMapScenario {
+    DistanceMapScenario {
+        name: "high_cost/across_four_rooms",
+        start: position(45, 45, room("W1N1")),
+        target: position(5, 5, room("W2N2")),
+        target_range: 0,
+        max_rooms: 4,
+        max_ops: ROOM_AREA * 4,
+        max_path_cost: usize::MAX,
+        cost_matrices: ["W1N1", "W2N1", "W1N2", "W2N2"]
+            .iter()
+            .map(|name| (room(name), ClockworkCostMatrix::new(Some(HIGH_COST))))
+            .collect(),
+        fallback_cost_matrix: None,
+    }
+}
+
 fn private_server_sector_portal_scenario(
     name: &'static str,
     portal_index: PortalIndex,
@@ -1,6 +1,10 @@
+use crate::algorithms::map::room_route::{restrict_to_rooms, unpack_rooms};
 use crate::algorithms::map::{corresponding_room_edge, next_directions, same_room_neighbor};
+use crate::datatypes::with_configured_landmark_index;
 use crate::datatypes::with_configured_portal_index;
 use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::GoalMask;
+use crate::datatypes::OpenList;
 use crate::datatypes::PortalIndex;
 use crate::datatypes::RoomDataCache;
 use crate::utils::set_panic_hook;
@@ -10,10 +14,14 @@ use screeps::RoomName;
 use std::convert::TryFrom;
 use std::ops::Fn;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen::throw_val;
+use wasm_bindgen::{throw_str, throw_val};
 
+use super::goals::{unpack_goals, Goal, GoalTracker};
 use super::heuristics::base_heuristic_with_range;
 use super::heuristics::closest_portal_heuristic_cached_with_range;
+use super::heuristics::landmark_heuristic_with_range;
+use super::seeds::{unpack_seeds, Seed};
+use super::turn_penalty::turn_penalty_search;
 use super::SearchResult;
 
 #[derive(Copy, Clone)]
@@ -26,116 +34,299 @@ struct State {
     open_direction: Option<Direction>,
     // The index of the position's room in the room data cache.
     room_key: usize,
+    // The label of the start position this path came from.
+    label: Option<u32>,
 }
 
 /// Creates a distance map for the given start positions, using A* to optimize the search and
 /// find the shortest path to the given destinations.
 #[allow(clippy::too_many_arguments)]
 pub fn astar_multiroom_distance_map(
-    start: Vec<Position>,
+    start: Vec<impl Into<Seed>>,
     get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
     max_rooms: usize,
     max_ops: usize,
     max_path_cost: usize,
     heuristic_fn: impl Fn(Position) -> usize,
-    any_of_destinations: Option<Vec<(Position, usize)>>,
-    all_of_destinations: Option<Vec<(Position, usize)>>,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
 ) -> SearchResult {
-    set_panic_hook();
-    // Since we expect the total cost to be limited (path costs above 1500 rarely make sense),
-    // we use a vec indexed by the f_score to store the open states rather than a proper priority queue.
-    let mut open: Vec<Vec<State>> = vec![Default::default()];
-    let mut min_idx = 0;
-    // We use this to limit the search to the given number of tiles.
-    let mut tiles_remaining = max_ops;
-    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
-    let any_of_targets: Option<Vec<(Position, usize)>> = any_of_destinations;
-    let mut all_of_targets = all_of_destinations.clone();
-    let mut found_targets = Vec::new();
-
-    // check if start position matches targets and return early if so
-    for neighbor in start.iter() {
-        if let Some(any_of_targets) = &any_of_targets {
-            if any_of_targets.iter().any(|(target, range)| {
-                target.room_name() == neighbor.room_name()
-                    && target.get_range_to(*neighbor) <= *range as u32
-            }) {
-                found_targets.push(*neighbor);
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - tiles_remaining,
-                );
-            }
-        }
-        if let Some(all_of_targets) = &mut all_of_targets {
-            let mut i = 0;
-            while i < all_of_targets.len() {
-                if all_of_targets[i].0.room_name() == neighbor.room_name()
-                    && all_of_targets[i].0.get_range_to(*neighbor) <= all_of_targets[i].1 as u32
+    weighted_astar_multiroom_distance_map(
+        start,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        1.0,
+        any_of_destinations,
+        all_of_destinations,
+    )
+}
+
+/// Weighted A*: the heuristic is multiplied by `heuristic_weight` when ordering the open
+/// list, which explores fewer tiles at the expense of path quality. With an admissible
+/// heuristic, the found paths cost at most `heuristic_weight` times the optimal cost, so a
+/// weight of `1 + ε` keeps paths within (1 + ε) of optimal. The result reports the bound
+/// actually achieved, which is often tighter than the weight.
+#[allow(clippy::too_many_arguments)]
+pub fn weighted_astar_multiroom_distance_map(
+    start: Vec<impl Into<Seed>>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    heuristic_weight: f64,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
+) -> SearchResult {
+    astar_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        heuristic_weight,
+        None,
+        GoalTracker::new(any_of_destinations, all_of_destinations),
+    )
+}
+
+/// Creates a distance map for the given start positions, stopping at the first tile
+/// in `goal_mask` that the search settles. `heuristic_fn` must not overestimate the
+/// cost to the nearest masked tile; use `|_| 0` if there's no better bound.
+#[allow(clippy::too_many_arguments)]
+pub fn astar_multiroom_distance_map_to_goal_mask(
+    start: Vec<impl Into<Seed>>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    goal_mask: GoalMask,
+) -> SearchResult {
+    astar_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        1.0,
+        None,
+        GoalTracker::with_mask(goal_mask),
+    )
+}
+
+/// Runs an A* search to completion. See `AstarSearch`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn astar_search<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    start: Vec<impl Into<Seed>>,
+    cached_room_data: RoomDataCache<F>,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    heuristic_weight: f64,
+    portal_index: Option<&PortalIndex>,
+    goals: GoalTracker,
+) -> SearchResult {
+    AstarSearch::new(
+        start,
+        cached_room_data,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        heuristic_weight,
+        portal_index,
+        goals,
+    )
+    .run()
+}
+
+/// The A* search behind the distance-map searches, the flee searches and the resumable
+/// search handle. It keeps its open list, loaded rooms and remaining goals between
+/// calls to `step`, so it can be run to completion or a few tiles at a time.
+///
+/// With `portal_index`, portal entrances are treated like exit tiles: stepping onto one
+/// lands on the corresponding portal exit. The movement cost is read from the portal
+/// entrance tile.
+pub(crate) struct AstarSearch<'a, F, H>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    H: Fn(Position) -> usize,
+{
+    // Open states are bucketed by f_score, see `OpenList`.
+    open: OpenList<State>,
+    cached_room_data: RoomDataCache<F>,
+    heuristic_fn: H,
+    heuristic_weight: f64,
+    portal_index: Option<&'a PortalIndex>,
+    goals: GoalTracker,
+    max_ops: usize,
+    max_path_cost: usize,
+    // The number of tiles opened so far, including the start positions.
+    ops: usize,
+    finished: bool,
+}
+
+impl<'a, F, H> AstarSearch<'a, F, H>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    H: Fn(Position) -> usize,
+{
+    /// Sets up a search from the given start positions. No tiles are explored until
+    /// `step` or `run` is called, other than checking whether a start position is
+    /// already a target.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        start: Vec<impl Into<Seed>>,
+        cached_room_data: RoomDataCache<F>,
+        max_ops: usize,
+        max_path_cost: usize,
+        heuristic_fn: H,
+        heuristic_weight: f64,
+        portal_index: Option<&'a PortalIndex>,
+        goals: GoalTracker,
+    ) -> Self {
+        set_panic_hook();
+        let mut search = Self {
+            open: OpenList::new(),
+            cached_room_data,
+            heuristic_fn,
+            heuristic_weight,
+            portal_index,
+            goals,
+            max_ops,
+            max_path_cost,
+            ops: 0,
+            finished: false,
+        };
+
+        let start: Vec<Seed> = start.into_iter().map(Into::into).collect();
+
+        // check if start position matches targets and finish early if so
+        if !search.checks_goals_when_settled() {
+            for seed in start.iter() {
+                if search
+                    .goals
+                    .reach(seed.position, seed.initial_cost, seed.label)
                 {
-                    found_targets.push(*neighbor);
-                    all_of_targets.remove(i);
-                } else {
-                    i += 1;
+                    search.finished = true;
+                    return search;
                 }
             }
-            if all_of_targets.is_empty() {
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - tiles_remaining,
+        }
+
+        // Initialize with start positions, each in the bucket for its head start
+        for seed in start {
+            let room_key = search
+                .cached_room_data
+                .get_room_key(seed.position.room_name());
+            if let Some(room_key) = room_key {
+                let distance =
+                    &mut search.cached_room_data[room_key].distance_map[seed.position.xy()];
+                if *distance <= seed.initial_cost {
+                    continue;
+                }
+                *distance = seed.initial_cost;
+                search.open.push(
+                    seed.initial_cost,
+                    State {
+                        g_score: seed.initial_cost,
+                        position: seed.position,
+                        open_direction: None,
+                        room_key,
+                        label: seed.label,
+                    },
                 );
+                search.ops += 1;
             }
         }
+
+        search
+    }
+
+    /// Weighted searches only accept a goal once it's settled: a tile reached early by
+    /// a greedy search may still be reached more cheaply later, and accepting it on
+    /// sight would break the weight's bound. Plain searches keep accepting goals as
+    /// soon as they're reached, which saves expanding the rest of the frontier.
+    fn checks_goals_when_settled(&self) -> bool {
+        self.heuristic_weight > 1.0
+    }
+
+    /// Returns true once the search has found its targets or run out of tiles to explore.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The number of tiles opened so far, across all steps.
+    pub(crate) fn ops(&self) -> usize {
+        self.ops
+    }
+
+    /// Runs the search until it finishes and returns the result.
+    pub(crate) fn run(mut self) -> SearchResult {
+        self.step(usize::MAX);
+        self.into_result()
     }
 
-    // Initialize with start positions
-    for position in start {
-        let room_key = cached_room_data.get_room_key(position.room_name());
-        if let Some(room_key) = room_key {
-            open[0].push(State {
-                g_score: 0,
+    /// Advances the search until roughly `ops` more tiles have been opened. The budget
+    /// is checked before each tile is expanded, so a step may overshoot by up to seven
+    /// tiles. Returns true if the search is finished.
+    pub(crate) fn step(&mut self, ops: usize) -> bool {
+        if self.finished {
+            return true;
+        }
+
+        let ops_limit = self.ops.saturating_add(ops);
+        let settle_goals = self.checks_goals_when_settled();
+
+        // Loop through all open tiles, starting with the lowest f_score.
+        while self.open.peek_key().is_some() {
+            if self.ops >= ops_limit {
+                return false;
+            }
+
+            let Some(State {
+                g_score,
                 position,
-                open_direction: None,
+                open_direction,
                 room_key,
-            });
-            cached_room_data[room_key].distance_map[position.xy()] = 0;
-            tiles_remaining -= 1;
-        }
-    }
+                label,
+            }) = self.open.pop()
+            else {
+                break;
+            };
 
-    // Loop through all open tiles, starting with the lowest f_score.
-    while min_idx < open.len() {
-        while let Some(State {
-            g_score,
-            position,
-            open_direction,
-            room_key,
-        }) = open[min_idx].pop()
-        {
-            if cached_room_data[room_key].distance_map[position.xy()] < g_score {
+            if self.cached_room_data[room_key].distance_map[position.xy()] < g_score {
                 continue;
             }
 
+            if (settle_goals && self.goals.reach(position, g_score, label))
+                || self.goals.settle(position, g_score, label)
+            {
+                self.finished = true;
+                return true;
+            }
+
             // Ignore paths that cost too much.
-            if g_score >= max_path_cost {
+            if g_score >= self.max_path_cost {
                 continue;
             }
 
-            let current_room_name = cached_room_data[room_key].room_name;
+            let current_room_name = self.cached_room_data[room_key].room_name;
 
             // Loop through relevant neighbors (not all directions can improve the path)
-            for neighbor_direction in next_directions(open_direction) {
-                // Charge the tile we step onto in the current room. Room edges are assumed
-                // to have cost 1 and map to passable corresponding edges in the next room.
+            for neighbor_direction in next_directions(position, open_direction) {
+                // Charge the tile we step onto in the current room. Crossing into the next
+                // room also checks the tile we land on there, see `RoomDataCache::crossing_cost`.
                 let step_pos = match same_room_neighbor(position, *neighbor_direction) {
                     Some(pos) => pos,
                     None => continue,
                 };
 
                 let terrain_cost =
-                    if let Some(cost_matrix) = &cached_room_data[room_key].cost_matrix {
+                    if let Some(cost_matrix) = &self.cached_room_data[room_key].cost_matrix {
                         let terrain_cost = cost_matrix.get(step_pos.xy());
                         if terrain_cost == 255 {
                             // impassable terrain
@@ -147,21 +338,33 @@ pub fn astar_multiroom_distance_map(
                         continue;
                     };
 
-                // If step_pos is a room edge, map it to the corresponding tile in
-                // the other room, where the creep would be after moving there.
-                let neighbor = corresponding_room_edge(step_pos);
+                // If step_pos is a portal entrance or a room edge, map it to the tile
+                // where the creep would be after moving there.
+                let portal_exit = self
+                    .portal_index
+                    .and_then(|portal_index| portal_index.exit(step_pos));
+                let neighbor = portal_exit.unwrap_or_else(|| corresponding_room_edge(step_pos));
 
                 // Get the room index for the neighbor, if it's different from the current position.
-                let room_key = if neighbor.room_name() == current_room_name {
-                    room_key
+                let (neighbor_room_key, terrain_cost) = if neighbor.room_name() == current_room_name
+                {
+                    (room_key, terrain_cost)
                 } else {
-                    match cached_room_data.get_room_key(neighbor.room_name()) {
-                        Some(key) => {
-                            if cached_room_data[key].cost_matrix.is_none() {
-                                continue;
-                            }
-                            key
-                        }
+                    let Some(key) = self.cached_room_data.get_room_key(neighbor.room_name()) else {
+                        continue;
+                    };
+                    // Portal exits aren't charged; only edge crossings check the landing tile.
+                    let crossing_cost = match portal_exit {
+                        Some(_) => self.cached_room_data[key]
+                            .cost_matrix
+                            .as_ref()
+                            .map(|_| terrain_cost),
+                        None => self
+                            .cached_room_data
+                            .crossing_cost(terrain_cost, key, neighbor),
+                    };
+                    match crossing_cost {
+                        Some(cost) => (key, cost),
                         None => continue,
                     }
                 };
@@ -171,92 +374,113 @@ pub fn astar_multiroom_distance_map(
 
                 // Skip this neighbor if we've already found a better path to it.
                 let neighbor_xy = neighbor.xy();
-                if cached_room_data[room_key].distance_map[neighbor_xy] <= next_cost {
+                if self.cached_room_data[neighbor_room_key].distance_map[neighbor_xy] <= next_cost {
                     // already visited and better path found
                     continue;
                 }
 
                 // Calculate the heuristic score for the neighbor.
                 // This is the estimated cost to the goal from the neighbor.
-                let h_score = heuristic_fn(neighbor);
+                let h_score =
+                    weighted_heuristic((self.heuristic_fn)(neighbor), self.heuristic_weight);
                 // The f_score is the sum of the cost to reach the neighbor and the heuristic score.
                 let f_score = next_cost.saturating_add(h_score);
 
-                // Ensure the open list has enough buckets to store the new state.
-                if f_score >= open.len() {
-                    open.resize_with(f_score.saturating_add(1), Vec::new);
-                }
-
-                // Add the new state to the open list and update the distance map.
-                open[f_score].push(State {
-                    g_score: next_cost,
-                    position: neighbor,
-                    open_direction: Some(*neighbor_direction),
-                    room_key,
-                });
-                cached_room_data[room_key].distance_map[neighbor_xy] = next_cost;
-                tiles_remaining -= 1;
-
-                // if the f_score is lower than the current min_idx, update min_idx
-                min_idx = min_idx.min(f_score);
+                // Add the new state to the open list and update the distance map. A portal
+                // exit can be left in any direction, so it's expanded like a start.
+                self.open.push(
+                    f_score,
+                    State {
+                        g_score: next_cost,
+                        position: neighbor,
+                        open_direction: match portal_exit {
+                            Some(_) => None,
+                            None => Some(*neighbor_direction),
+                        },
+                        room_key: neighbor_room_key,
+                        label,
+                    },
+                );
+                self.cached_room_data[neighbor_room_key].distance_map[neighbor_xy] = next_cost;
+                self.cached_room_data
+                    .set_parent(room_key, step_pos.xy(), -*neighbor_direction);
+                self.ops += 1;
 
                 // check off targets as they are reached
-                if let Some(all_of_targets) = &mut all_of_targets {
-                    let mut i = 0;
-                    while i < all_of_targets.len() {
-                        if all_of_targets[i].0.room_name() == neighbor.room_name()
-                            && all_of_targets[i].0.get_range_to(neighbor)
-                                <= all_of_targets[i].1 as u32
-                        {
-                            found_targets.push(neighbor);
-                            all_of_targets.remove(i);
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    if all_of_targets.is_empty() {
-                        return SearchResult::new(
-                            cached_room_data.into(),
-                            found_targets,
-                            max_ops - tiles_remaining,
-                        );
-                    }
+                if !settle_goals && self.goals.reach(neighbor, next_cost, label) {
+                    self.finished = true;
+                    return true;
                 }
 
-                if let Some(any_of_targets) = &any_of_targets {
-                    if any_of_targets.iter().any(|(target, range)| {
-                        target.room_name() == neighbor.room_name()
-                            && target.get_range_to(neighbor) <= *range as u32
-                    }) {
-                        found_targets.push(neighbor);
-                        return SearchResult::new(
-                            cached_room_data.into(),
-                            found_targets,
-                            max_ops - tiles_remaining,
-                        );
-                    }
-                }
-
-                // If the goal is reached or the max number of tiles has been processed, return the distance map.
-                if tiles_remaining == 0 {
-                    return SearchResult::new(
-                        cached_room_data.into(),
-                        found_targets,
-                        max_ops - tiles_remaining,
-                    );
+                // If the max number of tiles has been processed, stop here.
+                if self.ops >= self.max_ops {
+                    self.finished = true;
+                    return true;
                 }
             }
         }
-        // Move to the next bucket in the open list.
-        min_idx += 1;
+
+        self.finished = true;
+        true
     }
 
-    // If we've processed all tiles and haven't found the goal, return the distance map.
-    SearchResult::new(
-        cached_room_data.into(),
-        found_targets,
-        max_ops - tiles_remaining,
-    )
+    /// Consumes the search and returns the distance map explored so far. This may be
+    /// called before the search has finished to use a partial result.
+    pub(crate) fn into_result(mut self) -> SearchResult {
+        let suboptimality_bound = self.suboptimality_bound();
+        let parents = self.cached_room_data.take_parents();
+        self.goals
+            .into_result(self.cached_room_data.into(), self.ops)
+            .with_suboptimality_bound(suboptimality_bound)
+            .with_parents(parents)
+    }
+
+    /// Every path that hasn't been fully explored passes through a tile still in the open
+    /// list, so with an admissible heuristic the lowest unweighted f_score there is a lower
+    /// bound on the optimal cost. Comparing the found targets' costs against it gives the
+    /// suboptimality actually achieved. A weighted search that checks goals as they're
+    /// settled keeps this within its weight; a plain search that stops at the first goal
+    /// it reaches usually ends at 1, but not always.
+    ///
+    /// Returns 1 if no target was found.
+    fn suboptimality_bound(&mut self) -> f64 {
+        let cached_room_data = &self.cached_room_data;
+        let found_cost = self
+            .goals
+            .found_targets()
+            .iter()
+            .filter_map(|target| {
+                let room_key = cached_room_data.get_loaded_room_key(target.room_name())?;
+                Some(cached_room_data[room_key].distance_map[target.xy()])
+            })
+            .max();
+        let Some(found_cost) = found_cost else {
+            return 1.0;
+        };
+
+        let lower_bound = if self.heuristic_weight == 1.0 {
+            // Keys are already unweighted f_scores. Stale states only have higher keys
+            // than the tile's current state, so the lowest key is still a lower bound.
+            self.open.peek_key().unwrap_or(usize::MAX)
+        } else {
+            let heuristic_fn = &self.heuristic_fn;
+            self.open
+                .iter()
+                .filter(|state| {
+                    cached_room_data[state.room_key].distance_map[state.position.xy()]
+                        == state.g_score
+                })
+                .map(|state| state.g_score.saturating_add(heuristic_fn(state.position)))
+                .min()
+                .unwrap_or(usize::MAX)
+        };
+
+        if lower_bound >= found_cost {
+            1.0
+        } else {
+            found_cost as f64 / lower_bound.max(1) as f64
+        }
+    }
 }
 
 /// Creates a portal-aware distance map for the given start positions, using A* to optimize
@@ -266,212 +490,103 @@ pub fn astar_multiroom_distance_map(
 /// corresponding portal exit position. The movement cost is read from the portal entrance tile.
 #[allow(clippy::too_many_arguments)]
 pub fn astar_portal_multiroom_distance_map(
-    start: Vec<Position>,
+    start: Vec<impl Into<Seed>>,
     get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
     max_rooms: usize,
     max_ops: usize,
     max_path_cost: usize,
     heuristic_fn: impl Fn(Position) -> usize,
     portal_index: &PortalIndex,
-    any_of_destinations: Option<Vec<(Position, usize)>>,
-    all_of_destinations: Option<Vec<(Position, usize)>>,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
 ) -> SearchResult {
-    set_panic_hook();
-    let mut open: Vec<Vec<State>> = vec![Default::default()];
-    let mut min_idx = 0;
-    let mut tiles_remaining = max_ops;
-    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
-    let any_of_targets: Option<Vec<(Position, usize)>> = any_of_destinations;
-    let mut all_of_targets = all_of_destinations.clone();
-    let mut found_targets = Vec::new();
-
-    for neighbor in start.iter() {
-        if let Some(any_of_targets) = &any_of_targets {
-            if any_of_targets.iter().any(|(target, range)| {
-                target.room_name() == neighbor.room_name()
-                    && target.get_range_to(*neighbor) <= *range as u32
-            }) {
-                found_targets.push(*neighbor);
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - tiles_remaining,
-                );
-            }
-        }
-        if let Some(all_of_targets) = &mut all_of_targets {
-            let mut i = 0;
-            while i < all_of_targets.len() {
-                if all_of_targets[i].0.room_name() == neighbor.room_name()
-                    && all_of_targets[i].0.get_range_to(*neighbor) <= all_of_targets[i].1 as u32
-                {
-                    found_targets.push(*neighbor);
-                    all_of_targets.remove(i);
-                } else {
-                    i += 1;
-                }
-            }
-            if all_of_targets.is_empty() {
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - tiles_remaining,
-                );
-            }
-        }
-    }
-
-    for position in start {
-        let room_key = cached_room_data.get_room_key(position.room_name());
-        if let Some(room_key) = room_key {
-            open[0].push(State {
-                g_score: 0,
-                position,
-                open_direction: None,
-                room_key,
-            });
-            cached_room_data[room_key].distance_map[position.xy()] = 0;
-            tiles_remaining -= 1;
-        }
-    }
-
-    while min_idx < open.len() {
-        while let Some(State {
-            g_score,
-            position,
-            open_direction,
-            room_key,
-        }) = open[min_idx].pop()
-        {
-            if cached_room_data[room_key].distance_map[position.xy()] < g_score {
-                continue;
-            }
-
-            if g_score >= max_path_cost {
-                continue;
-            }
-
-            let current_room_name = cached_room_data[room_key].room_name;
-
-            for neighbor_direction in next_directions(open_direction) {
-                let step_pos = match same_room_neighbor(position, *neighbor_direction) {
-                    Some(pos) => pos,
-                    None => continue,
-                };
-
-                let terrain_cost =
-                    if let Some(cost_matrix) = &cached_room_data[room_key].cost_matrix {
-                        let terrain_cost = cost_matrix.get(step_pos.xy());
-                        if terrain_cost == 255 {
-                            continue;
-                        }
-                        terrain_cost
-                    } else {
-                        continue;
-                    };
-
-                let portal_exit = portal_index.exit(step_pos);
-                let neighbor = portal_exit.unwrap_or_else(|| corresponding_room_edge(step_pos));
-
-                let neighbor_room_key = if neighbor.room_name() == current_room_name {
-                    room_key
-                } else {
-                    match cached_room_data.get_room_key(neighbor.room_name()) {
-                        Some(key) => {
-                            if cached_room_data[key].cost_matrix.is_none() {
-                                continue;
-                            }
-                            key
-                        }
-                        None => continue,
-                    }
-                };
-
-                let next_cost = g_score.saturating_add(terrain_cost as usize);
-
-                let neighbor_xy = neighbor.xy();
-                if cached_room_data[neighbor_room_key].distance_map[neighbor_xy] <= next_cost {
-                    continue;
-                }
-
-                let h_score = heuristic_fn(neighbor);
-                let f_score = next_cost.saturating_add(h_score);
-
-                if f_score >= open.len() {
-                    open.resize_with(f_score.saturating_add(1), Vec::new);
-                }
+    weighted_astar_portal_multiroom_distance_map(
+        start,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        1.0,
+        portal_index,
+        any_of_destinations,
+        all_of_destinations,
+    )
+}
 
-                open[f_score].push(State {
-                    g_score: next_cost,
-                    position: neighbor,
-                    open_direction: if portal_exit.is_some() {
-                        None
-                    } else {
-                        Some(*neighbor_direction)
-                    },
-                    room_key: neighbor_room_key,
-                });
-                cached_room_data[neighbor_room_key].distance_map[neighbor_xy] = next_cost;
-                tiles_remaining -= 1;
-
-                min_idx = min_idx.min(f_score);
-
-                if let Some(all_of_targets) = &mut all_of_targets {
-                    let mut i = 0;
-                    while i < all_of_targets.len() {
-                        if all_of_targets[i].0.room_name() == neighbor.room_name()
-                            && all_of_targets[i].0.get_range_to(neighbor)
-                                <= all_of_targets[i].1 as u32
-                        {
-                            found_targets.push(neighbor);
-                            all_of_targets.remove(i);
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    if all_of_targets.is_empty() {
-                        return SearchResult::new(
-                            cached_room_data.into(),
-                            found_targets,
-                            max_ops - tiles_remaining,
-                        );
-                    }
-                }
+/// Portal-aware weighted A*. See `weighted_astar_multiroom_distance_map`.
+#[allow(clippy::too_many_arguments)]
+pub fn weighted_astar_portal_multiroom_distance_map(
+    start: Vec<impl Into<Seed>>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    heuristic_weight: f64,
+    portal_index: &PortalIndex,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
+) -> SearchResult {
+    astar_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        heuristic_weight,
+        Some(portal_index),
+        GoalTracker::new(any_of_destinations, all_of_destinations),
+    )
+}
 
-                if let Some(any_of_targets) = &any_of_targets {
-                    if any_of_targets.iter().any(|(target, range)| {
-                        target.room_name() == neighbor.room_name()
-                            && target.get_range_to(neighbor) <= *range as u32
-                    }) {
-                        found_targets.push(neighbor);
-                        return SearchResult::new(
-                            cached_room_data.into(),
-                            found_targets,
-                            max_ops - tiles_remaining,
-                        );
-                    }
-                }
+/// Portal-aware variant of `astar_multiroom_distance_map_to_goal_mask`.
+#[allow(clippy::too_many_arguments)]
+pub fn astar_portal_multiroom_distance_map_to_goal_mask(
+    start: Vec<impl Into<Seed>>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: &PortalIndex,
+    goal_mask: GoalMask,
+) -> SearchResult {
+    astar_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        1.0,
+        Some(portal_index),
+        GoalTracker::with_mask(goal_mask),
+    )
+}
 
-                if tiles_remaining == 0 {
-                    return SearchResult::new(
-                        cached_room_data.into(),
-                        found_targets,
-                        max_ops - tiles_remaining,
-                    );
-                }
-            }
-        }
-        min_idx += 1;
+#[inline]
+fn weighted_heuristic(h_score: usize, heuristic_weight: f64) -> usize {
+    if heuristic_weight == 1.0 {
+        h_score
+    } else {
+        (h_score as f64 * heuristic_weight) as usize
     }
+}
 
-    SearchResult::new(
-        cached_room_data.into(),
-        found_targets,
-        max_ops - tiles_remaining,
-    )
+/// Reads the `heuristic_weight` passed to a JS entry point, defaulting to 1 (plain A*).
+/// Weights below 1 or that aren't finite don't bound anything, so they throw.
+fn js_heuristic_weight(heuristic_weight: Option<f64>) -> f64 {
+    let heuristic_weight = heuristic_weight.unwrap_or(1.0);
+    if !heuristic_weight.is_finite() || heuristic_weight < 1.0 {
+        throw_str(&format!(
+            "Invalid heuristic weight: {} (must be a finite number of at least 1)",
+            heuristic_weight
+        ));
+    }
+    heuristic_weight
 }
 
-fn js_cost_matrix(
+pub(crate) fn js_cost_matrix(
     get_cost_matrix: &js_sys::Function,
     room: RoomName,
 ) -> Option<ClockworkCostMatrix> {
@@ -496,7 +611,21 @@ fn js_cost_matrix(
     }
 }
 
+/// `heuristic_weight` defaults to 1 (plain A*). Higher weights behave like PathFinder's
+/// `heuristicWeight`; a weight of `1 + ε` keeps paths within (1 + ε) of optimal.
+/// Weights below 1, NaN and infinity throw.
+/// `allowed_rooms`, if given, keeps the search to those rooms, such as the rooms of a
+/// route from `js_find_room_route`.
+/// If landmarks are configured (see `set_landmarks`), their ALT bound tightens the
+/// range heuristic. `start_costs` and `start_labels`, if given, are per-start head
+/// starts and labels (see `Seed`). `landing_tile_costs` (default true) charges the tile
+/// a room crossing lands on in the next room, see `RoomDataCache::crossing_cost`.
+/// `record_parents` keeps the search's parent tree, see `SearchResult.parents`.
+/// `turn_penalty`, if given, is charged each time a path changes direction, and the
+/// result keeps a `turn_distance_map` instead of a parent tree, so asking for both
+/// throws. The portal variant takes the same trailing options, minus `turn_penalty`.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn js_astar_multiroom_distance_map(
     start_packed: Vec<u32>,
     get_cost_matrix: &js_sys::Function,
@@ -505,49 +634,136 @@ pub fn js_astar_multiroom_distance_map(
     max_path_cost: usize,
     any_of_destinations: Option<Vec<u32>>,
     all_of_destinations: Option<Vec<u32>>,
+    heuristic_weight: Option<f64>,
+    allowed_rooms: Option<Vec<u16>>,
+    start_costs: Option<Vec<u32>>,
+    start_labels: Option<Vec<u32>>,
+    landing_tile_costs: Option<bool>,
+    record_parents: Option<bool>,
+    turn_penalty: Option<usize>,
 ) -> SearchResult {
-    let start_positions = start_packed
-        .iter()
-        .map(|pos| Position::from_packed(*pos))
-        .collect();
+    let heuristic_weight = js_heuristic_weight(heuristic_weight);
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, start_costs, start_labels);
+
+    let any_of_destinations = any_of_destinations.map(unpack_goals);
 
-    let any_of_destinations: Option<Vec<(Position, usize)>> =
-        any_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    let all_of_destinations: Option<Vec<(Position, usize)>> =
-        all_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    let all_destinations: Vec<(Position, usize)> = all_of_destinations
+    let all_of_destinations = all_of_destinations.map(unpack_goals);
+
+    let all_destinations: Vec<Goal> = all_of_destinations
         .clone()
         .unwrap_or_default()
         .into_iter()
         .chain(any_of_destinations.clone().unwrap_or_default())
         .collect();
 
-    let heuristic_fn = base_heuristic_with_range(&all_destinations);
+    with_configured_landmark_index(|landmarks| {
+        let range_fn = base_heuristic_with_range(&all_destinations);
+        let landmark_fn = landmark_heuristic_with_range(&all_destinations, landmarks);
+        let heuristic_fn = |position| range_fn(position).max(landmark_fn(position));
+
+        let cached_room_data = RoomDataCache::new(
+            max_rooms,
+            restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+        )
+        .with_landing_tile_costs(landing_tile_costs.unwrap_or(true));
+        let goals = GoalTracker::new(any_of_destinations, all_of_destinations);
+        if let Some(turn_penalty) = turn_penalty {
+            if record_parents.unwrap_or(false) {
+                throw_str("Turn-penalized searches keep a turn distance map, not parents");
+            }
+            return turn_penalty_search(
+                start_positions,
+                cached_room_data,
+                max_ops,
+                max_path_cost,
+                heuristic_fn,
+                heuristic_weight,
+                turn_penalty,
+                goals,
+            );
+        }
+
+        astar_search(
+            start_positions,
+            cached_room_data.with_parents(record_parents.unwrap_or(false)),
+            max_ops,
+            max_path_cost,
+            heuristic_fn,
+            heuristic_weight,
+            None,
+            goals,
+        )
+    })
+}
 
-    astar_multiroom_distance_map(
+/// Searches to the nearest tile in `goal_mask`. If given, `bound_destinations` are
+/// goals (packed like `any_of_destinations`) that every masked tile satisfies, such as
+/// the range the mask was built around; their range heuristic guides the search.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_astar_multiroom_distance_map_to_goal_mask(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    goal_mask: &GoalMask,
+    bound_destinations: Option<Vec<u32>>,
+    allowed_rooms: Option<Vec<u16>>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, None, None);
+    // With no bound, the range heuristic is 0 everywhere (Dijkstra's algorithm).
+    let bound_destinations = bound_destinations.map(unpack_goals).unwrap_or_default();
+
+    astar_multiroom_distance_map_to_goal_mask(
         start_positions,
-        |room| js_cost_matrix(get_cost_matrix, room),
+        restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
         max_rooms,
         max_ops,
         max_path_cost,
-        heuristic_fn,
-        any_of_destinations,
-        all_of_destinations,
+        base_heuristic_with_range(&bound_destinations),
+        goal_mask.clone(),
     )
 }
 
+/// Portal-aware variant of `js_astar_multiroom_distance_map_to_goal_mask`, using the
+/// configured portals (see `set_portals`).
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_astar_portal_multiroom_distance_map_to_goal_mask(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    goal_mask: &GoalMask,
+    bound_destinations: Option<Vec<u32>>,
+    allowed_rooms: Option<Vec<u16>>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, None, None);
+    let bound_destinations = bound_destinations.map(unpack_goals).unwrap_or_default();
+
+    with_configured_portal_index(|portal_index| {
+        astar_portal_multiroom_distance_map_to_goal_mask(
+            start_positions,
+            restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+            max_rooms,
+            max_ops,
+            max_path_cost,
+            closest_portal_heuristic_cached_with_range(&bound_destinations, portal_index),
+            portal_index,
+            goal_mask.clone(),
+        )
+    })
+}
+
+/// Portal-aware variant of `js_astar_multiroom_distance_map`, using the configured
+/// portals (see `set_portals`). `landing_tile_costs` and `record_parents` work the
+/// same way; steps onto a portal record their parent on the portal tile stepped onto,
+/// see `path_to_parent_tree_origin_with_portals`.
 #[wasm_bindgen]
 #[allow(clippy::too_many_arguments)]
 pub fn js_astar_portal_multiroom_distance_map(
@@ -558,29 +774,22 @@ pub fn js_astar_portal_multiroom_distance_map(
     max_path_cost: usize,
     any_of_destinations: Option<Vec<u32>>,
     all_of_destinations: Option<Vec<u32>>,
+    heuristic_weight: Option<f64>,
+    allowed_rooms: Option<Vec<u16>>,
+    start_costs: Option<Vec<u32>>,
+    start_labels: Option<Vec<u32>>,
+    landing_tile_costs: Option<bool>,
+    record_parents: Option<bool>,
 ) -> SearchResult {
-    let start_positions = start_packed
-        .iter()
-        .map(|pos| Position::from_packed(*pos))
-        .collect();
+    let heuristic_weight = js_heuristic_weight(heuristic_weight);
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, start_costs, start_labels);
 
-    let any_of_destinations: Option<Vec<(Position, usize)>> =
-        any_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    let all_of_destinations: Option<Vec<(Position, usize)>> =
-        all_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    let all_destinations: Vec<(Position, usize)> = all_of_destinations
+    let any_of_destinations = any_of_destinations.map(unpack_goals);
+
+    let all_of_destinations = all_of_destinations.map(unpack_goals);
+
+    let all_destinations: Vec<Goal> = all_of_destinations
         .clone()
         .unwrap_or_default()
         .into_iter()
@@ -588,19 +797,139 @@ pub fn js_astar_portal_multiroom_distance_map(
         .collect();
 
     with_configured_portal_index(|portal_index| {
-        let heuristic_fn =
-            closest_portal_heuristic_cached_with_range(&all_destinations, portal_index);
-
-        astar_portal_multiroom_distance_map(
-            start_positions,
-            |room| js_cost_matrix(get_cost_matrix, room),
-            max_rooms,
-            max_ops,
-            max_path_cost,
-            heuristic_fn,
-            portal_index,
-            any_of_destinations,
-            all_of_destinations,
-        )
+        with_configured_landmark_index(|landmarks| {
+            let portal_fn =
+                closest_portal_heuristic_cached_with_range(&all_destinations, portal_index);
+            let landmark_fn = landmark_heuristic_with_range(&all_destinations, landmarks);
+            // Landmarks measured without portals can overestimate once portals are usable.
+            let use_landmarks = landmarks.uses_portals();
+            let heuristic_fn = |position| {
+                let h_score = portal_fn(position);
+                if use_landmarks {
+                    h_score.max(landmark_fn(position))
+                } else {
+                    h_score
+                }
+            };
+
+            astar_search(
+                start_positions,
+                RoomDataCache::new(
+                    max_rooms,
+                    restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+                )
+                .with_landing_tile_costs(landing_tile_costs.unwrap_or(true))
+                .with_parents(record_parents.unwrap_or(false)),
+                max_ops,
+                max_path_cost,
+                heuristic_fn,
+                heuristic_weight,
+                Some(portal_index),
+                GoalTracker::new(any_of_destinations, all_of_destinations),
+            )
+        })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::test_fixtures::{callback, pos, random_matrix, room};
+
+    use std::collections::HashMap;
+
+    /// Plains with scattered swamps and walls, from a small deterministic generator.
+    fn swampy_matrix(seed: u32) -> ClockworkCostMatrix {
+        random_matrix(seed, 2, |_, roll| match roll % 10 {
+            0 => Some(255),
+            1..=4 => Some(10),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn weighted_search_stays_within_reported_bound() {
+        let start = pos("W1N1", 2, 2);
+        let goal = pos("W1N1", 47, 45);
+        for seed in 0..10 {
+            let mut matrix = swampy_matrix(seed);
+            matrix.set(start.xy(), 2);
+            matrix.set(goal.xy(), 2);
+            let matrices = HashMap::from([(room("W1N1"), matrix)]);
+            let destinations = vec![Goal::within(goal, 0)];
+
+            let optimal = dijkstra_multiroom_distance_map(
+                vec![start],
+                callback(matrices.clone()),
+                usize::MAX,
+                1,
+                usize::MAX,
+                None,
+                None,
+            )
+            .distance_map()
+            .get(goal);
+            if optimal == usize::MAX {
+                continue;
+            }
+
+            // Plains cost 2, so twice the range is still an admissible heuristic.
+            let heuristic = |position: Position| {
+                base_heuristic_with_range(&destinations)(position).saturating_mul(2)
+            };
+            let unweighted = astar_multiroom_distance_map(
+                vec![start],
+                callback(matrices.clone()),
+                1,
+                usize::MAX,
+                usize::MAX,
+                heuristic,
+                Some(destinations.clone()),
+                None,
+            );
+            let weighted = weighted_astar_multiroom_distance_map(
+                vec![start],
+                callback(matrices.clone()),
+                1,
+                usize::MAX,
+                usize::MAX,
+                heuristic,
+                1.5,
+                Some(destinations.clone()),
+                None,
+            );
+
+            // Stopping at the first goal reached usually finds the optimal path, and the
+            // reported bound says how far off it could be when it doesn't.
+            let unweighted_cost = unweighted.distance_map().get(goal);
+            let unweighted_bound = unweighted.suboptimality_bound();
+            assert!(
+                unweighted_cost as f64 <= optimal as f64 * unweighted_bound + 1e-9,
+                "seed {}: cost {}, optimal {}, bound {}",
+                seed,
+                unweighted_cost,
+                optimal,
+                unweighted_bound
+            );
+
+            let weighted_cost = weighted.distance_map().get(goal);
+            let bound = weighted.suboptimality_bound();
+            assert!(
+                (1.0..=1.5).contains(&bound),
+                "seed {}: bound {}",
+                seed,
+                bound
+            );
+            assert!(
+                weighted_cost as f64 <= optimal as f64 * bound + 1e-9,
+                "seed {}: cost {}, optimal {}, bound {}",
+                seed,
+                weighted_cost,
+                optimal,
+                bound
+            );
+            assert!(weighted.ops() <= unweighted.ops());
+        }
+    }
+}
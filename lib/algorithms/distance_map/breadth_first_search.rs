@@ -1,18 +1,21 @@
+use crate::algorithms::map::room_route::{restrict_to_rooms, unpack_rooms};
+use crate::algorithms::map::DirectionOrder;
 use crate::algorithms::map::{corresponding_room_edge, preferred_directions, same_room_neighbor};
-use crate::algorithms::map::{neighbors, DirectionOrder};
 use crate::datatypes::with_configured_portal_index;
 use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::GoalMask;
 use crate::datatypes::PortalIndex;
 use crate::datatypes::RoomDataCache;
 use crate::utils::set_panic_hook;
 use screeps::Position;
 use screeps::RoomName;
-use std::collections::HashSet;
+use std::cmp::Reverse;
 use std::collections::VecDeque;
-use std::convert::TryFrom;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen::throw_val;
 
+use super::astar::js_cost_matrix;
+use super::goals::{unpack_goals, Goal, GoalTracker};
+use super::seeds::{unpack_seeds, Seed};
 use super::SearchResult;
 
 #[derive(Copy, Clone)]
@@ -20,6 +23,7 @@ struct State {
     g_score: usize,
     position: Position,
     room_key: usize,
+    label: Option<u32>,
 }
 
 /// Creates a distance map for the given start positions, using a breadth-first search.
@@ -40,265 +44,127 @@ struct State {
 ///
 /// # Returns
 /// A `MultiroomDistanceMap` containing the distances from the start positions
+#[allow(dead_code)]
 pub fn bfs_multiroom_distance_map(
-    start: Vec<Position>,
+    start: Vec<impl Into<Seed>>,
     get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
     max_ops: usize,
     max_rooms: usize,
     max_path_cost: usize,
-    any_of_destinations: Option<Vec<(Position, usize)>>,
-    all_of_destinations: Option<Vec<(Position, usize)>>,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
 ) -> SearchResult {
-    set_panic_hook();
-    let mut frontier = VecDeque::new();
-    let any_of_destinations =
-        any_of_destinations.map(|d| d.iter().cloned().collect::<HashSet<_>>());
-    let mut all_of_destinations =
-        all_of_destinations.map(|d| d.iter().cloned().collect::<HashSet<_>>());
-    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
-    let mut ops_remaining = max_ops;
-    let mut found_targets = Vec::new();
-
-    // check if start position matches targets and return early if so
-    for neighbor in start.iter() {
-        if let Some(ref any_of_destinations) = any_of_destinations {
-            if any_of_destinations.iter().any(|(target, range)| {
-                target.room_name() == neighbor.room_name()
-                    && target.get_range_to(*neighbor) <= *range as u32
-            }) {
-                found_targets.push(*neighbor);
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - ops_remaining,
-                );
-            }
-        }
-        if let Some(ref mut all_of_destinations) = all_of_destinations {
-            all_of_destinations.retain(|(target, range)| {
-                if target.room_name() == neighbor.room_name()
-                    && target.get_range_to(*neighbor) <= *range as u32
-                {
-                    found_targets.push(*neighbor);
-                    false
-                } else {
-                    true
-                }
-            });
-            if all_of_destinations.is_empty() {
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - ops_remaining,
-                );
-            }
-        }
-    }
-
-    // Initialize with start positions
-    for position in start {
-        let room_key = cached_room_data.get_room_key(position.room_name());
-        if let Some(room_key) = room_key {
-            cached_room_data[room_key].distance_map[position.xy()] = 0;
-            frontier.push_back(State {
-                g_score: 0,
-                position,
-                room_key,
-            });
-        }
-    }
-
-    while let Some(State {
-        g_score,
-        position,
-        room_key,
-    }) = frontier.pop_front()
-    {
-        if ops_remaining == 0 {
-            return SearchResult::new(
-                cached_room_data.into(),
-                found_targets,
-                max_ops - ops_remaining,
-            );
-        }
-        ops_remaining -= 1;
-
-        if g_score >= max_path_cost {
-            continue;
-        }
-
-        for neighbor in neighbors(position, DirectionOrder::CardinalFirst) {
-            let neighbor_room_key = if neighbor.room_name() == position.room_name() {
-                room_key
-            } else {
-                match cached_room_data.get_room_key(neighbor.room_name()) {
-                    Some(key) => key,
-                    None => continue,
-                }
-            };
-
-            // check for obstacle
-            if !cached_room_data[neighbor_room_key]
-                .cost_matrix
-                .as_ref()
-                .map(|matrix| {
-                    let cost = matrix.get(neighbor.xy());
-                    cost < 255
-                })
-                .unwrap_or(false)
-            {
-                continue;
-            };
-
-            let next_cost = g_score.saturating_add(1);
-
-            // Skip this neighbor if we've already found a better path to it.
-            if cached_room_data[neighbor_room_key].distance_map[neighbor.xy()] <= next_cost {
-                // already visited and better path found
-                continue;
-            }
-
-            cached_room_data[neighbor_room_key].distance_map[neighbor.xy()] = next_cost;
-            frontier.push_back(State {
-                g_score: next_cost,
-                position: neighbor,
-                room_key: neighbor_room_key,
-            });
-            if let Some(ref mut all_of_destinations) = all_of_destinations {
-                all_of_destinations.retain(|(target, range)| {
-                    if target.room_name() == neighbor.room_name()
-                        && target.get_range_to(neighbor) <= *range as u32
-                    {
-                        found_targets.push(neighbor);
-                        false
-                    } else {
-                        true
-                    }
-                });
-                if all_of_destinations.is_empty() {
-                    return SearchResult::new(
-                        cached_room_data.into(),
-                        found_targets,
-                        max_ops - ops_remaining,
-                    );
-                }
-            }
-            if let Some(ref any_of_destinations) = any_of_destinations {
-                if any_of_destinations.iter().any(|(target, range)| {
-                    target.room_name() == neighbor.room_name()
-                        && target.get_range_to(neighbor) <= *range as u32
-                }) {
-                    found_targets.push(neighbor);
-                    return SearchResult::new(
-                        cached_room_data.into(),
-                        found_targets,
-                        max_ops - ops_remaining,
-                    );
-                }
-            }
-        }
-    }
+    bfs_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        None,
+        GoalTracker::new(any_of_destinations, all_of_destinations),
+    )
+}
 
-    SearchResult::new(
-        cached_room_data.into(),
-        found_targets,
-        max_ops - ops_remaining,
+/// Creates a distance map for the given start positions with a breadth-first search,
+/// stopping at the first tile in `goal_mask` that the search settles.
+pub fn bfs_multiroom_distance_map_to_goal_mask(
+    start: Vec<impl Into<Seed>>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    goal_mask: GoalMask,
+) -> SearchResult {
+    bfs_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        None,
+        GoalTracker::with_mask(goal_mask),
     )
 }
 
+#[allow(dead_code)]
 #[allow(clippy::too_many_arguments)]
 pub fn bfs_portal_multiroom_distance_map(
-    start: Vec<Position>,
+    start: Vec<impl Into<Seed>>,
     get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
     max_ops: usize,
     max_rooms: usize,
     max_path_cost: usize,
     portal_index: &PortalIndex,
-    any_of_destinations: Option<Vec<(Position, usize)>>,
-    all_of_destinations: Option<Vec<(Position, usize)>>,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
+) -> SearchResult {
+    bfs_search(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        Some(portal_index),
+        GoalTracker::new(any_of_destinations, all_of_destinations),
+    )
+}
+
+/// The breadth-first search behind the BFS distance maps. Steps onto a portal entrance
+/// in `portal_index`, if given, land on its exit, and like room crossings they record
+/// their parent on the tile stepped onto (see `RoomDataCache::set_parent`).
+pub(crate) fn bfs_search<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    start: Vec<impl Into<Seed>>,
+    mut cached_room_data: RoomDataCache<F>,
+    max_ops: usize,
+    max_path_cost: usize,
+    portal_index: Option<&PortalIndex>,
+    mut goals: GoalTracker,
 ) -> SearchResult {
     set_panic_hook();
     let mut frontier = VecDeque::new();
-    let any_of_destinations =
-        any_of_destinations.map(|d| d.iter().cloned().collect::<HashSet<_>>());
-    let mut all_of_destinations =
-        all_of_destinations.map(|d| d.iter().cloned().collect::<HashSet<_>>());
-    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
     let mut ops_remaining = max_ops;
-    let mut found_targets = Vec::new();
-
-    for neighbor in start.iter() {
-        if let Some(ref any_of_destinations) = any_of_destinations {
-            if any_of_destinations.iter().any(|(target, range)| {
-                target.room_name() == neighbor.room_name()
-                    && target.get_range_to(*neighbor) <= *range as u32
-            }) {
-                found_targets.push(*neighbor);
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - ops_remaining,
-                );
-            }
-        }
-        if let Some(ref mut all_of_destinations) = all_of_destinations {
-            all_of_destinations.retain(|(target, range)| {
-                if target.room_name() == neighbor.room_name()
-                    && target.get_range_to(*neighbor) <= *range as u32
-                {
-                    found_targets.push(*neighbor);
-                    false
-                } else {
-                    true
-                }
-            });
-            if all_of_destinations.is_empty() {
-                return SearchResult::new(
-                    cached_room_data.into(),
-                    found_targets,
-                    max_ops - ops_remaining,
-                );
-            }
-        }
-    }
 
-    for position in start {
-        let room_key = cached_room_data.get_room_key(position.room_name());
-        if let Some(room_key) = room_key {
-            cached_room_data[room_key].distance_map[position.xy()] = 0;
-            frontier.push_back(State {
-                g_score: 0,
-                position,
-                room_key,
-            });
+    let mut pending_seeds = pending_seeds(start);
+
+    'search: loop {
+        if release_seeds(
+            &mut pending_seeds,
+            &mut frontier,
+            &mut cached_room_data,
+            &mut goals,
+        ) {
+            break 'search;
         }
-    }
+        let Some(State {
+            g_score,
+            position,
+            room_key,
+            label,
+        }) = frontier.pop_front()
+        else {
+            if pending_seeds.is_empty() {
+                break;
+            }
+            continue;
+        };
 
-    while let Some(State {
-        g_score,
-        position,
-        room_key,
-    }) = frontier.pop_front()
-    {
         if ops_remaining == 0 {
-            return SearchResult::new(
-                cached_room_data.into(),
-                found_targets,
-                max_ops - ops_remaining,
-            );
+            break;
         }
         ops_remaining -= 1;
 
+        if goals.settle(position, g_score, label) {
+            break;
+        }
+
         if g_score >= max_path_cost {
             continue;
         }
 
         for direction in preferred_directions(DirectionOrder::CardinalFirst) {
-            let step_pos = match same_room_neighbor(position, *direction) {
+            let step_pos = match same_room_neighbor(position, direction) {
                 Some(pos) => pos,
                 None => continue,
             };
 
+            // check for obstacle
             if !cached_room_data[room_key]
                 .cost_matrix
                 .as_ref()
@@ -308,77 +174,103 @@ pub fn bfs_portal_multiroom_distance_map(
                 continue;
             }
 
-            let neighbor = portal_index
-                .exit(step_pos)
-                .unwrap_or_else(|| corresponding_room_edge(step_pos));
+            let portal_exit = portal_index.and_then(|portal_index| portal_index.exit(step_pos));
+            let neighbor = portal_exit.unwrap_or_else(|| corresponding_room_edge(step_pos));
 
             let neighbor_room_key = if neighbor.room_name() == position.room_name() {
                 room_key
             } else {
-                match cached_room_data.get_room_key(neighbor.room_name()) {
-                    Some(key) => {
-                        if cached_room_data[key].cost_matrix.is_none() {
-                            continue;
-                        }
-                        key
-                    }
-                    None => continue,
+                let Some(key) = cached_room_data.get_room_key(neighbor.room_name()) else {
+                    continue;
+                };
+                // Every step costs 1, so the landing tile only matters if it's blocked.
+                let passable = match portal_exit {
+                    Some(_) => cached_room_data[key].cost_matrix.is_some(),
+                    None => cached_room_data.crossing_cost(1, key, neighbor).is_some(),
+                };
+                if !passable {
+                    continue;
                 }
+                key
             };
 
             let next_cost = g_score.saturating_add(1);
 
+            // Skip this neighbor if we've already found a better path to it.
             if cached_room_data[neighbor_room_key].distance_map[neighbor.xy()] <= next_cost {
                 continue;
             }
 
             cached_room_data[neighbor_room_key].distance_map[neighbor.xy()] = next_cost;
+            cached_room_data.set_parent(room_key, step_pos.xy(), -direction);
             frontier.push_back(State {
                 g_score: next_cost,
                 position: neighbor,
                 room_key: neighbor_room_key,
+                label,
             });
 
-            if let Some(ref mut all_of_destinations) = all_of_destinations {
-                all_of_destinations.retain(|(target, range)| {
-                    if target.room_name() == neighbor.room_name()
-                        && target.get_range_to(neighbor) <= *range as u32
-                    {
-                        found_targets.push(neighbor);
-                        false
-                    } else {
-                        true
-                    }
-                });
-                if all_of_destinations.is_empty() {
-                    return SearchResult::new(
-                        cached_room_data.into(),
-                        found_targets,
-                        max_ops - ops_remaining,
-                    );
-                }
-            }
-            if let Some(ref any_of_destinations) = any_of_destinations {
-                if any_of_destinations.iter().any(|(target, range)| {
-                    target.room_name() == neighbor.room_name()
-                        && target.get_range_to(neighbor) <= *range as u32
-                }) {
-                    found_targets.push(neighbor);
-                    return SearchResult::new(
-                        cached_room_data.into(),
-                        found_targets,
-                        max_ops - ops_remaining,
-                    );
-                }
+            if goals.reach(neighbor, next_cost, label) {
+                break 'search;
             }
         }
     }
 
-    SearchResult::new(
-        cached_room_data.into(),
-        found_targets,
-        max_ops - ops_remaining,
-    )
+    let parents = cached_room_data.take_parents();
+    goals
+        .into_result(cached_room_data.into(), max_ops - ops_remaining)
+        .with_parents(parents)
+}
+
+/// Start positions not yet on the frontier, with the cheapest last.
+fn pending_seeds(start: Vec<impl Into<Seed>>) -> Vec<Seed> {
+    let mut seeds: Vec<Seed> = start.into_iter().map(Into::into).collect();
+    seeds.sort_by_key(|seed| Reverse(seed.initial_cost));
+    seeds
+}
+
+/// Moves pending start positions onto the frontier once it catches up to their head
+/// start, which keeps the frontier ordered by cost. Returns true if one of them
+/// completes the search's goals.
+fn release_seeds<F>(
+    pending_seeds: &mut Vec<Seed>,
+    frontier: &mut VecDeque<State>,
+    cached_room_data: &mut RoomDataCache<F>,
+    goals: &mut GoalTracker,
+) -> bool
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+{
+    let horizon = match (frontier.front(), pending_seeds.last()) {
+        (Some(state), _) => state.g_score.saturating_add(1),
+        (None, Some(seed)) => seed.initial_cost,
+        (None, None) => return false,
+    };
+    while pending_seeds
+        .last()
+        .is_some_and(|seed| seed.initial_cost <= horizon)
+    {
+        let Some(seed) = pending_seeds.pop() else {
+            break;
+        };
+        let Some(room_key) = cached_room_data.get_room_key(seed.position.room_name()) else {
+            continue;
+        };
+        if cached_room_data[room_key].distance_map[seed.position.xy()] <= seed.initial_cost {
+            continue;
+        }
+        cached_room_data[room_key].distance_map[seed.position.xy()] = seed.initial_cost;
+        frontier.push_back(State {
+            g_score: seed.initial_cost,
+            position: seed.position,
+            room_key,
+            label: seed.label,
+        });
+        if goals.reach(seed.position, seed.initial_cost, seed.label) {
+            return true;
+        }
+    }
+    false
 }
 
 /// WASM wrapper for the BFS multiroom distance map function.
@@ -392,10 +284,15 @@ pub fn bfs_portal_multiroom_distance_map(
 /// * `max_path_cost` - Maximum distance in tiles to explore
 /// * `any_of_destinations` - Array of packed positions to trigger early exit when any are reached
 /// * `all_of_destinations` - Array of packed positions to trigger early exit when all are reached
+/// * `allowed_rooms` - Optional rooms to keep the search to, such as the rooms of a route from `js_find_room_route`
+/// * `start_costs` - Optional head start (initial cost) for each start position
+/// * `start_labels` - Optional label for each start position, reported with found targets
+/// * `record_parents` - Whether to keep the search's parent tree, see `SearchResult.parents`
 ///
 /// # Returns
 /// A `MultiroomDistanceMap` containing the distances from the start positions
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn js_bfs_multiroom_distance_map(
     start_packed: Vec<u32>,
     get_cost_matrix: &js_sys::Function,
@@ -404,60 +301,59 @@ pub fn js_bfs_multiroom_distance_map(
     max_path_cost: usize,
     any_of_destinations: Option<Vec<u32>>,
     all_of_destinations: Option<Vec<u32>>,
+    allowed_rooms: Option<Vec<u16>>,
+    start_costs: Option<Vec<u32>>,
+    start_labels: Option<Vec<u32>>,
+    record_parents: Option<bool>,
 ) -> SearchResult {
-    let start_positions = start_packed
-        .iter()
-        .map(|pos| Position::from_packed(*pos))
-        .collect();
-
-    let any_of_destinations: Option<Vec<(Position, usize)>> =
-        any_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, start_costs, start_labels);
 
-    let all_of_destinations: Option<Vec<(Position, usize)>> =
-        all_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
+    let any_of_destinations = any_of_destinations.map(unpack_goals);
+
+    let all_of_destinations = all_of_destinations.map(unpack_goals);
 
-    bfs_multiroom_distance_map(
+    bfs_search(
         start_positions,
-        |room| {
-            let result = get_cost_matrix.call1(
-                &JsValue::null(),
-                &JsValue::from_f64(room.packed_repr() as f64),
-            );
-
-            let value = match result {
-                Ok(value) => value,
-                Err(e) => throw_val(e),
-            };
+        RoomDataCache::new(
+            max_rooms,
+            restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+        )
+        .with_parents(record_parents.unwrap_or(false)),
+        max_ops,
+        max_path_cost,
+        None,
+        GoalTracker::new(any_of_destinations, all_of_destinations),
+    )
+}
 
-            if value.is_undefined() {
-                None
-            } else {
-                Some(
-                    ClockworkCostMatrix::try_from(value)
-                        .ok()
-                        .expect_throw("Invalid ClockworkCostMatrix"),
-                )
-            }
-        },
+#[wasm_bindgen]
+pub fn js_bfs_multiroom_distance_map_to_goal_mask(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    goal_mask: &GoalMask,
+    allowed_rooms: Option<Vec<u16>>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, None, None);
+
+    bfs_multiroom_distance_map_to_goal_mask(
+        start_positions,
+        restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
         max_ops,
         max_rooms,
         max_path_cost,
-        any_of_destinations,
-        all_of_destinations,
+        goal_mask.clone(),
     )
 }
 
+/// Portal-aware variant of `js_bfs_multiroom_distance_map`, using the configured
+/// portals (see `set_portals`).
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn js_bfs_portal_multiroom_distance_map(
     start_packed: Vec<u32>,
     get_cost_matrix: &js_sys::Function,
@@ -466,58 +362,30 @@ pub fn js_bfs_portal_multiroom_distance_map(
     max_path_cost: usize,
     any_of_destinations: Option<Vec<u32>>,
     all_of_destinations: Option<Vec<u32>>,
+    allowed_rooms: Option<Vec<u16>>,
+    start_costs: Option<Vec<u32>>,
+    start_labels: Option<Vec<u32>>,
+    record_parents: Option<bool>,
 ) -> SearchResult {
-    let start_positions = start_packed
-        .iter()
-        .map(|pos| Position::from_packed(*pos))
-        .collect();
-
-    let any_of_destinations: Option<Vec<(Position, usize)>> =
-        any_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = unpack_seeds(start_packed, start_costs, start_labels);
 
-    let all_of_destinations: Option<Vec<(Position, usize)>> =
-        all_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
+    let any_of_destinations = any_of_destinations.map(unpack_goals);
+
+    let all_of_destinations = all_of_destinations.map(unpack_goals);
 
     with_configured_portal_index(|portal_index| {
-        bfs_portal_multiroom_distance_map(
+        bfs_search(
             start_positions,
-            |room| {
-                let result = get_cost_matrix.call1(
-                    &JsValue::null(),
-                    &JsValue::from_f64(room.packed_repr() as f64),
-                );
-
-                let value = match result {
-                    Ok(value) => value,
-                    Err(e) => throw_val(e),
-                };
-
-                if value.is_undefined() {
-                    None
-                } else {
-                    Some(
-                        ClockworkCostMatrix::try_from(value)
-                            .ok()
-                            .expect_throw("Invalid ClockworkCostMatrix"),
-                    )
-                }
-            },
+            RoomDataCache::new(
+                max_rooms,
+                restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+            )
+            .with_parents(record_parents.unwrap_or(false)),
             max_ops,
-            max_rooms,
             max_path_cost,
-            portal_index,
-            any_of_destinations,
-            all_of_destinations,
+            Some(portal_index),
+            GoalTracker::new(any_of_destinations, all_of_destinations),
         )
     })
 }
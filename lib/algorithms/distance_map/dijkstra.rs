@@ -1,15 +1,22 @@
 use crate::algorithms::distance_map::astar::astar_multiroom_distance_map;
+use crate::algorithms::distance_map::astar::astar_multiroom_distance_map_to_goal_mask;
 use crate::algorithms::distance_map::astar::astar_portal_multiroom_distance_map;
+use crate::algorithms::distance_map::astar::astar_search;
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::distance_map::turn_penalty::turn_penalty_search;
+use crate::algorithms::map::room_route::{restrict_to_rooms, unpack_rooms};
 use crate::datatypes::with_configured_portal_index;
 use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::GoalMask;
 use crate::datatypes::PortalIndex;
+use crate::datatypes::RoomDataCache;
 use crate::utils::set_panic_hook;
 use screeps::Position;
 use screeps::RoomName;
-use std::convert::TryFrom;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen::throw_val;
+use wasm_bindgen::throw_str;
 
+use super::goals::{unpack_goals, Goal, GoalTracker};
 use super::SearchResult;
 
 pub fn dijkstra_multiroom_distance_map(
@@ -18,8 +25,8 @@ pub fn dijkstra_multiroom_distance_map(
     max_ops: usize,
     max_rooms: usize,
     max_path_cost: usize,
-    any_of_destinations: Option<Vec<(Position, usize)>>,
-    all_of_destinations: Option<Vec<(Position, usize)>>,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
 ) -> SearchResult {
     set_panic_hook();
 
@@ -37,6 +44,27 @@ pub fn dijkstra_multiroom_distance_map(
     )
 }
 
+/// Creates a distance map for the given start positions, stopping at the first tile
+/// in `goal_mask` that the search settles.
+pub fn dijkstra_multiroom_distance_map_to_goal_mask(
+    start: Vec<Position>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    goal_mask: GoalMask,
+) -> SearchResult {
+    astar_multiroom_distance_map_to_goal_mask(
+        start,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        |_| 0,
+        goal_mask,
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn dijkstra_portal_multiroom_distance_map(
     start: Vec<Position>,
@@ -45,8 +73,8 @@ pub fn dijkstra_portal_multiroom_distance_map(
     max_rooms: usize,
     max_path_cost: usize,
     portal_index: &PortalIndex,
-    any_of_destinations: Option<Vec<(Position, usize)>>,
-    all_of_destinations: Option<Vec<(Position, usize)>>,
+    any_of_destinations: Option<Vec<Goal>>,
+    all_of_destinations: Option<Vec<Goal>>,
 ) -> SearchResult {
     set_panic_hook();
 
@@ -63,7 +91,16 @@ pub fn dijkstra_portal_multiroom_distance_map(
     )
 }
 
+/// `allowed_rooms`, if given, keeps the search to those rooms, such as the rooms of a
+/// route from `js_find_room_route`.
+/// `landing_tile_costs` (default true) charges the tile a room crossing lands on in the
+/// next room, see `RoomDataCache::crossing_cost`. `record_parents` keeps the search's
+/// parent tree, see `SearchResult.parents`. `turn_penalty`, if given, is charged each
+/// time a path changes direction, and the result keeps a `turn_distance_map` instead
+/// of a parent tree, so asking for both throws. The portal variant takes the same
+/// trailing options, minus `turn_penalty`.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn js_dijkstra_multiroom_distance_map(
     start_packed: Vec<u32>,
     get_cost_matrix: &js_sys::Function,
@@ -72,60 +109,86 @@ pub fn js_dijkstra_multiroom_distance_map(
     max_path_cost: usize,
     any_of_destinations: Option<Vec<u32>>,
     all_of_destinations: Option<Vec<u32>>,
+    allowed_rooms: Option<Vec<u16>>,
+    landing_tile_costs: Option<bool>,
+    record_parents: Option<bool>,
+    turn_penalty: Option<usize>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions: Vec<Position> = start_packed
+        .iter()
+        .map(|pos| Position::from_packed(*pos))
+        .collect();
+
+    let any_of_destinations = any_of_destinations.map(unpack_goals);
+
+    let all_of_destinations = all_of_destinations.map(unpack_goals);
+
+    let cached_room_data = RoomDataCache::new(
+        max_rooms,
+        restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+    )
+    .with_landing_tile_costs(landing_tile_costs.unwrap_or(true));
+    let goals = GoalTracker::new(any_of_destinations, all_of_destinations);
+
+    if let Some(turn_penalty) = turn_penalty {
+        if record_parents.unwrap_or(false) {
+            throw_str("Turn-penalized searches keep a turn distance map, not parents");
+        }
+        turn_penalty_search(
+            start_positions,
+            cached_room_data,
+            max_ops,
+            max_path_cost,
+            |_| 0,
+            1.0,
+            turn_penalty,
+            goals,
+        )
+    } else {
+        astar_search(
+            start_positions,
+            cached_room_data.with_parents(record_parents.unwrap_or(false)),
+            max_ops,
+            max_path_cost,
+            |_| 0,
+            1.0,
+            None,
+            goals,
+        )
+    }
+}
+
+#[wasm_bindgen]
+pub fn js_dijkstra_multiroom_distance_map_to_goal_mask(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    goal_mask: &GoalMask,
+    allowed_rooms: Option<Vec<u16>>,
 ) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
     let start_positions = start_packed
         .iter()
         .map(|pos| Position::from_packed(*pos))
         .collect();
 
-    let any_of_destinations: Option<Vec<(Position, usize)>> =
-        any_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    let all_of_destinations: Option<Vec<(Position, usize)>> =
-        all_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    dijkstra_multiroom_distance_map(
+    dijkstra_multiroom_distance_map_to_goal_mask(
         start_positions,
-        |room| {
-            let result = get_cost_matrix.call1(
-                &JsValue::null(),
-                &JsValue::from_f64(room.packed_repr() as f64),
-            );
-
-            let value = match result {
-                Ok(value) => value,
-                Err(e) => throw_val(e),
-            };
-
-            if value.is_undefined() {
-                None
-            } else {
-                Some(
-                    ClockworkCostMatrix::try_from(value)
-                        .ok()
-                        .expect_throw("Invalid ClockworkCostMatrix"),
-                )
-            }
-        },
+        restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
         max_ops,
         max_rooms,
         max_path_cost,
-        any_of_destinations,
-        all_of_destinations,
+        goal_mask.clone(),
     )
 }
 
+/// `landing_tile_costs` and `record_parents` work as in
+/// `js_dijkstra_multiroom_distance_map`.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn js_dijkstra_portal_multiroom_distance_map(
     start_packed: Vec<u32>,
     get_cost_matrix: &js_sys::Function,
@@ -134,58 +197,35 @@ pub fn js_dijkstra_portal_multiroom_distance_map(
     max_path_cost: usize,
     any_of_destinations: Option<Vec<u32>>,
     all_of_destinations: Option<Vec<u32>>,
+    allowed_rooms: Option<Vec<u16>>,
+    landing_tile_costs: Option<bool>,
+    record_parents: Option<bool>,
 ) -> SearchResult {
-    let start_positions = start_packed
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions: Vec<Position> = start_packed
         .iter()
         .map(|pos| Position::from_packed(*pos))
         .collect();
 
-    let any_of_destinations: Option<Vec<(Position, usize)>> =
-        any_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
-
-    let all_of_destinations: Option<Vec<(Position, usize)>> =
-        all_of_destinations.map(|destinations| {
-            destinations
-                .chunks(2)
-                .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
-                .collect()
-        });
+    let any_of_destinations = any_of_destinations.map(unpack_goals);
+
+    let all_of_destinations = all_of_destinations.map(unpack_goals);
 
     with_configured_portal_index(|portal_index| {
-        dijkstra_portal_multiroom_distance_map(
+        astar_search(
             start_positions,
-            |room| {
-                let result = get_cost_matrix.call1(
-                    &JsValue::null(),
-                    &JsValue::from_f64(room.packed_repr() as f64),
-                );
-
-                let value = match result {
-                    Ok(value) => value,
-                    Err(e) => throw_val(e),
-                };
-
-                if value.is_undefined() {
-                    None
-                } else {
-                    Some(
-                        ClockworkCostMatrix::try_from(value)
-                            .ok()
-                            .expect_throw("Invalid ClockworkCostMatrix"),
-                    )
-                }
-            },
+            RoomDataCache::new(
+                max_rooms,
+                restrict_to_rooms(allowed_rooms, |room| js_cost_matrix(get_cost_matrix, room)),
+            )
+            .with_landing_tile_costs(landing_tile_costs.unwrap_or(true))
+            .with_parents(record_parents.unwrap_or(false)),
             max_ops,
-            max_rooms,
             max_path_cost,
-            portal_index,
-            any_of_destinations,
-            all_of_destinations,
+            |_| 0,
+            1.0,
+            Some(portal_index),
+            GoalTracker::new(any_of_destinations, all_of_destinations),
         )
     })
 }
@@ -254,7 +294,7 @@ mod tests {
             2,
             10_000,
             &portals,
-            Some(vec![(target, 0)]),
+            Some(vec![Goal::within(target, 0)]),
             None,
         );
 
@@ -275,7 +315,7 @@ mod tests {
         let start = pos("W1N1", 9, 10);
         let target = pos("W5N1", 12, 10);
         let portals = portal_index();
-        let goals = vec![(target, 0)];
+        let goals = vec![Goal::within(target, 0)];
 
         let matrices = HashMap::from([
             (room("W1N1"), plain_matrix()),
@@ -383,7 +423,7 @@ mod tests {
             2,
             100,
             &portals,
-            Some(vec![(target, 0)]),
+            Some(vec![Goal::within(target, 0)]),
             None,
         )
         .distance_map();
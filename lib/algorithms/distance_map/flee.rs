@@ -0,0 +1,473 @@
+use crate::algorithms::map::room_route::{restrict_to_rooms, unpack_rooms};
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::PortalIndex;
+use crate::datatypes::RoomDataCache;
+use screeps::Position;
+use screeps::RoomName;
+use wasm_bindgen::prelude::*;
+
+use super::astar::{astar_search, js_cost_matrix};
+use super::goals::GoalTracker;
+use super::heuristics::flee_heuristic;
+use super::heuristics::flee_portal_heuristic_cached;
+use super::SearchResult;
+
+/// Searches away from the given threats, equivalent to PathFinder's `flee: true`.
+///
+/// The search stops at the cheapest tile where every threat is at least its given
+/// range away (measured in global range, so threats in neighboring rooms count).
+/// That tile is reported in `found_targets`, and the distance map is rooted at the
+/// start positions, so the usual flow-field and path helpers can walk back from it.
+#[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
+pub fn astar_flee_multiroom_distance_map(
+    start: Vec<Position>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    flee_from: Vec<(Position, usize)>,
+) -> SearchResult {
+    flee_multiroom_distance_map(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        None,
+        &flee_from,
+    )
+}
+
+/// Portal-aware variant of `astar_flee_multiroom_distance_map`. Stepping onto a portal
+/// entrance settles the corresponding portal exit, as in the other portal searches.
+#[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
+pub fn astar_flee_portal_multiroom_distance_map(
+    start: Vec<Position>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: &PortalIndex,
+    flee_from: Vec<(Position, usize)>,
+) -> SearchResult {
+    flee_multiroom_distance_map(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        Some(portal_index),
+        &flee_from,
+    )
+}
+
+/// Flee search with a heuristic of 0.
+#[allow(dead_code)]
+pub fn dijkstra_flee_multiroom_distance_map(
+    start: Vec<Position>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    flee_from: Vec<(Position, usize)>,
+) -> SearchResult {
+    flee_multiroom_distance_map(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        |_| 0,
+        None,
+        &flee_from,
+    )
+}
+
+/// Portal-aware flee search with a heuristic of 0.
+#[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
+pub fn dijkstra_flee_portal_multiroom_distance_map(
+    start: Vec<Position>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    portal_index: &PortalIndex,
+    flee_from: Vec<(Position, usize)>,
+) -> SearchResult {
+    flee_multiroom_distance_map(
+        start,
+        RoomDataCache::new(max_rooms, get_cost_matrix),
+        max_ops,
+        max_path_cost,
+        |_| 0,
+        Some(portal_index),
+        &flee_from,
+    )
+}
+
+/// Flee searches run on the shared A* search, with the first settled safe tile as
+/// their goal. Settling rather than reaching means the tile we stop on is the
+/// cheapest safe tile rather than the first one seen.
+#[allow(clippy::too_many_arguments)]
+fn flee_multiroom_distance_map<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    start: Vec<Position>,
+    cached_room_data: RoomDataCache<F>,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: Option<&PortalIndex>,
+    flee_from: &[(Position, usize)],
+) -> SearchResult {
+    astar_search(
+        start,
+        cached_room_data,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        1.0,
+        portal_index,
+        GoalTracker::fleeing(flee_from.to_vec()),
+    )
+}
+
+fn unpack_flee_targets(flee_from: Vec<u32>) -> Vec<(Position, usize)> {
+    flee_from
+        .chunks(2)
+        .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
+        .collect()
+}
+
+/// The room data cache behind the JS flee searches, loading cost matrices through
+/// `get_cost_matrix` for `allowed_rooms` only, if given. `landing_tile_costs`
+/// defaults to true, as in `js_astar_multiroom_distance_map`.
+fn js_room_data_cache(
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    allowed_rooms: Option<Vec<RoomName>>,
+    landing_tile_costs: Option<bool>,
+) -> RoomDataCache<impl Fn(RoomName) -> Option<ClockworkCostMatrix> + '_> {
+    RoomDataCache::new(
+        max_rooms,
+        restrict_to_rooms(allowed_rooms, move |room| {
+            js_cost_matrix(get_cost_matrix, room)
+        }),
+    )
+    .with_landing_tile_costs(landing_tile_costs.unwrap_or(true))
+}
+
+/// WASM wrapper for the A* flee search. `flee_from` is a flat list of packed
+/// position and range pairs. `allowed_rooms`, if given, keeps the search to those
+/// rooms, and `landing_tile_costs` (default true) charges the tile a room crossing
+/// lands on, like `js_astar_multiroom_distance_map`. The other flee entry points take
+/// the same trailing options.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_astar_flee_multiroom_distance_map(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    flee_from: Vec<u32>,
+    allowed_rooms: Option<Vec<u16>>,
+    landing_tile_costs: Option<bool>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = start_packed
+        .iter()
+        .map(|pos| Position::from_packed(*pos))
+        .collect();
+    let flee_from = unpack_flee_targets(flee_from);
+    let heuristic_fn = flee_heuristic(&flee_from);
+
+    flee_multiroom_distance_map(
+        start_positions,
+        js_room_data_cache(
+            get_cost_matrix,
+            max_rooms,
+            allowed_rooms,
+            landing_tile_costs,
+        ),
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        None,
+        &flee_from,
+    )
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_astar_flee_portal_multiroom_distance_map(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    flee_from: Vec<u32>,
+    allowed_rooms: Option<Vec<u16>>,
+    landing_tile_costs: Option<bool>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = start_packed
+        .iter()
+        .map(|pos| Position::from_packed(*pos))
+        .collect();
+    let flee_from = unpack_flee_targets(flee_from);
+
+    with_configured_portal_index(|portal_index| {
+        let heuristic_fn = flee_portal_heuristic_cached(&flee_from, portal_index);
+
+        flee_multiroom_distance_map(
+            start_positions,
+            js_room_data_cache(
+                get_cost_matrix,
+                max_rooms,
+                allowed_rooms,
+                landing_tile_costs,
+            ),
+            max_ops,
+            max_path_cost,
+            heuristic_fn,
+            Some(portal_index),
+            &flee_from,
+        )
+    })
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_dijkstra_flee_multiroom_distance_map(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    flee_from: Vec<u32>,
+    allowed_rooms: Option<Vec<u16>>,
+    landing_tile_costs: Option<bool>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = start_packed
+        .iter()
+        .map(|pos| Position::from_packed(*pos))
+        .collect();
+
+    flee_multiroom_distance_map(
+        start_positions,
+        js_room_data_cache(
+            get_cost_matrix,
+            max_rooms,
+            allowed_rooms,
+            landing_tile_costs,
+        ),
+        max_ops,
+        max_path_cost,
+        |_| 0,
+        None,
+        &unpack_flee_targets(flee_from),
+    )
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_dijkstra_flee_portal_multiroom_distance_map(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    flee_from: Vec<u32>,
+    allowed_rooms: Option<Vec<u16>>,
+    landing_tile_costs: Option<bool>,
+) -> SearchResult {
+    let allowed_rooms = unpack_rooms(allowed_rooms);
+    let start_positions = start_packed
+        .iter()
+        .map(|pos| Position::from_packed(*pos))
+        .collect();
+
+    with_configured_portal_index(|portal_index| {
+        flee_multiroom_distance_map(
+            start_positions,
+            js_room_data_cache(
+                get_cost_matrix,
+                max_rooms,
+                allowed_rooms,
+                landing_tile_costs,
+            ),
+            max_ops,
+            max_path_cost,
+            |_| 0,
+            Some(portal_index),
+            &unpack_flee_targets(flee_from),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::map::DirectionOrder;
+    use crate::algorithms::path::to_multiroom_distance_map_origin::path_to_multiroom_distance_map_origin;
+    use crate::test_fixtures::{callback, pos, room, xy};
+    use std::collections::HashMap;
+
+    #[test]
+    fn flee_stops_at_first_safe_tile() {
+        let start = pos("W1N1", 25, 25);
+        let threats = vec![(start, 3)];
+        let matrices = HashMap::from([(room("W1N1"), ClockworkCostMatrix::new(Some(1)))]);
+
+        let result = astar_flee_multiroom_distance_map(
+            vec![start],
+            callback(matrices),
+            1,
+            10_000,
+            100,
+            flee_heuristic(&threats),
+            threats.clone(),
+        );
+
+        assert_eq!(result.found_targets.len(), 1);
+        let safe = result.found_targets[0];
+        assert_eq!(safe.get_range_to(start), 3);
+
+        let distance_map = result.distance_map();
+        assert_eq!(distance_map.get(safe), 3);
+
+        let path = path_to_multiroom_distance_map_origin(
+            safe,
+            &distance_map,
+            DirectionOrder::CardinalFirst,
+        )
+        .unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path.get(3), Some(&start));
+    }
+
+    #[test]
+    fn flee_prefers_the_cheaper_direction() {
+        let start = pos("W1N1", 25, 25);
+        let threats = vec![(start, 3)];
+        // Everything but the column going straight up is swamp.
+        let mut matrix = ClockworkCostMatrix::new(Some(5));
+        for y in 0..25 {
+            matrix.set(xy(25, y), 1);
+        }
+        let matrices = HashMap::from([(room("W1N1"), matrix)]);
+
+        let astar = astar_flee_multiroom_distance_map(
+            vec![start],
+            callback(matrices.clone()),
+            1,
+            10_000,
+            100,
+            flee_heuristic(&threats),
+            threats.clone(),
+        );
+        let dijkstra = dijkstra_flee_multiroom_distance_map(
+            vec![start],
+            callback(matrices),
+            10_000,
+            1,
+            100,
+            threats,
+        );
+
+        assert_eq!(astar.found_targets, vec![pos("W1N1", 25, 22)]);
+        assert_eq!(astar.distance_map().get(pos("W1N1", 25, 22)), 3);
+        assert_eq!(
+            dijkstra.distance_map().get(dijkstra.found_targets[0]),
+            astar.distance_map().get(astar.found_targets[0])
+        );
+    }
+
+    #[test]
+    fn flee_returns_start_when_already_safe() {
+        let start = pos("W1N1", 25, 25);
+        let threats = vec![(pos("W1N1", 5, 5), 3)];
+        let matrices = HashMap::from([(room("W1N1"), ClockworkCostMatrix::new(Some(1)))]);
+
+        let result = dijkstra_flee_multiroom_distance_map(
+            vec![start],
+            callback(matrices),
+            10_000,
+            1,
+            100,
+            threats,
+        );
+
+        assert_eq!(result.found_targets, vec![start]);
+        assert_eq!(result.distance_map().get(start), 0);
+    }
+
+    #[test]
+    fn flee_through_portal_when_it_is_cheaper() {
+        let start = pos("W1N1", 10, 9);
+        let portal_entry = pos("W1N1", 10, 10);
+        let portal_exit = pos("W5N1", 10, 10);
+        let threats = vec![(start, 10)];
+        let mut portals = PortalIndex::default();
+        portals.add_bidirectional(portal_entry, portal_exit);
+
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W5N1"), ClockworkCostMatrix::new(Some(1))),
+        ]);
+
+        let result = astar_flee_portal_multiroom_distance_map(
+            vec![start],
+            callback(matrices),
+            2,
+            10_000,
+            100,
+            flee_portal_heuristic_cached(&threats, &portals),
+            &portals,
+            threats.clone(),
+        );
+
+        assert_eq!(result.found_targets, vec![portal_exit]);
+        assert_eq!(result.distance_map().get(portal_exit), 1);
+    }
+
+    #[test]
+    fn flee_heuristic_allows_for_room_crossings() {
+        // Stepping onto the edge lands in the next room, two tiles further from the
+        // threat for a single step.
+        let start = pos("W1N1", 1, 25);
+        let threats = vec![(pos("W1N1", 3, 25), 5)];
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W2N1"), ClockworkCostMatrix::new(Some(1))),
+        ]);
+
+        let astar = astar_flee_multiroom_distance_map(
+            vec![start],
+            callback(matrices.clone()),
+            2,
+            10_000,
+            100,
+            flee_heuristic(&threats),
+            threats.clone(),
+        );
+        let dijkstra = dijkstra_flee_multiroom_distance_map(
+            vec![start],
+            callback(matrices),
+            10_000,
+            2,
+            100,
+            threats.clone(),
+        );
+
+        let cost = dijkstra.distance_map().get(dijkstra.found_targets[0]);
+        assert_eq!(dijkstra.found_targets[0].room_name(), room("W2N1"));
+        assert!(flee_heuristic(&threats)(start) <= cost);
+        assert_eq!(astar.distance_map().get(astar.found_targets[0]), cost);
+    }
+}
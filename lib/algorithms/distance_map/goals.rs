@@ -0,0 +1,567 @@
+use crate::datatypes::GoalMask;
+use crate::datatypes::MultiroomDistanceMap;
+use screeps::Position;
+use wasm_bindgen::throw_str;
+use wasm_bindgen::JsValue;
+
+use super::SearchResult;
+
+/// Something a search can head toward: tiles in the same room as `position` whose
+/// range to it is between `min_range` and `max_range`.
+pub trait RangeTarget {
+    fn position(&self) -> Position;
+    fn min_range(&self) -> usize;
+    fn max_range(&self) -> usize;
+
+    fn is_satisfied_by(&self, position: Position) -> bool {
+        let target = self.position();
+        if target.room_name() != position.room_name() {
+            return false;
+        }
+        let range = target.get_range_to(position) as usize;
+        self.min_range() <= range && range <= self.max_range()
+    }
+
+    /// The fewest moves from `position` into the range band, ignoring terrain. Each
+    /// move changes the range by at most one, so this never overestimates.
+    fn moves_to_band(&self, position: Position) -> usize {
+        let range = self.position().get_range_to(position) as usize;
+        if range > self.max_range() {
+            range - self.max_range()
+        } else {
+            self.min_range().saturating_sub(range)
+        }
+    }
+}
+
+/// A plain `(position, range)` destination: anywhere within `range`.
+impl RangeTarget for (Position, usize) {
+    fn position(&self) -> Position {
+        self.0
+    }
+
+    fn min_range(&self) -> usize {
+        0
+    }
+
+    fn max_range(&self) -> usize {
+        self.1
+    }
+}
+
+/// The largest range a packed goal can hold, in either half of its packed range.
+const MAX_PACKED_RANGE: usize = 0xffff;
+
+/// A search destination. Plain "within range" goals have a `min_range` of 0; a
+/// ranged attacker that wants to stay at range 3 but no closer than 2 would use a
+/// band of 2..=3.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Goal {
+    pub position: Position,
+    pub min_range: usize,
+    pub max_range: usize,
+}
+
+impl Goal {
+    pub fn within(position: Position, range: usize) -> Self {
+        Self {
+            position,
+            min_range: 0,
+            max_range: range,
+        }
+    }
+
+    /// A goal for tiles between `min_range` and `max_range` of `position`. Fails if
+    /// `min_range` is above `max_range`, or if either doesn't fit the 16 bits it's
+    /// packed into (see `from_packed`).
+    pub fn band(
+        position: Position,
+        min_range: usize,
+        max_range: usize,
+    ) -> Result<Self, &'static str> {
+        if min_range > max_range {
+            return Err("Goal min range must not be above its max range");
+        }
+        if max_range > MAX_PACKED_RANGE {
+            return Err("Goal ranges must fit in 16 bits");
+        }
+        Ok(Self {
+            position,
+            min_range,
+            max_range,
+        })
+    }
+
+    /// Unpacks a goal passed in from JS as a `[position, range]` pair. The range's low
+    /// 16 bits are the maximum range and the high 16 bits the minimum range, so plain
+    /// ranges still mean "within range".
+    pub fn from_packed(packed_position: u32, packed_range: u32) -> Result<Self, &'static str> {
+        Self::band(
+            Position::from_packed(packed_position),
+            (packed_range >> 16) as usize,
+            (packed_range & 0xffff) as usize,
+        )
+    }
+}
+
+impl From<(Position, usize)> for Goal {
+    fn from((position, range): (Position, usize)) -> Self {
+        Self::within(position, range)
+    }
+}
+
+impl RangeTarget for Goal {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn min_range(&self) -> usize {
+        self.min_range
+    }
+
+    fn max_range(&self) -> usize {
+        self.max_range
+    }
+}
+
+/// Unpacks `[position, range]` pairs from JS, throwing if a pair is missing its range
+/// or holds an invalid band. See `Goal::from_packed`.
+pub(crate) fn unpack_goals(destinations: Vec<u32>) -> Vec<Goal> {
+    if !destinations.len().is_multiple_of(2) {
+        throw_str("Destination list must contain packed position and range pairs");
+    }
+    destinations
+        .chunks_exact(2)
+        .map(|chunk| Goal::from_packed(chunk[0], chunk[1]).unwrap_or_else(|error| throw_str(error)))
+        .collect()
+}
+
+/// A tile where a search reached one or more of its goals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoundTarget {
+    pub position: Position,
+    /// The path cost to `position` when it was reached.
+    pub cost: usize,
+    /// Indices of the satisfied goals in the destination list they came from.
+    /// Empty for tiles found with a goal mask.
+    pub destinations: Vec<usize>,
+    /// Whether `destinations` indexes `all_of_destinations` rather than
+    /// `any_of_destinations`.
+    pub all_of: bool,
+    /// The label of the start position the path came from, if it had one.
+    pub origin: Option<u32>,
+}
+
+impl From<&FoundTarget> for JsValue {
+    fn from(target: &FoundTarget) -> Self {
+        let object = js_sys::Object::new();
+        let destinations = js_sys::Array::new();
+        for index in &target.destinations {
+            destinations.push(&JsValue::from_f64(*index as f64));
+        }
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("position"),
+            &JsValue::from_f64(target.position.packed_repr() as f64),
+        )
+        .expect("setting position should not fail");
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("cost"),
+            &JsValue::from_f64(target.cost as f64),
+        )
+        .expect("setting cost should not fail");
+        js_sys::Reflect::set(&object, &JsValue::from_str("destinations"), &destinations)
+            .expect("setting destinations should not fail");
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("allOf"),
+            &JsValue::from_bool(target.all_of),
+        )
+        .expect("setting allOf should not fail");
+        if let Some(origin) = target.origin {
+            js_sys::Reflect::set(
+                &object,
+                &JsValue::from_str("origin"),
+                &JsValue::from_f64(origin as f64),
+            )
+            .expect("setting origin should not fail");
+        }
+        object.into()
+    }
+}
+
+/// Returns true if every threat is at least its flee range away from the position.
+/// Ranges are global, so threats in neighboring rooms count.
+pub(crate) fn is_safe(position: Position, flee_from: &[(Position, usize)]) -> bool {
+    flee_from
+        .iter()
+        .all(|(threat, range)| position.get_range_to(*threat) as usize >= *range)
+}
+
+/// Checks off `any_of` and `all_of` goals as a search reaches new tiles, and records
+/// which tile satisfied which goal. A goal mask and flee threats are checked as tiles
+/// are settled instead, so the first such tile found is also the closest.
+pub(crate) struct GoalTracker {
+    any_of: Option<Vec<Goal>>,
+    /// The `all_of` goals not reached yet, with their original indices.
+    all_of: Option<Vec<(usize, Goal)>>,
+    mask: Option<GoalMask>,
+    /// Threats and the range to keep from each, for flee searches.
+    flee_from: Option<Vec<(Position, usize)>>,
+    found_targets: Vec<Position>,
+    targets: Vec<FoundTarget>,
+}
+
+impl GoalTracker {
+    pub fn new(any_of: Option<Vec<Goal>>, all_of: Option<Vec<Goal>>) -> Self {
+        Self {
+            any_of,
+            all_of: all_of.map(|goals| goals.into_iter().enumerate().collect()),
+            mask: None,
+            flee_from: None,
+            found_targets: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Checks off the goals satisfied by a newly reached position, `cost` away from
+    /// the start labeled `origin`. Returns true once the search can stop: any one of the `any_of`
+    /// goals, or the last of the `all_of` goals, has been reached.
+    pub fn reach(&mut self, position: Position, cost: usize, origin: Option<u32>) -> bool {
+        if let Some(all_of) = &mut self.all_of {
+            let mut destinations = Vec::new();
+            let mut i = 0;
+            while i < all_of.len() {
+                if all_of[i].1.is_satisfied_by(position) {
+                    let (index, _) = all_of.remove(i);
+                    self.found_targets.push(position);
+                    destinations.push(index);
+                } else {
+                    i += 1;
+                }
+            }
+            if !destinations.is_empty() {
+                self.targets.push(FoundTarget {
+                    position,
+                    cost,
+                    destinations,
+                    all_of: true,
+                    origin,
+                });
+            }
+            if all_of.is_empty() {
+                return true;
+            }
+        }
+
+        if let Some(any_of) = &self.any_of {
+            let destinations: Vec<usize> = any_of
+                .iter()
+                .enumerate()
+                .filter(|(_, goal)| goal.is_satisfied_by(position))
+                .map(|(index, _)| index)
+                .collect();
+            if !destinations.is_empty() {
+                self.found_targets.push(position);
+                self.targets.push(FoundTarget {
+                    position,
+                    cost,
+                    destinations,
+                    all_of: false,
+                    origin,
+                });
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn with_mask(mask: GoalMask) -> Self {
+        Self {
+            mask: Some(mask),
+            ..Self::new(None, None)
+        }
+    }
+
+    /// Tracks a flee search: its goal is any tile where every threat is at least its
+    /// range away. See `is_safe`.
+    pub fn fleeing(flee_from: Vec<(Position, usize)>) -> Self {
+        Self {
+            flee_from: Some(flee_from),
+            ..Self::new(None, None)
+        }
+    }
+
+    /// Checks a settled position (one the search won't find a cheaper path to)
+    /// against the goal mask and flee threats. Returns true if it's a goal, so the
+    /// search can stop.
+    pub fn settle(&mut self, position: Position, cost: usize, origin: Option<u32>) -> bool {
+        let is_goal = self
+            .mask
+            .as_ref()
+            .is_some_and(|mask| mask.contains(position))
+            || self
+                .flee_from
+                .as_ref()
+                .is_some_and(|flee_from| is_safe(position, flee_from));
+        if !is_goal {
+            return false;
+        }
+        self.found_targets.push(position);
+        self.targets.push(FoundTarget {
+            position,
+            cost,
+            destinations: Vec::new(),
+            all_of: false,
+            origin,
+        });
+        true
+    }
+
+    pub fn found_targets(&self) -> &[Position] {
+        &self.found_targets
+    }
+
+    pub fn into_result(self, distance_map: MultiroomDistanceMap, ops: usize) -> SearchResult {
+        SearchResult::new(distance_map, self.found_targets, ops).with_targets(self.targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::astar::{
+        astar_multiroom_distance_map, astar_multiroom_distance_map_to_goal_mask,
+    };
+    use crate::algorithms::distance_map::breadth_first_search::{
+        bfs_multiroom_distance_map, bfs_multiroom_distance_map_to_goal_mask,
+    };
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map_to_goal_mask;
+    use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
+    use crate::datatypes::ClockworkCostMatrix;
+    use screeps::{RoomCoordinate, RoomName};
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            "W1N1".parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn band_goals_exclude_tiles_too_close() {
+        let goal = Goal::band(pos(25, 25), 2, 3).unwrap();
+        assert!(!goal.is_satisfied_by(pos(25, 25)));
+        assert!(!goal.is_satisfied_by(pos(26, 25)));
+        assert!(goal.is_satisfied_by(pos(27, 25)));
+        assert!(goal.is_satisfied_by(pos(28, 28)));
+        assert!(!goal.is_satisfied_by(pos(29, 25)));
+
+        assert_eq!(goal.moves_to_band(pos(25, 25)), 2);
+        assert_eq!(goal.moves_to_band(pos(27, 25)), 0);
+        assert_eq!(goal.moves_to_band(pos(35, 25)), 7);
+    }
+
+    #[test]
+    fn packed_ranges_round_trip() {
+        let goal = Goal::band(pos(25, 25), 2, 3).unwrap();
+        assert_eq!(
+            Goal::from_packed(goal.position.packed_repr(), (2 << 16) | 3),
+            Ok(goal)
+        );
+        assert_eq!(
+            Goal::from_packed(goal.position.packed_repr(), 3),
+            Ok(Goal::within(pos(25, 25), 3))
+        );
+    }
+
+    #[test]
+    fn bands_must_be_ordered_and_fit_in_16_bits() {
+        assert!(Goal::band(pos(25, 25), 3, 2).is_err());
+        assert!(Goal::band(pos(25, 25), 0, MAX_PACKED_RANGE).is_ok());
+        assert!(Goal::band(pos(25, 25), 0, MAX_PACKED_RANGE + 1).is_err());
+        assert!(Goal::from_packed(pos(25, 25).packed_repr(), (3 << 16) | 2).is_err());
+    }
+
+    #[test]
+    fn searches_stop_inside_the_band_and_report_the_goal() {
+        let start = pos(25, 25);
+        let band = Goal::band(start, 2, 3).unwrap();
+        let far = Goal::within(pos(45, 45), 0);
+        let goals = vec![far, band];
+
+        let result = astar_multiroom_distance_map(
+            vec![start],
+            |_: RoomName| Some(ClockworkCostMatrix::new(Some(1))),
+            1,
+            10_000,
+            10_000,
+            base_heuristic_with_range(&goals),
+            Some(goals.clone()),
+            None,
+        );
+
+        assert_eq!(result.found_targets.len(), 1);
+        assert_eq!(result.found_targets[0].get_range_to(start), 2);
+        assert_eq!(result.distance_map().get(result.found_targets[0]), 2);
+        assert_eq!(result.targets()[0].destinations, vec![1]);
+    }
+
+    #[test]
+    fn all_of_searches_report_every_satisfied_goal() {
+        let first = Goal::band(pos(25, 25), 1, 1).unwrap();
+        let second = Goal::within(pos(30, 25), 0);
+
+        let result = bfs_multiroom_distance_map(
+            vec![pos(25, 25)],
+            |_: RoomName| Some(ClockworkCostMatrix::new(Some(1))),
+            10_000,
+            1,
+            10_000,
+            None,
+            Some(vec![second, first]),
+        );
+
+        let targets = result.targets();
+        assert_eq!(targets.len(), 2);
+        assert!(targets.iter().all(|target| target.all_of));
+        assert_eq!(targets[0].destinations, vec![1]);
+        assert_eq!(targets[1].destinations, vec![0]);
+        assert_eq!(result.found_targets[1], pos(30, 25));
+    }
+
+    #[test]
+    fn found_targets_report_destination_indices_and_cost() {
+        let goals = vec![
+            Goal::within(pos(30, 25), 0),
+            Goal::within(pos(40, 40), 0),
+            Goal::within(pos(25, 29), 1),
+        ];
+
+        let result = bfs_multiroom_distance_map(
+            vec![pos(25, 25)],
+            |_: RoomName| Some(ClockworkCostMatrix::new(Some(1))),
+            10_000,
+            1,
+            10_000,
+            None,
+            Some(goals),
+        );
+
+        let targets = result.targets();
+        assert_eq!(targets.len(), 3);
+        assert!(targets.iter().all(|target| target.all_of));
+        assert_eq!(targets[0].destinations, vec![2]);
+        assert_eq!(targets[0].cost, 3);
+        assert_eq!(targets[1].position, pos(30, 25));
+        assert_eq!(targets[1].cost, 5);
+        assert_eq!(targets[1].destinations, vec![0]);
+        assert_eq!(targets[2].destinations, vec![1]);
+        assert_eq!(targets[2].cost, 15);
+    }
+
+    #[test]
+    fn any_of_targets_report_every_goal_they_satisfy() {
+        let goals = vec![
+            Goal::within(pos(40, 40), 0),
+            Goal::within(pos(28, 25), 1),
+            Goal::band(pos(28, 25), 0, 1).unwrap(),
+        ];
+
+        let result = astar_multiroom_distance_map(
+            vec![pos(25, 25)],
+            |_: RoomName| Some(ClockworkCostMatrix::new(Some(1))),
+            1,
+            10_000,
+            10_000,
+            base_heuristic_with_range(&goals),
+            Some(goals.clone()),
+            None,
+        );
+
+        let targets = result.targets();
+        assert_eq!(targets.len(), 1);
+        assert!(!targets[0].all_of);
+        assert_eq!(targets[0].position.x().u8(), 27);
+        assert_eq!(targets[0].destinations, vec![1, 2]);
+        assert_eq!(targets[0].cost, 2);
+    }
+
+    /// Plains with a band of swamp at x = 26..=29, and two masked goal tiles: one
+    /// just past the swamp and one farther away on open ground.
+    fn swamp_band() -> (ClockworkCostMatrix, GoalMask) {
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        for x in 26..=29 {
+            for y in 0..50 {
+                matrix.set(pos(x, y).xy(), 10);
+            }
+        }
+        let mut mask_matrix = ClockworkCostMatrix::new(Some(0));
+        mask_matrix.set(pos(30, 25).xy(), 1);
+        mask_matrix.set(pos(25, 40).xy(), 1);
+        let mut mask = GoalMask::new();
+        mask.insert_cost_matrix("W1N1".parse().unwrap(), &mask_matrix);
+        (matrix, mask)
+    }
+
+    #[test]
+    fn goal_masks_stop_at_the_nearest_settled_tile() {
+        let (matrix, mask) = swamp_band();
+        let get_matrix = |_: RoomName| Some(matrix.clone());
+
+        let bfs = bfs_multiroom_distance_map_to_goal_mask(
+            vec![pos(25, 25)],
+            get_matrix,
+            10_000,
+            1,
+            10_000,
+            mask.clone(),
+        );
+        assert_eq!(bfs.found_targets, vec![pos(30, 25)]);
+        assert_eq!(bfs.targets()[0].cost, 5);
+
+        let dijkstra = dijkstra_multiroom_distance_map_to_goal_mask(
+            vec![pos(25, 25)],
+            get_matrix,
+            10_000,
+            1,
+            10_000,
+            mask.clone(),
+        );
+        assert_eq!(dijkstra.found_targets, vec![pos(25, 40)]);
+        assert_eq!(dijkstra.targets()[0].cost, 15);
+
+        // Every masked tile is within range 0 of one of these, so they bound the search.
+        let bound = [Goal::within(pos(30, 25), 0), Goal::within(pos(25, 40), 0)];
+        let astar = astar_multiroom_distance_map_to_goal_mask(
+            vec![pos(25, 25)],
+            get_matrix,
+            1,
+            10_000,
+            10_000,
+            base_heuristic_with_range(&bound),
+            mask,
+        );
+        assert_eq!(astar.found_targets, vec![pos(25, 40)]);
+        assert_eq!(astar.targets()[0].cost, 15);
+        assert!(astar.ops() < dijkstra.ops());
+    }
+
+    #[test]
+    fn goal_masks_match_start_tiles() {
+        let (matrix, mask) = swamp_band();
+        let result = bfs_multiroom_distance_map_to_goal_mask(
+            vec![pos(30, 25)],
+            |_: RoomName| Some(matrix.clone()),
+            10_000,
+            1,
+            10_000,
+            mask,
+        );
+        assert_eq!(result.found_targets, vec![pos(30, 25)]);
+        assert_eq!(result.targets()[0].cost, 0);
+    }
+}
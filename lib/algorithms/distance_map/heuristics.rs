@@ -1,6 +1,10 @@
+use crate::datatypes::LandmarkIndex;
 use crate::datatypes::PortalIndex;
+use screeps::constants::extra::ROOM_SIZE;
 use screeps::Position;
 
+use super::goals::RangeTarget;
+
 /// A basic global range heuristic.
 #[allow(dead_code)]
 pub fn base_heuristic<'a>(goal: &'a [Position]) -> impl Fn(Position) -> usize + 'a {
@@ -13,21 +17,17 @@ pub fn base_heuristic<'a>(goal: &'a [Position]) -> impl Fn(Position) -> usize +
 }
 
 /// When the goal is to be within a certain range of a position, simply subtract the target
-/// range from the actual range to the goal.
+/// range from the actual range to the goal. For range bands, a position that's too close
+/// needs at least as many moves as it's short of the minimum range.
 #[allow(dead_code)]
-pub fn base_heuristic_with_range<'a>(
-    goal: &'a [(Position, usize)],
+pub fn base_heuristic_with_range<'a, T: RangeTarget>(
+    goal: &'a [T],
 ) -> impl Fn(Position) -> usize + 'a {
-    move |position| {
-        goal.iter()
-            .map(|(g, range)| position.get_range_to(*g).saturating_sub(*range as u32))
-            .min()
-            .unwrap_or(0) as usize
-    }
+    move |position| direct_goal_range(position, goal)
 }
 
-pub fn closest_portal_heuristic_cached_with_range<'a>(
-    goals: &'a [(Position, usize)],
+pub fn closest_portal_heuristic_cached_with_range<'a, T: RangeTarget>(
+    goals: &'a [T],
     portal_index: &'a PortalIndex,
 ) -> impl Fn(Position) -> usize + 'a {
     let target_portal_range = target_portal_range(goals, portal_index);
@@ -47,22 +47,78 @@ pub fn closest_portal_heuristic_cached_with_range<'a>(
     }
 }
 
-fn target_portal_range(goals: &[(Position, usize)], portal_index: &PortalIndex) -> usize {
+/// The ALT lower bound: the triangle inequality over the landmarks' exact distance
+/// maps. Returns 0 where the landmarks know nothing, so combine it with a range
+/// heuristic (taking the max of both keeps it admissible).
+pub fn landmark_heuristic_with_range<'a, T: RangeTarget>(
+    goals: &'a [T],
+    landmarks: &'a LandmarkIndex,
+) -> impl Fn(Position) -> usize + 'a {
+    let goal_distances: Vec<Vec<usize>> = goals
+        .iter()
+        .map(|goal| landmarks.goal_distances(goal.position(), goal.min_range(), goal.max_range()))
+        .collect();
+
+    move |position| {
+        goal_distances
+            .iter()
+            .map(|distances| landmarks.lower_bound(position, distances))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+fn target_portal_range<T: RangeTarget>(goals: &[T], portal_index: &PortalIndex) -> usize {
     goals
         .iter()
-        .map(|(goal, range)| {
+        .map(|goal| {
             portal_index
-                .nearest_endpoint_range(*goal)
-                .saturating_sub(*range)
+                .nearest_endpoint_range(goal.position())
+                .saturating_sub(goal.max_range())
         })
         .min()
         .unwrap_or(usize::MAX)
 }
 
-fn direct_goal_range(position: Position, goals: &[(Position, usize)]) -> usize {
+fn direct_goal_range<T: RangeTarget>(position: Position, goals: &[T]) -> usize {
     goals
         .iter()
-        .map(|(goal, range)| position.get_range_to(*goal).saturating_sub(*range as u32))
+        .map(|goal| goal.moves_to_band(position))
         .min()
-        .unwrap_or(0) as usize
+        .unwrap_or(0)
+}
+
+/// When fleeing, each step changes the range to a threat by at most one, except
+/// that stepping onto a room edge also lands on the next room's edge, moving two
+/// tiles at once. Crossing the same way again takes another 49 steps, so `k` steps
+/// gain at most `k + ceil(k / 49)` range, and the largest remaining shortfall less
+/// its share of crossings is a lower bound on the steps still needed.
+pub fn flee_heuristic<'a>(threats: &'a [(Position, usize)]) -> impl Fn(Position) -> usize + 'a {
+    move |position| {
+        let shortfall = threats
+            .iter()
+            .map(|(threat, range)| range.saturating_sub(position.get_range_to(*threat) as usize))
+            .max()
+            .unwrap_or(0);
+        shortfall - shortfall.div_ceil(ROOM_SIZE as usize - 1)
+    }
+}
+
+/// Portals can carry a fleeing creep arbitrarily far in one step, so the flee
+/// shortfall is capped by the range to the nearest portal endpoint.
+pub fn flee_portal_heuristic_cached<'a>(
+    threats: &'a [(Position, usize)],
+    portal_index: &'a PortalIndex,
+) -> impl Fn(Position) -> usize + 'a {
+    let shortfall = flee_heuristic(threats);
+
+    move |position| {
+        let direct = shortfall(position);
+
+        if direct == 0 || portal_index.is_empty() {
+            return direct;
+        }
+
+        direct.min(portal_index.nearest_endpoint_cached_range(position))
+    }
 }
@@ -0,0 +1,562 @@
+use crate::algorithms::map::{corresponding_room_edge, room_edge_sources, same_room_neighbor};
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::DistanceMap;
+use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::OpenList;
+use crate::datatypes::RoomDataCache;
+use crate::utils::set_panic_hook;
+use screeps::Direction;
+use screeps::Position;
+use screeps::RoomName;
+use std::ops::Fn;
+use wasm_bindgen::prelude::*;
+
+use super::astar::js_cost_matrix;
+
+static ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::Top,
+    Direction::TopRight,
+    Direction::Right,
+    Direction::BottomRight,
+    Direction::Bottom,
+    Direction::BottomLeft,
+    Direction::Left,
+    Direction::TopLeft,
+];
+
+/// A Dijkstra distance map that can be repaired when tile costs change, rather than
+/// recomputed from scratch.
+///
+/// This is Lifelong Planning A* (the incremental core of D* Lite) with a heuristic of
+/// 0. Alongside each tile's distance (`g`), it tracks a one-step lookahead (`rhs`)
+/// computed from the tile's predecessors. When a cost changes, only tiles whose
+/// lookahead no longer matches their distance are queued, and the repair spreads out
+/// from there until the map is consistent again.
+///
+/// Because the cost of a move is charged on the tile being stepped onto, changing a
+/// tile's cost only affects the tile a creep lands on after stepping there: the tile
+/// itself, or the corresponding edge tile in the next room.
+pub struct IncrementalDijkstra<F>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+{
+    // Cost matrices and current distances (`g`) for each loaded room.
+    cached_room_data: RoomDataCache<F>,
+    // One-step lookahead distances, indexed by the same room keys.
+    rhs: Vec<DistanceMap>,
+    start: Vec<Position>,
+    // Inconsistent tiles, keyed by min(g, rhs). Stale entries are skipped when popped.
+    open: OpenList<u32>,
+    max_path_cost: usize,
+    ops: usize,
+}
+
+impl<F> IncrementalDijkstra<F>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+{
+    /// Builds the initial distance map from the given start positions. Rooms are loaded
+    /// from `get_cost_matrix` as the search reaches them, up to `max_rooms`. Tiles at or
+    /// beyond `max_path_cost` are not expanded.
+    pub fn new(
+        start: Vec<Position>,
+        get_cost_matrix: F,
+        max_rooms: usize,
+        max_path_cost: usize,
+    ) -> Self {
+        set_panic_hook();
+        let mut search = Self {
+            cached_room_data: RoomDataCache::new(max_rooms, get_cost_matrix),
+            rhs: Vec::new(),
+            start: start.clone(),
+            open: OpenList::new(),
+            max_path_cost,
+            ops: 0,
+        };
+
+        for position in start {
+            if let Some(room_key) = search.room_key(position.room_name()) {
+                search.rhs[room_key][position.xy()] = 0;
+                search.open.push(0, position.packed_repr());
+            }
+        }
+        search.repair();
+
+        search
+    }
+
+    /// Copies out the current distance map.
+    pub fn distance_map(&self) -> MultiroomDistanceMap {
+        self.cached_room_data.to_distance_map()
+    }
+
+    /// Gets the current distance at a position.
+    pub fn get(&self, position: Position) -> usize {
+        match self
+            .cached_room_data
+            .get_loaded_room_key(position.room_name())
+        {
+            Some(room_key) => self.cached_room_data[room_key].distance_map[position.xy()],
+            None => usize::MAX,
+        }
+    }
+
+    /// The total number of tiles updated, including the initial search.
+    pub fn ops(&self) -> usize {
+        self.ops
+    }
+
+    /// Applies a list of changed tile costs and repairs the distance map. Changes to
+    /// rooms that haven't been loaded are ignored. Returns the number of tiles updated
+    /// by the repair.
+    pub fn update_costs(&mut self, changes: &[(Position, u8)]) -> usize {
+        for (position, cost) in changes {
+            let room_key = match self
+                .cached_room_data
+                .get_loaded_room_key(position.room_name())
+            {
+                Some(room_key) => room_key,
+                None => continue,
+            };
+            let cost_matrix = match self.cached_room_data[room_key].cost_matrix.as_mut() {
+                Some(cost_matrix) => cost_matrix,
+                // A room without a cost matrix is blocked; treat it as all walls
+                // until costs are provided for it.
+                None => self.cached_room_data[room_key]
+                    .cost_matrix
+                    .insert(ClockworkCostMatrix::new(Some(255))),
+            };
+            if cost_matrix.get(position.xy()) == *cost {
+                continue;
+            }
+            cost_matrix.set(position.xy(), *cost);
+            self.update_vertex(corresponding_room_edge(*position));
+            if position.is_room_edge() {
+                // Crossings from the neighboring room land here and pay this tile's cost.
+                self.update_vertex(*position);
+            }
+        }
+
+        self.repair()
+    }
+
+    /// Replaces a room's cost matrix, repairing the distance map for the tiles that
+    /// differ from the previous matrix. Returns the number of tiles updated.
+    pub fn update_cost_matrix(
+        &mut self,
+        room: RoomName,
+        cost_matrix: &ClockworkCostMatrix,
+    ) -> usize {
+        let room_key = match self.cached_room_data.get_loaded_room_key(room) {
+            Some(room_key) => room_key,
+            None => return 0,
+        };
+        let changes: Vec<(Position, u8)> = cost_matrix
+            .get_internal()
+            .iter()
+            .filter(|(xy, cost)| {
+                self.cached_room_data[room_key]
+                    .cost_matrix
+                    .as_ref()
+                    .map(|current| current.get(*xy) != *cost)
+                    .unwrap_or(true)
+            })
+            .map(|(xy, cost)| (Position::new(xy.x, xy.y, room), cost))
+            .collect();
+
+        self.update_costs(&changes)
+    }
+
+    fn room_key(&mut self, room: RoomName) -> Option<usize> {
+        let room_key = self.cached_room_data.get_room_key(room)?;
+        while self.rhs.len() <= room_key {
+            self.rhs.push(DistanceMap::new());
+        }
+        Some(room_key)
+    }
+
+    fn g(&self, room_key: usize, position: Position) -> usize {
+        self.cached_room_data[room_key].distance_map[position.xy()]
+    }
+
+    /// Recomputes the lookahead for a position from its predecessors and queues it
+    /// if it's inconsistent.
+    fn update_vertex(&mut self, position: Position) {
+        let room_key = match self
+            .cached_room_data
+            .get_loaded_room_key(position.room_name())
+        {
+            Some(room_key) => room_key,
+            None => return,
+        };
+        if self.cached_room_data[room_key].cost_matrix.is_none() {
+            return;
+        }
+
+        if !self.start.contains(&position) {
+            let mut rhs = usize::MAX;
+            for step in room_edge_sources(position) {
+                let step_room_key =
+                    match self.cached_room_data.get_loaded_room_key(step.room_name()) {
+                        Some(room_key) => room_key,
+                        None => continue,
+                    };
+                let cost = match &self.cached_room_data[step_room_key].cost_matrix {
+                    Some(cost_matrix) => cost_matrix.get(step.xy()),
+                    None => continue,
+                };
+                if cost == 255 {
+                    continue;
+                }
+                let cost = if step_room_key == room_key {
+                    cost
+                } else {
+                    match self
+                        .cached_room_data
+                        .crossing_cost(cost, room_key, position)
+                    {
+                        Some(cost) => cost,
+                        None => continue,
+                    }
+                };
+                for direction in ALL_DIRECTIONS.iter() {
+                    if let Some(predecessor) = same_room_neighbor(step, *direction) {
+                        rhs = rhs.min(
+                            self.g(step_room_key, predecessor)
+                                .saturating_add(cost as usize),
+                        );
+                    }
+                }
+            }
+            self.rhs[room_key][position.xy()] = rhs;
+        }
+
+        let g = self.g(room_key, position);
+        let rhs = self.rhs[room_key][position.xy()];
+        if g != rhs {
+            self.open.push(g.min(rhs), position.packed_repr());
+        }
+    }
+
+    /// Updates the lookahead of every tile reachable in one step from the position.
+    fn update_successors(&mut self, position: Position) {
+        let room_key = match self
+            .cached_room_data
+            .get_loaded_room_key(position.room_name())
+        {
+            Some(room_key) => room_key,
+            None => return,
+        };
+        for direction in ALL_DIRECTIONS.iter() {
+            let step = match same_room_neighbor(position, *direction) {
+                Some(step) => step,
+                None => continue,
+            };
+            let cost = match &self.cached_room_data[room_key].cost_matrix {
+                Some(cost_matrix) => cost_matrix.get(step.xy()),
+                None => return,
+            };
+            if cost == 255 {
+                continue;
+            }
+            let neighbor = corresponding_room_edge(step);
+            // Load rooms as the search expands into them, so the initial build and any
+            // later improvements can still grow into new rooms.
+            if self.room_key(neighbor.room_name()).is_none() {
+                continue;
+            }
+            self.update_vertex(neighbor);
+        }
+    }
+
+    /// Processes queued tiles until every tile below `max_path_cost` is consistent.
+    fn repair(&mut self) -> usize {
+        let mut ops = 0;
+        while let Some((key, packed)) = self.open.pop_with_key() {
+            if key >= self.max_path_cost {
+                self.open.push(key, packed);
+                break;
+            }
+
+            let position = Position::from_packed(packed);
+            let room_key = match self
+                .cached_room_data
+                .get_loaded_room_key(position.room_name())
+            {
+                Some(room_key) => room_key,
+                None => continue,
+            };
+            let g = self.g(room_key, position);
+            let rhs = self.rhs[room_key][position.xy()];
+            if g == rhs || key != g.min(rhs) {
+                // already consistent, or a stale queue entry
+                continue;
+            }
+
+            ops += 1;
+            if g > rhs {
+                // Overconsistent: a cheaper path was found, so settle it.
+                self.cached_room_data[room_key].distance_map[position.xy()] = rhs;
+                self.update_successors(position);
+            } else {
+                // Underconsistent: the old path got more expensive, so reset the tile
+                // and let its predecessors offer a new distance.
+                self.cached_room_data[room_key].distance_map[position.xy()] = usize::MAX;
+                self.update_vertex(position);
+                self.update_successors(position);
+            }
+        }
+        self.ops += ops;
+        ops
+    }
+}
+
+type JsCostMatrixFn = Box<dyn Fn(RoomName) -> Option<ClockworkCostMatrix>>;
+
+/// A handle to a distance map that can be repaired in place when cost matrices change.
+#[wasm_bindgen]
+pub struct IncrementalDijkstraHandle {
+    search: IncrementalDijkstra<JsCostMatrixFn>,
+}
+
+#[wasm_bindgen]
+impl IncrementalDijkstraHandle {
+    /// Builds the initial distance map. Rooms are loaded from `get_cost_matrix` as the
+    /// search reaches them; later changes are applied with `update_costs` or
+    /// `update_cost_matrix`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        start_packed: Vec<u32>,
+        get_cost_matrix: js_sys::Function,
+        max_rooms: usize,
+        max_path_cost: usize,
+    ) -> IncrementalDijkstraHandle {
+        let start_positions = start_packed
+            .iter()
+            .map(|pos| Position::from_packed(*pos))
+            .collect();
+        let get_cost_matrix: JsCostMatrixFn =
+            Box::new(move |room| js_cost_matrix(&get_cost_matrix, room));
+
+        IncrementalDijkstraHandle {
+            search: IncrementalDijkstra::new(
+                start_positions,
+                get_cost_matrix,
+                max_rooms,
+                max_path_cost,
+            ),
+        }
+    }
+
+    /// Applies changed tile costs, given as a flat list of packed position and cost
+    /// pairs. Returns the number of tiles updated.
+    pub fn update_costs(&mut self, changes: Vec<u32>) -> usize {
+        let changes: Vec<(Position, u8)> = changes
+            .chunks(2)
+            .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as u8))
+            .collect();
+        self.search.update_costs(&changes)
+    }
+
+    /// Replaces a room's cost matrix, repairing the tiles that changed. Returns the
+    /// number of tiles updated.
+    pub fn update_cost_matrix(
+        &mut self,
+        room_name: u16,
+        cost_matrix: &ClockworkCostMatrix,
+    ) -> usize {
+        self.search
+            .update_cost_matrix(RoomName::from_packed(room_name), cost_matrix)
+    }
+
+    /// Gets the distance at a packed position.
+    pub fn get(&self, packed_pos: u32) -> usize {
+        self.search.get(Position::from_packed(packed_pos))
+    }
+
+    /// Copies out the current distance map.
+    #[wasm_bindgen(getter)]
+    pub fn distance_map(&self) -> MultiroomDistanceMap {
+        self.search.distance_map()
+    }
+
+    /// The total number of tiles updated, including the initial search.
+    #[wasm_bindgen(getter)]
+    pub fn ops(&self) -> usize {
+        self.search.ops()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::map::DirectionOrder;
+    use crate::algorithms::path::to_multiroom_distance_map_origin::path_to_multiroom_distance_map_origin;
+    use crate::test_fixtures::{pos, room, xy};
+    use std::cell::RefCell;
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, HashMap};
+    use std::rc::Rc;
+
+    /// Runs a plain Dijkstra over all eight neighbors, for comparison.
+    fn reference(
+        start: Position,
+        matrices: &HashMap<RoomName, ClockworkCostMatrix>,
+    ) -> MultiroomDistanceMap {
+        let mut distances = MultiroomDistanceMap::new();
+        let mut open = BinaryHeap::new();
+        distances.set(start, 0);
+        open.push(Reverse((0, start.packed_repr())));
+        while let Some(Reverse((g, packed))) = open.pop() {
+            let position = Position::from_packed(packed);
+            if distances.get(position) < g {
+                continue;
+            }
+            for direction in ALL_DIRECTIONS.iter() {
+                let Some(step) = same_room_neighbor(position, *direction) else {
+                    continue;
+                };
+                let mut cost = matrices[&step.room_name()].get(step.xy());
+                if cost == 255 {
+                    continue;
+                }
+                let neighbor = corresponding_room_edge(step);
+                let Some(landing_matrix) = matrices.get(&neighbor.room_name()) else {
+                    continue;
+                };
+                if neighbor.room_name() != step.room_name() {
+                    // Crossings also pay for the tile they land on.
+                    match landing_matrix.get(neighbor.xy()) {
+                        255 => continue,
+                        landing_cost => cost = cost.max(landing_cost),
+                    }
+                }
+                let next = g + cost as usize;
+                if next < distances.get(neighbor) {
+                    distances.set(neighbor, next);
+                    open.push(Reverse((next, neighbor.packed_repr())));
+                }
+            }
+        }
+        distances
+    }
+
+    fn assert_maps_match(
+        actual: &MultiroomDistanceMap,
+        expected: &MultiroomDistanceMap,
+        rooms: &[&str],
+    ) {
+        for room_name in rooms {
+            for index in 0..2500 {
+                let xy = screeps::linear_index_to_xy(index);
+                let position = Position::new(xy.x, xy.y, room(room_name));
+                assert_eq!(
+                    actual.get(position),
+                    expected.get(position),
+                    "distance mismatch at {:?}",
+                    position
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn repairs_match_a_fresh_search() {
+        let start = pos("W1N1", 25, 25);
+        let rooms = ["W1N1", "W2N1"];
+        let matrices = Rc::new(RefCell::new(HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W2N1"), ClockworkCostMatrix::new(Some(2))),
+        ])));
+        let callback_matrices = matrices.clone();
+        let mut search = IncrementalDijkstra::new(
+            vec![start],
+            move |room| callback_matrices.borrow().get(&room).cloned(),
+            2,
+            usize::MAX,
+        );
+        assert_maps_match(
+            &search.distance_map(),
+            &reference(start, &matrices.borrow()),
+            &rooms,
+        );
+
+        // Wall off most of a column, then open part of it back up as swamp.
+        let mut wall = Vec::new();
+        for y in 5..45 {
+            wall.push((pos("W1N1", 20, y), 255));
+        }
+        search.update_costs(&wall);
+        for (position, cost) in wall.iter() {
+            matrices
+                .borrow_mut()
+                .get_mut(&position.room_name())
+                .unwrap()
+                .set(position.xy(), *cost);
+        }
+        assert_maps_match(
+            &search.distance_map(),
+            &reference(start, &matrices.borrow()),
+            &rooms,
+        );
+
+        let mut new_matrix = matrices.borrow()[&room("W1N1")].clone();
+        new_matrix.set(xy(20, 25), 5);
+        new_matrix.set(xy(0, 10), 4);
+        search.update_cost_matrix(room("W1N1"), &new_matrix);
+        matrices.borrow_mut().insert(room("W1N1"), new_matrix);
+        assert_maps_match(
+            &search.distance_map(),
+            &reference(start, &matrices.borrow()),
+            &rooms,
+        );
+    }
+
+    #[test]
+    fn repairs_only_touch_affected_tiles() {
+        let start = pos("W1N1", 10, 10);
+        let mut search = IncrementalDijkstra::new(
+            vec![start],
+            |_| Some(ClockworkCostMatrix::new(Some(1))),
+            1,
+            usize::MAX,
+        );
+        let initial_ops = search.ops();
+
+        // A tile in the far corner only affects the tiles behind it.
+        let updated = search.update_costs(&[(pos("W1N1", 45, 45), 10)]);
+        assert!(updated > 0);
+        assert!(updated < initial_ops / 10);
+        assert_eq!(search.get(pos("W1N1", 45, 45)), 44);
+    }
+
+    #[test]
+    fn repaired_map_works_with_path_helpers() {
+        let start = pos("W1N1", 10, 25);
+        let target = pos("W1N1", 30, 25);
+        let mut search = IncrementalDijkstra::new(
+            vec![start],
+            |_| Some(ClockworkCostMatrix::new(Some(1))),
+            1,
+            usize::MAX,
+        );
+        let mut wall = Vec::new();
+        for y in 2..48 {
+            wall.push((pos("W1N1", 20, y), 255));
+        }
+        search.update_costs(&wall);
+
+        let distance_map = search.distance_map();
+        let path = path_to_multiroom_distance_map_origin(
+            target,
+            &distance_map,
+            DirectionOrder::CardinalFirst,
+        )
+        .unwrap();
+        assert_eq!(path.get(path.len() - 1), Some(&start));
+        assert!((0..path.len())
+            .map(|i| *path.get(i).unwrap())
+            .filter(|step| step.x().u8() == 20)
+            .all(|step| step.y().u8() == 1 || step.y().u8() == 48));
+    }
+}
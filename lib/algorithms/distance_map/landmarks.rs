@@ -0,0 +1,357 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+use crate::algorithms::distance_map::dijkstra::dijkstra_portal_multiroom_distance_map;
+use crate::datatypes::configure_landmark_index;
+use crate::datatypes::with_configured_landmark_index;
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::LandmarkIndex;
+use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::PortalIndex;
+use crate::utils::set_panic_hook;
+use screeps::{Position, RoomName};
+use wasm_bindgen::prelude::*;
+
+/// Runs an unbounded Dijkstra search out from a landmark, limited to `max_rooms`.
+fn landmark_distances(
+    landmark: Position,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    portal_index: Option<&PortalIndex>,
+) -> MultiroomDistanceMap {
+    let result = match portal_index {
+        Some(portal_index) => dijkstra_portal_multiroom_distance_map(
+            vec![landmark],
+            get_cost_matrix,
+            usize::MAX,
+            max_rooms,
+            usize::MAX,
+            portal_index,
+            None,
+            None,
+        ),
+        None => dijkstra_multiroom_distance_map(
+            vec![landmark],
+            get_cost_matrix,
+            usize::MAX,
+            max_rooms,
+            usize::MAX,
+            None,
+            None,
+        ),
+    };
+    result.distance_map()
+}
+
+/// Builds distance maps from each of the given landmarks, exploring at most
+/// `max_rooms` rooms per landmark. Landmarks that don't fit in the room budget
+/// are skipped.
+pub fn build_landmark_index(
+    landmarks: Vec<Position>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    room_budget: usize,
+    portal_index: Option<&PortalIndex>,
+) -> LandmarkIndex {
+    set_panic_hook();
+    let mut index = LandmarkIndex::with_room_budget(room_budget);
+    index.set_uses_portals(portal_index.is_some());
+    for landmark in landmarks {
+        let max_rooms = max_rooms.min(index.remaining_room_budget());
+        if max_rooms == 0 {
+            break;
+        }
+        let distances = landmark_distances(landmark, &get_cost_matrix, max_rooms, portal_index);
+        index.insert(landmark, distances);
+    }
+    index
+}
+
+/// Picks up to `count` landmarks around `seed` by farthest-point selection: the
+/// first is the reachable tile farthest from the seed, and each following one is
+/// the tile farthest from all landmarks chosen so far. Landmarks on the edges of
+/// the explored area give the most useful bounds.
+pub fn select_landmarks(
+    seed: Position,
+    count: usize,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    room_budget: usize,
+    portal_index: Option<&PortalIndex>,
+) -> LandmarkIndex {
+    set_panic_hook();
+    let mut index = LandmarkIndex::with_room_budget(room_budget);
+    index.set_uses_portals(portal_index.is_some());
+
+    let seed_distances = landmark_distances(seed, &get_cost_matrix, max_rooms, portal_index);
+    let mut candidate = farthest_tile(&seed_distances, |position| seed_distances.get(position));
+
+    while let Some(landmark) = candidate {
+        if index.positions().len() >= count {
+            break;
+        }
+        let max_rooms = max_rooms.min(index.remaining_room_budget());
+        if max_rooms == 0 {
+            break;
+        }
+        let distances = landmark_distances(landmark, &get_cost_matrix, max_rooms, portal_index);
+        if !index.insert(landmark, distances) {
+            break;
+        }
+        candidate = farthest_tile(&seed_distances, |position| {
+            index.nearest_landmark_distance(position)
+        });
+    }
+
+    index
+}
+
+/// The tile reached from the seed with the highest `score`, ignoring unreached
+/// tiles and tiles that are already landmarks (score 0).
+fn farthest_tile(
+    seed_distances: &MultiroomDistanceMap,
+    score: impl Fn(Position) -> usize,
+) -> Option<Position> {
+    let mut best: Option<(usize, Position)> = None;
+    for (room, map) in seed_distances.maps.iter() {
+        for (xy, distance) in map.enumerate() {
+            if *distance == usize::MAX {
+                continue;
+            }
+            let position = Position::new(xy.x, xy.y, room);
+            let score = score(position);
+            if score == 0 || score == usize::MAX {
+                continue;
+            }
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, position));
+            }
+        }
+    }
+    best.map(|(_, position)| position)
+}
+
+/// Builds landmarks at the given positions and makes them the configured landmarks
+/// used by the A* entry points. Pass terrain-only matrices (or the lowest costs
+/// searches will use) so the bounds stay admissible. With `use_portals`, distances
+/// are measured through the configured portals, and the landmarks can also be used
+/// by portal searches.
+#[wasm_bindgen]
+pub fn set_landmarks(
+    packed_landmarks: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    use_portals: Option<bool>,
+) {
+    let landmarks = packed_landmarks
+        .into_iter()
+        .map(Position::from_packed)
+        .collect();
+    let room_budget = with_configured_landmark_index(|index| index.room_budget());
+    let index = build_js_landmark_index(
+        |portal_index| {
+            build_landmark_index(
+                landmarks,
+                |room| js_cost_matrix(get_cost_matrix, room),
+                max_rooms,
+                room_budget,
+                portal_index,
+            )
+        },
+        use_portals.unwrap_or(false),
+    );
+    configure_landmark_index(index);
+}
+
+/// Chooses up to `count` landmarks around `seed` and makes them the configured
+/// landmarks. Returns the chosen landmark positions.
+#[wasm_bindgen]
+pub fn choose_landmarks(
+    seed_packed: u32,
+    count: usize,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    use_portals: Option<bool>,
+) -> Vec<u32> {
+    let room_budget = with_configured_landmark_index(|index| index.room_budget());
+    let index = build_js_landmark_index(
+        |portal_index| {
+            select_landmarks(
+                Position::from_packed(seed_packed),
+                count,
+                |room| js_cost_matrix(get_cost_matrix, room),
+                max_rooms,
+                room_budget,
+                portal_index,
+            )
+        },
+        use_portals.unwrap_or(false),
+    );
+    let positions = index
+        .positions()
+        .iter()
+        .map(|position| position.packed_repr())
+        .collect();
+    configure_landmark_index(index);
+    positions
+}
+
+/// Rebuilds the configured landmarks' distance maps, e.g. after the cost matrices
+/// they were built from have changed.
+#[wasm_bindgen]
+pub fn rebuild_landmarks(get_cost_matrix: &js_sys::Function, max_rooms: usize) {
+    let (landmarks, room_budget, use_portals) = with_configured_landmark_index(|index| {
+        (index.positions(), index.room_budget(), index.uses_portals())
+    });
+    let index = build_js_landmark_index(
+        |portal_index| {
+            build_landmark_index(
+                landmarks,
+                |room| js_cost_matrix(get_cost_matrix, room),
+                max_rooms,
+                room_budget,
+                portal_index,
+            )
+        },
+        use_portals,
+    );
+    configure_landmark_index(index);
+}
+
+fn build_js_landmark_index(
+    build: impl FnOnce(Option<&PortalIndex>) -> LandmarkIndex,
+    use_portals: bool,
+) -> LandmarkIndex {
+    if use_portals {
+        with_configured_portal_index(|portal_index| build(Some(portal_index)))
+    } else {
+        build(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::astar::astar_multiroom_distance_map;
+    use crate::algorithms::distance_map::goals::Goal;
+    use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
+    use crate::algorithms::distance_map::heuristics::landmark_heuristic_with_range;
+    use screeps::RoomCoordinate;
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            "W1N1".parse().unwrap(),
+        )
+    }
+
+    /// A single room split by a wall with one gap at the bottom, so straight-line
+    /// range badly underestimates the cost of crossing it.
+    fn walled_room(room: RoomName) -> Option<ClockworkCostMatrix> {
+        if room != "W1N1" {
+            return None;
+        }
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        for y in 0..47 {
+            matrix.set(
+                screeps::RoomXY::new(
+                    RoomCoordinate::new(25).unwrap(),
+                    RoomCoordinate::new(y).unwrap(),
+                ),
+                255,
+            );
+        }
+        Some(matrix)
+    }
+
+    #[test]
+    fn landmark_bound_is_admissible_and_tighter_than_range() {
+        let index = select_landmarks(pos(10, 10), 3, walled_room, 1, 8, None);
+        assert_eq!(index.positions().len(), 3);
+
+        let goal = pos(40, 5);
+        let goals = vec![Goal::within(goal, 0)];
+        let exact = dijkstra_multiroom_distance_map(
+            vec![goal],
+            walled_room,
+            usize::MAX,
+            1,
+            usize::MAX,
+            None,
+            None,
+        )
+        .distance_map();
+        let heuristic = landmark_heuristic_with_range(&goals, &index);
+
+        // The cost matrix is symmetric, so distances from the goal are distances to it.
+        for x in 1..49 {
+            for y in 1..49 {
+                let position = pos(x, y);
+                let actual = exact.get(position);
+                if actual != usize::MAX {
+                    assert!(
+                        heuristic(position) <= actual,
+                        "bound {} exceeds cost {} at {}",
+                        heuristic(position),
+                        actual,
+                        position
+                    );
+                }
+            }
+        }
+
+        let start = pos(10, 5);
+        assert!(heuristic(start) > start.get_range_to(goal) as usize);
+    }
+
+    #[test]
+    fn landmarks_reduce_astar_ops_without_changing_cost() {
+        let start = pos(10, 5);
+        let goals = vec![Goal::within(pos(40, 5), 0)];
+        let index = select_landmarks(start, 4, walled_room, 1, 8, None);
+
+        let base = astar_multiroom_distance_map(
+            vec![start],
+            walled_room,
+            1,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&goals),
+            Some(goals.clone()),
+            None,
+        );
+        let landmark_fn = landmark_heuristic_with_range(&goals, &index);
+        let range_fn = base_heuristic_with_range(&goals);
+        let alt = astar_multiroom_distance_map(
+            vec![start],
+            walled_room,
+            1,
+            usize::MAX,
+            usize::MAX,
+            |position| range_fn(position).max(landmark_fn(position)),
+            Some(goals.clone()),
+            None,
+        );
+
+        assert_eq!(
+            base.distance_map().get(goals[0].position),
+            alt.distance_map().get(goals[0].position)
+        );
+        assert!(alt.ops() < base.ops());
+    }
+
+    #[test]
+    fn landmarks_respect_room_budget() {
+        let open = |_: RoomName| Some(ClockworkCostMatrix::new(Some(1)));
+        let index = build_landmark_index(vec![pos(10, 10), pos(40, 40)], open, 3, 3, None);
+
+        assert!(index.stored_rooms() <= 3);
+        assert_eq!(index.positions()[0], pos(10, 10));
+
+        let mut index = build_landmark_index(vec![pos(10, 10), pos(40, 40)], open, 1, 4, None);
+        assert_eq!(index.positions().len(), 2);
+        index.set_room_budget(1);
+        assert_eq!(index.positions(), vec![pos(10, 10)]);
+    }
+}
@@ -1,25 +1,43 @@
 use screeps::Position;
 use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
 
+use crate::datatypes::MultiroomCompactDistanceMap;
 use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::MultiroomMonoFlowField;
+use crate::datatypes::MultiroomTurnDistanceMap;
+use std::convert::TryFrom;
+
+use self::goals::FoundTarget;
 
 pub mod astar;
 pub mod breadth_first_search;
 pub mod dijkstra;
+pub mod flee;
+pub mod goals;
 pub mod heuristics;
+pub mod incremental;
+pub mod landmarks;
+pub mod resumable;
+pub mod seeds;
+pub mod turn_penalty;
+pub mod voronoi;
 
 /// A distance map search returns both the distance map (filled out
 /// with all tiles explored) and the targets found. These aren't necessarily
 /// the same positions specified as targets - if the target range is 5, then
-/// this is the first position in range 5 of the target. If multiple targets
-/// are specified, and you care about matching the found target with one of
-/// the original targets, you can iterate through your list and figure out the
-/// ones that are in range of the found target(s).
+/// this is the first position in range 5 of the target. `targets` records,
+/// for each found target, its path cost and the indices of the destinations it
+/// satisfied.
 #[wasm_bindgen]
 pub struct SearchResult {
     distance_map: MultiroomDistanceMap,
     found_targets: Vec<Position>,
+    targets: Vec<FoundTarget>,
     ops: usize,
+    suboptimality_bound: f64,
+    parents: Option<MultiroomMonoFlowField>,
+    turn_distance_map: Option<MultiroomTurnDistanceMap>,
 }
 
 impl SearchResult {
@@ -31,9 +49,67 @@ impl SearchResult {
         Self {
             distance_map,
             found_targets,
+            targets: Vec::new(),
             ops,
+            suboptimality_bound: 1.0,
+            parents: None,
+            turn_distance_map: None,
         }
     }
+
+    /// Records the cost and destination indices of each tile where goals were reached.
+    pub fn with_targets(mut self, targets: Vec<FoundTarget>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn targets(&self) -> &[FoundTarget] {
+        &self.targets
+    }
+
+    /// Records how far from optimal the found targets' costs may be, for searches
+    /// that trade path quality for speed.
+    pub fn with_suboptimality_bound(mut self, suboptimality_bound: f64) -> Self {
+        self.suboptimality_bound = suboptimality_bound;
+        self
+    }
+
+    /// Records the parents of the tiles the search reached, for searches asked to keep
+    /// them.
+    pub fn with_parents(mut self, parents: Option<MultiroomMonoFlowField>) -> Self {
+        self.parents = parents;
+        self
+    }
+
+    /// For each tile the search reached, the direction back toward the start it came
+    /// from, if the search kept them. The parents form a tree, so they're a
+    /// `MultiroomMonoFlowField` that leads back to the starts without cycles; use
+    /// `path_to_parent_tree_origin` to walk one back from a reached tile.
+    pub fn parents(&self) -> Option<&MultiroomMonoFlowField> {
+        self.parents.as_ref()
+    }
+
+    /// Records the per-direction distances of a search that charged for turns.
+    pub fn with_turn_distance_map(
+        mut self,
+        turn_distance_map: Option<MultiroomTurnDistanceMap>,
+    ) -> Self {
+        self.turn_distance_map = turn_distance_map;
+        self
+    }
+
+    /// For searches that charged for turns, the distance to each tile for every
+    /// direction it was entered from. The plain distance map holds the lowest of
+    /// these; use `path_to_turn_distance_map_origin` to walk a path back exactly.
+    pub fn turn_distance_map(&self) -> Option<&MultiroomTurnDistanceMap> {
+        self.turn_distance_map.as_ref()
+    }
+
+    /// The distance map with `u16` storage, or an error if a distance doesn't fit.
+    /// Keep a search's result this way to halve the memory it holds onto.
+    pub fn compact_distance_map(&self) -> Result<MultiroomCompactDistanceMap, &'static str> {
+        MultiroomCompactDistanceMap::try_from(&self.distance_map)
+    }
 }
 
 #[wasm_bindgen]
@@ -43,6 +119,27 @@ impl SearchResult {
         self.distance_map.clone()
     }
 
+    /// The distance map with `u16` storage. Throws if a distance is 65535 or more,
+    /// which a `max_path_cost` below that rules out.
+    #[wasm_bindgen(getter, js_name = compact_distance_map)]
+    pub fn js_compact_distance_map(&self) -> MultiroomCompactDistanceMap {
+        self.compact_distance_map()
+            .unwrap_or_else(|error| wasm_bindgen::throw_str(error))
+    }
+
+    /// The parent tree as a `MultiroomMonoFlowField`, or undefined if the search
+    /// wasn't asked to record it.
+    #[wasm_bindgen(getter, js_name = parents)]
+    pub fn js_parents(&self) -> Option<MultiroomMonoFlowField> {
+        self.parents.clone()
+    }
+
+    /// The per-direction distances, or undefined if the search didn't charge for turns.
+    #[wasm_bindgen(getter, js_name = turn_distance_map)]
+    pub fn js_turn_distance_map(&self) -> Option<MultiroomTurnDistanceMap> {
+        self.turn_distance_map.clone()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn found_targets(&self) -> Vec<u32> {
         self.found_targets
@@ -51,8 +148,25 @@ impl SearchResult {
             .collect()
     }
 
+    /// Each tile where goals were reached, as `{ position, cost, destinations, allOf,
+    /// origin }` objects. `destinations` holds indices into `all_of_destinations` if
+    /// `allOf` is set, or `any_of_destinations` otherwise. `origin` is the label of
+    /// the start the path came from, if starts were labeled.
+    #[wasm_bindgen(getter, js_name = targets)]
+    pub fn js_targets(&self) -> js_sys::Array {
+        self.targets.iter().map(JsValue::from).collect()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn ops(&self) -> usize {
         self.ops
     }
+
+    /// The found targets cost at most this many times their optimal cost, measured
+    /// against the cheapest path the search left unexplored. 1 means they're known to
+    /// be optimal, or that nothing was found.
+    #[wasm_bindgen(getter)]
+    pub fn suboptimality_bound(&self) -> f64 {
+        self.suboptimality_bound
+    }
 }
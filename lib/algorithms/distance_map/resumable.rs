@@ -0,0 +1,251 @@
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::RoomDataCache;
+use screeps::Position;
+use screeps::RoomName;
+use std::ops::Fn;
+use wasm_bindgen::prelude::*;
+
+use super::astar::{js_cost_matrix, AstarSearch};
+use super::goals::{unpack_goals, Goal, GoalTracker};
+use super::heuristics::base_heuristic_with_range;
+use super::SearchResult;
+
+/// An A* distance-map search that can be advanced a few tiles at a time.
+///
+/// This runs the same search as `astar_multiroom_distance_map`, but keeps the open list,
+/// room data and remaining targets between calls to `step`, so a large search can be
+/// spread across several ticks instead of being thrown away when it runs out of ops.
+pub struct ResumableAstarSearch<F, H>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    H: Fn(Position) -> usize,
+{
+    search: AstarSearch<'static, F, H>,
+}
+
+impl<F, H> ResumableAstarSearch<F, H>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    H: Fn(Position) -> usize,
+{
+    /// Sets up a search from the given start positions. No tiles are explored until
+    /// `step` is called, other than checking whether a start position is already a target.
+    pub fn new(
+        start: Vec<Position>,
+        get_cost_matrix: F,
+        max_rooms: usize,
+        max_path_cost: usize,
+        heuristic_fn: H,
+        any_of_destinations: Option<Vec<Goal>>,
+        all_of_destinations: Option<Vec<Goal>>,
+    ) -> Self {
+        Self {
+            search: AstarSearch::new(
+                start,
+                RoomDataCache::new(max_rooms, get_cost_matrix),
+                usize::MAX,
+                max_path_cost,
+                heuristic_fn,
+                1.0,
+                None,
+                GoalTracker::new(any_of_destinations, all_of_destinations),
+            ),
+        }
+    }
+
+    /// Returns true once the search has found its targets or run out of tiles to explore.
+    pub fn is_finished(&self) -> bool {
+        self.search.is_finished()
+    }
+
+    /// The number of tiles opened so far, across all steps.
+    pub fn ops(&self) -> usize {
+        self.search.ops()
+    }
+
+    /// Advances the search until roughly `ops` more tiles have been opened. The budget
+    /// is checked before each tile is expanded, so a step may overshoot by up to seven
+    /// tiles. Returns true if the search is finished.
+    pub fn step(&mut self, ops: usize) -> bool {
+        self.search.step(ops)
+    }
+
+    /// Consumes the search and returns the distance map explored so far. This may be
+    /// called before the search has finished to use a partial result.
+    pub fn into_result(self) -> SearchResult {
+        self.search.into_result()
+    }
+}
+
+type JsCostMatrixFn = Box<dyn Fn(RoomName) -> Option<ClockworkCostMatrix>>;
+type JsHeuristicFn = Box<dyn Fn(Position) -> usize>;
+
+/// A handle to an A* distance-map search that can be advanced across several ticks.
+#[wasm_bindgen]
+pub struct AstarSearchHandle {
+    search: Option<ResumableAstarSearch<JsCostMatrixFn, JsHeuristicFn>>,
+}
+
+#[wasm_bindgen]
+impl AstarSearchHandle {
+    /// Sets up a resumable A* search. Arguments match `js_astar_multiroom_distance_map`,
+    /// except that the ops budget is passed to each call to `step`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        start_packed: Vec<u32>,
+        get_cost_matrix: js_sys::Function,
+        max_rooms: usize,
+        max_path_cost: usize,
+        any_of_destinations: Option<Vec<u32>>,
+        all_of_destinations: Option<Vec<u32>>,
+    ) -> AstarSearchHandle {
+        let start_positions = start_packed
+            .iter()
+            .map(|pos| Position::from_packed(*pos))
+            .collect();
+
+        let any_of_destinations = any_of_destinations.map(unpack_goals);
+
+        let all_of_destinations = all_of_destinations.map(unpack_goals);
+
+        let all_destinations: Vec<Goal> = all_of_destinations
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .chain(any_of_destinations.clone().unwrap_or_default())
+            .collect();
+
+        // The heuristic outlives this call, so it owns its copy of the destinations.
+        let heuristic_fn: JsHeuristicFn =
+            Box::new(move |position| base_heuristic_with_range(&all_destinations)(position));
+        let get_cost_matrix: JsCostMatrixFn =
+            Box::new(move |room| js_cost_matrix(&get_cost_matrix, room));
+
+        AstarSearchHandle {
+            search: Some(ResumableAstarSearch::new(
+                start_positions,
+                get_cost_matrix,
+                max_rooms,
+                max_path_cost,
+                heuristic_fn,
+                any_of_destinations,
+                all_of_destinations,
+            )),
+        }
+    }
+
+    /// Advances the search by roughly `ops` tiles. Returns true if the search is finished.
+    pub fn step(&mut self, ops: usize) -> bool {
+        match &mut self.search {
+            Some(search) => search.step(ops),
+            None => wasm_bindgen::throw_str("Search has already been finalized or cancelled"),
+        }
+    }
+
+    /// Whether the search has finished (or was cancelled).
+    #[wasm_bindgen(getter)]
+    pub fn finished(&self) -> bool {
+        self.search
+            .as_ref()
+            .map(|search| search.is_finished())
+            .unwrap_or(true)
+    }
+
+    /// The number of tiles opened so far.
+    #[wasm_bindgen(getter)]
+    pub fn ops(&self) -> usize {
+        self.search.as_ref().map(|search| search.ops()).unwrap_or(0)
+    }
+
+    /// Returns the search result, releasing the search state. If the search has not
+    /// finished, the result contains the partial distance map explored so far.
+    pub fn finalize(&mut self) -> SearchResult {
+        match self.search.take() {
+            Some(search) => search.into_result(),
+            None => wasm_bindgen::throw_str("Search has already been finalized or cancelled"),
+        }
+    }
+
+    /// Stops the search and releases its state without producing a result.
+    pub fn cancel(&mut self) {
+        self.search = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::astar::astar_multiroom_distance_map;
+    use crate::test_fixtures::pos;
+
+    fn plain_rooms(_: RoomName) -> Option<ClockworkCostMatrix> {
+        Some(ClockworkCostMatrix::new(Some(1)))
+    }
+
+    #[test]
+    fn stepped_search_matches_single_search() {
+        let start = pos("W1N1", 10, 10);
+        let target = pos("W3N2", 40, 40);
+        let goals = vec![Goal::within(target, 1)];
+
+        let single = astar_multiroom_distance_map(
+            vec![start],
+            plain_rooms,
+            9,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&goals),
+            Some(goals.clone()),
+            None,
+        );
+
+        let mut search = ResumableAstarSearch::new(
+            vec![start],
+            plain_rooms,
+            9,
+            usize::MAX,
+            base_heuristic_with_range(&goals),
+            Some(goals.clone()),
+            None,
+        );
+        let mut steps = 0;
+        while !search.step(25) {
+            steps += 1;
+        }
+        assert!(steps > 1);
+        assert!(search.is_finished());
+        let stepped = search.into_result();
+
+        assert_eq!(stepped.found_targets, single.found_targets);
+        assert_eq!(stepped.ops(), single.ops());
+        let found = stepped.found_targets[0];
+        assert_eq!(
+            stepped.distance_map().get(found),
+            single.distance_map().get(found)
+        );
+    }
+
+    #[test]
+    fn partial_result_contains_explored_tiles() {
+        let start = pos("W1N1", 10, 10);
+        let target = pos("W1N1", 40, 40);
+        let goals = vec![Goal::within(target, 0)];
+
+        let mut search = ResumableAstarSearch::new(
+            vec![start],
+            plain_rooms,
+            1,
+            usize::MAX,
+            base_heuristic_with_range(&goals),
+            Some(goals.clone()),
+            None,
+        );
+
+        assert!(!search.step(5));
+        assert!(!search.is_finished());
+        let result = search.into_result();
+        assert!(result.found_targets.is_empty());
+        assert_eq!(result.distance_map().get(start), 0);
+        assert_eq!(result.distance_map().get(target), usize::MAX);
+    }
+}
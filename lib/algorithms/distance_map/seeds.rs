@@ -0,0 +1,156 @@
+use screeps::Position;
+
+/// A search origin with a head start. The search treats `position` as already
+/// `initial_cost` away, so an origin that's busy (a spawn with a queue, a container
+/// that's nearly empty) only wins tiles it can still reach sooner than the others.
+/// The `label`, if any, is reported with the targets reached from this origin.
+///
+/// Flow fields built from the result lead to whichever origin is best after its head
+/// start. `path_to_multiroom_distance_map_origin` still stops only at a distance of 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Seed {
+    pub position: Position,
+    pub initial_cost: usize,
+    pub label: Option<u32>,
+}
+
+impl Seed {
+    pub fn new(position: Position, initial_cost: usize) -> Self {
+        Self {
+            position,
+            initial_cost,
+            label: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: u32) -> Self {
+        self.label = Some(label);
+        self
+    }
+}
+
+impl From<Position> for Seed {
+    fn from(position: Position) -> Self {
+        Self::new(position, 0)
+    }
+}
+
+/// Unpacks start positions from JS, with optional initial costs and labels given
+/// in the same order. Missing costs default to 0.
+pub(crate) fn unpack_seeds(
+    start_packed: Vec<u32>,
+    start_costs: Option<Vec<u32>>,
+    start_labels: Option<Vec<u32>>,
+) -> Vec<Seed> {
+    let start_costs = start_costs.unwrap_or_default();
+    start_packed
+        .iter()
+        .enumerate()
+        .map(|(i, packed)| {
+            let seed = Seed::new(
+                Position::from_packed(*packed),
+                start_costs.get(i).copied().unwrap_or(0) as usize,
+            );
+            match start_labels.as_ref().and_then(|labels| labels.get(i)) {
+                Some(label) => seed.with_label(*label),
+                None => seed,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::astar::astar_multiroom_distance_map;
+    use crate::algorithms::distance_map::breadth_first_search::bfs_multiroom_distance_map;
+    use crate::algorithms::distance_map::goals::Goal;
+    use crate::algorithms::flow_field::multiroom_flow_field::multiroom_flow_field;
+    use crate::algorithms::map::DirectionOrder;
+    use crate::algorithms::path::to_multiroom_flow_field_origin::path_to_multiroom_flow_field_origin;
+    use crate::datatypes::{ClockworkCostMatrix, Path};
+    use screeps::{RoomCoordinate, RoomName};
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            "W1N1".parse().unwrap(),
+        )
+    }
+
+    fn plains(_: RoomName) -> Option<ClockworkCostMatrix> {
+        Some(ClockworkCostMatrix::new(Some(1)))
+    }
+
+    fn last(path: &Path) -> Position {
+        *path.get(path.len() - 1).unwrap()
+    }
+
+    fn seeds() -> Vec<Seed> {
+        vec![
+            Seed::new(pos(10, 25), 0).with_label(1),
+            Seed::new(pos(40, 25), 10).with_label(2),
+        ]
+    }
+
+    #[test]
+    fn astar_distances_include_the_head_start() {
+        let result = astar_multiroom_distance_map(
+            seeds(),
+            plains,
+            1,
+            10_000,
+            10_000,
+            |_| 0,
+            Some(vec![Goal::within(pos(35, 25), 0)]),
+            None,
+        );
+
+        let target = &result.targets()[0];
+        assert_eq!(target.position, pos(35, 25));
+        assert_eq!(target.cost, 15);
+        assert_eq!(target.origin, Some(2));
+
+        let distance_map = result.distance_map();
+        assert_eq!(distance_map.get(pos(40, 25)), 10);
+        assert_eq!(distance_map.get(pos(20, 25)), 10);
+        assert_eq!(distance_map.get(pos(37, 25)), 13);
+    }
+
+    #[test]
+    fn bfs_releases_seeds_once_the_frontier_catches_up() {
+        let mut seeds = seeds();
+        // Too slow to matter: the first seed gets here sooner.
+        seeds.push(Seed::new(pos(12, 25), 50).with_label(3));
+
+        let result = bfs_multiroom_distance_map(
+            seeds,
+            plains,
+            10_000,
+            1,
+            10_000,
+            Some(vec![Goal::within(pos(39, 40), 0)]),
+            None,
+        );
+
+        let distance_map = result.distance_map();
+        assert_eq!(distance_map.get(pos(12, 25)), 2);
+        assert_eq!(distance_map.get(pos(39, 25)), 11);
+        assert_eq!(distance_map.get(pos(25, 25)), 15);
+        assert_eq!(result.targets()[0].cost, 25);
+        assert_eq!(result.targets()[0].origin, Some(2));
+    }
+
+    #[test]
+    fn flow_fields_lead_to_the_best_adjusted_origin() {
+        let result = bfs_multiroom_distance_map(seeds(), plains, 10_000, 1, 10_000, None, None);
+        let flow_field =
+            multiroom_flow_field(&result.distance_map(), DirectionOrder::CardinalFirst);
+
+        let path = path_to_multiroom_flow_field_origin(pos(33, 25), &flow_field).unwrap();
+        assert_eq!(last(&path), pos(40, 25));
+        let path = path_to_multiroom_flow_field_origin(pos(27, 25), &flow_field).unwrap();
+        assert_eq!(last(&path), pos(10, 25));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::algorithms::map::{corresponding_room_edge, next_directions, same_room_neighbor};
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::MultiroomTurnDistanceMap;
+use crate::datatypes::OpenList;
+use crate::datatypes::RoomDataCache;
+use crate::utils::set_panic_hook;
+use screeps::Direction;
+use screeps::Position;
+use screeps::RoomName;
+use std::collections::HashSet;
+
+use super::goals::GoalTracker;
+use super::seeds::Seed;
+use super::SearchResult;
+
+#[derive(Copy, Clone)]
+struct State {
+    // The cost to reach the current position.
+    g_score: usize,
+    // The current position.
+    position: Position,
+    // The direction the current position was entered in, or None for a start position.
+    direction: Option<Direction>,
+    // The index of the position's room in the room data cache.
+    room_key: usize,
+    // The label of the start position this path came from.
+    label: Option<u32>,
+}
+
+/// Creates a distance map for the given start positions like `astar_multiroom_distance_map`,
+/// but charging `turn_penalty` each time a path changes direction, which favors
+/// straighter paths with fewer zigzags. Each tile is searched once per direction it can
+/// be entered from, and those distances are kept in the result's
+/// `turn_distance_map`; the plain distance map holds the lowest for each tile.
+///
+/// Paths are found with `path_to_turn_distance_map_origin`. Weights above 1 make the
+/// search greedier, like `weighted_astar_multiroom_distance_map`, and the result
+/// reports the suboptimality bound actually achieved.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn turn_penalty_search<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    start: Vec<impl Into<Seed>>,
+    mut cached_room_data: RoomDataCache<F>,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    heuristic_weight: f64,
+    turn_penalty: usize,
+    mut goals: GoalTracker,
+) -> SearchResult {
+    set_panic_hook();
+    let mut open = OpenList::new();
+    let mut tiles_remaining = max_ops;
+    let mut turn_distance_map = MultiroomTurnDistanceMap::new(turn_penalty);
+    let mut settled = HashSet::new();
+
+    let start: Vec<Seed> = start.into_iter().map(Into::into).collect();
+
+    for seed in start.iter() {
+        if goals.reach(seed.position, seed.initial_cost, seed.label) {
+            turn_distance_map.set(seed.position, None, seed.initial_cost);
+            return goals
+                .into_result(cached_room_data.into(), max_ops - tiles_remaining)
+                .with_turn_distance_map(Some(turn_distance_map));
+        }
+    }
+
+    for seed in start {
+        let room_key = cached_room_data.get_room_key(seed.position.room_name());
+        if let Some(room_key) = room_key {
+            if turn_distance_map.get(seed.position, None) <= seed.initial_cost {
+                continue;
+            }
+            open.push(
+                seed.initial_cost,
+                State {
+                    g_score: seed.initial_cost,
+                    position: seed.position,
+                    direction: None,
+                    room_key,
+                    label: seed.label,
+                },
+            );
+            turn_distance_map.set(seed.position, None, seed.initial_cost);
+            let distance = &mut cached_room_data[room_key].distance_map[seed.position.xy()];
+            *distance = (*distance).min(seed.initial_cost);
+            tiles_remaining -= 1;
+        }
+    }
+
+    'search: while let Some(State {
+        g_score,
+        position,
+        direction,
+        room_key,
+        label,
+    }) = open.pop()
+    {
+        if turn_distance_map.get(position, direction) < g_score {
+            continue;
+        }
+
+        // A tile is settled by the first of its directions to come off the open list.
+        // Goals are checked here rather than when tiles are reached, because a path
+        // reaching a tile first may still turn more often than one found later.
+        if settled.insert(position)
+            && (goals.reach(position, g_score, label) || goals.settle(position, g_score, label))
+        {
+            break 'search;
+        }
+
+        if g_score >= max_path_cost {
+            continue;
+        }
+
+        let current_room_name = cached_room_data[room_key].room_name;
+
+        // Turns can make it cheaper to reach a neighbor through this tile than around
+        // it, so every direction is considered.
+        for neighbor_direction in next_directions(position, None) {
+            let step_pos = match same_room_neighbor(position, *neighbor_direction) {
+                Some(pos) => pos,
+                None => continue,
+            };
+
+            let terrain_cost = match &cached_room_data[room_key].cost_matrix {
+                Some(cost_matrix) => match cost_matrix.get(step_pos.xy()) {
+                    255 => continue,
+                    terrain_cost => terrain_cost,
+                },
+                None => continue,
+            };
+
+            let neighbor = corresponding_room_edge(step_pos);
+
+            let (neighbor_room_key, terrain_cost) = if neighbor.room_name() == current_room_name {
+                (room_key, terrain_cost)
+            } else {
+                let Some(key) = cached_room_data.get_room_key(neighbor.room_name()) else {
+                    continue;
+                };
+                match cached_room_data.crossing_cost(terrain_cost, key, neighbor) {
+                    Some(cost) => (key, cost),
+                    None => continue,
+                }
+            };
+
+            let next_cost = g_score
+                .saturating_add(terrain_cost as usize)
+                .saturating_add(turn_distance_map.turn_cost(direction, *neighbor_direction));
+
+            if turn_distance_map.get(neighbor, Some(*neighbor_direction)) <= next_cost {
+                continue;
+            }
+
+            let h_score = (heuristic_fn(neighbor) as f64 * heuristic_weight) as usize;
+            open.push(
+                next_cost.saturating_add(h_score),
+                State {
+                    g_score: next_cost,
+                    position: neighbor,
+                    direction: Some(*neighbor_direction),
+                    room_key: neighbor_room_key,
+                    label,
+                },
+            );
+            turn_distance_map.set(neighbor, Some(*neighbor_direction), next_cost);
+            tiles_remaining -= 1;
+
+            // The tile's plain distance is its cheapest direction.
+            let distance = &mut cached_room_data[neighbor_room_key].distance_map[neighbor.xy()];
+            *distance = (*distance).min(next_cost);
+
+            if tiles_remaining == 0 {
+                break 'search;
+            }
+        }
+    }
+
+    let suboptimality_bound = suboptimality_bound(
+        &goals,
+        &cached_room_data,
+        &turn_distance_map,
+        &open,
+        heuristic_fn,
+    );
+    goals
+        .into_result(cached_room_data.into(), max_ops - tiles_remaining)
+        .with_suboptimality_bound(suboptimality_bound)
+        .with_turn_distance_map(Some(turn_distance_map))
+}
+
+/// The suboptimality the search actually achieved, like `AstarSearch` reports. Every
+/// path that hasn't been fully explored passes through a state still in the open list,
+/// and turns only add cost, so the lowest unweighted f_score there is a lower bound on
+/// the optimal cost.
+///
+/// Returns 1 if no target was found.
+fn suboptimality_bound<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    goals: &GoalTracker,
+    cached_room_data: &RoomDataCache<F>,
+    turn_distance_map: &MultiroomTurnDistanceMap,
+    open: &OpenList<State>,
+    heuristic_fn: impl Fn(Position) -> usize,
+) -> f64 {
+    let found_cost = goals
+        .found_targets()
+        .iter()
+        .filter_map(|target| {
+            let room_key = cached_room_data.get_loaded_room_key(target.room_name())?;
+            Some(cached_room_data[room_key].distance_map[target.xy()])
+        })
+        .max();
+    let Some(found_cost) = found_cost else {
+        return 1.0;
+    };
+
+    // Stale states have a cheaper path to their tile and direction elsewhere in the list.
+    let lower_bound = open
+        .iter()
+        .filter(|state| turn_distance_map.get(state.position, state.direction) == state.g_score)
+        .map(|state| state.g_score.saturating_add(heuristic_fn(state.position)))
+        .min()
+        .unwrap_or(usize::MAX);
+
+    if lower_bound >= found_cost {
+        1.0
+    } else {
+        found_cost as f64 / lower_bound.max(1) as f64
+    }
+}
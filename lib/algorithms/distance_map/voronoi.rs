@@ -0,0 +1,347 @@
+use crate::algorithms::map::{
+    corresponding_room_edge, neighbors, preferred_directions, same_room_neighbor, DirectionOrder,
+};
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::MultiroomLabelMap;
+use crate::datatypes::OpenList;
+use crate::datatypes::RoomDataCache;
+use crate::datatypes::NO_LABEL;
+use crate::utils::set_panic_hook;
+use screeps::Position;
+use screeps::RoomName;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+use super::astar::js_cost_matrix;
+use super::seeds::{unpack_seeds, Seed};
+
+#[derive(Copy, Clone)]
+struct State {
+    g_score: usize,
+    position: Position,
+    room_key: usize,
+    label: u32,
+}
+
+/// A distance map from several origins, plus the origin that owns each tile.
+#[wasm_bindgen]
+pub struct VoronoiResult {
+    distance_map: MultiroomDistanceMap,
+    labels: MultiroomLabelMap,
+    ops: usize,
+}
+
+impl VoronoiResult {
+    pub fn labels(&self) -> &MultiroomLabelMap {
+        &self.labels
+    }
+
+    /// The tiles owned by each origin, sorted by packed position.
+    pub fn territories(&self) -> BTreeMap<u32, Vec<Position>> {
+        let mut territories: BTreeMap<u32, Vec<Position>> = BTreeMap::new();
+        for (position, label) in self.labels.labeled_positions() {
+            territories.entry(label).or_default().push(position);
+        }
+        for tiles in territories.values_mut() {
+            tiles.sort_by_key(|position| position.packed_repr());
+        }
+        territories
+    }
+
+    /// Owned tiles next to a tile owned by a different origin, sorted by packed
+    /// position. Both sides of a border are included.
+    pub fn boundary_tiles(&self) -> Vec<Position> {
+        let mut boundary: Vec<Position> = self
+            .labels
+            .labeled_positions()
+            .filter(|(position, label)| {
+                neighbors(*position, DirectionOrder::CardinalFirst).any(|neighbor| {
+                    let neighbor_label = self.labels.get(neighbor);
+                    neighbor_label != NO_LABEL && neighbor_label != *label
+                })
+            })
+            .map(|(position, _)| position)
+            .collect();
+        boundary.sort_by_key(|position| position.packed_repr());
+        boundary
+    }
+}
+
+#[wasm_bindgen]
+impl VoronoiResult {
+    #[wasm_bindgen(getter)]
+    pub fn distance_map(&self) -> MultiroomDistanceMap {
+        self.distance_map.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = labels)]
+    pub fn js_labels(&self) -> MultiroomLabelMap {
+        self.labels.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ops(&self) -> usize {
+        self.ops
+    }
+
+    /// The packed positions owned by the origin with this label.
+    #[wasm_bindgen(js_name = territory)]
+    pub fn js_territory(&self, label: u32) -> Vec<u32> {
+        self.territories()
+            .remove(&label)
+            .unwrap_or_default()
+            .iter()
+            .map(|position| position.packed_repr())
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = boundaryTiles)]
+    pub fn js_boundary_tiles(&self) -> Vec<u32> {
+        self.boundary_tiles()
+            .iter()
+            .map(|position| position.packed_repr())
+            .collect()
+    }
+}
+
+/// Creates a distance map from several origins with Dijkstra's algorithm, labeling
+/// each tile with the origin it's closest to (after any head start, see `Seed`).
+/// Origins without a label are labeled by their index in `start`.
+///
+/// Ties go to the lowest label, so the result doesn't depend on the order tiles
+/// are explored in.
+pub fn voronoi_multiroom_distance_map(
+    start: Vec<Seed>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+) -> VoronoiResult {
+    set_panic_hook();
+    let mut open = OpenList::new();
+    let mut tiles_remaining = max_ops;
+    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
+    let mut labels = MultiroomLabelMap::new();
+
+    for (index, seed) in start.into_iter().enumerate() {
+        let label = seed.label.unwrap_or(index as u32);
+        let Some(room_key) = cached_room_data.get_room_key(seed.position.room_name()) else {
+            continue;
+        };
+        let distance = cached_room_data[room_key].distance_map[seed.position.xy()];
+        if !improves(
+            distance,
+            labels.get(seed.position),
+            seed.initial_cost,
+            label,
+        ) {
+            continue;
+        }
+        if distance == usize::MAX {
+            tiles_remaining = tiles_remaining.saturating_sub(1);
+        }
+        open.push(
+            seed.initial_cost,
+            State {
+                g_score: seed.initial_cost,
+                position: seed.position,
+                room_key,
+                label,
+            },
+        );
+        cached_room_data[room_key].distance_map[seed.position.xy()] = seed.initial_cost;
+        labels.set(seed.position, label);
+    }
+
+    'search: while let Some(State {
+        g_score,
+        position,
+        room_key,
+        label,
+    }) = open.pop()
+    {
+        // Skip states that were since beaten, by cost or by a lower label.
+        if cached_room_data[room_key].distance_map[position.xy()] != g_score
+            || labels.get(position) != label
+        {
+            continue;
+        }
+
+        if g_score >= max_path_cost {
+            continue;
+        }
+
+        let current_room_name = cached_room_data[room_key].room_name;
+
+        for direction in preferred_directions(DirectionOrder::CardinalFirst) {
+            let step_pos = match same_room_neighbor(position, direction) {
+                Some(pos) => pos,
+                None => continue,
+            };
+
+            let terrain_cost = match &cached_room_data[room_key].cost_matrix {
+                Some(cost_matrix) => cost_matrix.get(step_pos.xy()),
+                None => continue,
+            };
+            if terrain_cost == 255 {
+                continue;
+            }
+
+            let neighbor = corresponding_room_edge(step_pos);
+
+            let (neighbor_room_key, terrain_cost) = if neighbor.room_name() == current_room_name {
+                (room_key, terrain_cost)
+            } else {
+                let Some(key) = cached_room_data.get_room_key(neighbor.room_name()) else {
+                    continue;
+                };
+                match cached_room_data.crossing_cost(terrain_cost, key, neighbor) {
+                    Some(cost) => (key, cost),
+                    None => continue,
+                }
+            };
+
+            let next_cost = g_score.saturating_add(terrain_cost as usize);
+            let neighbor_xy = neighbor.xy();
+            let distance = cached_room_data[neighbor_room_key].distance_map[neighbor_xy];
+            if !improves(distance, labels.get(neighbor), next_cost, label) {
+                continue;
+            }
+
+            open.push(
+                next_cost,
+                State {
+                    g_score: next_cost,
+                    position: neighbor,
+                    room_key: neighbor_room_key,
+                    label,
+                },
+            );
+            cached_room_data[neighbor_room_key].distance_map[neighbor_xy] = next_cost;
+            labels.set(neighbor, label);
+            if distance == usize::MAX {
+                tiles_remaining -= 1;
+                if tiles_remaining == 0 {
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    VoronoiResult {
+        distance_map: cached_room_data.into(),
+        labels,
+        ops: max_ops - tiles_remaining,
+    }
+}
+
+/// Whether reaching a tile at `cost` from the origin `label` beats what it has: a
+/// cheaper path, or an equally cheap one from a lower label.
+#[inline]
+fn improves(distance: usize, current_label: u32, cost: usize, label: u32) -> bool {
+    cost < distance || (cost == distance && label < current_label)
+}
+
+/// WASM wrapper for `voronoi_multiroom_distance_map`. `start_costs` and
+/// `start_labels`, if given, are per-start head starts and labels.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_voronoi_multiroom_distance_map(
+    start_packed: Vec<u32>,
+    get_cost_matrix: &js_sys::Function,
+    max_ops: usize,
+    max_rooms: usize,
+    max_path_cost: usize,
+    start_costs: Option<Vec<u32>>,
+    start_labels: Option<Vec<u32>>,
+) -> VoronoiResult {
+    voronoi_multiroom_distance_map(
+        unpack_seeds(start_packed, start_costs, start_labels),
+        |room| js_cost_matrix(get_cost_matrix, room),
+        max_ops,
+        max_rooms,
+        max_path_cost,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::pos;
+
+    fn plains(_: RoomName) -> Option<ClockworkCostMatrix> {
+        Some(ClockworkCostMatrix::new(Some(1)))
+    }
+
+    #[test]
+    fn tiles_belong_to_the_nearest_origin() {
+        let result = voronoi_multiroom_distance_map(
+            vec![
+                Seed::new(pos("W1N1", 10, 25), 0).with_label(7),
+                Seed::new(pos("W1N1", 30, 25), 4).with_label(3),
+            ],
+            plains,
+            10_000,
+            1,
+            10_000,
+        );
+
+        let labels = result.labels();
+        assert_eq!(labels.get(pos("W1N1", 21, 25)), 7);
+        assert_eq!(labels.get(pos("W1N1", 23, 25)), 3);
+        assert_eq!(result.distance_map().get(pos("W1N1", 23, 25)), 11);
+        // Both origins are 12 away (counting the head start): the lower label wins.
+        assert_eq!(labels.get(pos("W1N1", 22, 25)), 3);
+        assert_eq!(result.distance_map().get(pos("W1N1", 22, 25)), 12);
+    }
+
+    #[test]
+    fn ties_do_not_depend_on_start_order() {
+        let seeds = [
+            Seed::new(pos("W1N1", 10, 10), 0),
+            Seed::new(pos("W1N1", 20, 20), 0),
+            Seed::new(pos("W1N1", 10, 30), 0),
+        ];
+        let labeled: Vec<Seed> = seeds
+            .iter()
+            .enumerate()
+            .map(|(index, seed)| seed.with_label(index as u32))
+            .collect();
+        let reversed: Vec<Seed> = labeled.iter().rev().copied().collect();
+
+        let forward = voronoi_multiroom_distance_map(labeled, plains, 10_000, 1, 10_000);
+        let backward = voronoi_multiroom_distance_map(reversed, plains, 10_000, 1, 10_000);
+
+        assert_eq!(forward.territories(), backward.territories());
+        assert_eq!(forward.boundary_tiles(), backward.boundary_tiles());
+    }
+
+    #[test]
+    fn territories_and_boundaries_cover_the_room() {
+        let result = voronoi_multiroom_distance_map(
+            vec![
+                Seed::new(pos("W1N1", 10, 25), 0),
+                Seed::new(pos("W1N1", 40, 25), 0),
+            ],
+            plains,
+            10_000,
+            1,
+            10_000,
+        );
+
+        let territories = result.territories();
+        assert_eq!(territories.len(), 2);
+        // Edge tiles belong to the neighboring rooms, which are out of range here.
+        assert_eq!(territories[&0].len() + territories[&1].len(), 48 * 48);
+        assert!(territories[&0].contains(&pos("W1N1", 1, 1)));
+        assert!(territories[&1].contains(&pos("W1N1", 48, 48)));
+
+        // Tiles 15 from both origins go to origin 0; the border runs between x = 25 and 26.
+        let boundary = result.boundary_tiles();
+        assert!(boundary.contains(&pos("W1N1", 25, 25)));
+        assert!(boundary.contains(&pos("W1N1", 26, 25)));
+        assert!(!boundary.contains(&pos("W1N1", 24, 25)));
+        assert!(!boundary.contains(&pos("W1N1", 27, 25)));
+    }
+}
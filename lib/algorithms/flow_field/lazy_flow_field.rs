@@ -0,0 +1,362 @@
+use crate::algorithms::map::{js_direction_priority, DirectionOrder, DirectionPriority};
+use crate::datatypes::configured_portal_index;
+use crate::datatypes::direction_bit;
+use crate::datatypes::FlowField;
+use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::PortalIndex;
+use crate::utils::set_panic_hook;
+use screeps::constants::extra::ROOM_AREA;
+use screeps::{linear_index_to_xy, Direction, Position, RoomName};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+use super::{flow_directions, portal_flow_directions};
+
+/// A flow field that works out each tile's directions from a distance map when it's
+/// asked for them, instead of building every room up front. A creep following a
+/// route only pays for the tiles it actually stands on.
+///
+/// Reads match `multiroomFlowField` (or `multiroomPortalFlowField` with portals),
+/// and values written through `set`, `setDirections` or `addDirection` take
+/// precedence over computed ones.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct MultiroomLazyFlowField {
+    distance_map: MultiroomDistanceMap,
+    direction_order: DirectionPriority,
+    portal_index: Option<Rc<PortalIndex>>,
+    memoize: bool,
+    overrides: HashMap<Position, u8>,
+    memo: RefCell<HashMap<Position, u8>>,
+}
+
+impl MultiroomLazyFlowField {
+    /// Creates a lazy flow field over the given distance map.
+    pub fn new(
+        distance_map: MultiroomDistanceMap,
+        direction_order: impl Into<DirectionPriority>,
+    ) -> Self {
+        MultiroomLazyFlowField {
+            distance_map,
+            direction_order: direction_order.into(),
+            portal_index: None,
+            memoize: false,
+            overrides: HashMap::new(),
+            memo: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Follows portals in the given index, like `multiroom_portal_flow_field_with_index`.
+    /// The index is shared rather than copied, so fields can keep the configured portals
+    /// (see `configured_portal_index`).
+    pub fn with_portal_index(mut self, portal_index: Rc<PortalIndex>) -> Self {
+        self.portal_index = Some(portal_index);
+        self
+    }
+
+    /// Keeps each computed tile, so repeated reads don't recompute it.
+    pub fn with_memoization(mut self, memoize: bool) -> Self {
+        self.memoize = memoize;
+        self
+    }
+
+    /// Gets the flow field value at a given position
+    pub fn get(&self, pos: Position) -> u8 {
+        if let Some(value) = self.overrides.get(&pos) {
+            return *value;
+        }
+        if let Some(value) = self.memo.borrow().get(&pos) {
+            return *value;
+        }
+
+        let value = self.compute(pos);
+        if self.memoize {
+            self.memo.borrow_mut().insert(pos, value);
+        }
+        value
+    }
+
+    /// Sets the flow field value at a given position
+    pub fn set(&mut self, pos: Position, value: u8) {
+        self.overrides.insert(pos, value);
+    }
+
+    /// Gets the list of valid directions at a given position across rooms
+    pub fn get_directions(&self, pos: Position) -> Vec<Direction> {
+        let value = self.get(pos);
+        self.direction_order
+            .directions()
+            .iter()
+            .cloned()
+            .filter(|direction| value & direction_bit(*direction) != 0)
+            .collect()
+    }
+
+    /// Gets the first valid direction at a given position, respecting the tie-break order.
+    pub fn get_first_direction(&self, pos: Position) -> Option<Direction> {
+        let value = self.get(pos);
+        self.direction_order
+            .directions()
+            .iter()
+            .cloned()
+            .find(|direction| value & direction_bit(*direction) != 0)
+    }
+
+    /// Sets the list of valid directions at a given position across rooms
+    pub fn set_directions(&mut self, pos: Position, directions: Vec<Direction>) {
+        let value = directions
+            .into_iter()
+            .fold(0, |value, direction| value | direction_bit(direction));
+        self.set(pos, value);
+    }
+
+    /// Adds a direction to the list of valid directions at a given position across rooms
+    pub fn add_direction(&mut self, pos: Position, direction: Direction) {
+        let value = self.get(pos);
+        self.set(pos, value | direction_bit(direction));
+    }
+
+    /// Whether a creep standing on the position has reached the origin. Only portal
+    /// fields mark terminals, matching `multiroom_portal_flow_field_with_index`.
+    pub fn is_terminal(&self, pos: Position) -> bool {
+        self.portal_index.is_some() && self.distance_map.get(pos) == 0
+    }
+
+    /// Gets the list of rooms the field has values for
+    pub fn rooms(&self) -> Vec<RoomName> {
+        let mut rooms = self.distance_map.rooms();
+        for pos in self.overrides.keys() {
+            if !rooms.contains(&pos.room_name()) {
+                rooms.push(pos.room_name());
+            }
+        }
+        rooms
+    }
+
+    /// Builds the full FlowField for a given room, if the field has values for it
+    pub fn get_room_map(&self, room_name: RoomName) -> Option<FlowField> {
+        if !self.rooms().contains(&room_name) {
+            return None;
+        }
+
+        let mut flow_field = FlowField::new_with_direction_order(self.direction_order);
+        for index in 0..ROOM_AREA {
+            let xy = linear_index_to_xy(index);
+            flow_field.set(xy.x, xy.y, self.get(Position::new(xy.x, xy.y, room_name)));
+        }
+        Some(flow_field)
+    }
+
+    fn compute(&self, pos: Position) -> u8 {
+        let Some(room_map) = self.distance_map.get_room_map(pos.room_name()) else {
+            return 0;
+        };
+
+        match &self.portal_index {
+            None => flow_directions(&self.distance_map, room_map, pos, self.direction_order)
+                .fold(0, |value, direction| value | direction_bit(direction)),
+            Some(_) if room_map[pos.xy()] == 0 => 0,
+            Some(portal_index) => {
+                portal_flow_directions(room_map, pos, self.direction_order, portal_index)
+                    .fold(0, |value, direction| value | direction_bit(direction))
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl MultiroomLazyFlowField {
+    /// Creates a lazy flow field over the given distance map (JavaScript constructor).
+    /// The field takes the distance map over instead of copying it, so the JavaScript
+    /// object passed in can't be used afterwards. With `portals`, the currently
+    /// configured portals are followed, and later `set_portals` calls don't change them.
+    /// `custom_directions`, if given, breaks ties in that order instead of
+    /// `direction_order`.
+    #[wasm_bindgen(constructor)]
+    pub fn js_new(
+        distance_map: MultiroomDistanceMap,
+        direction_order: DirectionOrder,
+        portals: Option<bool>,
+        memoize: Option<bool>,
+        custom_directions: Option<Vec<Direction>>,
+    ) -> Self {
+        set_panic_hook();
+        let direction_order = js_direction_priority(direction_order, custom_directions);
+        let mut flow_field =
+            Self::new(distance_map, direction_order).with_memoization(memoize.unwrap_or(false));
+        if portals.unwrap_or(false) {
+            flow_field = flow_field.with_portal_index(configured_portal_index());
+        }
+        flow_field
+    }
+
+    /// Gets the flow field value at a given position
+    #[wasm_bindgen(js_name = get)]
+    pub fn js_get(&self, packed_pos: u32) -> u8 {
+        let pos = Position::from_packed(packed_pos);
+        self.get(pos)
+    }
+
+    /// Sets the flow field value at a given position
+    #[wasm_bindgen(js_name = set)]
+    pub fn js_set(&mut self, packed_pos: u32, value: u8) {
+        let pos = Position::from_packed(packed_pos);
+        self.set(pos, value);
+    }
+
+    /// Gets the list of rooms in the flow field
+    #[wasm_bindgen(js_name = getRooms)]
+    pub fn js_get_rooms(&self) -> Vec<u16> {
+        self.rooms().iter().map(|k| k.packed_repr()).collect()
+    }
+
+    /// Gets the FlowField for a given room, computing every tile in it
+    #[wasm_bindgen(js_name = getRoom)]
+    pub fn js_get_room(&self, room_name: u16) -> Option<FlowField> {
+        let room_name = RoomName::from_packed(room_name);
+        self.get_room_map(room_name)
+    }
+
+    /// Gets the list of valid directions at a given position (JavaScript)
+    #[wasm_bindgen(js_name = getDirections)]
+    pub fn js_get_directions(&self, packed_pos: u32) -> Vec<Direction> {
+        let pos = Position::from_packed(packed_pos);
+        self.get_directions(pos)
+    }
+
+    /// Sets the list of valid directions at a given position (JavaScript)
+    #[wasm_bindgen(js_name = setDirections)]
+    pub fn js_set_directions(&mut self, packed_pos: u32, directions: Vec<Direction>) {
+        let pos = Position::from_packed(packed_pos);
+        self.set_directions(pos, directions);
+    }
+
+    /// Adds a direction to the list of valid directions at a given position (JavaScript)
+    #[wasm_bindgen(js_name = addDirection)]
+    pub fn js_add_direction(&mut self, packed_pos: u32, direction: Direction) {
+        let pos = Position::from_packed(packed_pos);
+        self.add_direction(pos, direction);
+    }
+
+    /// Gets the first valid direction at a given position (JavaScript)
+    #[wasm_bindgen(js_name = getFirstDirection)]
+    pub fn js_get_first_direction(&self, packed_pos: u32) -> Option<Direction> {
+        let pos = Position::from_packed(packed_pos);
+        self.get_first_direction(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::algorithms::flow_field::multiroom_flow_field::{
+        multiroom_flow_field, multiroom_portal_flow_field_with_index,
+    };
+    use crate::datatypes::ClockworkCostMatrix;
+    use crate::test_fixtures::pos;
+
+    /// Plains with a wall down the middle and a portal on a wall tile, so the search
+    /// leaves it unreached.
+    fn distance_map() -> MultiroomDistanceMap {
+        dijkstra_multiroom_distance_map(
+            vec![pos("W1N1", 10, 25)],
+            |room_name: RoomName| {
+                let mut matrix = ClockworkCostMatrix::new(Some(1));
+                if room_name == "W1N1".parse::<RoomName>().unwrap() {
+                    for y in 5..45 {
+                        matrix.set(pos("W1N1", 25, y).xy(), 255);
+                    }
+                }
+                Some(matrix)
+            },
+            usize::MAX,
+            3,
+            usize::MAX,
+            None,
+            None,
+        )
+        .distance_map()
+    }
+
+    fn every_tile(distance_map: &MultiroomDistanceMap) -> impl Iterator<Item = Position> {
+        distance_map.rooms().into_iter().flat_map(|room_name| {
+            (0..ROOM_AREA).map(move |index| {
+                let xy = linear_index_to_xy(index);
+                Position::new(xy.x, xy.y, room_name)
+            })
+        })
+    }
+
+    #[test]
+    fn lazy_fields_match_built_fields() {
+        let distance_map = distance_map();
+        for order in [DirectionOrder::CardinalFirst, DirectionOrder::DiagonalFirst] {
+            let flow_field = multiroom_flow_field(&distance_map, order);
+            let lazy_flow_field = MultiroomLazyFlowField::new(distance_map.clone(), order);
+            for pos in every_tile(&distance_map) {
+                assert_eq!(lazy_flow_field.get(pos), flow_field.get(pos), "{}", pos);
+                assert_eq!(
+                    lazy_flow_field.get_first_direction(pos),
+                    flow_field.get_directions(pos).first().copied(),
+                    "{}",
+                    pos
+                );
+            }
+
+            let mut portal_index = PortalIndex::default();
+            portal_index.add_bidirectional(pos("W1N1", 25, 30), pos("W2N1", 25, 25));
+            let flow_field =
+                multiroom_portal_flow_field_with_index(&distance_map, order, &portal_index);
+            let lazy_flow_field = MultiroomLazyFlowField::new(distance_map.clone(), order)
+                .with_portal_index(Rc::new(portal_index));
+            for pos in every_tile(&distance_map) {
+                assert_eq!(lazy_flow_field.get(pos), flow_field.get(pos), "{}", pos);
+                assert_eq!(
+                    lazy_flow_field.is_terminal(pos),
+                    flow_field.is_terminal(pos),
+                    "{}",
+                    pos
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lazy_fields_keep_written_values() {
+        let distance_map = distance_map();
+        let mut lazy_flow_field =
+            MultiroomLazyFlowField::new(distance_map.clone(), DirectionOrder::CardinalFirst)
+                .with_memoization(true);
+        let tile = pos("W1N1", 12, 25);
+        assert_eq!(
+            lazy_flow_field.get_first_direction(tile),
+            Some(Direction::Left)
+        );
+        assert_eq!(lazy_flow_field.memo.borrow().len(), 1);
+
+        lazy_flow_field.set_directions(tile, vec![Direction::Bottom]);
+        assert_eq!(
+            lazy_flow_field.get_first_direction(tile),
+            Some(Direction::Bottom)
+        );
+        lazy_flow_field.add_direction(tile, Direction::Top);
+        assert_eq!(
+            lazy_flow_field.get_directions(tile),
+            vec![Direction::Top, Direction::Bottom]
+        );
+
+        let outside = pos("W5N5", 25, 25);
+        assert_eq!(
+            lazy_flow_field
+                .get_room_map(outside.room_name())
+                .map(|_| ()),
+            None
+        );
+        lazy_flow_field.set(outside, 1);
+        let room_map = lazy_flow_field.get_room_map(outside.room_name()).unwrap();
+        assert_eq!(room_map.get(outside.x(), outside.y()), 1);
+    }
+}
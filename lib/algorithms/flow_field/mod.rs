@@ -1,2 +1,169 @@
+pub mod lazy_flow_field;
 pub mod multiroom_flow_field;
 pub mod multiroom_mono_flow_field;
+pub mod secondary_cost;
+
+use crate::algorithms::map::{corresponding_room_edge, same_room_neighbor, DirectionPriority};
+use crate::datatypes::DistanceMap;
+use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::PortalIndex;
+use screeps::{Direction, Position};
+
+/// How far a creep standing on a tile is from the origin. Searches record a room
+/// crossing on the tile they land on in the next room, and a creep standing on a room
+/// edge retraces that crossing backwards, so edge tiles read their distance from the
+/// corresponding tile.
+fn standing_distance(
+    distance_map: &MultiroomDistanceMap,
+    room_map: &DistanceMap,
+    position: Position,
+) -> usize {
+    let distance = room_map[position.xy()];
+    if distance == 0 || !position.is_room_edge() {
+        return distance;
+    }
+    distance_map.get(corresponding_room_edge(position))
+}
+
+/// The directions a creep standing on `pos` can take toward the origin, in
+/// `direction_order`. Tiles the search didn't reach get none.
+fn flow_directions<'a>(
+    distance_map: &'a MultiroomDistanceMap,
+    room_map: &'a DistanceMap,
+    pos: Position,
+    direction_order: DirectionPriority,
+) -> impl Iterator<Item = Direction> + 'a {
+    // Nothing is closer than 0, so this leaves unreachable tiles without directions.
+    let distance = match standing_distance(distance_map, room_map, pos) {
+        usize::MAX => 0,
+        distance => distance,
+    };
+    downhill_directions(room_map, pos, direction_order, distance)
+}
+
+/// Like `flow_directions`, for portal-aware fields. Portals and room edges lead on
+/// even if the search never reached them, since stepping onto them moves the creep.
+fn portal_flow_directions<'a>(
+    room_map: &'a DistanceMap,
+    pos: Position,
+    direction_order: DirectionPriority,
+    portal_index: &PortalIndex,
+) -> impl Iterator<Item = Direction> + 'a {
+    let distance = room_map[pos.xy()];
+    let leads_on = distance != usize::MAX || portal_index.exit(pos).is_some() || pos.is_room_edge();
+    downhill_directions(
+        room_map,
+        pos,
+        direction_order,
+        if leads_on { distance } else { 0 },
+    )
+}
+
+/// The directions from `pos` onto the neighbors in its room that are closest to the
+/// origin, if they're closer than `distance`. Every tie is kept, in `direction_order`.
+fn downhill_directions(
+    room_map: &DistanceMap,
+    pos: Position,
+    direction_order: DirectionPriority,
+    distance: usize,
+) -> impl Iterator<Item = Direction> + '_ {
+    let step_distance = move |direction: &Direction| {
+        same_room_neighbor(pos, *direction).map(|step| room_map[step.xy()])
+    };
+    let directions = direction_order.directions();
+    let min_distance = directions
+        .iter()
+        .filter_map(step_distance)
+        .min()
+        .filter(|min_distance| *min_distance < distance);
+    IntoIterator::into_iter(directions)
+        .filter(move |direction| min_distance.is_some() && step_distance(direction) == min_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::multiroom_flow_field::multiroom_flow_field;
+    use super::multiroom_mono_flow_field::multiroom_mono_flow_field;
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::algorithms::distance_map::goals::Goal;
+    use crate::algorithms::map::DirectionOrder;
+    use crate::algorithms::path::to_multiroom_flow_field_origin::path_to_multiroom_flow_field_origin;
+    use crate::algorithms::path::to_multiroom_mono_flow_field_origin::path_to_multiroom_mono_flow_field_origin;
+    use crate::datatypes::ClockworkCostMatrix;
+    use crate::test_fixtures::{pos, positions, xy};
+    use screeps::{Direction, RoomName};
+
+    /// Plains with walls in the corners, like every room in the game.
+    fn walled_corners() -> ClockworkCostMatrix {
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        for (x, y) in [(0, 0), (0, 49), (49, 0), (49, 49)] {
+            matrix.set(xy(x, y), 255);
+        }
+        matrix
+    }
+
+    #[test]
+    fn fields_cross_every_border() {
+        let borders = [
+            (pos("W1N1", 25, 1), Direction::Top),
+            (pos("W1N1", 48, 25), Direction::Right),
+            (pos("W1N1", 25, 48), Direction::Bottom),
+            (pos("W1N1", 1, 25), Direction::Left),
+        ];
+        for (inside, direction) in borders {
+            // The origin sits just past the border, so the search only records the
+            // crossing on the edge tile in W1N1, not on the one a creep lands on.
+            let exit = inside.checked_add_direction(direction).unwrap();
+            let landing = corresponding_room_edge(exit);
+            let origin = landing.checked_add_direction(direction).unwrap();
+            let mut distance_map = MultiroomDistanceMap::new();
+            distance_map.set(origin, 0);
+            distance_map.set(exit, 1);
+            distance_map.set(inside, 2);
+
+            let flow_field = multiroom_flow_field(&distance_map, DirectionOrder::CardinalFirst);
+            assert_eq!(flow_field.get_directions(inside), vec![direction]);
+            assert_eq!(flow_field.get_directions(landing), vec![direction]);
+
+            let mono_flow_field =
+                multiroom_mono_flow_field(&distance_map, DirectionOrder::CardinalFirst);
+            assert_eq!(mono_flow_field.get(inside), Some(direction));
+            assert_eq!(mono_flow_field.get(landing), Some(direction));
+        }
+    }
+
+    #[test]
+    fn fields_lead_around_every_corner() {
+        let corners = [
+            (pos("W1N1", 1, 1), pos("W2N2", 48, 48)),
+            (pos("W1N1", 48, 1), pos("W0N2", 1, 48)),
+            (pos("W1N1", 1, 48), pos("W2N0", 48, 1)),
+            (pos("W1N1", 48, 48), pos("W0N0", 1, 1)),
+        ];
+        for (start, origin) in corners {
+            // Stopping at the start leaves most tiles the path lands on unset.
+            let distance_map = dijkstra_multiroom_distance_map(
+                vec![origin],
+                |_: RoomName| Some(walled_corners()),
+                usize::MAX,
+                4,
+                usize::MAX,
+                Some(vec![Goal::within(start, 0)]),
+                None,
+            )
+            .distance_map();
+
+            for order in [DirectionOrder::CardinalFirst, DirectionOrder::DiagonalFirst] {
+                let flow_field = multiroom_flow_field(&distance_map, order);
+                let path = path_to_multiroom_flow_field_origin(start, &flow_field).unwrap();
+                assert_eq!(positions(&path).last(), Some(&origin));
+
+                let mono_flow_field = multiroom_mono_flow_field(&distance_map, order);
+                let path =
+                    path_to_multiroom_mono_flow_field_origin(start, &mono_flow_field).unwrap();
+                assert_eq!(positions(&path).last(), Some(&origin));
+            }
+        }
+    }
+}
@@ -1,41 +1,30 @@
-use crate::algorithms::map::{
-    preferred_directions, room_xy_neighbors, same_room_neighbor, DirectionOrder,
-};
+use crate::algorithms::map::{js_direction_priority, DirectionOrder, DirectionPriority};
 use crate::datatypes::with_configured_portal_index;
 use crate::datatypes::MultiroomDistanceMap;
 use crate::datatypes::MultiroomFlowField;
 use crate::datatypes::PortalIndex;
 use crate::utils::set_panic_hook;
-use screeps::Position;
+use screeps::{Direction, Position};
 use wasm_bindgen::prelude::*;
 
+use super::{flow_directions, portal_flow_directions};
+
 /// Creates a flow field for the given distance map.
-#[wasm_bindgen(js_name = "multiroomFlowField")]
 pub fn multiroom_flow_field(
     distance_map: &MultiroomDistanceMap,
-    direction_order: DirectionOrder,
+    direction_order: impl Into<DirectionPriority>,
 ) -> MultiroomFlowField {
-    set_panic_hook();
+    let direction_order = direction_order.into();
     let mut flow_field = MultiroomFlowField::new_with_direction_order(direction_order);
 
     for room in distance_map.rooms() {
         let room_map = distance_map.get_room_map(room).unwrap();
-        for (position, &value) in room_map.enumerate() {
-            if value == usize::MAX {
-                continue; // unreachable
-            }
-            let min_distance = room_xy_neighbors(position, direction_order)
-                .map(|neighbor| room_map[neighbor])
-                .min();
-            if let Some(min_distance) = min_distance {
-                if min_distance < value {
-                    let directions = room_xy_neighbors(position, direction_order)
-                        .filter(|neighbor| room_map[*neighbor] == min_distance)
-                        .map(|neighbor| position.get_direction_to(neighbor).unwrap())
-                        .collect();
-                    flow_field
-                        .set_directions(Position::new(position.x, position.y, room), directions);
-                }
+        for (position, _) in room_map.enumerate() {
+            let pos = Position::new(position.x, position.y, room);
+            let directions: Vec<Direction> =
+                flow_directions(distance_map, room_map, pos, direction_order).collect();
+            if !directions.is_empty() {
+                flow_field.set_directions(pos, directions);
             }
         }
     }
@@ -43,22 +32,12 @@ pub fn multiroom_flow_field(
     flow_field
 }
 
-#[wasm_bindgen(js_name = "multiroomPortalFlowField")]
-pub fn multiroom_portal_flow_field(
-    distance_map: &MultiroomDistanceMap,
-    direction_order: DirectionOrder,
-) -> MultiroomFlowField {
-    set_panic_hook();
-    with_configured_portal_index(|portal_index| {
-        multiroom_portal_flow_field_with_index(distance_map, direction_order, portal_index)
-    })
-}
-
 pub fn multiroom_portal_flow_field_with_index(
     distance_map: &MultiroomDistanceMap,
-    direction_order: DirectionOrder,
+    direction_order: impl Into<DirectionPriority>,
     portal_index: &PortalIndex,
 ) -> MultiroomFlowField {
+    let direction_order = direction_order.into();
     let mut flow_field = MultiroomFlowField::new_with_direction_order(direction_order);
 
     for room in distance_map.rooms() {
@@ -70,34 +49,43 @@ pub fn multiroom_portal_flow_field_with_index(
                 continue;
             }
 
-            if value == usize::MAX && portal_index.exit(pos).is_none() && !pos.is_room_edge() {
-                continue;
-            }
-
-            let min_distance = preferred_directions(direction_order)
-                .iter()
-                .filter_map(|direction| same_room_neighbor(pos, *direction))
-                .map(|step| distance_map.get(step))
-                .min();
-
-            if let Some(min_distance) = min_distance {
-                if min_distance != usize::MAX && (min_distance < value || value == usize::MAX) {
-                    let directions = preferred_directions(direction_order)
-                        .iter()
-                        .filter_map(|direction| {
-                            let step = same_room_neighbor(pos, *direction)?;
-                            if distance_map.get(step) == min_distance {
-                                Some(*direction)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-                    flow_field.set_directions(pos, directions);
-                }
+            let directions: Vec<Direction> =
+                portal_flow_directions(room_map, pos, direction_order, portal_index).collect();
+            if !directions.is_empty() {
+                flow_field.set_directions(pos, directions);
             }
         }
     }
 
     flow_field
 }
+
+/// Creates a flow field for the given distance map. `custom_directions`, if given,
+/// breaks ties in that order instead of `direction_order` (see `seededDirectionOrder`).
+#[wasm_bindgen(js_name = "multiroomFlowField")]
+pub fn js_multiroom_flow_field(
+    distance_map: &MultiroomDistanceMap,
+    direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
+) -> MultiroomFlowField {
+    set_panic_hook();
+    multiroom_flow_field(
+        distance_map,
+        js_direction_priority(direction_order, custom_directions),
+    )
+}
+
+/// Portal-aware variant of `multiroomFlowField`, using the configured portals (see
+/// `set_portals`).
+#[wasm_bindgen(js_name = "multiroomPortalFlowField")]
+pub fn js_multiroom_portal_flow_field(
+    distance_map: &MultiroomDistanceMap,
+    direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
+) -> MultiroomFlowField {
+    set_panic_hook();
+    let direction_order = js_direction_priority(direction_order, custom_directions);
+    with_configured_portal_index(|portal_index| {
+        multiroom_portal_flow_field_with_index(distance_map, direction_order, portal_index)
+    })
+}
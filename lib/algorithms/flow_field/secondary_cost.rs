@@ -0,0 +1,308 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::map::{
+    corresponding_room_edge, js_direction_priority, same_room_neighbor, DirectionOrder,
+    DirectionPriority,
+};
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::MultiroomFlowField;
+use crate::datatypes::MultiroomMonoFlowField;
+use crate::utils::set_panic_hook;
+use screeps::{Direction, Position, RoomName};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use super::{flow_directions, standing_distance};
+
+/// Secondary costs used to break ties between equally short directions, such as
+/// preferring roads or avoiding tiles next to walls. Each room's matrix is fetched
+/// once, and rooms without one cost nothing.
+pub(crate) struct SecondaryCosts<F: Fn(RoomName) -> Option<ClockworkCostMatrix>> {
+    get_secondary_cost: F,
+    matrices: HashMap<RoomName, Option<ClockworkCostMatrix>>,
+}
+
+impl<F: Fn(RoomName) -> Option<ClockworkCostMatrix>> SecondaryCosts<F> {
+    pub(crate) fn new(get_secondary_cost: F) -> Self {
+        SecondaryCosts {
+            get_secondary_cost,
+            matrices: HashMap::new(),
+        }
+    }
+
+    /// The secondary cost of stepping onto a tile.
+    pub(crate) fn get(&mut self, pos: Position) -> usize {
+        let get_secondary_cost = &self.get_secondary_cost;
+        self.matrices
+            .entry(pos.room_name())
+            .or_insert_with(|| get_secondary_cost(pos.room_name()))
+            .as_ref()
+            .map_or(0, |matrix| matrix.get(pos.xy()) as usize)
+    }
+}
+
+/// The lowest secondary cost from each reached tile to the origin, only taking
+/// directions the plain flow field would. Tiles closer to the origin always come
+/// first, so every step's remaining cost is known by the time it's needed.
+fn secondary_distance_map<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    distance_map: &MultiroomDistanceMap,
+    secondary_costs: &mut SecondaryCosts<F>,
+    direction_order: DirectionPriority,
+) -> MultiroomDistanceMap {
+    let mut tiles = Vec::new();
+    for room in distance_map.rooms() {
+        let room_map = distance_map.get_room_map(room).unwrap();
+        for (position, _) in room_map.enumerate() {
+            let pos = Position::new(position.x, position.y, room);
+            match standing_distance(distance_map, room_map, pos) {
+                usize::MAX => {}
+                distance => tiles.push((distance, pos)),
+            }
+        }
+    }
+    tiles.sort_unstable_by_key(|(distance, _)| *distance);
+
+    let mut secondary_distance_map = MultiroomDistanceMap::new();
+    for (distance, pos) in tiles {
+        let secondary_distance = if distance == 0 {
+            0
+        } else {
+            let room_map = distance_map.get_room_map(pos.room_name()).unwrap();
+            flow_directions(distance_map, room_map, pos, direction_order)
+                .map(|direction| {
+                    secondary_step_distance(
+                        &secondary_distance_map,
+                        secondary_costs,
+                        pos,
+                        direction,
+                    )
+                })
+                .min()
+                .unwrap_or(usize::MAX)
+        };
+        secondary_distance_map.set(pos, secondary_distance);
+    }
+    secondary_distance_map
+}
+
+/// The secondary cost of stepping from `pos` in `direction` and then on to the origin.
+fn secondary_step_distance<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    secondary_distance_map: &MultiroomDistanceMap,
+    secondary_costs: &mut SecondaryCosts<F>,
+    pos: Position,
+    direction: Direction,
+) -> usize {
+    let step = same_room_neighbor(pos, direction).expect("flow directions stay in the room");
+    secondary_distance_map
+        .get(corresponding_room_edge(step))
+        .saturating_add(secondary_costs.get(step))
+}
+
+/// The flow directions from `pos` that are also cheapest by secondary cost, in
+/// `direction_order`.
+fn tie_broken_directions<F: Fn(RoomName) -> Option<ClockworkCostMatrix>>(
+    distance_map: &MultiroomDistanceMap,
+    secondary_distance_map: &MultiroomDistanceMap,
+    secondary_costs: &mut SecondaryCosts<F>,
+    pos: Position,
+    direction_order: DirectionPriority,
+) -> Vec<Direction> {
+    let room_map = distance_map.get_room_map(pos.room_name()).unwrap();
+    let directions: Vec<(Direction, usize)> =
+        flow_directions(distance_map, room_map, pos, direction_order)
+            .map(|direction| {
+                let secondary_distance = secondary_step_distance(
+                    secondary_distance_map,
+                    secondary_costs,
+                    pos,
+                    direction,
+                );
+                (direction, secondary_distance)
+            })
+            .collect();
+    let min_secondary_distance = directions.iter().map(|(_, distance)| *distance).min();
+    directions
+        .into_iter()
+        .filter(|(_, distance)| Some(*distance) == min_secondary_distance)
+        .map(|(direction, _)| direction)
+        .collect()
+}
+
+/// Creates a flow field for the given distance map that only keeps the directions
+/// leading to paths that are shortest first, and cheapest by secondary cost second.
+/// The secondary cost of a path is the sum of the tiles it steps onto.
+pub fn multiroom_flow_field_with_secondary_cost(
+    distance_map: &MultiroomDistanceMap,
+    get_secondary_cost: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    direction_order: impl Into<DirectionPriority>,
+) -> MultiroomFlowField {
+    let direction_order = direction_order.into();
+    let mut secondary_costs = SecondaryCosts::new(get_secondary_cost);
+    let secondary_distance_map =
+        secondary_distance_map(distance_map, &mut secondary_costs, direction_order);
+    let mut flow_field = MultiroomFlowField::new_with_direction_order(direction_order);
+
+    for room in distance_map.rooms() {
+        let room_map = distance_map.get_room_map(room).unwrap();
+        for (position, _) in room_map.enumerate() {
+            let pos = Position::new(position.x, position.y, room);
+            let directions = tie_broken_directions(
+                distance_map,
+                &secondary_distance_map,
+                &mut secondary_costs,
+                pos,
+                direction_order,
+            );
+            if !directions.is_empty() {
+                flow_field.set_directions(pos, directions);
+            }
+        }
+    }
+
+    flow_field
+}
+
+/// Creates a monodirectional flow field for the given distance map, following paths
+/// that are shortest first and cheapest by secondary cost second.
+pub fn multiroom_mono_flow_field_with_secondary_cost(
+    distance_map: &MultiroomDistanceMap,
+    get_secondary_cost: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    direction_order: impl Into<DirectionPriority>,
+) -> MultiroomMonoFlowField {
+    let direction_order = direction_order.into();
+    let mut secondary_costs = SecondaryCosts::new(get_secondary_cost);
+    let secondary_distance_map =
+        secondary_distance_map(distance_map, &mut secondary_costs, direction_order);
+    let mut flow_field = MultiroomMonoFlowField::new();
+
+    for room in distance_map.rooms() {
+        let room_map = distance_map.get_room_map(room).unwrap();
+        for (position, _) in room_map.enumerate() {
+            let pos = Position::new(position.x, position.y, room);
+            let direction = tie_broken_directions(
+                distance_map,
+                &secondary_distance_map,
+                &mut secondary_costs,
+                pos,
+                direction_order,
+            )
+            .first()
+            .copied();
+            if direction.is_some() {
+                flow_field.set(pos, direction);
+            }
+        }
+    }
+
+    flow_field
+}
+
+#[wasm_bindgen(js_name = "multiroomFlowFieldWithSecondaryCost")]
+pub fn js_multiroom_flow_field_with_secondary_cost(
+    distance_map: &MultiroomDistanceMap,
+    get_secondary_cost: &js_sys::Function,
+    direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
+) -> MultiroomFlowField {
+    set_panic_hook();
+    multiroom_flow_field_with_secondary_cost(
+        distance_map,
+        |room| js_cost_matrix(get_secondary_cost, room),
+        js_direction_priority(direction_order, custom_directions),
+    )
+}
+
+#[wasm_bindgen(js_name = "multiroomMonoFlowFieldWithSecondaryCost")]
+pub fn js_multiroom_mono_flow_field_with_secondary_cost(
+    distance_map: &MultiroomDistanceMap,
+    get_secondary_cost: &js_sys::Function,
+    direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
+) -> MultiroomMonoFlowField {
+    set_panic_hook();
+    multiroom_mono_flow_field_with_secondary_cost(
+        distance_map,
+        |room| js_cost_matrix(get_secondary_cost, room),
+        js_direction_priority(direction_order, custom_directions),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::algorithms::flow_field::multiroom_flow_field::multiroom_flow_field;
+    use crate::algorithms::path::to_multiroom_flow_field_origin::{
+        path_to_multiroom_flow_field_origin,
+        path_to_multiroom_flow_field_origin_with_secondary_cost,
+    };
+    use crate::algorithms::path::to_multiroom_mono_flow_field_origin::path_to_multiroom_mono_flow_field_origin;
+    use crate::test_fixtures::positions;
+    use screeps::RoomCoordinate;
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            "W1N1".parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn ties_go_to_the_cheapest_secondary_path() {
+        let origin = pos(25, 25);
+        let start = pos(25, 30);
+        // A road that detours sideways without making the path any longer.
+        let road = [pos(26, 29), pos(27, 28), pos(26, 27), pos(25, 26)];
+        let get_secondary_cost = |_: RoomName| {
+            let mut matrix = ClockworkCostMatrix::new(Some(5));
+            for tile in road {
+                matrix.set(tile.xy(), 0);
+            }
+            Some(matrix)
+        };
+        let distance_map = dijkstra_multiroom_distance_map(
+            vec![origin],
+            |_: RoomName| Some(ClockworkCostMatrix::new(Some(1))),
+            usize::MAX,
+            1,
+            usize::MAX,
+            None,
+            None,
+        )
+        .distance_map();
+
+        let mut expected = vec![start];
+        expected.extend(road);
+        expected.push(origin);
+
+        let flow_field = multiroom_flow_field(&distance_map, DirectionOrder::CardinalFirst);
+        let path = path_to_multiroom_flow_field_origin(start, &flow_field).unwrap();
+        assert_eq!(positions(&path).len(), expected.len());
+        assert_ne!(positions(&path), expected);
+
+        let path = path_to_multiroom_flow_field_origin_with_secondary_cost(
+            start,
+            &flow_field,
+            get_secondary_cost,
+        )
+        .unwrap();
+        assert_eq!(positions(&path), expected);
+
+        let flow_field = multiroom_flow_field_with_secondary_cost(
+            &distance_map,
+            get_secondary_cost,
+            DirectionOrder::CardinalFirst,
+        );
+        let path = path_to_multiroom_flow_field_origin(start, &flow_field).unwrap();
+        assert_eq!(positions(&path), expected);
+
+        let flow_field = multiroom_mono_flow_field_with_secondary_cost(
+            &distance_map,
+            get_secondary_cost,
+            DirectionOrder::DiagonalFirst,
+        );
+        let path = path_to_multiroom_mono_flow_field_origin(start, &flow_field).unwrap();
+        assert_eq!(positions(&path), expected);
+    }
+}
@@ -1,6 +1,8 @@
-use screeps::{Direction, Position, RoomCoordinate, RoomXY};
+use screeps::{Direction, Position, RoomCoordinate};
 use wasm_bindgen::prelude::*;
 
+pub mod room_route;
+
 /// If the position is on a room edge, return the corresponding room edge.
 /// Otherwise, just return the position.
 pub fn corresponding_room_edge(position: Position) -> Position {
@@ -23,6 +25,35 @@ pub fn corresponding_room_edge(position: Position) -> Position {
     position
 }
 
+static CARDINAL_DIRECTIONS: [Direction; 4] = [
+    Direction::Top,
+    Direction::Right,
+    Direction::Bottom,
+    Direction::Left,
+];
+
+/// The inverse of `corresponding_room_edge`: returns the tiles a creep can step onto
+/// to end up at the given position. Interior tiles are only reached by stepping onto
+/// them directly, while room edge tiles are reached from the matching edge tile of
+/// the neighboring room.
+pub fn room_edge_sources(position: Position) -> impl Iterator<Item = Position> {
+    let is_edge = position.is_room_edge();
+    let interior = (!is_edge).then_some(position);
+    let edges = CARDINAL_DIRECTIONS.iter().filter_map(move |direction| {
+        if !is_edge {
+            return None;
+        }
+        let source = position.checked_add_direction(*direction).ok()?;
+        if source.room_name() != position.room_name() && corresponding_room_edge(source) == position
+        {
+            Some(source)
+        } else {
+            None
+        }
+    });
+    interior.into_iter().chain(edges)
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 pub enum DirectionOrder {
@@ -52,13 +83,84 @@ static DIAGONAL_FIRST_DIRECTIONS: [Direction; 8] = [
     Direction::Left,
 ];
 
-pub fn preferred_directions(order: DirectionOrder) -> &'static [Direction; 8] {
+/// The directions in the order ties are broken for `order`.
+pub fn preferred_directions(order: DirectionOrder) -> [Direction; 8] {
     match order {
-        DirectionOrder::CardinalFirst => &CARDINAL_FIRST_DIRECTIONS,
-        DirectionOrder::DiagonalFirst => &DIAGONAL_FIRST_DIRECTIONS,
+        DirectionOrder::CardinalFirst => CARDINAL_FIRST_DIRECTIONS,
+        DirectionOrder::DiagonalFirst => DIAGONAL_FIRST_DIRECTIONS,
+    }
+}
+
+/// The directions a flow field or path tries, first to last, when breaking ties.
+/// Built-in orders convert from `DirectionOrder`, and custom or seeded orders are
+/// built here and passed along with the field, so nothing is kept between calls.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DirectionPriority([Direction; 8]);
+
+impl DirectionPriority {
+    /// An order listing `directions` first to last. It must list every direction once.
+    pub fn custom(directions: &[Direction]) -> Result<Self, &'static str> {
+        let mut order = CARDINAL_FIRST_DIRECTIONS;
+        if directions.len() != order.len()
+            || !ANY_DIRECTIONS
+                .iter()
+                .all(|direction| directions.contains(direction))
+        {
+            return Err("Custom direction order must list every direction once");
+        }
+        order.copy_from_slice(directions);
+        Ok(DirectionPriority(order))
+    }
+
+    /// A shuffled order, so creeps given different seeds don't all break ties the
+    /// same way. The same seed always gives the same order.
+    pub fn seeded(seed: u32) -> Self {
+        let mut order = CARDINAL_FIRST_DIRECTIONS;
+        // Fisher-Yates, drawing from a splitmix32 sequence.
+        let mut state = seed;
+        for i in (1..order.len()).rev() {
+            state = state.wrapping_add(0x9e37_79b9);
+            let mut z = state;
+            z = (z ^ (z >> 16)).wrapping_mul(0x85eb_ca6b);
+            z = (z ^ (z >> 13)).wrapping_mul(0xc2b2_ae35);
+            z ^= z >> 16;
+            order.swap(i, z as usize % (i + 1));
+        }
+        DirectionPriority(order)
+    }
+
+    pub fn directions(self) -> [Direction; 8] {
+        self.0
+    }
+}
+
+impl From<DirectionOrder> for DirectionPriority {
+    fn from(order: DirectionOrder) -> Self {
+        DirectionPriority(preferred_directions(order))
     }
 }
 
+/// The order for a JavaScript call: `custom_directions` if given (see
+/// `seededDirectionOrder`), otherwise `direction_order`. Throws if the custom order
+/// doesn't list every direction once.
+pub(crate) fn js_direction_priority(
+    direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
+) -> DirectionPriority {
+    match custom_directions {
+        Some(directions) => DirectionPriority::custom(&directions)
+            .unwrap_or_else(|error| wasm_bindgen::throw_str(error)),
+        None => direction_order.into(),
+    }
+}
+
+/// The shuffled direction order for `seed`, to pass as the custom direction order of
+/// flow fields and paths. The same seed always gives the same order.
+#[wasm_bindgen(js_name = "seededDirectionOrder")]
+pub fn js_seeded_direction_order(seed: u32) -> Vec<Direction> {
+    DirectionPriority::seeded(seed).directions().to_vec()
+}
+
 #[inline(always)]
 fn direction_stays_in_room(position: Position, direction: Direction) -> bool {
     match direction {
@@ -92,10 +194,12 @@ pub fn same_room_neighbor(position: Position, direction: Direction) -> Option<Po
 
 /// Adjacency in Screeps is not perfectly euclidean: we need to apply
 /// special rules at room edges.
-pub fn neighbors(position: Position, order: DirectionOrder) -> impl Iterator<Item = Position> {
-    preferred_directions(order)
-        .iter()
-        .filter_map(move |dir| same_room_neighbor(position, *dir))
+pub fn neighbors(
+    position: Position,
+    order: impl Into<DirectionPriority>,
+) -> impl Iterator<Item = Position> {
+    IntoIterator::into_iter(order.into().directions())
+        .filter_map(move |dir| same_room_neighbor(position, dir))
         .map(corresponding_room_edge)
 }
 
@@ -103,17 +207,10 @@ pub fn neighbors(position: Position, order: DirectionOrder) -> impl Iterator<Ite
 /// special rules at room edges.
 pub fn neighbors_without_edges(
     position: Position,
-    order: DirectionOrder,
+    order: impl Into<DirectionPriority>,
 ) -> impl Iterator<Item = Position> {
-    preferred_directions(order)
-        .iter()
-        .filter_map(move |dir| same_room_neighbor(position, *dir))
-}
-
-pub fn room_xy_neighbors(position: RoomXY, order: DirectionOrder) -> impl Iterator<Item = RoomXY> {
-    preferred_directions(order)
-        .iter()
-        .filter_map(move |dir| position.checked_add_direction(*dir))
+    IntoIterator::into_iter(order.into().directions())
+        .filter_map(move |dir| same_room_neighbor(position, dir))
 }
 
 static ANY_DIRECTIONS: [Direction; 8] = [
@@ -188,8 +285,73 @@ static DIRECTION_LOOKUP: [&[Direction]; 9] = [
     &TOP_LEFT_DIRECTIONS,
 ];
 
-/// Returns the next directions to consider, based on the direction from which the tile
-/// was entered. Lateral directions can be ruled out as an optimization.
-pub fn next_directions(open_direction: Option<Direction>) -> &'static [Direction] {
+/// Returns the next directions to consider from `position`, based on the direction from
+/// which the tile was entered. Lateral directions can be ruled out as an optimization,
+/// since the previous tile could have stepped there directly. That doesn't hold on a
+/// room edge: a tile there was landed on from the next room, and stepping along the
+/// edge crosses back to a tile the previous one couldn't reach, so every direction is
+/// considered.
+pub fn next_directions(
+    position: Position,
+    open_direction: Option<Direction>,
+) -> &'static [Direction] {
+    if position.is_room_edge() {
+        return &ANY_DIRECTIONS;
+    }
     DIRECTION_LOOKUP[open_direction.map(|d| d as usize).unwrap_or(0)]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datatypes::MultiroomFlowField;
+    use screeps::RoomName;
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            "W1N1".parse::<RoomName>().unwrap(),
+        )
+    }
+
+    #[test]
+    fn fields_keep_the_order_they_were_created_with() {
+        let reversed: Vec<Direction> = CARDINAL_FIRST_DIRECTIONS.iter().rev().copied().collect();
+        let order = DirectionPriority::custom(&reversed).unwrap();
+        assert!(DirectionPriority::custom(&reversed[1..]).is_err());
+        assert!(DirectionPriority::custom(&[Direction::Top; 8]).is_err());
+
+        let mut flow_field = MultiroomFlowField::new_with_direction_order(order);
+        let mut cardinal_field = MultiroomFlowField::new_with_direction_order(
+            DirectionPriority::custom(&CARDINAL_FIRST_DIRECTIONS).unwrap(),
+        );
+        for field in [&mut flow_field, &mut cardinal_field] {
+            field.set_directions(pos(25, 25), vec![Direction::Top, Direction::TopLeft]);
+        }
+
+        assert_eq!(
+            flow_field.get_directions(pos(25, 25)),
+            vec![Direction::TopLeft, Direction::Top]
+        );
+        assert_eq!(
+            cardinal_field.get_directions(pos(25, 25)),
+            vec![Direction::Top, Direction::TopLeft]
+        );
+    }
+
+    #[test]
+    fn seeded_orders_are_repeatable_shuffles() {
+        let orders: Vec<[Direction; 8]> = (0..16)
+            .map(|seed| DirectionPriority::seeded(seed).directions())
+            .collect();
+
+        for order in &orders {
+            assert!(ANY_DIRECTIONS
+                .iter()
+                .all(|direction| order.contains(direction)));
+        }
+        assert!(orders.iter().any(|order| *order != orders[0]));
+        assert_eq!(DirectionPriority::seeded(3).directions(), orders[3]);
+    }
+}
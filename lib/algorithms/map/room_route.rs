@@ -0,0 +1,319 @@
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::PortalIndex;
+use crate::utils::set_panic_hook;
+use screeps::RoomName;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::throw_val;
+
+static ADJACENT_ROOM_OFFSETS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+#[derive(Copy, Clone, PartialEq)]
+struct State {
+    cost: f64,
+    room: RoomName,
+}
+
+impl Eq for State {}
+
+// Reversed so the `BinaryHeap` pops the cheapest room first.
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The rooms reachable in one move from `room`: the four adjacent rooms, plus the
+/// destination rooms of any portals in it.
+fn room_neighbors(room: RoomName, portal_index: Option<&PortalIndex>) -> Vec<RoomName> {
+    let mut neighbors: Vec<RoomName> = ADJACENT_ROOM_OFFSETS
+        .iter()
+        .filter_map(|offset| room.checked_add(*offset))
+        .collect();
+    if let Some(portal_index) = portal_index {
+        for portal in portal_index.portals_in_room(room) {
+            if let Some(exit) = portal_index.exit(*portal) {
+                if exit.room_name() != room && !neighbors.contains(&exit.room_name()) {
+                    neighbors.push(exit.room_name());
+                }
+            }
+        }
+    }
+    neighbors
+}
+
+/// Finds the cheapest sequence of rooms from `from` to `to`, like `Game.map.findRoute`.
+/// `room_cost` is called with the room being entered and the room it's entered from,
+/// and returns the cost of entering it, or `None` to forbid it. At most `max_rooms`
+/// rooms are explored.
+///
+/// Returns the rooms in order, starting with `from` and ending with `to`, or `None`
+/// if no route was found.
+pub fn find_room_route(
+    from: RoomName,
+    to: RoomName,
+    room_cost: impl Fn(RoomName, RoomName) -> Option<f64>,
+    max_rooms: usize,
+) -> Option<Vec<RoomName>> {
+    room_route(from, to, room_cost, max_rooms, None)
+}
+
+/// Finds a room route that may also travel through the given portals.
+pub fn find_portal_room_route(
+    from: RoomName,
+    to: RoomName,
+    room_cost: impl Fn(RoomName, RoomName) -> Option<f64>,
+    max_rooms: usize,
+    portal_index: &PortalIndex,
+) -> Option<Vec<RoomName>> {
+    room_route(from, to, room_cost, max_rooms, Some(portal_index))
+}
+
+fn room_route(
+    from: RoomName,
+    to: RoomName,
+    room_cost: impl Fn(RoomName, RoomName) -> Option<f64>,
+    max_rooms: usize,
+    portal_index: Option<&PortalIndex>,
+) -> Option<Vec<RoomName>> {
+    set_panic_hook();
+    let mut open = BinaryHeap::new();
+    let mut costs: HashMap<RoomName, f64> = HashMap::new();
+    let mut parents: HashMap<RoomName, RoomName> = HashMap::new();
+    let mut rooms_explored = 0;
+
+    costs.insert(from, 0.0);
+    open.push(State {
+        cost: 0.0,
+        room: from,
+    });
+
+    while let Some(State { cost, room }) = open.pop() {
+        if costs.get(&room).copied().unwrap_or(f64::INFINITY) < cost {
+            continue;
+        }
+
+        if room == to {
+            let mut route = vec![to];
+            let mut current = to;
+            while let Some(parent) = parents.get(&current) {
+                route.push(*parent);
+                current = *parent;
+            }
+            route.reverse();
+            return Some(route);
+        }
+
+        if rooms_explored >= max_rooms {
+            break;
+        }
+        rooms_explored += 1;
+
+        for neighbor in room_neighbors(room, portal_index) {
+            let Some(step_cost) = room_cost(neighbor, room) else {
+                continue;
+            };
+            if !step_cost.is_finite() || step_cost < 0.0 {
+                continue;
+            }
+            let next_cost = cost + step_cost;
+            if costs.get(&neighbor).copied().unwrap_or(f64::INFINITY) <= next_cost {
+                continue;
+            }
+            costs.insert(neighbor, next_cost);
+            parents.insert(neighbor, room);
+            open.push(State {
+                cost: next_cost,
+                room: neighbor,
+            });
+        }
+    }
+
+    None
+}
+
+/// Wraps a cost matrix callback so that rooms outside `allowed_rooms` are treated as
+/// blocked, without asking the callback for them. This restricts a distance map search
+/// to the rooms of a route. If `allowed_rooms` is `None`, every room is allowed.
+pub fn restrict_to_rooms(
+    allowed_rooms: Option<Vec<RoomName>>,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+) -> impl Fn(RoomName) -> Option<ClockworkCostMatrix> {
+    move |room| match &allowed_rooms {
+        Some(allowed_rooms) if !allowed_rooms.contains(&room) => None,
+        _ => get_cost_matrix(room),
+    }
+}
+
+/// Unpacks an optional list of packed room names passed in from JS.
+pub(crate) fn unpack_rooms(packed_rooms: Option<Vec<u16>>) -> Option<Vec<RoomName>> {
+    packed_rooms.map(|rooms| rooms.into_iter().map(RoomName::from_packed).collect())
+}
+
+/// Calls the JS route callback, which works like `findRoute`'s `routeCallback`:
+/// it returns the cost of entering the room, `Infinity` to forbid it, or nothing
+/// to use the default cost of 1.
+fn js_room_cost(
+    room_cost: &Option<js_sys::Function>,
+    room: RoomName,
+    from: RoomName,
+) -> Option<f64> {
+    let Some(room_cost) = room_cost else {
+        return Some(1.0);
+    };
+    let result = room_cost.call2(
+        &JsValue::null(),
+        &JsValue::from_f64(room.packed_repr() as f64),
+        &JsValue::from_f64(from.packed_repr() as f64),
+    );
+    let value = match result {
+        Ok(value) => value,
+        Err(e) => throw_val(e),
+    };
+    if value.is_undefined() {
+        return Some(1.0);
+    }
+    value.as_f64().filter(|cost| cost.is_finite())
+}
+
+#[wasm_bindgen]
+pub fn js_find_room_route(
+    from: u16,
+    to: u16,
+    room_cost: Option<js_sys::Function>,
+    max_rooms: usize,
+) -> Option<Vec<u16>> {
+    find_room_route(
+        RoomName::from_packed(from),
+        RoomName::from_packed(to),
+        |room, from| js_room_cost(&room_cost, room, from),
+        max_rooms,
+    )
+    .map(|route| route.iter().map(|room| room.packed_repr()).collect())
+}
+
+#[wasm_bindgen]
+pub fn js_find_portal_room_route(
+    from: u16,
+    to: u16,
+    room_cost: Option<js_sys::Function>,
+    max_rooms: usize,
+) -> Option<Vec<u16>> {
+    with_configured_portal_index(|portal_index| {
+        find_portal_room_route(
+            RoomName::from_packed(from),
+            RoomName::from_packed(to),
+            |room, from| js_room_cost(&room_cost, room, from),
+            max_rooms,
+            portal_index,
+        )
+    })
+    .map(|route| route.iter().map(|room| room.packed_repr()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::test_fixtures::{pos, room};
+
+    #[test]
+    fn route_goes_around_forbidden_rooms() {
+        let route = find_room_route(
+            room("W1N1"),
+            room("W3N1"),
+            |room_name, _| {
+                if room_name == room("W2N1") {
+                    None
+                } else {
+                    Some(1.0)
+                }
+            },
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(route.first(), Some(&room("W1N1")));
+        assert_eq!(route.last(), Some(&room("W3N1")));
+        assert_eq!(route.len(), 5);
+        assert!(!route.contains(&room("W2N1")));
+    }
+
+    #[test]
+    fn route_prefers_cheaper_rooms() {
+        let route = find_room_route(
+            room("W1N1"),
+            room("W3N1"),
+            |room_name, _| Some(if room_name == room("W2N1") { 5.0 } else { 1.0 }),
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(route.len(), 5);
+        assert!(!route.contains(&room("W2N1")));
+    }
+
+    #[test]
+    fn route_uses_portals() {
+        let mut portal_index = PortalIndex::default();
+        portal_index.add_bidirectional(pos("W1N1", 25, 25), pos("W10N1", 25, 25));
+
+        let route = find_portal_room_route(
+            room("W1N1"),
+            room("W11N1"),
+            |_, _| Some(1.0),
+            100,
+            &portal_index,
+        )
+        .unwrap();
+
+        assert_eq!(route, vec![room("W1N1"), room("W10N1"), room("W11N1")]);
+    }
+
+    #[test]
+    fn route_respects_max_rooms() {
+        let route = find_room_route(room("W1N1"), room("W20N1"), |_, _| Some(1.0), 5);
+        assert!(route.is_none());
+    }
+
+    #[test]
+    fn search_is_restricted_to_route_rooms() {
+        let route = vec![room("W1N1"), room("W2N1")];
+        let start = pos("W1N1", 25, 25);
+        let result = dijkstra_multiroom_distance_map(
+            vec![start],
+            restrict_to_rooms(Some(route.clone()), |_| {
+                Some(ClockworkCostMatrix::new(Some(1)))
+            }),
+            usize::MAX,
+            10,
+            40,
+            None,
+            None,
+        );
+
+        let mut rooms = result.distance_map().rooms();
+        rooms.retain(|room_name| {
+            result
+                .distance_map()
+                .get_room_map(*room_name)
+                .map(|map| map.enumerate().any(|(_, value)| *value != usize::MAX))
+                .unwrap_or(false)
+        });
+        rooms.sort();
+        let mut expected = route;
+        expected.sort();
+        assert_eq!(rooms, expected);
+    }
+}
@@ -2,3 +2,4 @@ pub mod distance_map;
 pub mod flow_field;
 pub mod map;
 pub mod path;
+pub mod pathfinding;
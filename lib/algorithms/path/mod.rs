@@ -1,3 +1,5 @@
 pub mod to_multiroom_distance_map_origin;
 pub mod to_multiroom_flow_field_origin;
 pub mod to_multiroom_mono_flow_field_origin;
+pub mod to_parent_tree_origin;
+pub mod to_turn_distance_map_origin;
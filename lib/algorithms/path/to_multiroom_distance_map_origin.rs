@@ -1,13 +1,12 @@
 use crate::algorithms::map::corresponding_room_edge;
 use crate::algorithms::map::neighbors_without_edges;
-use crate::algorithms::map::preferred_directions;
 use crate::algorithms::map::same_room_neighbor;
-use crate::algorithms::map::DirectionOrder;
+use crate::algorithms::map::{js_direction_priority, DirectionOrder, DirectionPriority};
 use crate::datatypes::with_configured_portal_index;
 use crate::datatypes::MultiroomDistanceMap;
 use crate::datatypes::Path;
 use crate::datatypes::PortalIndex;
-use screeps::Position;
+use screeps::{Direction, Position};
 use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
 
@@ -17,8 +16,9 @@ const MAX_STEPS: usize = 2500;
 pub fn path_to_multiroom_distance_map_origin(
     start: Position,
     distance_map: &MultiroomDistanceMap,
-    direction_order: DirectionOrder,
+    direction_order: impl Into<DirectionPriority>,
 ) -> Result<Path, &'static str> {
+    let direction_order = direction_order.into();
     let mut path = Path::new();
     let mut current = start;
     let mut steps = 0;
@@ -74,9 +74,10 @@ pub fn path_to_multiroom_distance_map_origin(
 pub fn path_to_multiroom_distance_map_origin_with_portals(
     start: Position,
     distance_map: &MultiroomDistanceMap,
-    direction_order: DirectionOrder,
+    direction_order: impl Into<DirectionPriority>,
     portal_index: &PortalIndex,
 ) -> Result<Path, &'static str> {
+    let direction_order = direction_order.into();
     let mut path = Path::new();
     let mut current = start;
     let mut visited = HashSet::new();
@@ -86,7 +87,12 @@ pub fn path_to_multiroom_distance_map_origin_with_portals(
         path.add(current);
         visited.insert(current);
 
-        let current_distance = distance_map.get(current);
+        // Every probe below is in the current room, so look its map up once.
+        let room_map = distance_map.get_room_map(current.room_name());
+        let distance_at =
+            |position: Position| room_map.map_or(usize::MAX, |map| map[position.xy()]);
+
+        let current_distance = distance_at(current);
         if current_distance == usize::MAX
             && portal_index.exit(current).is_none()
             && !current.is_room_edge()
@@ -101,12 +107,12 @@ pub fn path_to_multiroom_distance_map_origin_with_portals(
         let mut next_landed = None;
         let mut min_distance = current_distance;
 
-        for direction in preferred_directions(direction_order) {
-            let step = match same_room_neighbor(current, *direction) {
+        for direction in direction_order.directions() {
+            let step = match same_room_neighbor(current, direction) {
                 Some(step) => step,
                 None => continue,
             };
-            let neighbor_distance = distance_map.get(step);
+            let neighbor_distance = distance_at(step);
 
             if neighbor_distance < min_distance {
                 min_distance = neighbor_distance;
@@ -164,7 +170,9 @@ pub fn js_path_to_multiroom_distance_map_origin(
     start: u32,
     distance_map: &MultiroomDistanceMap,
     direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
 ) -> Result<Path, JsValue> {
+    let direction_order = js_direction_priority(direction_order, custom_directions);
     match path_to_multiroom_distance_map_origin(
         Position::from_packed(start),
         distance_map,
@@ -184,7 +192,9 @@ pub fn js_path_to_multiroom_distance_map_origin_with_portals(
     start: u32,
     distance_map: &MultiroomDistanceMap,
     direction_order: DirectionOrder,
+    custom_directions: Option<Vec<Direction>>,
 ) -> Result<Path, JsValue> {
+    let direction_order = js_direction_priority(direction_order, custom_directions);
     match with_configured_portal_index(|portal_index| {
         path_to_multiroom_distance_map_origin_with_portals(
             Position::from_packed(start),
@@ -1,10 +1,14 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
+    algorithms::distance_map::astar::js_cost_matrix,
+    algorithms::flow_field::secondary_cost::SecondaryCosts,
     algorithms::map::corresponding_room_edge,
-    datatypes::{with_configured_portal_index, MultiroomFlowField, Path, PortalIndex},
+    datatypes::{
+        with_configured_portal_index, ClockworkCostMatrix, MultiroomFlowField, Path, PortalIndex,
+    },
 };
-use screeps::Position;
+use screeps::{Position, RoomName};
 use wasm_bindgen::prelude::*;
 
 // Maximum iterations to prevent infinite loops
@@ -126,6 +130,86 @@ pub fn path_to_multiroom_flow_field_origin_with_portals(
     Err("Path exceeded maximum length")
 }
 
+/// Follows the flow field like `path_to_multiroom_flow_field_origin`, but where a
+/// tile has several directions, takes the one whose path onward steps onto the
+/// cheapest tiles by secondary cost. Ties still go to the field's direction order.
+pub fn path_to_multiroom_flow_field_origin_with_secondary_cost(
+    start: Position,
+    flow_field: &MultiroomFlowField,
+    get_secondary_cost: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+) -> Result<Path, &'static str> {
+    let mut secondary_costs = SecondaryCosts::new(get_secondary_cost);
+
+    // Works out the cheapest secondary cost onward from every tile the field can
+    // lead the start to, children first.
+    let mut remaining_costs: HashMap<Position, usize> = HashMap::new();
+    let mut stack = vec![start];
+    let mut on_stack = HashSet::from([start]);
+    while let Some(&current) = stack.last() {
+        let steps = flow_field_steps(flow_field, current)?;
+        if let Some(&(_, next)) = steps
+            .iter()
+            .find(|(_, next)| !remaining_costs.contains_key(next))
+        {
+            if !on_stack.insert(next) {
+                return Err("Cycle detected in flow field");
+            }
+            if stack.len() >= MAX_STEPS {
+                return Err("Path exceeded maximum length");
+            }
+            stack.push(next);
+            continue;
+        }
+
+        let remaining_cost = steps
+            .iter()
+            .map(|(step, next)| secondary_costs.get(*step) + remaining_costs[next])
+            .min()
+            .unwrap_or(0);
+        remaining_costs.insert(current, remaining_cost);
+        on_stack.remove(&current);
+        stack.pop();
+    }
+
+    let mut path = Path::new();
+    let mut current = start;
+    loop {
+        path.add(current);
+        let cheapest_step = flow_field_steps(flow_field, current)?
+            .into_iter()
+            .min_by_key(|(step, next)| secondary_costs.get(*step) + remaining_costs[next]);
+        let (step, next) = match cheapest_step {
+            None => return Ok(path),
+            Some(cheapest_step) => cheapest_step,
+        };
+        if step.is_room_edge() {
+            path.add(step);
+        }
+        current = next;
+    }
+}
+
+/// The tiles a creep can step onto from `current` by following the flow field, and
+/// where it ends up after each (across the border, for room edges).
+fn flow_field_steps(
+    flow_field: &MultiroomFlowField,
+    current: Position,
+) -> Result<Vec<(Position, Position)>, &'static str> {
+    if flow_field.is_terminal(current) {
+        return Ok(Vec::new());
+    }
+    flow_field
+        .get_directions(current)
+        .into_iter()
+        .map(|direction| {
+            let step = current
+                .checked_add_direction(direction)
+                .map_err(|_| "Direction points outside room bounds")?;
+            Ok((step, corresponding_room_edge(step)))
+        })
+        .collect()
+}
+
 #[wasm_bindgen]
 pub fn js_path_to_multiroom_flow_field_origin(
     start: u32,
@@ -161,3 +245,23 @@ pub fn js_path_to_multiroom_flow_field_origin_with_portals(
         .into()),
     }
 }
+
+#[wasm_bindgen]
+pub fn js_path_to_multiroom_flow_field_origin_with_secondary_cost(
+    start: u32,
+    flow_field: &MultiroomFlowField,
+    get_secondary_cost: &js_sys::Function,
+) -> Result<Path, JsValue> {
+    match path_to_multiroom_flow_field_origin_with_secondary_cost(
+        Position::from_packed(start),
+        flow_field,
+        |room| js_cost_matrix(get_secondary_cost, room),
+    ) {
+        Ok(path) => Ok(path),
+        Err(e) => Err(js_sys::Error::new(&format!(
+            "Error calculating secondary cost path to multiroom flow field origin: {}",
+            e
+        ))
+        .into()),
+    }
+}
@@ -0,0 +1,252 @@
+use crate::algorithms::map::corresponding_room_edge;
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::MultiroomMonoFlowField;
+use crate::datatypes::Path;
+use crate::datatypes::PortalIndex;
+use screeps::Position;
+use wasm_bindgen::prelude::*;
+
+/// Walks a search's parent tree (see `SearchResult::parents`) from `target`, a tile
+/// the search reached, back to the start it was reached from. Every tile has one
+/// parent, so this takes one step per path tile and, unlike descending a distance map
+/// or an arbitrary flow field, can't cycle or stall.
+///
+/// Parents of room crossings are recorded on the edge tile stepped onto, so like the
+/// flow field paths, crossings show up as that edge tile and the tile landed on.
+pub fn path_to_parent_tree_origin(target: Position, parents: &MultiroomMonoFlowField) -> Path {
+    walk_parent_tree(target, parents, None)
+}
+
+/// Walks the parent tree of a portal-aware search back from `target` like
+/// `path_to_parent_tree_origin`. Portal crossings are recorded on the portal tile
+/// stepped onto, so they show up as that tile and the portal's exit, the same way
+/// room crossings do.
+pub fn path_to_parent_tree_origin_with_portals(
+    target: Position,
+    parents: &MultiroomMonoFlowField,
+    portal_index: &PortalIndex,
+) -> Path {
+    walk_parent_tree(target, parents, Some(portal_index))
+}
+
+fn walk_parent_tree(
+    target: Position,
+    parents: &MultiroomMonoFlowField,
+    portal_index: Option<&PortalIndex>,
+) -> Path {
+    // The tile a step onto `tile` was recorded on: the other end of a portal or room
+    // edge, or the tile itself.
+    let recorded_on = |tile: Position| {
+        portal_index
+            .and_then(|portal_index| portal_index.exit(tile))
+            .unwrap_or_else(|| corresponding_room_edge(tile))
+    };
+
+    let mut path = Path::new();
+    path.add(target);
+    let mut current = recorded_on(target);
+    if current != target {
+        path.add(current);
+    }
+
+    while let Some(direction) = parents.get(current) {
+        // Parents always point back within the room of the tile they're recorded on.
+        let Ok(next) = current.checked_add_direction(direction) else {
+            break;
+        };
+        path.add(next);
+        current = recorded_on(next);
+        if current != next {
+            path.add(current);
+        }
+    }
+
+    path
+}
+
+#[wasm_bindgen]
+pub fn js_path_to_parent_tree_origin(target: u32, parents: &MultiroomMonoFlowField) -> Path {
+    path_to_parent_tree_origin(Position::from_packed(target), parents)
+}
+
+#[wasm_bindgen]
+pub fn js_path_to_parent_tree_origin_with_portals(
+    target: u32,
+    parents: &MultiroomMonoFlowField,
+) -> Path {
+    with_configured_portal_index(|portal_index| {
+        path_to_parent_tree_origin_with_portals(
+            Position::from_packed(target),
+            parents,
+            portal_index,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::astar::astar_search;
+    use crate::algorithms::distance_map::breadth_first_search::bfs_search;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::algorithms::distance_map::goals::{Goal, GoalTracker};
+    use crate::algorithms::path::to_multiroom_mono_flow_field_origin::path_to_multiroom_mono_flow_field_origin;
+    use crate::datatypes::ClockworkCostMatrix;
+    use crate::datatypes::RoomDataCache;
+    use crate::test_fixtures::{pos, positions, xy};
+    use screeps::RoomName;
+
+    /// Plains and swamps in a fixed pattern that differs per room, with walls in the
+    /// corners.
+    fn matrix(room: RoomName) -> ClockworkCostMatrix {
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        let seed = room.packed_repr() as usize;
+        for x in 0..50u8 {
+            for y in 0..50u8 {
+                let corner = (x == 0 || x == 49) && (y == 0 || y == 49);
+                let cost = match (x as usize * 7 + y as usize * 13 + seed) % 5 {
+                    _ if corner => 255,
+                    0 => 5,
+                    _ => 1,
+                };
+                matrix.set(xy(x, y), cost);
+            }
+        }
+        matrix
+    }
+
+    /// The cost of walking `path` from its last tile (the origin) to its first.
+    fn forward_cost(path: &[Position]) -> usize {
+        let mut cost = 0;
+        let mut tiles = path.iter().rev().peekable();
+        tiles.next();
+        while let Some(tile) = tiles.next() {
+            let step_cost = matrix(tile.room_name()).get(tile.xy()) as usize;
+            match tiles.peek() {
+                // A crossing costs the higher of the edge tile and the tile landed on.
+                Some(landing) if landing.room_name() != tile.room_name() => {
+                    let landing_cost = matrix(landing.room_name()).get(landing.xy()) as usize;
+                    cost += step_cost.max(landing_cost);
+                    tiles.next();
+                }
+                _ => cost += step_cost,
+            }
+        }
+        cost
+    }
+
+    #[test]
+    fn parent_tree_paths_are_exact() {
+        let origin = pos("W1N1", 25, 25);
+        let result = astar_search(
+            vec![origin],
+            RoomDataCache::new(9, |room: RoomName| Some(matrix(room))).with_parents(true),
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            1.0,
+            None,
+            GoalTracker::new(None, None),
+        );
+        let distance_map = result.distance_map();
+        let parents = result.parents().unwrap();
+
+        let targets = [
+            pos("W1N1", 40, 10),
+            pos("W2N1", 49, 20),
+            pos("W2N1", 10, 30),
+            pos("W1N2", 25, 49),
+            pos("W0N0", 3, 4),
+            pos("W2N2", 45, 45),
+        ];
+        for target in targets {
+            let path = positions(&path_to_parent_tree_origin(target, parents));
+            assert_eq!(path.last(), Some(&origin));
+            assert_eq!(forward_cost(&path), distance_map.get(target));
+            for window in path.windows(2) {
+                let (from, to) = (window[0], window[1]);
+                if from.room_name() == to.room_name() {
+                    assert_eq!(from.get_range_to(to), 1, "{:?} -> {:?}", from, to);
+                } else {
+                    assert_eq!(corresponding_room_edge(from), to);
+                }
+            }
+
+            // The parents are a flow field too: a creep standing on the tile the
+            // target's parent is recorded on follows the same path.
+            let standing = corresponding_room_edge(target);
+            let flow_path = path_to_multiroom_mono_flow_field_origin(standing, parents).unwrap();
+            assert_eq!(
+                positions(&flow_path),
+                path[path.len() - flow_path.len()..].to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn searches_only_keep_parents_when_asked() {
+        let result = dijkstra_multiroom_distance_map(
+            vec![pos("W1N1", 25, 25)],
+            |room: RoomName| Some(matrix(room)),
+            1_000,
+            1,
+            usize::MAX,
+            None,
+            None,
+        );
+        assert!(result.parents().is_none());
+    }
+
+    #[test]
+    fn portal_parent_tree_paths_cross_portals() {
+        let start = pos("W1N1", 5, 10);
+        let entrance = pos("W1N1", 10, 10);
+        let exit = pos("W5N1", 10, 10);
+        let target = pos("W5N1", 15, 10);
+        let mut portals = PortalIndex::default();
+        portals.add_bidirectional(entrance, exit);
+        let plains = |room: RoomName| {
+            (room == start.room_name() || room == target.room_name())
+                .then(|| ClockworkCostMatrix::new(Some(1)))
+        };
+        let goals = || GoalTracker::new(Some(vec![Goal::within(target, 0)]), None);
+
+        let astar = astar_search(
+            vec![start],
+            RoomDataCache::new(2, plains).with_parents(true),
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            1.0,
+            Some(&portals),
+            goals(),
+        );
+        let bfs = bfs_search(
+            vec![start],
+            RoomDataCache::new(2, plains).with_parents(true),
+            usize::MAX,
+            usize::MAX,
+            Some(&portals),
+            goals(),
+        );
+
+        for result in [astar, bfs] {
+            assert_eq!(result.distance_map().get(target), 10);
+            let path = positions(&path_to_parent_tree_origin_with_portals(
+                target,
+                result.parents().unwrap(),
+                &portals,
+            ));
+            assert_eq!(path.len(), 12);
+            assert_eq!(path.first(), Some(&target));
+            assert_eq!(path.last(), Some(&start));
+            assert_eq!(path[5..7], [exit, entrance]);
+            for window in path.windows(2) {
+                let (from, to) = (window[0], window[1]);
+                if from.room_name() == to.room_name() {
+                    assert_eq!(from.get_range_to(to), 1, "{:?} -> {:?}", from, to);
+                }
+            }
+        }
+    }
+}
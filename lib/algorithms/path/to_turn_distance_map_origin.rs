@@ -0,0 +1,263 @@
+use crate::algorithms::map::corresponding_room_edge;
+use crate::datatypes::MultiroomTurnDistanceMap;
+use crate::datatypes::Path;
+use screeps::{Direction, Position};
+use wasm_bindgen::prelude::*;
+
+// Maximum iterations to prevent infinite loops
+const MAX_STEPS: usize = 2500;
+
+/// Every state a tile can be in, with start tiles first so a path ends as soon as it
+/// can.
+const STATES: [Option<Direction>; 9] = [
+    None,
+    Some(Direction::Top),
+    Some(Direction::TopRight),
+    Some(Direction::Right),
+    Some(Direction::BottomRight),
+    Some(Direction::Bottom),
+    Some(Direction::BottomLeft),
+    Some(Direction::Left),
+    Some(Direction::TopLeft),
+];
+
+/// Walks a turn-penalized search (see `SearchResult::turn_distance_map`) from
+/// `target` back to the start it was reached from, along the path the search charged
+/// for. Each step picks the direction the previous tile was entered in that, with the
+/// turn it makes, accounts for the current tile's distance.
+///
+/// Like `path_to_parent_tree_origin`, room crossings show up as the edge tile stepped
+/// onto and the tile landed on.
+pub fn path_to_turn_distance_map_origin(
+    target: Position,
+    turn_distance_map: &MultiroomTurnDistanceMap,
+) -> Result<Path, &'static str> {
+    let mut direction = cheapest_state(|state| turn_distance_map.get(target, state))
+        .ok_or("Target was not reached by the search")?;
+
+    let mut path = Path::new();
+    path.add(target);
+    let mut current = corresponding_room_edge(target);
+    if current != target {
+        path.add(current);
+    }
+
+    let mut steps = 0;
+    while let Some(entered) = direction {
+        if steps == MAX_STEPS {
+            return Err("Path exceeded maximum length");
+        }
+
+        // The search entered this tile from the previous one, so head back the other way.
+        let next = current
+            .checked_add_direction(-entered)
+            .map_err(|_| "Direction points outside room bounds")?;
+        path.add(next);
+        direction = cheapest_state(|state| {
+            turn_distance_map
+                .get(next, state)
+                .saturating_add(turn_distance_map.turn_cost(state, entered))
+        })
+        .ok_or("Path left the searched area")?;
+
+        current = corresponding_room_edge(next);
+        if current != next {
+            path.add(current);
+        }
+        steps += 1;
+    }
+
+    Ok(path)
+}
+
+/// The state with the lowest cost, if any was reached. Ties go to start tiles, then
+/// to the first direction.
+fn cheapest_state(cost: impl Fn(Option<Direction>) -> usize) -> Option<Option<Direction>> {
+    STATES
+        .iter()
+        .map(|state| (*state, cost(*state)))
+        .min_by_key(|(_, cost)| *cost)
+        .filter(|(_, cost)| *cost != usize::MAX)
+        .map(|(state, _)| state)
+}
+
+#[wasm_bindgen]
+pub fn js_path_to_turn_distance_map_origin(
+    target: u32,
+    turn_distance_map: &MultiroomTurnDistanceMap,
+) -> Result<Path, JsValue> {
+    match path_to_turn_distance_map_origin(Position::from_packed(target), turn_distance_map) {
+        Ok(path) => Ok(path),
+        Err(e) => Err(js_sys::Error::new(&format!(
+            "Error calculating path to turn distance map origin: {}",
+            e
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::algorithms::distance_map::goals::{Goal, GoalTracker};
+    use crate::algorithms::distance_map::turn_penalty::turn_penalty_search;
+    use crate::datatypes::ClockworkCostMatrix;
+    use crate::datatypes::RoomDataCache;
+    use crate::test_fixtures::{pos, positions, random_matrix};
+    use screeps::RoomName;
+
+    /// The directions a path moves in, leaving out the hop from an edge tile to the
+    /// tile landed on in the next room.
+    fn moves(path: &Path) -> Vec<Direction> {
+        positions(path)
+            .windows(2)
+            .filter(|pair| pair[0].room_name() == pair[1].room_name())
+            .map(|pair| pair[0].get_direction_to(pair[1]).unwrap())
+            .collect()
+    }
+
+    fn turns(moves: &[Direction]) -> usize {
+        moves.windows(2).filter(|pair| pair[0] != pair[1]).count()
+    }
+
+    #[test]
+    fn turn_penalties_straighten_paths() {
+        let origin = pos("W1N1", 10, 25);
+        let target = pos("W1N1", 20, 30);
+        let plains = |_: RoomName| Some(ClockworkCostMatrix::new(Some(1)));
+
+        let result = turn_penalty_search(
+            vec![origin],
+            RoomDataCache::new(1, plains),
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            1.0,
+            5,
+            GoalTracker::new(Some(vec![Goal::within(target, 0)]), None),
+        );
+        let turn_distance_map = result.turn_distance_map().unwrap();
+        let path = path_to_turn_distance_map_origin(target, turn_distance_map).unwrap();
+        let path_moves = moves(&path);
+
+        assert_eq!(positions(&path).first(), Some(&target));
+        assert_eq!(positions(&path).last(), Some(&origin));
+        assert_eq!(path_moves.len(), 10);
+        assert_eq!(turns(&path_moves), 1);
+        assert_eq!(
+            result.distance_map().get(target),
+            path_moves.len() + 5 * turns(&path_moves)
+        );
+    }
+
+    #[test]
+    fn turn_distances_without_a_penalty_match_plain_distances() {
+        let origin = pos("W1N1", 10, 25);
+        let matrix = |room: RoomName| {
+            let mut matrix = ClockworkCostMatrix::new(Some(1));
+            if room == "W1N1".parse::<RoomName>().unwrap() {
+                for y in 10..40 {
+                    matrix.set(pos("W1N1", 30, y).xy(), 255);
+                    matrix.set(pos("W1N1", 35, y + 5).xy(), 5);
+                }
+            }
+            Some(matrix)
+        };
+        let plain = dijkstra_multiroom_distance_map(
+            vec![origin],
+            matrix,
+            usize::MAX,
+            2,
+            usize::MAX,
+            None,
+            None,
+        )
+        .distance_map();
+        let result = turn_penalty_search(
+            vec![origin],
+            RoomDataCache::new(2, matrix),
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            1.0,
+            0,
+            GoalTracker::new(None, None),
+        );
+
+        let distance_map = result.distance_map();
+        assert_eq!(distance_map.rooms().len(), plain.rooms().len());
+        for room in plain.rooms() {
+            let room_map = plain.get_room_map(room).unwrap();
+            for (xy, distance) in room_map.enumerate() {
+                let tile = Position::new(xy.x, xy.y, room);
+                assert_eq!(distance_map.get(tile), *distance, "{}", tile);
+            }
+        }
+
+        // Every reached tile walks back to the origin, crossing rooms as it goes.
+        let turn_distance_map = result.turn_distance_map().unwrap();
+        for target in [
+            pos("W1N1", 45, 25),
+            pos("W2N1", 40, 10),
+            pos("W2N1", 48, 48),
+        ] {
+            let path = path_to_turn_distance_map_origin(target, turn_distance_map).unwrap();
+            let path_positions = positions(&path);
+            assert_eq!(path_positions.last(), Some(&origin));
+            assert!(path_positions
+                .windows(2)
+                .all(|pair| pair[0].get_range_to(pair[1]) == 1));
+        }
+    }
+
+    #[test]
+    fn weighted_turn_searches_report_the_bound_they_achieve() {
+        let target = pos("W1N1", 45, 40);
+        for seed in 0..8 {
+            let matrix = random_matrix(seed, 2, |_, roll| match roll % 10 {
+                0 => Some(255),
+                1..=4 => Some(10),
+                _ => None,
+            });
+            let search = |heuristic_weight: f64| {
+                turn_penalty_search(
+                    vec![pos("W1N1", 5, 10)],
+                    RoomDataCache::new(1, |_| Some(matrix.clone())),
+                    usize::MAX,
+                    usize::MAX,
+                    // Plains cost 2, so twice the range is still an admissible heuristic.
+                    |position: Position| position.get_range_to(target) as usize * 2,
+                    heuristic_weight,
+                    3,
+                    GoalTracker::new(Some(vec![Goal::within(target, 0)]), None),
+                )
+            };
+
+            let optimal = search(1.0);
+            let optimal_cost = optimal.distance_map().get(target);
+            if optimal_cost == usize::MAX {
+                continue;
+            }
+            assert_eq!(optimal.suboptimality_bound(), 1.0, "seed {}", seed);
+
+            let weighted = search(2.0);
+            let cost = weighted.distance_map().get(target);
+            let bound = weighted.suboptimality_bound();
+            assert!(
+                (1.0..=2.0).contains(&bound),
+                "seed {}: bound {}",
+                seed,
+                bound
+            );
+            assert!(
+                cost as f64 <= optimal_cost as f64 * bound + 1e-9,
+                "seed {}: cost {}, optimal {}, bound {}",
+                seed,
+                cost,
+                optimal_cost,
+                bound
+            );
+        }
+    }
+}
@@ -0,0 +1,499 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::map::{corresponding_room_edge, room_edge_sources, same_room_neighbor};
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::DistanceMap;
+use crate::datatypes::OpenList;
+use crate::datatypes::Path;
+use crate::datatypes::RoomDataCache;
+use crate::utils::set_panic_hook;
+use screeps::Direction;
+use screeps::Position;
+use screeps::RoomName;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use super::PathResult;
+
+static ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::Top,
+    Direction::TopRight,
+    Direction::Right,
+    Direction::BottomRight,
+    Direction::Bottom,
+    Direction::BottomLeft,
+    Direction::Left,
+    Direction::TopLeft,
+];
+
+/// The range between two positions, less one for each room boundary between them:
+/// stepping onto an edge tile also carries the creep into the next room, covering two
+/// tiles of range with a single move. This keeps the heuristic consistent in both
+/// directions, which the stopping condition relies on.
+fn heuristic(from: Position, to: Position) -> usize {
+    let (from_x, from_y) = from.world_coords();
+    let (to_x, to_y) = to.world_coords();
+    let crossings = (from_x.div_euclid(50) - to_x.div_euclid(50)).unsigned_abs()
+        + (from_y.div_euclid(50) - to_y.div_euclid(50)).unsigned_abs();
+    (from.get_range_to(to) as usize).saturating_sub(crossings as usize)
+}
+
+#[derive(Copy, Clone)]
+struct State {
+    // The cost between the current position and this frontier's origin.
+    g_score: usize,
+    // The current position.
+    position: Position,
+    // The index of the position's room in the room data cache.
+    room_key: usize,
+}
+
+/// One half of the search: a bucketed open list, plus the link each reached position
+/// was relaxed through. A link is the neighboring position toward this frontier's
+/// origin and the tile stepped onto between the two, which differs from the
+/// position itself only when the move crosses a room edge.
+struct Frontier {
+    open: OpenList<State>,
+    links: HashMap<Position, (Position, Position)>,
+}
+
+impl Frontier {
+    fn new() -> Self {
+        Self {
+            open: OpenList::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, state: State, f_score: usize) {
+        self.open.push(f_score, state);
+    }
+
+    /// The lowest f_score left in the open list, or `usize::MAX` if it's empty.
+    fn min_f_score(&mut self) -> usize {
+        self.open.peek_key().unwrap_or(usize::MAX)
+    }
+
+    fn pop(&mut self) -> Option<State> {
+        self.open.pop()
+    }
+}
+
+struct BidirectionalSearch<F>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+{
+    start: Position,
+    goal: Position,
+    max_path_cost: usize,
+    // Holds the cost matrices and the forward distances from the start.
+    cached_room_data: RoomDataCache<F>,
+    // Backward distances to the goal, indexed by the same room keys.
+    backward_distances: Vec<DistanceMap>,
+    forward: Frontier,
+    backward: Frontier,
+    // The cheapest complete path seen so far, as its cost and the position where
+    // the two frontiers met.
+    best: Option<(usize, Position)>,
+    ops: usize,
+}
+
+impl<F> BidirectionalSearch<F>
+where
+    F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
+{
+    /// Gets the key for a room with a cost matrix, loading it if needed.
+    fn room_key(&mut self, room: RoomName) -> Option<usize> {
+        let key = self.cached_room_data.get_room_key(room)?;
+        self.cached_room_data[key].cost_matrix.as_ref()?;
+        if key >= self.backward_distances.len() {
+            self.backward_distances
+                .resize_with(key + 1, DistanceMap::new);
+        }
+        Some(key)
+    }
+
+    /// The cost to step onto `step` from within its own room, or `None` if it's impassable.
+    fn step_cost(&self, room_key: usize, step: Position) -> Option<usize> {
+        let cost = self.cached_room_data[room_key]
+            .cost_matrix
+            .as_ref()?
+            .get(step.xy());
+        if cost == 255 {
+            None
+        } else {
+            Some(cost as usize)
+        }
+    }
+
+    /// The cost of a step that crosses a room edge and lands on `landing`, see
+    /// `RoomDataCache::crossing_cost`.
+    fn crossing_cost(
+        &self,
+        cost: usize,
+        landing_room_key: usize,
+        landing: Position,
+    ) -> Option<usize> {
+        self.cached_room_data
+            .crossing_cost(cost as u8, landing_room_key, landing)
+            .map(usize::from)
+    }
+
+    fn check_meeting(&mut self, position: Position, room_key: usize) {
+        let forward = self.cached_room_data[room_key].distance_map[position.xy()];
+        let backward = self.backward_distances[room_key][position.xy()];
+        if forward == usize::MAX || backward == usize::MAX {
+            return;
+        }
+        let total = forward.saturating_add(backward);
+        if total > self.max_path_cost {
+            return;
+        }
+        if self.best.map(|(cost, _)| total < cost).unwrap_or(true) {
+            self.best = Some((total, position));
+        }
+    }
+
+    /// Relaxes every move out of the current position, as in unidirectional A*.
+    fn expand_forward(&mut self, state: State) {
+        for direction in ALL_DIRECTIONS.iter() {
+            let Some(step) = same_room_neighbor(state.position, *direction) else {
+                continue;
+            };
+            let Some(cost) = self.step_cost(state.room_key, step) else {
+                continue;
+            };
+            let neighbor = corresponding_room_edge(step);
+            let (room_key, cost) = if neighbor.room_name() == state.position.room_name() {
+                (state.room_key, cost)
+            } else {
+                let Some(key) = self.room_key(neighbor.room_name()) else {
+                    continue;
+                };
+                match self.crossing_cost(cost, key, neighbor) {
+                    Some(cost) => (key, cost),
+                    None => continue,
+                }
+            };
+
+            let next_cost = state.g_score.saturating_add(cost);
+            if self.cached_room_data[room_key].distance_map[neighbor.xy()] <= next_cost {
+                continue;
+            }
+            self.cached_room_data[room_key].distance_map[neighbor.xy()] = next_cost;
+            self.forward.links.insert(neighbor, (state.position, step));
+            self.forward.push(
+                State {
+                    g_score: next_cost,
+                    position: neighbor,
+                    room_key,
+                },
+                next_cost.saturating_add(heuristic(neighbor, self.goal)),
+            );
+            self.ops += 1;
+            self.check_meeting(neighbor, room_key);
+        }
+    }
+
+    /// Relaxes every move into the current position. Moves are charged for the tile
+    /// stepped onto, so the backward edge from a position to its predecessor costs
+    /// the same as the forward move: the step tile's cost in the predecessor's room.
+    /// Room edge tiles are only entered from the matching edge of the neighboring room.
+    fn expand_backward(&mut self, state: State) {
+        for step in room_edge_sources(state.position) {
+            let step_room_key = if step.room_name() == state.position.room_name() {
+                state.room_key
+            } else {
+                match self.room_key(step.room_name()) {
+                    Some(key) => key,
+                    None => continue,
+                }
+            };
+            let Some(mut cost) = self.step_cost(step_room_key, step) else {
+                continue;
+            };
+            if step_room_key != state.room_key {
+                match self.crossing_cost(cost, state.room_key, state.position) {
+                    Some(crossing_cost) => cost = crossing_cost,
+                    None => continue,
+                }
+            }
+            let next_cost = state.g_score.saturating_add(cost);
+
+            for direction in ALL_DIRECTIONS.iter() {
+                let Some(previous) = same_room_neighbor(step, *direction) else {
+                    continue;
+                };
+                if self.backward_distances[step_room_key][previous.xy()] <= next_cost {
+                    continue;
+                }
+                self.backward_distances[step_room_key][previous.xy()] = next_cost;
+                self.backward.links.insert(previous, (state.position, step));
+                self.backward.push(
+                    State {
+                        g_score: next_cost,
+                        position: previous,
+                        room_key: step_room_key,
+                    },
+                    next_cost.saturating_add(heuristic(previous, self.start)),
+                );
+                self.ops += 1;
+                self.check_meeting(previous, step_room_key);
+            }
+        }
+    }
+
+    fn build_path(&self, meeting: Position) -> Path {
+        let mut reversed = vec![meeting];
+        let mut current = meeting;
+        while let Some((previous, step)) = self.forward.links.get(&current) {
+            if *step != current {
+                reversed.push(*step);
+            }
+            reversed.push(*previous);
+            current = *previous;
+        }
+
+        let mut path = Path::new();
+        for position in reversed.into_iter().rev() {
+            path.add(position);
+        }
+
+        let mut current = meeting;
+        while let Some((next, step)) = self.backward.links.get(&current) {
+            if step != next {
+                path.add(*step);
+            }
+            path.add(*next);
+            current = *next;
+        }
+        path
+    }
+}
+
+/// Finds the cheapest path from `start` to `goal` by running A* from both ends until
+/// the frontiers meet. Each side estimates the remaining cost with the range to the
+/// other end, and the search stops once either side can no longer improve on the best
+/// meeting point. Like the other A* searches, this assumes passable tiles cost at least 1.
+///
+/// Paths costing more than `max_path_cost` are not returned.
+pub fn bidirectional_astar_multiroom_path(
+    start: Position,
+    goal: Position,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+) -> PathResult {
+    set_panic_hook();
+    let mut search = BidirectionalSearch {
+        start,
+        goal,
+        max_path_cost,
+        cached_room_data: RoomDataCache::new(max_rooms, get_cost_matrix),
+        backward_distances: Vec::new(),
+        forward: Frontier::new(),
+        backward: Frontier::new(),
+        best: None,
+        ops: 0,
+    };
+
+    let (Some(start_key), Some(goal_key)) = (
+        search.room_key(start.room_name()),
+        search.room_key(goal.room_name()),
+    ) else {
+        return PathResult::not_found(search.ops);
+    };
+
+    search.cached_room_data[start_key].distance_map[start.xy()] = 0;
+    search.forward.push(
+        State {
+            g_score: 0,
+            position: start,
+            room_key: start_key,
+        },
+        heuristic(start, goal),
+    );
+    search.backward_distances[goal_key][goal.xy()] = 0;
+    search.backward.push(
+        State {
+            g_score: 0,
+            position: goal,
+            room_key: goal_key,
+        },
+        heuristic(goal, start),
+    );
+    search.ops += 2;
+    search.check_meeting(start, start_key);
+
+    while search.ops < max_ops {
+        let forward_min = search.forward.min_f_score();
+        let backward_min = search.backward.min_f_score();
+        // Once either frontier is exhausted, every path it could contribute to has
+        // already been seen by the other side.
+        if forward_min == usize::MAX || backward_min == usize::MAX {
+            break;
+        }
+        if let Some((cost, _)) = search.best {
+            if forward_min.max(backward_min) >= cost {
+                break;
+            }
+        }
+
+        if forward_min <= backward_min {
+            let Some(state) = search.forward.pop() else {
+                break;
+            };
+            if search.cached_room_data[state.room_key].distance_map[state.position.xy()]
+                < state.g_score
+                || state.g_score >= max_path_cost
+            {
+                continue;
+            }
+            search.expand_forward(state);
+        } else {
+            let Some(state) = search.backward.pop() else {
+                break;
+            };
+            if search.backward_distances[state.room_key][state.position.xy()] < state.g_score
+                || state.g_score >= max_path_cost
+            {
+                continue;
+            }
+            search.expand_backward(state);
+        }
+    }
+
+    match search.best {
+        Some((cost, meeting)) => PathResult::new(search.build_path(meeting), cost, search.ops),
+        None => PathResult::not_found(search.ops),
+    }
+}
+
+#[wasm_bindgen]
+pub fn js_bidirectional_astar_multiroom_path(
+    start_packed: u32,
+    goal_packed: u32,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+) -> PathResult {
+    bidirectional_astar_multiroom_path(
+        Position::from_packed(start_packed),
+        Position::from_packed(goal_packed),
+        |room| js_cost_matrix(get_cost_matrix, room),
+        max_rooms,
+        max_ops,
+        max_path_cost,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::test_fixtures::{callback, pos, random_matrix, room, xy};
+
+    /// Mixes plains, swamps and walls with a small deterministic generator.
+    fn random_terrain(seed: u32) -> ClockworkCostMatrix {
+        random_matrix(seed, 1, |_, roll| match roll % 10 {
+            0 | 1 => Some(255),
+            2..=4 => Some(5),
+            _ => None,
+        })
+    }
+
+    fn path_cost(path: &Path, matrices: &HashMap<RoomName, ClockworkCostMatrix>) -> usize {
+        let mut cost = 0;
+        for i in 1..path.len() {
+            let from = *path.get(i - 1).unwrap();
+            let to = *path.get(i).unwrap();
+            if from.room_name() == to.room_name() {
+                assert_eq!(from.get_range_to(to), 1);
+                let step_cost = matrices[&to.room_name()].get(to.xy());
+                assert!(step_cost < 255);
+                cost += step_cost as usize;
+            } else {
+                assert_eq!(to, corresponding_room_edge(from));
+            }
+        }
+        cost
+    }
+
+    #[test]
+    fn bidirectional_matches_dijkstra_across_rooms() {
+        let start = pos("W1N1", 30, 10);
+        let goal = pos("W2N2", 15, 40);
+        for seed in 0..10 {
+            let mut matrices = HashMap::from([
+                (room("W1N1"), random_terrain(seed * 4)),
+                (room("W2N1"), random_terrain(seed * 4 + 1)),
+                (room("W1N2"), random_terrain(seed * 4 + 2)),
+                (room("W2N2"), random_terrain(seed * 4 + 3)),
+            ]);
+            matrices.get_mut(&room("W1N1")).unwrap().set(start.xy(), 1);
+            matrices.get_mut(&room("W2N2")).unwrap().set(goal.xy(), 1);
+
+            let expected = dijkstra_multiroom_distance_map(
+                vec![start],
+                callback(matrices.clone()),
+                usize::MAX,
+                4,
+                usize::MAX,
+                None,
+                None,
+            )
+            .distance_map()
+            .get(goal);
+
+            let result = bidirectional_astar_multiroom_path(
+                start,
+                goal,
+                callback(matrices.clone()),
+                4,
+                usize::MAX,
+                usize::MAX,
+            );
+
+            if expected == usize::MAX {
+                assert!(!result.is_found());
+                continue;
+            }
+            assert_eq!(result.cost(), expected, "seed {seed}");
+            assert_eq!(result.path.get(0), Some(&start));
+            assert_eq!(result.path.get(result.path.len() - 1), Some(&goal));
+            assert_eq!(path_cost(&result.path, &matrices), expected);
+        }
+    }
+
+    #[test]
+    fn bidirectional_handles_start_at_goal() {
+        let start = pos("W1N1", 25, 25);
+        let matrices = HashMap::from([(room("W1N1"), ClockworkCostMatrix::new(Some(1)))]);
+        let result =
+            bidirectional_astar_multiroom_path(start, start, callback(matrices), 1, 100, 100);
+
+        assert_eq!(result.cost(), 0);
+        assert_eq!(result.path.len(), 1);
+    }
+
+    #[test]
+    fn bidirectional_reports_unreachable_goal() {
+        let start = pos("W1N1", 10, 10);
+        let goal = pos("W1N1", 40, 40);
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        for y in 0..50 {
+            matrix.set(xy(25, y), 255);
+        }
+        let result = bidirectional_astar_multiroom_path(
+            start,
+            goal,
+            callback(HashMap::from([(room("W1N1"), matrix)])),
+            1,
+            usize::MAX,
+            usize::MAX,
+        );
+
+        assert!(!result.is_found());
+    }
+}
@@ -0,0 +1,729 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
+use crate::algorithms::distance_map::heuristics::closest_portal_heuristic_cached_with_range;
+use crate::algorithms::map::corresponding_room_edge;
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::DistanceMap;
+use crate::datatypes::ExitGraph;
+use crate::datatypes::OpenList;
+use crate::datatypes::Path;
+use crate::datatypes::PortalIndex;
+use crate::datatypes::RoomDataCache;
+use crate::datatypes::RoomExits;
+use crate::utils::set_panic_hook;
+use screeps::constants::extra::ROOM_AREA;
+use screeps::{xy_to_linear_index, Direction, Position, RoomCoordinate, RoomName, RoomXY};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use super::PathResult;
+
+static ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::Top,
+    Direction::TopRight,
+    Direction::Right,
+    Direction::BottomRight,
+    Direction::Bottom,
+    Direction::BottomLeft,
+    Direction::Left,
+    Direction::TopLeft,
+];
+
+fn xy(x: u8, y: u8) -> RoomXY {
+    RoomXY::new(
+        RoomCoordinate::new(x).unwrap(),
+        RoomCoordinate::new(y).unwrap(),
+    )
+}
+
+/// Stepping onto a room edge or a portal entrance moves the creep out of the room.
+fn is_transition(position: Position, portal_entrances: &[Position]) -> bool {
+    position.is_room_edge() || portal_entrances.contains(&position)
+}
+
+/// Where a creep ends up after stepping onto a transition tile.
+fn landing(step: Position, portal_index: Option<&PortalIndex>) -> Position {
+    portal_index
+        .and_then(|portal_index| portal_index.exit(step))
+        .unwrap_or_else(|| corresponding_room_edge(step))
+}
+
+/// Picks the exits for a room: the middle tile of each run of passable edge tiles,
+/// plus every portal entrance.
+fn find_room_exits(
+    room: RoomName,
+    cost_matrix: &ClockworkCostMatrix,
+    portal_entrances: &[Position],
+) -> Vec<Position> {
+    // Corners belong to the left and right edges, matching `corresponding_room_edge`.
+    let sides: [Vec<RoomXY>; 4] = [
+        (1..49).map(|x| xy(x, 0)).collect(),
+        (0..50).map(|y| xy(49, y)).collect(),
+        (1..49).map(|x| xy(x, 49)).collect(),
+        (0..50).map(|y| xy(0, y)).collect(),
+    ];
+
+    let mut exits = Vec::new();
+    for side in sides.iter() {
+        let mut run: Vec<Position> = Vec::new();
+        for tile in side.iter().map(|tile| Position::new(tile.x, tile.y, room)) {
+            if cost_matrix.get(tile.xy()) < 255 && !portal_entrances.contains(&tile) {
+                run.push(tile);
+            } else if !run.is_empty() {
+                exits.push(run[run.len() / 2]);
+                run.clear();
+            }
+        }
+        if !run.is_empty() {
+            exits.push(run[run.len() / 2]);
+        }
+    }
+    exits.extend(portal_entrances.iter().copied());
+    exits
+}
+
+/// Whether any tile of `room` is within `range` of `goal`. Ranges are global, so a goal
+/// near a room edge can be reached from the neighboring rooms too.
+fn room_in_range(room: RoomName, goal: Position, range: usize) -> bool {
+    let corner = xy(0, 0);
+    let (room_x, room_y) = Position::new(corner.x, corner.y, room).world_coords();
+    let (goal_x, goal_y) = goal.world_coords();
+    let range_x = (goal_x - goal_x.clamp(room_x, room_x + 49)).unsigned_abs();
+    let range_y = (goal_y - goal_y.clamp(room_y, room_y + 49)).unsigned_abs();
+    range_x.max(range_y) as usize <= range
+}
+
+/// A Dijkstra search confined to one room, from a tile where the creep is standing.
+/// Transition tiles are reached (so the cost of leaving through them is known) but
+/// never expanded, since stepping onto one leaves the room.
+struct RoomSearch {
+    source: Position,
+    distances: DistanceMap,
+    parents: Vec<Option<RoomXY>>,
+    ops: usize,
+}
+
+impl RoomSearch {
+    fn run(
+        source: Position,
+        cost_matrix: &ClockworkCostMatrix,
+        portal_entrances: &[Position],
+    ) -> Self {
+        let room = source.room_name();
+        let mut distances = DistanceMap::new();
+        let mut parents = vec![None; ROOM_AREA];
+        let mut open = OpenList::new();
+        let mut ops = 0;
+
+        distances[source.xy()] = 0;
+        open.push(0, xy_to_linear_index(source.xy()));
+
+        while let Some((distance, index)) = open.pop_with_key() {
+            let tile = screeps::linear_index_to_xy(index);
+            if distances[tile] < distance {
+                continue;
+            }
+            ops += 1;
+
+            for direction in ALL_DIRECTIONS.iter() {
+                let Some(neighbor) = tile.checked_add_direction(*direction) else {
+                    continue;
+                };
+                let cost = cost_matrix.get(neighbor);
+                if cost == 255 {
+                    continue;
+                }
+                let next_distance = distance.saturating_add(cost as usize);
+                if distances[neighbor] <= next_distance {
+                    continue;
+                }
+                distances[neighbor] = next_distance;
+                parents[xy_to_linear_index(neighbor)] = Some(tile);
+                let neighbor_position = Position::new(neighbor.x, neighbor.y, room);
+                if !is_transition(neighbor_position, portal_entrances) {
+                    open.push(next_distance, xy_to_linear_index(neighbor));
+                }
+            }
+        }
+
+        Self {
+            source,
+            distances,
+            parents,
+            ops,
+        }
+    }
+
+    fn distance(&self, position: Position) -> usize {
+        self.distances[position.xy()]
+    }
+
+    /// Whether the creep can end its path here, rather than passing through into
+    /// another room.
+    fn can_stop_at(&self, position: Position, portal_entrances: &[Position]) -> bool {
+        position == self.source
+            || (self.distance(position) != usize::MAX && !is_transition(position, portal_entrances))
+    }
+
+    /// Appends the tiles from the source (exclusive) to `target` (inclusive) to the path.
+    fn extend_path(&self, path: &mut Path, target: Position) {
+        let mut reversed = Vec::new();
+        let mut current = target.xy();
+        while current != self.source.xy() {
+            reversed.push(current);
+            match self.parents[xy_to_linear_index(current)] {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        for tile in reversed.into_iter().rev() {
+            path.add(Position::new(tile.x, tile.y, target.room_name()));
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct State {
+    // The cost to reach the current node.
+    g_score: usize,
+    // The start position, or a tile where the creep arrives in a room.
+    position: Position,
+    // The index of the position's room in the room data cache.
+    room_key: usize,
+}
+
+/// Plans a path across many rooms on an abstract graph instead of tile by tile. Each
+/// room is reduced to its exits (one tile per passable stretch of room edge, plus portal
+/// entrances) and the costs between the tiles where creeps arrive and those exits. The
+/// costs are computed once per room and kept in `exit_graph` between searches; entries
+/// are rebuilt automatically when a room's cost matrix changes.
+///
+/// Once the abstract route is found, it's refined into a concrete `Path` by searching
+/// only the rooms along the route. Crossing at a single tile per edge stretch makes the
+/// result slightly suboptimal compared to a full tile-level search.
+#[allow(clippy::too_many_arguments)]
+pub fn hierarchical_multiroom_path(
+    exit_graph: &mut ExitGraph,
+    start: Position,
+    goal: Position,
+    range: usize,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+) -> PathResult {
+    hierarchical_search(
+        exit_graph,
+        start,
+        goal,
+        range,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        heuristic_fn,
+        None,
+    )
+}
+
+/// Portal-aware hierarchical pathfinding: portal entrances are exits of their room,
+/// leading to the corresponding portal exit.
+#[allow(clippy::too_many_arguments)]
+pub fn hierarchical_portal_multiroom_path(
+    exit_graph: &mut ExitGraph,
+    start: Position,
+    goal: Position,
+    range: usize,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: &PortalIndex,
+) -> PathResult {
+    hierarchical_search(
+        exit_graph,
+        start,
+        goal,
+        range,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        heuristic_fn,
+        Some(portal_index),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn hierarchical_search(
+    exit_graph: &mut ExitGraph,
+    start: Position,
+    goal: Position,
+    range: usize,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: Option<&PortalIndex>,
+) -> PathResult {
+    set_panic_hook();
+    let mut open = OpenList::new();
+    let mut ops = 0;
+    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
+    let mut best_costs: HashMap<Position, usize> = HashMap::new();
+    // For each arrival tile, the node it was reached from and the exit stepped onto.
+    let mut parents: HashMap<Position, (Position, Position)> = HashMap::new();
+    // The cheapest way found to finish: its cost, the last node, and the final tile.
+    let mut best_goal: Option<(usize, Position, Position)> = None;
+    // Each room's `RoomExits::fingerprint`, hashed once per search.
+    let mut fingerprints: HashMap<RoomName, u64> = HashMap::new();
+    let portal_entrances = |room: RoomName| {
+        portal_index
+            .map(|portal_index| portal_index.portals_in_room(room))
+            .unwrap_or(&[])
+    };
+
+    let Some(room_key) = cached_room_data.get_room_key(start.room_name()) else {
+        return PathResult::not_found(ops);
+    };
+    best_costs.insert(start, 0);
+    open.push(
+        0,
+        State {
+            g_score: 0,
+            position: start,
+            room_key,
+        },
+    );
+
+    'search: while let Some((
+        min_f_score,
+        State {
+            g_score,
+            position,
+            room_key,
+        },
+    )) = open.pop_with_key()
+    {
+        if best_costs.get(&position).copied().unwrap_or(usize::MAX) < g_score {
+            continue;
+        }
+        if let Some((goal_cost, _, _)) = best_goal {
+            if min_f_score >= goal_cost {
+                break 'search;
+            }
+        }
+        if ops >= max_ops {
+            break 'search;
+        }
+        ops += 1;
+
+        let room = position.room_name();
+        let entrances = portal_entrances(room);
+        let Some(cost_matrix) = cached_room_data[room_key].cost_matrix.as_ref() else {
+            continue;
+        };
+
+        let fingerprint = *fingerprints
+            .entry(room)
+            .or_insert_with(|| RoomExits::fingerprint(cost_matrix, entrances));
+        let room_exits = match exit_graph.current_room(room, fingerprint) {
+            Some(room_exits) => room_exits,
+            None => exit_graph.insert_room(
+                room,
+                RoomExits::new(fingerprint, find_room_exits(room, cost_matrix, entrances)),
+            ),
+        };
+
+        // The start isn't an arrival tile, and rooms the goal's range reaches into also
+        // need the cost to the tiles in range, so both get a fresh search rather than
+        // the cached costs.
+        let is_goal_room = room_in_range(room, goal, range);
+        let exit_costs = match room_exits.costs_from(position) {
+            Some(costs) if position != start && !is_goal_room => costs.to_vec(),
+            _ => {
+                let search = RoomSearch::run(position, cost_matrix, entrances);
+                ops += search.ops;
+                let costs: Vec<usize> = room_exits
+                    .exits()
+                    .iter()
+                    .map(|exit| search.distance(*exit))
+                    .collect();
+                if position != start {
+                    room_exits.insert_costs(position, costs.clone());
+                }
+
+                if is_goal_room {
+                    for (target, distance) in search.distances.enumerate() {
+                        let target = Position::new(target.x, target.y, room);
+                        if target.get_range_to(goal) as usize > range
+                            || !search.can_stop_at(target, entrances)
+                        {
+                            continue;
+                        }
+                        let total = g_score.saturating_add(*distance);
+                        if best_goal.map(|(cost, _, _)| total < cost).unwrap_or(true) {
+                            best_goal = Some((total, position, target));
+                        }
+                    }
+                }
+                costs
+            }
+        };
+        let exits = room_exits.exits().to_vec();
+
+        for (exit, cost) in exits.into_iter().zip(exit_costs) {
+            if cost == usize::MAX {
+                continue;
+            }
+            let arrival = landing(exit, portal_index);
+            let arrival_room_key = match cached_room_data.get_room_key(arrival.room_name()) {
+                Some(key) if cached_room_data[key].cost_matrix.is_some() => key,
+                _ => continue,
+            };
+            let mut next_cost = g_score.saturating_add(cost);
+            if portal_index
+                .and_then(|portals| portals.exit(exit))
+                .is_none()
+            {
+                // `cost` already paid for the exit tile; add anything extra the landing
+                // tile charges.
+                let exit_cost = cached_room_data[room_key]
+                    .cost_matrix
+                    .as_ref()
+                    .map_or(0, |cost_matrix| cost_matrix.get(exit.xy()));
+                let Some(crossing_cost) =
+                    cached_room_data.crossing_cost(exit_cost, arrival_room_key, arrival)
+                else {
+                    continue;
+                };
+                next_cost = next_cost.saturating_add((crossing_cost - exit_cost) as usize);
+            }
+            if best_costs.get(&arrival).copied().unwrap_or(usize::MAX) <= next_cost {
+                continue;
+            }
+            best_costs.insert(arrival, next_cost);
+            parents.insert(arrival, (position, exit));
+
+            let f_score = next_cost.saturating_add(heuristic_fn(arrival));
+            open.push(
+                f_score,
+                State {
+                    g_score: next_cost,
+                    position: arrival,
+                    room_key: arrival_room_key,
+                },
+            );
+        }
+    }
+
+    let Some((cost, last_node, target)) = best_goal else {
+        return PathResult::not_found(ops);
+    };
+
+    // Walk back through the abstract route, then search each traversed room to fill in
+    // the tiles between its arrival tile and the exit it was left through.
+    let mut legs = vec![(last_node, target, None)];
+    let mut current = last_node;
+    while let Some((previous, exit)) = parents.get(&current) {
+        legs.push((*previous, *exit, Some(current)));
+        current = *previous;
+    }
+
+    let mut path = Path::new();
+    path.add(start);
+    for (from, to, arrival) in legs.into_iter().rev() {
+        let room = from.room_name();
+        let Some(cost_matrix) = cached_room_data
+            .get_loaded_room_key(room)
+            .and_then(|room_key| cached_room_data[room_key].cost_matrix.as_ref())
+        else {
+            return PathResult::not_found(ops);
+        };
+        let search = RoomSearch::run(from, cost_matrix, portal_entrances(room));
+        ops += search.ops;
+        search.extend_path(&mut path, to);
+        if let Some(arrival) = arrival {
+            path.add(arrival);
+        }
+    }
+
+    PathResult::new(path, cost, ops)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_hierarchical_multiroom_path(
+    exit_graph: &mut ExitGraph,
+    start_packed: u32,
+    goal_packed: u32,
+    range: usize,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+) -> PathResult {
+    let goal = Position::from_packed(goal_packed);
+    let destinations = [(goal, range)];
+
+    hierarchical_multiroom_path(
+        exit_graph,
+        Position::from_packed(start_packed),
+        goal,
+        range,
+        |room| js_cost_matrix(get_cost_matrix, room),
+        max_rooms,
+        max_ops,
+        base_heuristic_with_range(&destinations),
+    )
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_hierarchical_portal_multiroom_path(
+    exit_graph: &mut ExitGraph,
+    start_packed: u32,
+    goal_packed: u32,
+    range: usize,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+) -> PathResult {
+    let goal = Position::from_packed(goal_packed);
+    let destinations = [(goal, range)];
+
+    with_configured_portal_index(|portal_index| {
+        hierarchical_portal_multiroom_path(
+            exit_graph,
+            Position::from_packed(start_packed),
+            goal,
+            range,
+            |room| js_cost_matrix(get_cost_matrix, room),
+            max_rooms,
+            max_ops,
+            closest_portal_heuristic_cached_with_range(&destinations, portal_index),
+            portal_index,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::algorithms::distance_map::goals::Goal;
+    use crate::test_fixtures::{callback, pos, room};
+
+    /// A room with plains, walls along the edges except for a few gaps, and a wall
+    /// across the middle with an opening.
+    fn walled_room(gaps: &[u8]) -> ClockworkCostMatrix {
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        for i in 0..50 {
+            if !gaps.contains(&i) {
+                matrix.set(xy(i, 0), 255);
+                matrix.set(xy(i, 49), 255);
+                matrix.set(xy(0, i), 255);
+                matrix.set(xy(49, i), 255);
+            }
+            if i != 10 {
+                matrix.set(xy(25, i), 255);
+            }
+        }
+        matrix
+    }
+
+    fn path_cost(
+        path: &Path,
+        matrices: &HashMap<RoomName, ClockworkCostMatrix>,
+        portal_index: Option<&PortalIndex>,
+    ) -> usize {
+        let mut cost = 0;
+        for i in 1..path.len() {
+            let from = *path.get(i - 1).unwrap();
+            let to = *path.get(i).unwrap();
+            if from.room_name() == to.room_name() && from.get_range_to(to) == 1 {
+                let step_cost = matrices[&to.room_name()].get(to.xy());
+                assert!(step_cost < 255);
+                cost += step_cost as usize;
+            } else {
+                assert_eq!(to, landing(from, portal_index));
+            }
+        }
+        cost
+    }
+
+    fn row_of_rooms() -> HashMap<RoomName, ClockworkCostMatrix> {
+        HashMap::from([
+            (room("W3N1"), walled_room(&[20, 21, 22, 40])),
+            (room("W2N1"), walled_room(&[20, 21, 22, 40])),
+            (room("W1N1"), walled_room(&[20, 21, 22, 40])),
+        ])
+    }
+
+    #[test]
+    fn hierarchical_path_crosses_rooms() {
+        let start = pos("W1N1", 40, 40);
+        let goal = pos("W3N1", 5, 5);
+        let matrices = row_of_rooms();
+        let destinations = [Goal::within(goal, 1)];
+        let mut exit_graph = ExitGraph::new();
+
+        let result = hierarchical_multiroom_path(
+            &mut exit_graph,
+            start,
+            goal,
+            1,
+            callback(matrices.clone()),
+            3,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+        );
+
+        let optimal = dijkstra_multiroom_distance_map(
+            vec![start],
+            callback(matrices.clone()),
+            usize::MAX,
+            3,
+            usize::MAX,
+            Some(destinations.to_vec()),
+            None,
+        );
+        let optimal_cost = optimal
+            .distance_map()
+            .get(Position::from_packed(optimal.found_targets()[0]));
+
+        assert!(result.is_found());
+        assert_eq!(result.path.get(0), Some(&start));
+        let end = *result.path.get(result.path.len() - 1).unwrap();
+        assert!(end.get_range_to(goal) <= 1);
+        assert_eq!(path_cost(&result.path, &matrices, None), result.cost());
+        assert!(result.cost() >= optimal_cost);
+        assert!(result.cost() <= optimal_cost + 10);
+        assert_eq!(exit_graph.room_count(), 3);
+    }
+
+    #[test]
+    fn hierarchical_path_rebuilds_changed_rooms() {
+        let start = pos("W1N1", 40, 40);
+        let goal = pos("W2N1", 40, 40);
+        let mut matrices = row_of_rooms();
+        let destinations = [(goal, 0)];
+        let mut exit_graph = ExitGraph::new();
+
+        let first = hierarchical_multiroom_path(
+            &mut exit_graph,
+            start,
+            goal,
+            0,
+            callback(matrices.clone()),
+            2,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+        );
+        assert!(first.path.find_next_index(&pos("W1N1", 0, 40)).is_some());
+
+        // Close the gap that the first path used; the cached costs must not be reused.
+        matrices.get_mut(&room("W1N1")).unwrap().set(xy(0, 40), 255);
+        let second = hierarchical_multiroom_path(
+            &mut exit_graph,
+            start,
+            goal,
+            0,
+            callback(matrices.clone()),
+            2,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+        );
+
+        assert!(second.is_found());
+        assert!(second.path.find_next_index(&pos("W1N1", 0, 40)).is_none());
+        assert_eq!(path_cost(&second.path, &matrices, None), second.cost());
+        assert!(second.cost() > first.cost());
+    }
+
+    #[test]
+    fn hierarchical_path_uses_portals() {
+        let start = pos("W1N1", 30, 30);
+        let entry = pos("W1N1", 35, 30);
+        let exit = pos("W9N9", 20, 20);
+        let goal = pos("W9N9", 20, 25);
+        let mut portal_index = PortalIndex::default();
+        portal_index.add_bidirectional(entry, exit);
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W9N9"), ClockworkCostMatrix::new(Some(1))),
+        ]);
+        let destinations = [(goal, 0)];
+        let mut exit_graph = ExitGraph::new();
+
+        let result = hierarchical_portal_multiroom_path(
+            &mut exit_graph,
+            start,
+            goal,
+            0,
+            callback(matrices.clone()),
+            2,
+            usize::MAX,
+            closest_portal_heuristic_cached_with_range(&destinations, &portal_index),
+            &portal_index,
+        );
+
+        assert!(result.is_found());
+        assert_eq!(result.cost(), 10);
+        assert!(result.path.find_next_index(&entry).is_some());
+        assert!(result.path.find_next_index(&exit).is_some());
+        assert_eq!(
+            path_cost(&result.path, &matrices, Some(&portal_index)),
+            result.cost()
+        );
+    }
+
+    #[test]
+    fn hierarchical_path_within_one_room() {
+        let start = pos("W1N1", 5, 5);
+        let goal = pos("W1N1", 40, 5);
+        let matrices = row_of_rooms();
+        let destinations = [(goal, 0)];
+        let result = hierarchical_multiroom_path(
+            &mut ExitGraph::new(),
+            start,
+            goal,
+            0,
+            callback(matrices.clone()),
+            1,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+        );
+
+        // Around the wall through its opening at y = 10.
+        assert_eq!(result.cost(), 35);
+        assert_eq!(path_cost(&result.path, &matrices, None), 35);
+    }
+
+    #[test]
+    fn hierarchical_path_stops_in_range_across_a_room_edge() {
+        // The goal is near the edge of W1N1, so the creep can stop in W2N1.
+        let start = pos("W2N1", 40, 25);
+        let goal = pos("W1N1", 1, 25);
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W2N1"), ClockworkCostMatrix::new(Some(1))),
+        ]);
+        let destinations = [(goal, 3)];
+        let result = hierarchical_multiroom_path(
+            &mut ExitGraph::new(),
+            start,
+            goal,
+            3,
+            callback(matrices.clone()),
+            2,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+        );
+
+        assert!(result.is_found());
+        let end = *result.path.get(result.path.len() - 1).unwrap();
+        assert_eq!(end.room_name(), room("W2N1"));
+        assert!(end.get_range_to(goal) <= 3);
+        assert_eq!(result.cost(), 8);
+        assert_eq!(path_cost(&result.path, &matrices, None), 8);
+    }
+}
@@ -0,0 +1,654 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
+use crate::algorithms::distance_map::heuristics::closest_portal_heuristic_cached_with_range;
+use crate::algorithms::map::{corresponding_room_edge, same_room_neighbor};
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::OpenList;
+use crate::datatypes::Path;
+use crate::datatypes::PortalIndex;
+use crate::datatypes::RoomDataCache;
+use crate::utils::set_panic_hook;
+use screeps::Direction;
+use screeps::Position;
+use screeps::RoomName;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use super::PathResult;
+
+static ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::Top,
+    Direction::TopRight,
+    Direction::Right,
+    Direction::BottomRight,
+    Direction::Bottom,
+    Direction::BottomLeft,
+    Direction::Left,
+    Direction::TopLeft,
+];
+
+#[derive(Copy, Clone)]
+struct State {
+    // The number of moves to reach the current jump point.
+    g_score: usize,
+    // The current jump point.
+    position: Position,
+    // The direction of the jump that reached this point. Jump points that can reach a
+    // room exit or portal (and start/landing tiles) are expanded in every direction.
+    open_direction: Option<Direction>,
+    // The index of the position's room in the room data cache.
+    room_key: usize,
+}
+
+/// How a jump point was reached: from another jump point in the same room along a
+/// straight or diagonal line, or by stepping onto a room edge or portal entrance tile.
+#[derive(Copy, Clone)]
+struct Parent {
+    position: Position,
+    step: Option<Position>,
+}
+
+/// A view of a single room's cost matrix for jumping. Room edges and portal entrances
+/// are never jumped over: stepping onto one moves the creep to another room, so they are
+/// treated as obstacles by the rays and handled as explicit transitions instead.
+struct RoomGrid<'a> {
+    cost_matrix: &'a ClockworkCostMatrix,
+    portal_index: Option<&'a PortalIndex>,
+    destinations: &'a [(Position, usize)],
+}
+
+impl RoomGrid<'_> {
+    fn is_transition(&self, position: Position) -> bool {
+        position.is_room_edge()
+            || self
+                .portal_index
+                .map(|portal_index| portal_index.exit(position).is_some())
+                .unwrap_or(false)
+    }
+
+    fn is_passable(&self, position: Position) -> bool {
+        self.cost_matrix.get(position.xy()) < 255
+    }
+
+    fn is_walkable(&self, position: Option<Position>) -> bool {
+        position
+            .map(|position| !self.is_transition(position) && self.is_passable(position))
+            .unwrap_or(false)
+    }
+
+    fn is_exit(&self, position: Position) -> bool {
+        self.is_transition(position) && self.is_passable(position)
+    }
+
+    fn is_near_exit(&self, position: Position) -> bool {
+        ALL_DIRECTIONS.iter().any(|direction| {
+            same_room_neighbor(position, *direction)
+                .map(|neighbor| self.is_exit(neighbor))
+                .unwrap_or(false)
+        })
+    }
+
+    fn is_goal(&self, position: Position) -> bool {
+        is_goal(position, self.destinations)
+    }
+
+    /// Directions that become necessary when an obstacle beside the jump blocks the
+    /// symmetric path around it. Each entry pairs the blocked side with the direction
+    /// that is forced open by it.
+    fn forced_directions(direction: Direction) -> [(Direction, Direction); 2] {
+        if direction.is_diagonal() {
+            [
+                (direction.multi_rot(-3), direction.multi_rot(-2)),
+                (direction.multi_rot(3), direction.multi_rot(2)),
+            ]
+        } else {
+            [
+                (direction.multi_rot(-2), direction.rot_ccw()),
+                (direction.multi_rot(2), direction.rot_cw()),
+            ]
+        }
+    }
+
+    fn forced_neighbors(
+        &self,
+        position: Position,
+        direction: Direction,
+    ) -> impl Iterator<Item = Direction> + '_ {
+        IntoIterator::into_iter(Self::forced_directions(direction))
+            .filter(move |(side, forced)| {
+                !self.is_walkable(same_room_neighbor(position, *side))
+                    && self.is_walkable(same_room_neighbor(position, *forced))
+            })
+            .map(|(_, forced)| forced)
+    }
+
+    fn has_forced_neighbor(&self, position: Position, direction: Direction) -> bool {
+        self.forced_neighbors(position, direction).next().is_some()
+    }
+
+    /// The directions worth expanding from a jump point reached by `open_direction`.
+    fn successor_directions(
+        &self,
+        position: Position,
+        open_direction: Option<Direction>,
+    ) -> Vec<Direction> {
+        let Some(direction) = open_direction else {
+            return ALL_DIRECTIONS.to_vec();
+        };
+        let mut directions = vec![direction];
+        if direction.is_diagonal() {
+            directions.push(direction.rot_ccw());
+            directions.push(direction.rot_cw());
+        }
+        directions.extend(self.forced_neighbors(position, direction));
+        directions
+    }
+
+    /// Moves from `from` in `direction` until reaching a jump point, returning it, or
+    /// until hitting an obstacle, returning `None`. Each scanned tile counts as one op.
+    fn jump(&self, from: Position, direction: Direction, ops: &mut usize) -> Option<Position> {
+        let mut current = from;
+        loop {
+            let next = same_room_neighbor(current, direction);
+            if !self.is_walkable(next) {
+                return None;
+            }
+            let next = next?;
+            *ops += 1;
+
+            if self.is_goal(next)
+                || self.is_near_exit(next)
+                || self.has_forced_neighbor(next, direction)
+            {
+                return Some(next);
+            }
+
+            if direction.is_diagonal()
+                && (self.jump(next, direction.rot_ccw(), ops).is_some()
+                    || self.jump(next, direction.rot_cw(), ops).is_some())
+            {
+                return Some(next);
+            }
+
+            current = next;
+        }
+    }
+}
+
+fn is_goal(position: Position, destinations: &[(Position, usize)]) -> bool {
+    destinations.iter().any(|(target, range)| {
+        target.room_name() == position.room_name() && target.get_range_to(position) <= *range as u32
+    })
+}
+
+/// Finds a path from `start` to any of the destinations with jump point search. Every
+/// passable tile costs 1 to enter, so cost matrix values only matter as obstacles (255).
+///
+/// Only jump points are added to the open list, so the ops count tracks tiles scanned
+/// while jumping rather than tiles settled.
+pub fn jps_multiroom_path(
+    start: Position,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    destinations: Vec<(Position, usize)>,
+) -> PathResult {
+    jump_point_search(
+        start,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        None,
+        &destinations,
+    )
+}
+
+/// Finds a path with jump point search, treating portal entrances like exit tiles:
+/// stepping onto the entrance moves the creep to the corresponding portal exit.
+#[allow(clippy::too_many_arguments)]
+pub fn jps_portal_multiroom_path(
+    start: Position,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: &PortalIndex,
+    destinations: Vec<(Position, usize)>,
+) -> PathResult {
+    jump_point_search(
+        start,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        Some(portal_index),
+        &destinations,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn jump_point_search(
+    start: Position,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: Option<&PortalIndex>,
+    destinations: &[(Position, usize)],
+) -> PathResult {
+    set_panic_hook();
+    let mut open = OpenList::new();
+    let mut ops = 0;
+    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
+    let mut parents: HashMap<Position, Parent> = HashMap::new();
+
+    let Some(room_key) = cached_room_data.get_room_key(start.room_name()) else {
+        return PathResult::not_found(ops);
+    };
+    cached_room_data[room_key].distance_map[start.xy()] = 0;
+    open.push(
+        0,
+        State {
+            g_score: 0,
+            position: start,
+            open_direction: None,
+            room_key,
+        },
+    );
+
+    while let Some(State {
+        g_score,
+        position,
+        open_direction,
+        room_key,
+    }) = open.pop()
+    {
+        if cached_room_data[room_key].distance_map[position.xy()] < g_score {
+            continue;
+        }
+
+        if is_goal(position, destinations) {
+            return PathResult::new(build_path(start, position, &parents), g_score, ops);
+        }
+
+        if g_score >= max_path_cost || ops >= max_ops {
+            continue;
+        }
+
+        // Collect successors first: the rays borrow the room's cost matrix, while
+        // room transitions may need to load another room into the cache.
+        let mut jumps = Vec::new();
+        let mut transitions = Vec::new();
+        {
+            let Some(cost_matrix) = &cached_room_data[room_key].cost_matrix else {
+                continue;
+            };
+            let grid = RoomGrid {
+                cost_matrix,
+                portal_index,
+                destinations,
+            };
+            for direction in grid.successor_directions(position, open_direction) {
+                let Some(step) = same_room_neighbor(position, direction) else {
+                    continue;
+                };
+                if grid.is_transition(step) {
+                    if grid.is_passable(step) {
+                        ops += 1;
+                        transitions.push(step);
+                    }
+                } else if let Some(jump_point) = grid.jump(position, direction, &mut ops) {
+                    let open_direction = if grid.is_near_exit(jump_point) {
+                        None
+                    } else {
+                        Some(direction)
+                    };
+                    jumps.push((jump_point, open_direction));
+                }
+            }
+        }
+
+        for (jump_point, open_direction) in jumps {
+            let next_cost = g_score.saturating_add(position.get_range_to(jump_point) as usize);
+            if cached_room_data[room_key].distance_map[jump_point.xy()] <= next_cost {
+                continue;
+            }
+            cached_room_data[room_key].distance_map[jump_point.xy()] = next_cost;
+            parents.insert(
+                jump_point,
+                Parent {
+                    position,
+                    step: None,
+                },
+            );
+            let f_score = next_cost.saturating_add(heuristic_fn(jump_point));
+            open.push(
+                f_score,
+                State {
+                    g_score: next_cost,
+                    position: jump_point,
+                    open_direction,
+                    room_key,
+                },
+            );
+        }
+
+        for step in transitions {
+            let portal_exit = portal_index.and_then(|portal_index| portal_index.exit(step));
+            let landing = portal_exit.unwrap_or_else(|| corresponding_room_edge(step));
+            let landing_room_key = if landing.room_name() == position.room_name() {
+                room_key
+            } else {
+                // Costs are uniform, so the landing tile only matters if it's blocked.
+                match cached_room_data.get_room_key(landing.room_name()) {
+                    Some(key) if portal_exit.is_some() => match cached_room_data[key].cost_matrix {
+                        Some(_) => key,
+                        None => continue,
+                    },
+                    Some(key) if cached_room_data.crossing_cost(1, key, landing).is_some() => key,
+                    _ => continue,
+                }
+            };
+            let next_cost = g_score.saturating_add(1);
+            if cached_room_data[landing_room_key].distance_map[landing.xy()] <= next_cost {
+                continue;
+            }
+            cached_room_data[landing_room_key].distance_map[landing.xy()] = next_cost;
+            parents.insert(
+                landing,
+                Parent {
+                    position,
+                    step: Some(step),
+                },
+            );
+            let f_score = next_cost.saturating_add(heuristic_fn(landing));
+            open.push(
+                f_score,
+                State {
+                    g_score: next_cost,
+                    position: landing,
+                    open_direction: None,
+                    room_key: landing_room_key,
+                },
+            );
+        }
+    }
+
+    PathResult::not_found(ops)
+}
+
+/// Walks the jump point parents back to the start, filling in the tiles between
+/// consecutive jump points.
+fn build_path(start: Position, goal: Position, parents: &HashMap<Position, Parent>) -> Path {
+    let mut reversed = vec![goal];
+    let mut current = goal;
+    while current != start {
+        let parent = parents[&current];
+        if let Some(step) = parent.step {
+            reversed.push(step);
+        } else if let Some(direction) = current.get_direction_to(parent.position) {
+            let mut tile = current;
+            while let Some(next) = same_room_neighbor(tile, direction) {
+                if next == parent.position {
+                    break;
+                }
+                reversed.push(next);
+                tile = next;
+            }
+        }
+        reversed.push(parent.position);
+        current = parent.position;
+    }
+
+    let mut path = Path::new();
+    for position in reversed.into_iter().rev() {
+        path.add(position);
+    }
+    path
+}
+
+fn unpack_destinations(destinations: Vec<u32>) -> Vec<(Position, usize)> {
+    destinations
+        .chunks(2)
+        .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn js_jps_multiroom_path(
+    start_packed: u32,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    destinations: Vec<u32>,
+) -> PathResult {
+    let destinations = unpack_destinations(destinations);
+    let heuristic_destinations = destinations.clone();
+    let heuristic_fn = base_heuristic_with_range(&heuristic_destinations);
+
+    jps_multiroom_path(
+        Position::from_packed(start_packed),
+        |room| js_cost_matrix(get_cost_matrix, room),
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        destinations,
+    )
+}
+
+#[wasm_bindgen]
+pub fn js_jps_portal_multiroom_path(
+    start_packed: u32,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    destinations: Vec<u32>,
+) -> PathResult {
+    let destinations = unpack_destinations(destinations);
+    let heuristic_destinations = destinations.clone();
+
+    with_configured_portal_index(|portal_index| {
+        let heuristic_fn =
+            closest_portal_heuristic_cached_with_range(&heuristic_destinations, portal_index);
+
+        jps_portal_multiroom_path(
+            Position::from_packed(start_packed),
+            |room| js_cost_matrix(get_cost_matrix, room),
+            max_rooms,
+            max_ops,
+            max_path_cost,
+            heuristic_fn,
+            portal_index,
+            destinations,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::breadth_first_search::bfs_multiroom_distance_map;
+    use crate::test_fixtures::{callback, pos, random_matrix, room, xy};
+    use screeps::RoomXY;
+
+    /// Scatters walls over the room interior with a small deterministic generator.
+    fn random_walls(seed: u32, keep_clear: &[RoomXY]) -> ClockworkCostMatrix {
+        random_matrix(seed, 1, |tile, roll| {
+            let inside = (1..49).contains(&tile.x.u8()) && (1..49).contains(&tile.y.u8());
+            (inside && roll % 100 < 30 && !keep_clear.contains(&tile)).then_some(255)
+        })
+    }
+
+    fn bfs_cost(
+        start: Position,
+        goal: Position,
+        matrices: &HashMap<RoomName, ClockworkCostMatrix>,
+    ) -> usize {
+        bfs_multiroom_distance_map(
+            vec![start],
+            callback(matrices.clone()),
+            usize::MAX,
+            matrices.len(),
+            usize::MAX,
+            None,
+            None,
+        )
+        .distance_map()
+        .get(goal)
+    }
+
+    fn assert_valid_path(
+        result: &PathResult,
+        start: Position,
+        matrices: &HashMap<RoomName, ClockworkCostMatrix>,
+        portal_index: Option<&PortalIndex>,
+    ) {
+        let path = &result.path;
+        assert_eq!(path.get(0), Some(&start));
+        for i in 1..path.len() {
+            let from = *path.get(i - 1).unwrap();
+            let to = *path.get(i).unwrap();
+            if from.room_name() == to.room_name() && from.get_range_to(to) == 1 {
+                assert!(matrices[&to.room_name()].get(to.xy()) < 255);
+            } else {
+                let landing = portal_index
+                    .and_then(|portal_index| portal_index.exit(from))
+                    .unwrap_or_else(|| corresponding_room_edge(from));
+                assert_eq!(to, landing, "invalid step from {from:?} to {to:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn jps_matches_bfs_cost_on_random_rooms() {
+        let start = pos("W1N1", 3, 3);
+        let goal = pos("W1N1", 46, 44);
+        for seed in 0..20 {
+            let matrices =
+                HashMap::from([(room("W1N1"), random_walls(seed, &[start.xy(), goal.xy()]))]);
+            let destinations = vec![(goal, 0)];
+            let result = jps_multiroom_path(
+                start,
+                callback(matrices.clone()),
+                1,
+                usize::MAX,
+                usize::MAX,
+                base_heuristic_with_range(&destinations),
+                destinations.clone(),
+            );
+
+            let expected = bfs_cost(start, goal, &matrices);
+            if expected == usize::MAX {
+                assert!(!result.is_found());
+                continue;
+            }
+            assert_eq!(result.cost(), expected, "seed {seed}");
+            assert_eq!(result.path.len(), expected + 1);
+            assert_eq!(result.path.get(result.path.len() - 1), Some(&goal));
+            assert_valid_path(&result, start, &matrices, None);
+        }
+    }
+
+    #[test]
+    fn jps_crosses_room_edges() {
+        let start = pos("W1N1", 5, 10);
+        let goal = pos("W2N1", 40, 30);
+        let mut matrices = HashMap::new();
+        let mut east = random_walls(7, &[start.xy()]);
+        let mut west = ClockworkCostMatrix::new(Some(1));
+        // Leave a single gap in the border between the rooms.
+        for y in 0..50 {
+            if y != 40 {
+                east.set(xy(0, y), 255);
+                west.set(xy(49, y), 255);
+            }
+        }
+        matrices.insert(room("W1N1"), east);
+        matrices.insert(room("W2N1"), west);
+
+        let destinations = vec![(goal, 0)];
+        let result = jps_multiroom_path(
+            start,
+            callback(matrices.clone()),
+            2,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+            destinations.clone(),
+        );
+
+        let expected = bfs_cost(start, goal, &matrices);
+        assert_ne!(expected, usize::MAX);
+        assert_eq!(result.cost(), expected);
+        assert!(result.path.find_next_index(&pos("W1N1", 0, 40)).is_some());
+        assert!(result.path.find_next_index(&pos("W2N1", 49, 40)).is_some());
+        assert_valid_path(&result, start, &matrices, None);
+    }
+
+    #[test]
+    fn jps_uses_portals() {
+        let start = pos("W1N1", 25, 25);
+        let entry = pos("W1N1", 27, 25);
+        let exit = pos("W9N9", 10, 10);
+        let goal = pos("W9N9", 10, 14);
+        let mut portal_index = PortalIndex::default();
+        portal_index.add_bidirectional(entry, exit);
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W9N9"), ClockworkCostMatrix::new(Some(1))),
+        ]);
+
+        let destinations = vec![(goal, 1)];
+        let result = jps_portal_multiroom_path(
+            start,
+            callback(matrices.clone()),
+            2,
+            usize::MAX,
+            usize::MAX,
+            closest_portal_heuristic_cached_with_range(&destinations, &portal_index),
+            &portal_index,
+            destinations.clone(),
+        );
+
+        assert!(result.is_found());
+        assert_eq!(result.cost(), 5);
+        assert!(result.path.find_next_index(&entry).is_some());
+        assert!(result.path.find_next_index(&exit).is_some());
+        assert_valid_path(&result, start, &matrices, Some(&portal_index));
+    }
+
+    #[test]
+    fn jps_reports_unreachable_goal() {
+        let start = pos("W1N1", 10, 10);
+        let goal = pos("W1N1", 40, 40);
+        let mut matrix = ClockworkCostMatrix::new(Some(1));
+        for y in 0..50 {
+            matrix.set(xy(25, y), 255);
+        }
+        let destinations = vec![(goal, 0)];
+        let result = jps_multiroom_path(
+            start,
+            callback(HashMap::from([(room("W1N1"), matrix)])),
+            1,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+            destinations.clone(),
+        );
+
+        assert!(!result.is_found());
+        assert_eq!(result.path.len(), 0);
+        assert!(result.ops() > 0);
+    }
+}
@@ -0,0 +1,61 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::datatypes::Path;
+
+pub mod bidirectional_astar;
+pub mod hierarchical;
+pub mod jump_point_search;
+pub mod space_time_astar;
+pub mod travel_time;
+
+/// Searches that plan a single route return the path itself rather than a distance
+/// map. The path runs from the start position to the tile where the search stopped,
+/// including both tiles of any room edge or portal crossing. If no path was found,
+/// `path` is empty and `cost` is `usize::MAX`.
+#[wasm_bindgen]
+pub struct PathResult {
+    path: Path,
+    cost: usize,
+    ops: usize,
+}
+
+impl PathResult {
+    pub fn new(path: Path, cost: usize, ops: usize) -> Self {
+        Self { path, cost, ops }
+    }
+
+    pub fn not_found(ops: usize) -> Self {
+        Self {
+            path: Path::new(),
+            cost: usize::MAX,
+            ops,
+        }
+    }
+
+    pub fn is_found(&self) -> bool {
+        self.cost != usize::MAX
+    }
+}
+
+#[wasm_bindgen]
+impl PathResult {
+    #[wasm_bindgen(getter)]
+    pub fn path(&self) -> Path {
+        self.path.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ops(&self) -> usize {
+        self.ops
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn found(&self) -> bool {
+        self.is_found()
+    }
+}
@@ -0,0 +1,558 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
+use crate::algorithms::distance_map::heuristics::closest_portal_heuristic_cached_with_range;
+use crate::algorithms::map::{corresponding_room_edge, same_room_neighbor};
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::OpenList;
+use crate::datatypes::Path;
+use crate::datatypes::PortalIndex;
+use crate::datatypes::ReservationTable;
+use crate::datatypes::RoomDataCache;
+use crate::utils::set_panic_hook;
+use screeps::Direction;
+use screeps::Position;
+use screeps::RoomName;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use super::PathResult;
+
+static ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::Top,
+    Direction::TopRight,
+    Direction::Right,
+    Direction::BottomRight,
+    Direction::Bottom,
+    Direction::BottomLeft,
+    Direction::Left,
+    Direction::TopLeft,
+];
+
+/// Waiting in place costs the same as a move onto plains.
+const WAIT_COST: usize = 1;
+
+/// A search node: a position at a number of ticks after the start. Past the
+/// reservation window time no longer matters, so all later ticks share one node.
+type Node = (Position, u32);
+
+#[derive(Copy, Clone)]
+struct State {
+    // The cost to reach the current node.
+    g_score: usize,
+    // The current position.
+    position: Position,
+    // Ticks since the start, capped at the window.
+    elapsed: u32,
+    // The index of the position's room in the room data cache.
+    room_key: usize,
+}
+
+/// How a node was reached: the previous node, and the tile stepped onto if the move
+/// crossed a room edge or portal.
+#[derive(Copy, Clone)]
+struct Parent {
+    node: Node,
+    step: Option<Position>,
+}
+
+fn is_goal(position: Position, destinations: &[(Position, usize)]) -> bool {
+    destinations.iter().any(|(target, range)| {
+        target.room_name() == position.room_name() && target.get_range_to(position) <= *range as u32
+    })
+}
+
+/// Space-time A*: finds a path for `agent` that avoids tiles other agents have reserved
+/// in `reservations`, waiting in place when that's cheaper than going around. Each move
+/// or wait takes one tick, starting at `start_tick`; moves cost the cost matrix value of
+/// the tile entered and waiting costs 1.
+///
+/// Reservations are only respected for `window` ticks, after which the search carries
+/// on as plain A* (windowed hierarchical cooperative A*). The agent's previous
+/// reservations are released before searching, and the found path is written back to
+/// the table for the window, including waiting at the goal until the window ends.
+///
+/// Waits appear in the result's path as repeated positions, so the path's index is the
+/// tick offset from `start_tick` (room edge and portal crossings add their step tile).
+#[allow(clippy::too_many_arguments)]
+pub fn space_time_astar_multiroom_path(
+    start: Position,
+    start_tick: u32,
+    agent: u32,
+    reservations: &mut ReservationTable,
+    window: u32,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    destinations: Vec<(Position, usize)>,
+) -> PathResult {
+    space_time_astar(
+        start,
+        start_tick,
+        agent,
+        reservations,
+        window,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        None,
+        &destinations,
+    )
+}
+
+/// Space-time A* that may also travel through portals. See
+/// `space_time_astar_multiroom_path`.
+#[allow(clippy::too_many_arguments)]
+pub fn space_time_astar_portal_multiroom_path(
+    start: Position,
+    start_tick: u32,
+    agent: u32,
+    reservations: &mut ReservationTable,
+    window: u32,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: &PortalIndex,
+    destinations: Vec<(Position, usize)>,
+) -> PathResult {
+    space_time_astar(
+        start,
+        start_tick,
+        agent,
+        reservations,
+        window,
+        get_cost_matrix,
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        Some(portal_index),
+        &destinations,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn space_time_astar(
+    start: Position,
+    start_tick: u32,
+    agent: u32,
+    reservations: &mut ReservationTable,
+    window: u32,
+    get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: Option<&PortalIndex>,
+    destinations: &[(Position, usize)],
+) -> PathResult {
+    set_panic_hook();
+    reservations.release_agent(agent);
+
+    let mut open = OpenList::new();
+    let mut ops = 0;
+    let mut cached_room_data = RoomDataCache::new(max_rooms, get_cost_matrix);
+    let mut costs: HashMap<Node, usize> = HashMap::new();
+    let mut parents: HashMap<Node, Parent> = HashMap::new();
+
+    let Some(room_key) = cached_room_data.get_room_key(start.room_name()) else {
+        return PathResult::not_found(ops);
+    };
+    costs.insert((start, 0), 0);
+    open.push(
+        0,
+        State {
+            g_score: 0,
+            position: start,
+            elapsed: 0,
+            room_key,
+        },
+    );
+
+    while let Some(State {
+        g_score,
+        position,
+        elapsed,
+        room_key,
+    }) = open.pop()
+    {
+        let node = (position, elapsed);
+        if costs.get(&node).is_some_and(|cost| *cost < g_score) {
+            continue;
+        }
+
+        if is_goal(position, destinations) {
+            let timeline = build_timeline(start, node, &parents);
+            reserve_timeline(reservations, &timeline, start_tick, window, agent);
+            let path = Path::from(
+                timeline
+                    .into_iter()
+                    .map(|(position, _)| position)
+                    .collect::<Vec<_>>(),
+            );
+            return PathResult::new(path, g_score, ops);
+        }
+
+        if g_score >= max_path_cost || ops >= max_ops {
+            continue;
+        }
+        ops += 1;
+
+        let tick = start_tick + elapsed;
+        let next_elapsed = (elapsed + 1).min(window);
+        let in_window = elapsed < window;
+        let current_room_name = cached_room_data[room_key].room_name;
+
+        // Waiting only helps while there are reservations to wait out.
+        if in_window && !reservations.is_move_blocked(position, position, tick, agent) {
+            push_state(
+                &mut open,
+                &mut costs,
+                &mut parents,
+                &heuristic_fn,
+                node,
+                None,
+                State {
+                    g_score: g_score.saturating_add(WAIT_COST),
+                    position,
+                    elapsed: next_elapsed,
+                    room_key,
+                },
+            );
+        }
+
+        for direction in ALL_DIRECTIONS.iter() {
+            let Some(step_pos) = same_room_neighbor(position, *direction) else {
+                continue;
+            };
+
+            let Some(cost_matrix) = &cached_room_data[room_key].cost_matrix else {
+                continue;
+            };
+            let terrain_cost = cost_matrix.get(step_pos.xy());
+            if terrain_cost == 255 {
+                continue;
+            }
+
+            let portal_exit = portal_index.and_then(|portals| portals.exit(step_pos));
+            let neighbor = portal_exit.unwrap_or_else(|| corresponding_room_edge(step_pos));
+
+            if in_window && reservations.is_move_blocked(position, neighbor, tick, agent) {
+                continue;
+            }
+
+            let (neighbor_room_key, terrain_cost) = if neighbor.room_name() == current_room_name {
+                (room_key, terrain_cost)
+            } else {
+                let Some(key) = cached_room_data.get_room_key(neighbor.room_name()) else {
+                    continue;
+                };
+                // Portal exits aren't charged; only edge crossings check the landing tile.
+                let crossing_cost = match portal_exit {
+                    Some(_) => cached_room_data[key]
+                        .cost_matrix
+                        .as_ref()
+                        .map(|_| terrain_cost),
+                    None => cached_room_data.crossing_cost(terrain_cost, key, neighbor),
+                };
+                match crossing_cost {
+                    Some(cost) => (key, cost),
+                    None => continue,
+                }
+            };
+
+            push_state(
+                &mut open,
+                &mut costs,
+                &mut parents,
+                &heuristic_fn,
+                node,
+                (step_pos != neighbor).then_some(step_pos),
+                State {
+                    g_score: g_score.saturating_add(terrain_cost as usize),
+                    position: neighbor,
+                    elapsed: next_elapsed,
+                    room_key: neighbor_room_key,
+                },
+            );
+        }
+    }
+
+    PathResult::not_found(ops)
+}
+
+fn push_state(
+    open: &mut OpenList<State>,
+    costs: &mut HashMap<Node, usize>,
+    parents: &mut HashMap<Node, Parent>,
+    heuristic_fn: impl Fn(Position) -> usize,
+    parent: Node,
+    step: Option<Position>,
+    state: State,
+) {
+    let node = (state.position, state.elapsed);
+    if costs.get(&node).is_some_and(|cost| *cost <= state.g_score) {
+        return;
+    }
+    costs.insert(node, state.g_score);
+    parents.insert(node, Parent { node: parent, step });
+
+    let f_score = state.g_score.saturating_add(heuristic_fn(state.position));
+    open.push(f_score, state);
+}
+
+/// Walks the parents back to the start, returning each tile on the path with its tick
+/// offset. A room edge or portal step tile shares the tick of the tile it leads to.
+fn build_timeline(start: Position, goal: Node, parents: &HashMap<Node, Parent>) -> Vec<Node> {
+    let mut reversed = vec![goal];
+    let mut current = goal;
+    while current != (start, 0) {
+        let parent = parents[&current];
+        if let Some(step) = parent.step {
+            reversed.push((step, current.1));
+        }
+        reversed.push(parent.node);
+        current = parent.node;
+    }
+    reversed.reverse();
+    reversed
+}
+
+/// Reserves each tile of the path at its tick, then the final tile until the window
+/// closes, as the agent will wait there.
+fn reserve_timeline(
+    reservations: &mut ReservationTable,
+    timeline: &[Node],
+    start_tick: u32,
+    window: u32,
+    agent: u32,
+) {
+    for (position, elapsed) in timeline {
+        if *elapsed < window {
+            reservations.reserve(*position, start_tick + elapsed, agent);
+        }
+    }
+    if let Some((goal, arrived)) = timeline.last() {
+        for elapsed in *arrived..window {
+            reservations.reserve(*goal, start_tick + elapsed, agent);
+        }
+    }
+}
+
+fn unpack_destinations(destinations: Vec<u32>) -> Vec<(Position, usize)> {
+    destinations
+        .chunks(2)
+        .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
+        .collect()
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_space_time_astar_multiroom_path(
+    start_packed: u32,
+    start_tick: u32,
+    agent: u32,
+    reservations: &mut ReservationTable,
+    window: u32,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    destinations: Vec<u32>,
+) -> PathResult {
+    let destinations = unpack_destinations(destinations);
+    let heuristic_destinations = destinations.clone();
+    let heuristic_fn = base_heuristic_with_range(&heuristic_destinations);
+
+    space_time_astar_multiroom_path(
+        Position::from_packed(start_packed),
+        start_tick,
+        agent,
+        reservations,
+        window,
+        |room| js_cost_matrix(get_cost_matrix, room),
+        max_rooms,
+        max_ops,
+        max_path_cost,
+        heuristic_fn,
+        destinations,
+    )
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn js_space_time_astar_portal_multiroom_path(
+    start_packed: u32,
+    start_tick: u32,
+    agent: u32,
+    reservations: &mut ReservationTable,
+    window: u32,
+    get_cost_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_path_cost: usize,
+    destinations: Vec<u32>,
+) -> PathResult {
+    let destinations = unpack_destinations(destinations);
+    let heuristic_destinations = destinations.clone();
+
+    with_configured_portal_index(|portal_index| {
+        let heuristic_fn =
+            closest_portal_heuristic_cached_with_range(&heuristic_destinations, portal_index);
+
+        space_time_astar_portal_multiroom_path(
+            Position::from_packed(start_packed),
+            start_tick,
+            agent,
+            reservations,
+            window,
+            |room| js_cost_matrix(get_cost_matrix, room),
+            max_rooms,
+            max_ops,
+            max_path_cost,
+            heuristic_fn,
+            portal_index,
+            destinations,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::positions;
+    use screeps::{RoomCoordinate, RoomXY};
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            "W1N1".parse().unwrap(),
+        )
+    }
+
+    /// A one-tile-wide corridor along y = 10 from x = 10 to x = 20.
+    fn corridor(room: RoomName) -> Option<ClockworkCostMatrix> {
+        if room != "W1N1" {
+            return None;
+        }
+        let mut matrix = ClockworkCostMatrix::new(Some(255));
+        for x in 10..=20 {
+            matrix.set(
+                RoomXY::new(
+                    RoomCoordinate::new(x).unwrap(),
+                    RoomCoordinate::new(10).unwrap(),
+                ),
+                1,
+            );
+        }
+        Some(matrix)
+    }
+
+    fn plan(
+        reservations: &mut ReservationTable,
+        agent: u32,
+        start: Position,
+        goal: Position,
+        get_cost_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    ) -> PathResult {
+        let destinations = vec![(goal, 0)];
+        space_time_astar_multiroom_path(
+            start,
+            100,
+            agent,
+            reservations,
+            16,
+            get_cost_matrix,
+            1,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&destinations),
+            destinations.clone(),
+        )
+    }
+
+    #[test]
+    fn waits_for_a_reserved_tile_to_clear() {
+        let mut reservations = ReservationTable::new();
+        // Another creep sits on (13, 10) for the next few ticks.
+        for tick in 100..105 {
+            reservations.reserve(pos(13, 10), tick, 1);
+        }
+
+        let result = plan(&mut reservations, 2, pos(10, 10), pos(15, 10), corridor);
+        let path = positions(&result.path);
+
+        // Five moves plus two ticks waiting for (13, 10) to clear at tick 105.
+        assert_eq!(result.cost(), 7);
+        assert_eq!(path.len(), 8);
+        assert_eq!(path[5], pos(13, 10));
+        for (offset, position) in path.iter().enumerate() {
+            assert_ne!(
+                reservations.reserved_by(*position, 100 + offset as u32),
+                Some(1)
+            );
+        }
+    }
+
+    #[test]
+    fn writes_reservations_back_and_replans() {
+        let mut reservations = ReservationTable::new();
+        let open_room = |_: RoomName| Some(ClockworkCostMatrix::new(Some(1)));
+
+        let first = plan(&mut reservations, 1, pos(10, 10), pos(20, 10), open_room);
+        let first_path = positions(&first.path);
+        for (offset, position) in first_path.iter().enumerate() {
+            assert_eq!(
+                reservations.reserved_by(*position, 100 + offset as u32),
+                Some(1)
+            );
+        }
+        // The first agent waits at its goal until the window closes.
+        assert_eq!(reservations.reserved_by(pos(20, 10), 115), Some(1));
+
+        // A second agent coming the other way doesn't collide or swap with it.
+        let second = plan(&mut reservations, 2, pos(20, 10), pos(10, 10), open_room);
+        let second_path = positions(&second.path);
+        assert!(second.is_found());
+        for offset in 0..first_path.len().min(second_path.len()) {
+            assert_ne!(first_path[offset], second_path[offset]);
+            if offset > 0 {
+                assert!(
+                    !(first_path[offset] == second_path[offset - 1]
+                        && first_path[offset - 1] == second_path[offset]),
+                    "agents swapped at tick {}",
+                    offset
+                );
+            }
+        }
+
+        // Replanning the first agent releases its old reservations.
+        let count = reservations.len();
+        plan(&mut reservations, 1, pos(10, 10), pos(20, 10), open_room);
+        assert!(reservations.len() <= count);
+    }
+
+    #[test]
+    fn reservations_are_ignored_past_the_window() {
+        let mut reservations = ReservationTable::new();
+        for tick in 100..200 {
+            reservations.reserve(pos(13, 10), tick, 1);
+        }
+
+        let result = plan(&mut reservations, 2, pos(10, 10), pos(15, 10), corridor);
+
+        // Once the window closes the reservation is ignored, so the path goes through
+        // after waiting out the rest of the window.
+        assert!(result.is_found());
+        let path = positions(&result.path);
+        let through = path.iter().position(|p| *p == pos(13, 10)).unwrap();
+        assert!(through >= 16);
+    }
+}
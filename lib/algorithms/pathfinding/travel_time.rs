@@ -0,0 +1,617 @@
+use crate::algorithms::distance_map::astar::js_cost_matrix;
+use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
+use crate::algorithms::distance_map::heuristics::closest_portal_heuristic_cached_with_range;
+use crate::algorithms::map::{corresponding_room_edge, next_directions, same_room_neighbor};
+use crate::datatypes::with_configured_portal_index;
+use crate::datatypes::ClockworkCostMatrix;
+use crate::datatypes::OpenList;
+use crate::datatypes::Path;
+use crate::datatypes::PortalIndex;
+use crate::datatypes::RoomDataCache;
+use crate::utils::set_panic_hook;
+use screeps::Direction;
+use screeps::Position;
+use screeps::RoomName;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use super::PathResult;
+
+/// The parts of a creep's body that matter for movement.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug)]
+pub struct CreepBody {
+    move_parts: usize,
+    loaded_carry_parts: usize,
+    empty_carry_parts: usize,
+    other_parts: usize,
+    move_boost: u8,
+}
+
+impl CreepBody {
+    /// `move_boost` is the boost tier of the MOVE parts: 0 for unboosted, or 1-3 for
+    /// T1-T3 (ZO, ZHO2, XZHO2).
+    pub fn new(
+        move_parts: usize,
+        loaded_carry_parts: usize,
+        empty_carry_parts: usize,
+        other_parts: usize,
+        move_boost: u8,
+    ) -> Self {
+        Self {
+            move_parts,
+            loaded_carry_parts,
+            empty_carry_parts,
+            other_parts,
+            move_boost: move_boost.min(3),
+        }
+    }
+
+    /// Every part except MOVE and empty CARRY parts generates fatigue when moving.
+    pub fn fatigue_parts(&self) -> usize {
+        self.loaded_carry_parts + self.other_parts
+    }
+
+    /// Fatigue removed each tick: 2 per MOVE part, multiplied by the boost.
+    pub fn fatigue_recovery(&self) -> usize {
+        self.move_parts * 2 * (self.move_boost as usize + 1)
+    }
+
+    /// Ticks from moving onto a tile until the creep can move again, given the tile's
+    /// fatigue per part (1 for roads, 2 for plains, 10 for swamps). Returns `None` if
+    /// the creep can't move at all.
+    pub fn move_ticks(&self, fatigue_per_part: u8) -> Option<usize> {
+        if self.move_parts == 0 {
+            return None;
+        }
+        let fatigue = self.fatigue_parts() * fatigue_per_part as usize;
+        let recovery = self.fatigue_recovery();
+        Some(fatigue.div_ceil(recovery).max(1))
+    }
+}
+
+#[wasm_bindgen]
+impl CreepBody {
+    #[wasm_bindgen(constructor)]
+    pub fn js_new(
+        move_parts: usize,
+        loaded_carry_parts: usize,
+        empty_carry_parts: usize,
+        other_parts: usize,
+        move_boost: Option<u8>,
+    ) -> Self {
+        Self::new(
+            move_parts,
+            loaded_carry_parts,
+            empty_carry_parts,
+            other_parts,
+            move_boost.unwrap_or(0),
+        )
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn move_parts(&self) -> usize {
+        self.move_parts
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn loaded_carry_parts(&self) -> usize {
+        self.loaded_carry_parts
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn empty_carry_parts(&self) -> usize {
+        self.empty_carry_parts
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn other_parts(&self) -> usize {
+        self.other_parts
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn move_boost(&self) -> u8 {
+        self.move_boost
+    }
+}
+
+#[derive(Copy, Clone)]
+struct State {
+    // The tick at which the creep can move on from the current position.
+    g_score: usize,
+    // The current position.
+    position: Position,
+    // The direction from the previous position that led to the current position.
+    open_direction: Option<Direction>,
+    // The index of the position's room in the room data cache.
+    room_key: usize,
+}
+
+/// How a tile was reached: the tile moved from, and the tile stepped onto if the move
+/// crossed a room edge or portal.
+#[derive(Copy, Clone)]
+struct Parent {
+    position: Position,
+    step: Option<Position>,
+}
+
+fn is_goal(position: Position, destinations: &[(Position, usize)]) -> bool {
+    destinations.iter().any(|(target, range)| {
+        target.room_name() == position.room_name() && target.get_range_to(position) <= *range as u32
+    })
+}
+
+/// Finds the path that gets `body` to any of the destinations in the fewest ticks.
+///
+/// `get_fatigue_matrix` returns, for each tile, the fatigue each part generates when
+/// moving onto it (1 for roads, 2 for plains, 10 for swamps), or 255 if impassable. The
+/// creep can't move again until the fatigue from its last move has recovered, so the
+/// terrain of each tile delays the next move; the fatigue from the final move doesn't
+/// delay arrival. The heuristic should be in moves (e.g. range), since every move takes
+/// at least one tick.
+///
+/// The result's `cost` is the number of ticks until the creep arrives, assuming it
+/// starts with no fatigue.
+#[allow(clippy::too_many_arguments)]
+pub fn travel_time_multiroom_path(
+    start: Position,
+    body: &CreepBody,
+    get_fatigue_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_ticks: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    destinations: Vec<(Position, usize)>,
+) -> PathResult {
+    travel_time_search(
+        start,
+        body,
+        get_fatigue_matrix,
+        max_rooms,
+        max_ops,
+        max_ticks,
+        heuristic_fn,
+        None,
+        &destinations,
+    )
+}
+
+/// Finds the fastest path for `body`, also travelling through portals. See
+/// `travel_time_multiroom_path`.
+#[allow(clippy::too_many_arguments)]
+pub fn travel_time_portal_multiroom_path(
+    start: Position,
+    body: &CreepBody,
+    get_fatigue_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_ticks: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: &PortalIndex,
+    destinations: Vec<(Position, usize)>,
+) -> PathResult {
+    travel_time_search(
+        start,
+        body,
+        get_fatigue_matrix,
+        max_rooms,
+        max_ops,
+        max_ticks,
+        heuristic_fn,
+        Some(portal_index),
+        &destinations,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn travel_time_search(
+    start: Position,
+    body: &CreepBody,
+    get_fatigue_matrix: impl Fn(RoomName) -> Option<ClockworkCostMatrix>,
+    max_rooms: usize,
+    max_ops: usize,
+    max_ticks: usize,
+    heuristic_fn: impl Fn(Position) -> usize,
+    portal_index: Option<&PortalIndex>,
+    destinations: &[(Position, usize)],
+) -> PathResult {
+    set_panic_hook();
+    let mut open = OpenList::new();
+    let mut ops = 0;
+    let mut cached_room_data = RoomDataCache::new(max_rooms, get_fatigue_matrix);
+    let mut parents: HashMap<Position, Parent> = HashMap::new();
+    // The earliest arrival at a destination so far, and where.
+    let mut best: Option<(usize, Position)> = None;
+
+    if is_goal(start, destinations) {
+        return PathResult::new(build_path(start, start, &parents), 0, ops);
+    }
+    if body.move_ticks(0).is_none() {
+        return PathResult::not_found(ops);
+    }
+    let Some(room_key) = cached_room_data.get_room_key(start.room_name()) else {
+        return PathResult::not_found(ops);
+    };
+    cached_room_data[room_key].distance_map[start.xy()] = 0;
+    open.push(
+        0,
+        State {
+            g_score: 0,
+            position: start,
+            open_direction: None,
+            room_key,
+        },
+    );
+
+    'search: while let Some((
+        min_f_score,
+        State {
+            g_score,
+            position,
+            open_direction,
+            room_key,
+        },
+    )) = open.pop_with_key()
+    {
+        // The f_score is a lower bound on any arrival through this tile.
+        if best.is_some_and(|(arrival, _)| min_f_score >= arrival) {
+            break 'search;
+        }
+
+        if cached_room_data[room_key].distance_map[position.xy()] < g_score {
+            continue;
+        }
+
+        if g_score >= max_ticks || ops >= max_ops {
+            continue;
+        }
+        ops += 1;
+
+        let current_room_name = cached_room_data[room_key].room_name;
+
+        for neighbor_direction in next_directions(position, open_direction) {
+            let Some(step_pos) = same_room_neighbor(position, *neighbor_direction) else {
+                continue;
+            };
+
+            let Some(cost_matrix) = &cached_room_data[room_key].cost_matrix else {
+                continue;
+            };
+            let fatigue_per_part = cost_matrix.get(step_pos.xy());
+            if fatigue_per_part == 255 {
+                continue;
+            }
+
+            let portal_exit = portal_index.and_then(|portals| portals.exit(step_pos));
+            let neighbor = portal_exit.unwrap_or_else(|| corresponding_room_edge(step_pos));
+
+            let (neighbor_room_key, fatigue_per_part) = if neighbor.room_name() == current_room_name
+            {
+                (room_key, fatigue_per_part)
+            } else {
+                let Some(key) = cached_room_data.get_room_key(neighbor.room_name()) else {
+                    continue;
+                };
+                // Portal exits aren't charged; only edge crossings check the landing tile.
+                let crossing_cost = match portal_exit {
+                    Some(_) => cached_room_data[key]
+                        .cost_matrix
+                        .as_ref()
+                        .map(|_| fatigue_per_part),
+                    None => cached_room_data.crossing_cost(fatigue_per_part, key, neighbor),
+                };
+                match crossing_cost {
+                    Some(cost) => (key, cost),
+                    None => continue,
+                }
+            };
+            let Some(move_ticks) = body.move_ticks(fatigue_per_part) else {
+                continue;
+            };
+
+            let arrival = g_score + 1;
+            let next_ready = g_score.saturating_add(move_ticks);
+
+            let neighbor_xy = neighbor.xy();
+            if cached_room_data[neighbor_room_key].distance_map[neighbor_xy] <= next_ready {
+                continue;
+            }
+            cached_room_data[neighbor_room_key].distance_map[neighbor_xy] = next_ready;
+            parents.insert(
+                neighbor,
+                Parent {
+                    position,
+                    step: (step_pos != neighbor).then_some(step_pos),
+                },
+            );
+
+            if is_goal(neighbor, destinations) {
+                if best.is_none_or(|(best_arrival, _)| arrival < best_arrival) {
+                    best = Some((arrival, neighbor));
+                }
+                continue;
+            }
+
+            let f_score = next_ready.saturating_add(heuristic_fn(neighbor));
+
+            open.push(
+                f_score,
+                State {
+                    g_score: next_ready,
+                    position: neighbor,
+                    open_direction: if portal_exit.is_some() {
+                        None
+                    } else {
+                        Some(*neighbor_direction)
+                    },
+                    room_key: neighbor_room_key,
+                },
+            );
+        }
+    }
+
+    match best {
+        Some((arrival, goal)) => PathResult::new(build_path(start, goal, &parents), arrival, ops),
+        None => PathResult::not_found(ops),
+    }
+}
+
+fn build_path(start: Position, goal: Position, parents: &HashMap<Position, Parent>) -> Path {
+    let mut reversed = vec![goal];
+    let mut current = goal;
+    while current != start {
+        let parent = parents[&current];
+        if let Some(step) = parent.step {
+            reversed.push(step);
+        }
+        reversed.push(parent.position);
+        current = parent.position;
+    }
+
+    let mut path = Path::new();
+    for position in reversed.into_iter().rev() {
+        path.add(position);
+    }
+    path
+}
+
+fn unpack_destinations(destinations: Vec<u32>) -> Vec<(Position, usize)> {
+    destinations
+        .chunks(2)
+        .map(|chunk| (Position::from_packed(chunk[0]), chunk[1] as usize))
+        .collect()
+}
+
+/// `get_fatigue_matrix` returns matrices of fatigue per part: 1 for roads, 2 for
+/// plains, 10 for swamps, 255 for impassable tiles.
+#[wasm_bindgen]
+pub fn js_travel_time_multiroom_path(
+    start_packed: u32,
+    body: &CreepBody,
+    get_fatigue_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_ticks: usize,
+    destinations: Vec<u32>,
+) -> PathResult {
+    let destinations = unpack_destinations(destinations);
+    let heuristic_destinations = destinations.clone();
+    let heuristic_fn = base_heuristic_with_range(&heuristic_destinations);
+
+    travel_time_multiroom_path(
+        Position::from_packed(start_packed),
+        body,
+        |room| js_cost_matrix(get_fatigue_matrix, room),
+        max_rooms,
+        max_ops,
+        max_ticks,
+        heuristic_fn,
+        destinations,
+    )
+}
+
+#[wasm_bindgen]
+pub fn js_travel_time_portal_multiroom_path(
+    start_packed: u32,
+    body: &CreepBody,
+    get_fatigue_matrix: &js_sys::Function,
+    max_rooms: usize,
+    max_ops: usize,
+    max_ticks: usize,
+    destinations: Vec<u32>,
+) -> PathResult {
+    let destinations = unpack_destinations(destinations);
+    let heuristic_destinations = destinations.clone();
+
+    with_configured_portal_index(|portal_index| {
+        let heuristic_fn =
+            closest_portal_heuristic_cached_with_range(&heuristic_destinations, portal_index);
+
+        travel_time_portal_multiroom_path(
+            Position::from_packed(start_packed),
+            body,
+            |room| js_cost_matrix(get_fatigue_matrix, room),
+            max_rooms,
+            max_ops,
+            max_ticks,
+            heuristic_fn,
+            portal_index,
+            destinations,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{pos, positions, room, xy};
+
+    const ROAD: u8 = 1;
+    const PLAINS: u8 = 2;
+    const SWAMP: u8 = 10;
+
+    /// A corridor along y = 10 between two walls, with the given fatigue per tile.
+    fn corridor(tiles: &[(u8, u8)]) -> impl Fn(RoomName) -> Option<ClockworkCostMatrix> {
+        let mut matrix = ClockworkCostMatrix::new(Some(255));
+        for (x, fatigue) in tiles {
+            matrix.set(xy(*x, 10), *fatigue);
+        }
+        move |room_name| (room_name == room("W1N1")).then(|| matrix.clone())
+    }
+
+    #[test]
+    fn move_ticks_follow_body_composition() {
+        let worker = CreepBody::new(1, 0, 0, 1, 0);
+        assert_eq!(worker.move_ticks(ROAD), Some(1));
+        assert_eq!(worker.move_ticks(PLAINS), Some(1));
+        assert_eq!(worker.move_ticks(SWAMP), Some(5));
+
+        let hauler = CreepBody::new(1, 2, 0, 0, 0);
+        assert_eq!(hauler.move_ticks(ROAD), Some(1));
+        assert_eq!(hauler.move_ticks(PLAINS), Some(2));
+        assert_eq!(hauler.move_ticks(SWAMP), Some(10));
+
+        let empty_hauler = CreepBody::new(1, 0, 2, 0, 0);
+        assert_eq!(empty_hauler.move_ticks(SWAMP), Some(1));
+
+        let boosted = CreepBody::new(1, 0, 0, 4, 3);
+        assert_eq!(boosted.move_ticks(PLAINS), Some(1));
+        assert_eq!(boosted.move_ticks(SWAMP), Some(5));
+
+        assert_eq!(CreepBody::new(0, 0, 0, 1, 0).move_ticks(PLAINS), None);
+    }
+
+    #[test]
+    fn fatigue_delays_the_next_move_but_not_arrival() {
+        let body = CreepBody::new(1, 0, 0, 1, 0);
+        let get_matrix = corridor(&[(10, PLAINS), (11, SWAMP), (12, SWAMP)]);
+
+        // Entering the first swamp takes one tick, then the creep waits four ticks
+        // before it can move onto the second; that last move's fatigue doesn't count.
+        let result = travel_time_multiroom_path(
+            pos("W1N1", 10, 10),
+            &body,
+            get_matrix,
+            1,
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            vec![(pos("W1N1", 12, 10), 0)],
+        );
+
+        assert_eq!(result.cost(), 6);
+        assert_eq!(
+            positions(&result.path),
+            vec![
+                pos("W1N1", 10, 10),
+                pos("W1N1", 11, 10),
+                pos("W1N1", 12, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn prefers_roads_around_swamps_for_heavy_bodies() {
+        // A straight swamp crossing versus a longer road around it.
+        let mut matrix = ClockworkCostMatrix::new(Some(255));
+        for x in 10..=20 {
+            matrix.set(xy(x, 10), SWAMP);
+            matrix.set(xy(x, 14), ROAD);
+        }
+        matrix.set(xy(10, 10), PLAINS);
+        matrix.set(xy(20, 10), PLAINS);
+        for y in 11..14 {
+            matrix.set(xy(10, y), ROAD);
+            matrix.set(xy(20, y), ROAD);
+        }
+        let get_matrix = move |_: RoomName| Some(matrix.clone());
+        let start = pos("W1N1", 10, 10);
+        let goal = vec![(pos("W1N1", 20, 10), 0)];
+
+        let heavy = travel_time_multiroom_path(
+            start,
+            &CreepBody::new(1, 0, 0, 2, 0),
+            &get_matrix,
+            1,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&goal),
+            goal.clone(),
+        );
+        // The road around the swamp is 16 moves, at one tick each.
+        assert_eq!(heavy.cost(), 16);
+        assert!(positions(&heavy.path).contains(&pos("W1N1", 15, 14)));
+
+        let light = travel_time_multiroom_path(
+            start,
+            &CreepBody::new(1, 0, 4, 0, 0),
+            &get_matrix,
+            1,
+            usize::MAX,
+            usize::MAX,
+            base_heuristic_with_range(&goal),
+            goal.clone(),
+        );
+        assert_eq!(light.cost(), 10);
+        assert!(positions(&light.path).contains(&pos("W1N1", 15, 10)));
+    }
+
+    #[test]
+    fn crosses_room_edges_and_portals() {
+        let plains = |_: RoomName| Some(ClockworkCostMatrix::new(Some(PLAINS)));
+        let body = CreepBody::new(1, 0, 0, 1, 0);
+
+        let result = travel_time_multiroom_path(
+            pos("W1N1", 25, 25),
+            &body,
+            plains,
+            9,
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            vec![(pos("W2N1", 25, 25), 0)],
+        );
+        assert_eq!(result.cost(), 49);
+        let path = positions(&result.path);
+        assert!(path.contains(&pos("W1N1", 0, 25)));
+        assert!(path.contains(&pos("W2N1", 49, 25)));
+
+        let mut portal_index = PortalIndex::default();
+        portal_index.add_bidirectional(pos("W1N1", 26, 25), pos("W5N1", 25, 25));
+        let result = travel_time_portal_multiroom_path(
+            pos("W1N1", 25, 25),
+            &body,
+            plains,
+            5,
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            &portal_index,
+            vec![(pos("W5N1", 25, 27), 0)],
+        );
+        assert_eq!(result.cost(), 3);
+        assert_eq!(
+            positions(&result.path)[..3],
+            [
+                pos("W1N1", 25, 25),
+                pos("W1N1", 26, 25),
+                pos("W5N1", 25, 25)
+            ]
+        );
+    }
+
+    #[test]
+    fn bodies_without_move_parts_cannot_travel() {
+        let result = travel_time_multiroom_path(
+            pos("W1N1", 10, 10),
+            &CreepBody::new(0, 0, 0, 1, 0),
+            corridor(&[(10, PLAINS), (11, PLAINS)]),
+            1,
+            usize::MAX,
+            usize::MAX,
+            |_| 0,
+            vec![(pos("W1N1", 11, 10), 0)],
+        );
+        assert!(!result.is_found());
+    }
+}
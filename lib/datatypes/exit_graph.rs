@@ -0,0 +1,122 @@
+use screeps::{Position, RoomName};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use wasm_bindgen::prelude::*;
+
+use super::ClockworkCostMatrix;
+
+/// The abstraction of a single room used by hierarchical pathfinding: the tiles a creep
+/// can step onto to leave the room, and the cost of reaching each of them from the tiles
+/// where creeps arrive in the room.
+#[derive(Clone, Debug)]
+pub struct RoomExits {
+    // A fingerprint of the cost matrix and portal entrances the exits were built from.
+    // If either changes, the cached costs no longer apply.
+    fingerprint: u64,
+    exits: Vec<Position>,
+    // Cost from each arrival tile to each exit, in the same order as `exits`.
+    costs: HashMap<Position, Vec<usize>>,
+}
+
+impl RoomExits {
+    pub fn new(fingerprint: u64, exits: Vec<Position>) -> Self {
+        Self {
+            fingerprint,
+            exits,
+            costs: HashMap::new(),
+        }
+    }
+
+    pub fn exits(&self) -> &[Position] {
+        &self.exits
+    }
+
+    pub fn costs_from(&self, arrival: Position) -> Option<&[usize]> {
+        self.costs.get(&arrival).map(|costs| costs.as_slice())
+    }
+
+    pub fn insert_costs(&mut self, arrival: Position, costs: Vec<usize>) {
+        self.costs.insert(arrival, costs);
+    }
+
+    /// Hashes a room's cost matrix and portal entrances, so cached exits can be checked
+    /// against them without keeping a copy of the matrix. Searches should compute this
+    /// once per room rather than each time they visit it.
+    pub fn fingerprint(cost_matrix: &ClockworkCostMatrix, portal_entrances: &[Position]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        cost_matrix.get_internal().get_bits().hash(&mut hasher);
+        portal_entrances.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A cache of room abstractions for hierarchical pathfinding, kept between searches.
+/// Entries are checked against a fingerprint of the room's current cost matrix whenever
+/// they're used and rebuilt if it has changed, so callers only need to invalidate rooms explicitly
+/// to free memory.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct ExitGraph {
+    rooms: HashMap<RoomName, RoomExits>,
+}
+
+impl ExitGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the cached exits for a room, dropping the entry if it was built from a
+    /// different cost matrix or set of portals. See `RoomExits::fingerprint`.
+    pub fn current_room(&mut self, room: RoomName, fingerprint: u64) -> Option<&mut RoomExits> {
+        if self.rooms.get(&room)?.fingerprint != fingerprint {
+            self.rooms.remove(&room);
+            return None;
+        }
+        self.rooms.get_mut(&room)
+    }
+
+    pub fn insert_room(&mut self, room: RoomName, exits: RoomExits) -> &mut RoomExits {
+        self.rooms.insert(room, exits);
+        self.rooms.get_mut(&room).unwrap()
+    }
+
+    pub fn invalidate_room(&mut self, room: RoomName) {
+        self.rooms.remove(&room);
+    }
+
+    pub fn clear(&mut self) {
+        self.rooms.clear();
+    }
+
+    pub fn room_count(&self) -> usize {
+        self.rooms.len()
+    }
+}
+
+#[wasm_bindgen]
+impl ExitGraph {
+    /// Creates a new, empty exit graph cache (JavaScript constructor)
+    #[wasm_bindgen(constructor)]
+    pub fn js_new() -> Self {
+        Self::new()
+    }
+
+    /// Drops the cached abstraction for a room
+    #[wasm_bindgen(js_name = invalidate_room)]
+    pub fn js_invalidate_room(&mut self, room_name: u16) {
+        self.invalidate_room(RoomName::from_packed(room_name));
+    }
+
+    /// Drops all cached room abstractions
+    #[wasm_bindgen(js_name = clear)]
+    pub fn js_clear(&mut self) {
+        self.clear();
+    }
+
+    /// The number of rooms currently cached
+    #[wasm_bindgen(getter = room_count)]
+    pub fn js_room_count(&self) -> usize {
+        self.room_count()
+    }
+}
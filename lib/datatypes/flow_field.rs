@@ -1,4 +1,4 @@
-use crate::algorithms::map::{preferred_directions, DirectionOrder};
+use crate::algorithms::map::{DirectionOrder, DirectionPriority};
 use screeps::{Direction, RoomCoordinate};
 use wasm_bindgen::prelude::*;
 
@@ -10,7 +10,7 @@ use wasm_bindgen::prelude::*;
 #[derive(Debug, Clone)]
 pub struct FlowField {
     data: [u8; 2500],
-    direction_order: DirectionOrder,
+    direction_order: DirectionPriority,
 }
 
 impl FlowField {
@@ -20,10 +20,10 @@ impl FlowField {
     }
 
     /// Create a new flow field with the given tie-break order for directions.
-    pub fn new_with_direction_order(direction_order: DirectionOrder) -> Self {
+    pub fn new_with_direction_order(direction_order: impl Into<DirectionPriority>) -> Self {
         FlowField {
             data: [0; 2500],
-            direction_order,
+            direction_order: direction_order.into(),
         }
     }
 
@@ -41,7 +41,7 @@ impl FlowField {
     pub fn get_directions(&self, x: RoomCoordinate, y: RoomCoordinate) -> Vec<Direction> {
         let value = self.get(x, y);
         let mut directions = Vec::new();
-        for direction in preferred_directions(self.direction_order).iter().cloned() {
+        for direction in self.direction_order.directions() {
             if value & direction_bit(direction) != 0 {
                 directions.push(direction);
             }
@@ -52,7 +52,8 @@ impl FlowField {
     /// Get the first valid direction for a given coordinate, respecting the field's tie-break order.
     pub fn get_first_direction(&self, x: RoomCoordinate, y: RoomCoordinate) -> Option<Direction> {
         let value = self.get(x, y);
-        preferred_directions(self.direction_order)
+        self.direction_order
+            .directions()
             .iter()
             .cloned()
             .find(|direction| value & direction_bit(*direction) != 0)
@@ -78,7 +79,7 @@ impl FlowField {
     }
 }
 
-fn direction_bit(direction: Direction) -> u8 {
+pub(crate) fn direction_bit(direction: Direction) -> u8 {
     1 << (direction as u8 - 1)
 }
 
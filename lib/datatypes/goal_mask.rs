@@ -0,0 +1,69 @@
+use screeps::constants::extra::ROOM_AREA;
+use screeps::{linear_index_to_xy, Position, RoomName};
+use wasm_bindgen::prelude::*;
+
+use super::ClockworkCostMatrix;
+use super::RoomSlab;
+use super::TileSet;
+
+/// A set of goal tiles across multiple rooms, stored as one bit per tile. Searches
+/// given a mask stop at the first masked tile they settle, for goals that don't fit
+/// a position and range ("the nearest free tile near the controller").
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct GoalMask {
+    rooms: RoomSlab<TileSet>,
+}
+
+impl GoalMask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, position: Position) {
+        self.rooms
+            .get_or_insert_with(position.room_name(), TileSet::new)
+            .insert(position.xy());
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.rooms
+            .get(position.room_name())
+            .is_some_and(|tiles| tiles.contains(position.xy()))
+    }
+
+    /// Marks every tile with a non-zero value in `matrix` as a goal in `room_name`.
+    pub fn insert_cost_matrix(&mut self, room_name: RoomName, matrix: &ClockworkCostMatrix) {
+        for index in 0..ROOM_AREA {
+            let xy = linear_index_to_xy(index);
+            if matrix.get(xy) != 0 {
+                self.insert(Position::new(xy.x, xy.y, room_name));
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl GoalMask {
+    #[wasm_bindgen(constructor)]
+    pub fn js_new() -> Self {
+        Self::new()
+    }
+
+    /// Marks a packed position as a goal.
+    #[wasm_bindgen(js_name = add)]
+    pub fn js_add(&mut self, packed_position: u32) {
+        self.insert(Position::from_packed(packed_position));
+    }
+
+    /// Marks every tile with a non-zero value in the cost matrix as a goal.
+    #[wasm_bindgen(js_name = addRoom)]
+    pub fn js_add_room(&mut self, room_name: u16, matrix: &ClockworkCostMatrix) {
+        self.insert_cost_matrix(RoomName::from_packed(room_name), matrix);
+    }
+
+    #[wasm_bindgen(js_name = has)]
+    pub fn js_has(&self, packed_position: u32) -> bool {
+        self.contains(Position::from_packed(packed_position))
+    }
+}
@@ -0,0 +1,268 @@
+use screeps::{Position, RoomName};
+use std::cell::RefCell;
+use std::mem::size_of;
+use wasm_bindgen::prelude::*;
+
+use super::distance_map::DistanceMap;
+use super::multiroom_distance_map::MultiroomDistanceMap;
+
+const DEFAULT_ROOM_BUDGET: usize = 64;
+
+#[derive(Debug, Clone)]
+struct Landmark {
+    position: Position,
+    distances: MultiroomDistanceMap,
+}
+
+/// Exact distance maps from a handful of landmark positions, used for the ALT
+/// (A*, landmarks, triangle inequality) heuristic.
+///
+/// For any landmark `L`, `d(L, goal) <= d(L, n) + d(n, goal)`, so
+/// `d(L, goal) - d(L, n)` is a lower bound on the remaining cost from `n`. The
+/// bound only holds while searches use costs at least as high as the matrices
+/// the landmarks were built with, so build them from terrain-only matrices (or
+/// rebuild them when the matrices change).
+///
+/// The total number of rooms stored across all landmarks is capped by the room
+/// budget.
+#[derive(Debug, Clone)]
+pub struct LandmarkIndex {
+    landmarks: Vec<Landmark>,
+    room_budget: usize,
+    uses_portals: bool,
+}
+
+thread_local! {
+    static CONFIGURED_LANDMARKS: RefCell<LandmarkIndex> = RefCell::new(LandmarkIndex::default());
+}
+
+impl Default for LandmarkIndex {
+    fn default() -> Self {
+        Self::with_room_budget(DEFAULT_ROOM_BUDGET)
+    }
+}
+
+impl LandmarkIndex {
+    pub fn with_room_budget(room_budget: usize) -> Self {
+        Self {
+            landmarks: Vec::new(),
+            room_budget,
+            uses_portals: false,
+        }
+    }
+
+    pub fn room_budget(&self) -> usize {
+        self.room_budget
+    }
+
+    /// Changes the room budget, dropping the most recently added landmarks until
+    /// the stored rooms fit.
+    pub fn set_room_budget(&mut self, room_budget: usize) {
+        self.room_budget = room_budget;
+        while self.stored_rooms() > room_budget {
+            self.landmarks.pop();
+        }
+    }
+
+    pub fn remaining_room_budget(&self) -> usize {
+        self.room_budget.saturating_sub(self.stored_rooms())
+    }
+
+    /// The number of room distance maps stored across all landmarks.
+    pub fn stored_rooms(&self) -> usize {
+        self.landmarks
+            .iter()
+            .map(|landmark| landmark.distances.maps.len())
+            .sum()
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.landmarks
+            .iter()
+            .map(|landmark| landmark.position)
+            .collect()
+    }
+
+    /// Whether the landmark distances were measured with portals. Distances without
+    /// portals can overestimate, so they're only used for searches without portals.
+    pub fn uses_portals(&self) -> bool {
+        self.uses_portals
+    }
+
+    pub fn set_uses_portals(&mut self, uses_portals: bool) {
+        self.uses_portals = uses_portals;
+    }
+
+    /// Adds a landmark with its distance map. Rooms the search never reached are
+    /// dropped. Returns false (and stores nothing) if the remaining rooms don't fit
+    /// in the budget.
+    pub fn insert(&mut self, position: Position, mut distances: MultiroomDistanceMap) -> bool {
+        distances
+            .maps
+            .retain(|_, map| map.enumerate().any(|(_, distance)| *distance != usize::MAX));
+        if distances.maps.len() > self.remaining_room_budget() {
+            return false;
+        }
+        self.landmarks.push(Landmark {
+            position,
+            distances,
+        });
+        true
+    }
+
+    /// The closest distance from any landmark to `position`, or `usize::MAX` if no
+    /// landmark reached it.
+    pub fn nearest_landmark_distance(&self, position: Position) -> usize {
+        self.landmarks
+            .iter()
+            .map(|landmark| landmark.distances.get(position))
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// For each landmark, the shortest distance to any tile between `min_range` and
+    /// `max_range` of `goal` (in the goal's room), or `usize::MAX` if the landmark
+    /// didn't reach one.
+    pub fn goal_distances(&self, goal: Position, min_range: usize, max_range: usize) -> Vec<usize> {
+        self.landmarks
+            .iter()
+            .map(|landmark| {
+                landmark
+                    .distances
+                    .get_room_map(goal.room_name())
+                    .map(|map| nearest_in_range(map, goal, min_range, max_range))
+                    .unwrap_or(usize::MAX)
+            })
+            .collect()
+    }
+
+    /// The triangle-inequality lower bound on the cost from `position` to a goal,
+    /// given that goal's `goal_distances`. Landmarks without a distance to either
+    /// end contribute nothing.
+    pub fn lower_bound(&self, position: Position, goal_distances: &[usize]) -> usize {
+        self.landmarks
+            .iter()
+            .zip(goal_distances)
+            .filter(|(_, goal_distance)| **goal_distance != usize::MAX)
+            .map(|(landmark, goal_distance)| {
+                let distance = landmark.distances.get(position);
+                if distance == usize::MAX {
+                    0
+                } else {
+                    goal_distance.saturating_sub(distance)
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn debug_info(&self) -> LandmarkIndexDebugInfo {
+        LandmarkIndexDebugInfo {
+            landmarks: self
+                .landmarks
+                .iter()
+                .map(|landmark| landmark.position.packed_repr())
+                .collect(),
+            stored_rooms: self.stored_rooms(),
+            room_budget: self.room_budget,
+            uses_portals: self.uses_portals,
+            total_size_bytes: self.approximate_memory_size_bytes(),
+        }
+    }
+
+    fn approximate_memory_size_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.landmarks.capacity() * size_of::<Landmark>()
+            + self.stored_rooms() * (size_of::<RoomName>() + size_of::<DistanceMap>())
+    }
+}
+
+fn nearest_in_range(
+    map: &DistanceMap,
+    goal: Position,
+    min_range: usize,
+    max_range: usize,
+) -> usize {
+    map.enumerate()
+        .filter(|(xy, _)| (min_range..=max_range).contains(&(xy.get_range_to(goal.xy()) as usize)))
+        .map(|(_, distance)| *distance)
+        .min()
+        .unwrap_or(usize::MAX)
+}
+
+pub struct LandmarkIndexDebugInfo {
+    landmarks: Vec<u32>,
+    stored_rooms: usize,
+    room_budget: usize,
+    uses_portals: bool,
+    total_size_bytes: usize,
+}
+
+impl From<LandmarkIndexDebugInfo> for JsValue {
+    fn from(info: LandmarkIndexDebugInfo) -> Self {
+        let object = js_sys::Object::new();
+        let landmarks = js_sys::Array::new();
+        for landmark in info.landmarks {
+            landmarks.push(&JsValue::from_f64(landmark as f64));
+        }
+        js_sys::Reflect::set(&object, &JsValue::from_str("landmarks"), &landmarks)
+            .expect("setting landmarks should not fail");
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("storedRooms"),
+            &JsValue::from_f64(info.stored_rooms as f64),
+        )
+        .expect("setting storedRooms should not fail");
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("roomBudget"),
+            &JsValue::from_f64(info.room_budget as f64),
+        )
+        .expect("setting roomBudget should not fail");
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("usesPortals"),
+            &JsValue::from_bool(info.uses_portals),
+        )
+        .expect("setting usesPortals should not fail");
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str("totalSizeBytes"),
+            &JsValue::from_f64(info.total_size_bytes as f64),
+        )
+        .expect("setting totalSizeBytes should not fail");
+        object.into()
+    }
+}
+
+pub fn with_configured_landmark_index<R>(f: impl FnOnce(&LandmarkIndex) -> R) -> R {
+    CONFIGURED_LANDMARKS.with(|landmarks| f(&landmarks.borrow()))
+}
+
+/// Replaces the configured landmarks with a freshly built index.
+pub fn configure_landmark_index(index: LandmarkIndex) {
+    CONFIGURED_LANDMARKS.with(|landmarks| {
+        *landmarks.borrow_mut() = index;
+    });
+}
+
+#[wasm_bindgen]
+pub fn set_landmark_room_budget(room_budget: usize) {
+    CONFIGURED_LANDMARKS.with(|landmarks| {
+        landmarks.borrow_mut().set_room_budget(room_budget);
+    });
+}
+
+#[wasm_bindgen]
+pub fn clear_landmarks() {
+    CONFIGURED_LANDMARKS.with(|landmarks| {
+        let mut landmarks = landmarks.borrow_mut();
+        let room_budget = landmarks.room_budget();
+        *landmarks = LandmarkIndex::with_room_budget(room_budget);
+    });
+}
+
+#[wasm_bindgen]
+pub fn debug_landmark_index() -> JsValue {
+    with_configured_landmark_index(|landmarks| landmarks.debug_info().into())
+}
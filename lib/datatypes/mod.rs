@@ -1,20 +1,48 @@
+mod compact_distance_map;
 mod cost_matrix;
 mod distance_map;
+mod exit_graph;
 mod flow_field;
+mod goal_mask;
+mod landmark_index;
 mod mono_flow_field;
+mod multiroom_compact_distance_map;
 mod multiroom_distance_map;
 mod multiroom_flow_field;
+mod multiroom_label_map;
 mod multiroom_mono_flow_field;
+mod multiroom_turn_distance_map;
+mod open_list;
 mod path;
 mod portal_index;
+mod reservation_table;
 mod room_data_cache;
+mod room_slab;
+mod tile_set;
 
 pub use cost_matrix::ClockworkCostMatrix;
 pub use distance_map::DistanceMap;
+pub use exit_graph::ExitGraph;
+pub use exit_graph::RoomExits;
+pub(crate) use flow_field::direction_bit;
+pub use flow_field::FlowField;
+pub use goal_mask::GoalMask;
+pub use landmark_index::configure_landmark_index;
+pub use landmark_index::with_configured_landmark_index;
+pub use landmark_index::LandmarkIndex;
+pub use multiroom_compact_distance_map::MultiroomCompactDistanceMap;
 pub use multiroom_distance_map::MultiroomDistanceMap;
 pub use multiroom_flow_field::MultiroomFlowField;
+pub use multiroom_label_map::MultiroomLabelMap;
+pub use multiroom_label_map::NO_LABEL;
 pub use multiroom_mono_flow_field::MultiroomMonoFlowField;
+pub use multiroom_turn_distance_map::MultiroomTurnDistanceMap;
+pub use open_list::OpenList;
 pub use path::Path;
+pub use portal_index::configured_portal_index;
 pub use portal_index::with_configured_portal_index;
 pub use portal_index::PortalIndex;
+pub use reservation_table::ReservationTable;
 pub use room_data_cache::RoomDataCache;
+pub use room_slab::RoomSlab;
+pub(crate) use tile_set::TileSet;
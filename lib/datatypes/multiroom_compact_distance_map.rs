@@ -0,0 +1,133 @@
+use screeps::{Position, RoomName};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+use super::compact_distance_map::CompactDistanceMap;
+use super::DistanceMap;
+use super::MultiroomDistanceMap;
+use super::RoomSlab;
+
+/// Maps distance values across multiple rooms with `u16` storage, see
+/// `CompactDistanceMap`. Reads the same as a `MultiroomDistanceMap`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct MultiroomCompactDistanceMap {
+    maps: RoomSlab<CompactDistanceMap>,
+}
+
+impl MultiroomCompactDistanceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the distance value at a given position, or `usize::MAX` if unreachable
+    pub fn get(&self, pos: Position) -> usize {
+        self.maps
+            .get(pos.room_name())
+            .map(|map| map.get(pos.xy()))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Gets a reference to the CompactDistanceMap for a given room, if it exists
+    pub fn get_room_map(&self, room_name: RoomName) -> Option<&CompactDistanceMap> {
+        self.maps.get(room_name)
+    }
+
+    /// Gets the list of rooms in the map
+    pub fn rooms(&self) -> Vec<RoomName> {
+        self.maps.rooms().collect()
+    }
+}
+
+impl TryFrom<&MultiroomDistanceMap> for MultiroomCompactDistanceMap {
+    type Error = &'static str;
+
+    fn try_from(distance_map: &MultiroomDistanceMap) -> Result<Self, Self::Error> {
+        let mut maps = RoomSlab::new();
+        for (room_name, map) in distance_map.maps.iter() {
+            maps.insert(room_name, CompactDistanceMap::try_from(map)?);
+        }
+        Ok(Self { maps })
+    }
+}
+
+impl From<&MultiroomCompactDistanceMap> for MultiroomDistanceMap {
+    fn from(compact: &MultiroomCompactDistanceMap) -> Self {
+        let mut maps = RoomSlab::new();
+        for (room_name, map) in compact.maps.iter() {
+            maps.insert(room_name, DistanceMap::from(map));
+        }
+        MultiroomDistanceMap { maps }
+    }
+}
+
+#[wasm_bindgen]
+impl MultiroomCompactDistanceMap {
+    /// Gets the distance value at a given position
+    #[wasm_bindgen(js_name = get)]
+    pub fn js_get(&self, packed_pos: u32) -> usize {
+        self.get(Position::from_packed(packed_pos))
+    }
+
+    /// Gets the list of rooms in the map
+    #[wasm_bindgen(js_name = get_rooms)]
+    pub fn js_get_rooms(&self) -> Vec<u16> {
+        self.maps.rooms().map(|r| r.packed_repr()).collect()
+    }
+
+    /// Gets the CompactDistanceMap for a given room
+    #[wasm_bindgen(js_name = get_room)]
+    pub fn js_get_room(&self, room_name: u16) -> Option<CompactDistanceMap> {
+        self.maps.get(RoomName::from_packed(room_name)).cloned()
+    }
+
+    /// A zero-copy `Uint16Array` view of a room's distances, with the same caveats
+    /// as `CompactDistanceMap.view`.
+    #[wasm_bindgen(js_name = view_room)]
+    pub fn js_view_room(&self, room_name: u16) -> Option<js_sys::Uint16Array> {
+        self.maps
+            .get(RoomName::from_packed(room_name))
+            .map(CompactDistanceMap::js_view)
+    }
+
+    /// Converts back to a full `MultiroomDistanceMap`.
+    #[wasm_bindgen(js_name = toDistanceMap)]
+    pub fn to_distance_map(&self) -> MultiroomDistanceMap {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
+    use crate::datatypes::ClockworkCostMatrix;
+    use crate::test_fixtures::pos;
+
+    #[test]
+    fn search_results_convert_losslessly() {
+        let result = dijkstra_multiroom_distance_map(
+            vec![pos("W1N1", 25, 25)],
+            |_: RoomName| Some(ClockworkCostMatrix::new(Some(3))),
+            10_000,
+            2,
+            10_000,
+            None,
+            None,
+        );
+
+        let compact = result.compact_distance_map().unwrap();
+        let distance_map = result.distance_map();
+        assert_eq!(compact.get(pos("W1N1", 30, 25)), 15);
+        assert_eq!(compact.get(pos("W9N9", 30, 25)), usize::MAX);
+        assert_eq!(compact.rooms(), distance_map.rooms());
+
+        let restored = MultiroomDistanceMap::from(&compact);
+        for room_name in distance_map.rooms() {
+            assert_eq!(
+                restored.get_room_map(room_name).unwrap().to_vec(),
+                distance_map.get_room_map(room_name).unwrap().to_vec()
+            );
+        }
+    }
+}
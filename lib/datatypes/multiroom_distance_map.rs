@@ -1,58 +1,59 @@
 use screeps::{Position, RoomName};
-use std::collections::HashMap;
+use std::convert::TryFrom;
 use wasm_bindgen::prelude::*;
 
 use super::distance_map::DistanceMap;
+use super::MultiroomCompactDistanceMap;
+use super::RoomSlab;
 
 /// Maps distance values across multiple rooms, storing a DistanceMap for each room
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct MultiroomDistanceMap {
     #[wasm_bindgen(skip)]
-    pub maps: HashMap<RoomName, DistanceMap>,
+    pub maps: RoomSlab<DistanceMap>,
 }
 
 impl MultiroomDistanceMap {
     /// Creates a new empty multiroom distance map
     pub fn new() -> Self {
         MultiroomDistanceMap {
-            maps: HashMap::new(),
+            maps: RoomSlab::new(),
         }
     }
 
     /// Gets the distance value at a given position
     pub fn get(&self, pos: Position) -> usize {
         self.maps
-            .get(&pos.room_name())
+            .get(pos.room_name())
             .map(|map| map[pos.xy()])
             .unwrap_or(usize::MAX)
     }
 
     /// Sets the distance value at a given position
     pub fn set(&mut self, pos: Position, value: usize) {
-        let room_name = pos.room_name();
-        let map = self.maps.entry(room_name).or_default();
+        let map = self.get_or_create_room_map(pos.room_name());
         map[pos.xy()] = value;
     }
 
     /// Returns whether the map contains data for a given room
     pub fn contains_room(&self, room_name: RoomName) -> bool {
-        self.maps.contains_key(&room_name)
+        self.maps.contains(room_name)
     }
 
     /// Gets a reference to the DistanceMap for a given room, if it exists
     pub fn get_room_map(&self, room_name: RoomName) -> Option<&DistanceMap> {
-        self.maps.get(&room_name)
+        self.maps.get(room_name)
     }
 
     /// Gets a mutable reference to the DistanceMap for a given room, creating it if it doesn't exist
     pub fn get_or_create_room_map(&mut self, room_name: RoomName) -> &mut DistanceMap {
-        self.maps.entry(room_name).or_default()
+        self.maps.get_or_insert_with(room_name, DistanceMap::new)
     }
 
     /// Gets the list of rooms in the map
     pub fn rooms(&self) -> Vec<RoomName> {
-        self.maps.keys().cloned().collect()
+        self.maps.rooms().collect()
     }
 }
 
@@ -88,7 +89,15 @@ impl MultiroomDistanceMap {
     #[wasm_bindgen(js_name = get_room)]
     pub fn js_get_room(&self, room_name: u16) -> Option<DistanceMap> {
         let room_name = RoomName::from_packed(room_name);
-        self.maps.get(&room_name).cloned()
+        self.maps.get(room_name).cloned()
+    }
+
+    /// Converts to `u16` storage, see `MultiroomCompactDistanceMap`. Throws if a
+    /// distance is 65535 or more.
+    #[wasm_bindgen(js_name = toCompact)]
+    pub fn js_to_compact(&self) -> MultiroomCompactDistanceMap {
+        MultiroomCompactDistanceMap::try_from(self)
+            .unwrap_or_else(|error| wasm_bindgen::throw_str(error))
     }
 }
 
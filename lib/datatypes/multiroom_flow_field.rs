@@ -1,17 +1,19 @@
-use crate::algorithms::map::DirectionOrder;
+use crate::algorithms::map::{DirectionOrder, DirectionPriority};
 use screeps::{Direction, Position, RoomName};
-use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
 use super::flow_field::FlowField;
+use super::RoomSlab;
+use super::TileSet;
 
 /// Maps flow field values across multiple rooms, storing a FlowField for each room
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct MultiroomFlowField {
-    maps: HashMap<RoomName, FlowField>,
-    direction_order: DirectionOrder,
-    terminals: HashSet<Position>,
+    maps: RoomSlab<FlowField>,
+    direction_order: DirectionPriority,
+    // Tiles where path extraction stops: the origins of the distance map it was built from.
+    terminals: RoomSlab<TileSet>,
 }
 
 impl MultiroomFlowField {
@@ -21,87 +23,76 @@ impl MultiroomFlowField {
     }
 
     /// Creates a new empty multiroom flow field with the given tie-break order.
-    pub fn new_with_direction_order(direction_order: DirectionOrder) -> Self {
+    pub fn new_with_direction_order(direction_order: impl Into<DirectionPriority>) -> Self {
         MultiroomFlowField {
-            maps: HashMap::new(),
-            direction_order,
-            terminals: HashSet::new(),
+            maps: RoomSlab::new(),
+            direction_order: direction_order.into(),
+            terminals: RoomSlab::new(),
         }
     }
 
     /// Gets the flow field value at a given position
     pub fn get(&self, pos: Position) -> u8 {
         self.maps
-            .get(&pos.room_name())
+            .get(pos.room_name())
             .map(|map| map.get(pos.x(), pos.y()))
             .unwrap_or(0) // Default value if room not present
     }
 
     /// Sets the flow field value at a given position
     pub fn set(&mut self, pos: Position, value: u8) {
-        let room_name = pos.room_name();
-        let direction_order = self.direction_order;
-        let map = self
-            .maps
-            .entry(room_name)
-            .or_insert_with(|| FlowField::new_with_direction_order(direction_order));
+        let map = self.get_or_create_room_map(pos.room_name());
         map.set(pos.x(), pos.y(), value);
     }
 
     /// Returns whether the flow field contains data for a given room
     pub fn contains_room(&self, room_name: RoomName) -> bool {
-        self.maps.contains_key(&room_name)
+        self.maps.contains(room_name)
     }
 
     /// Gets a reference to the FlowField for a given room, if it exists
     pub fn get_room_map(&self, room_name: RoomName) -> Option<&FlowField> {
-        self.maps.get(&room_name)
+        self.maps.get(room_name)
     }
 
     /// Gets a mutable reference to the FlowField for a given room, creating it if it doesn't exist
     pub fn get_or_create_room_map(&mut self, room_name: RoomName) -> &mut FlowField {
         let direction_order = self.direction_order;
-        self.maps
-            .entry(room_name)
-            .or_insert_with(|| FlowField::new_with_direction_order(direction_order))
+        self.maps.get_or_insert_with(room_name, || {
+            FlowField::new_with_direction_order(direction_order)
+        })
     }
 
     /// Gets the list of valid directions at a given position across rooms
     pub fn get_directions(&self, pos: Position) -> Vec<Direction> {
         self.maps
-            .get(&pos.room_name())
+            .get(pos.room_name())
             .map(|map| map.get_directions(pos.x(), pos.y()))
             .unwrap_or_default()
     }
 
     /// Sets the list of valid directions at a given position across rooms
     pub fn set_directions(&mut self, pos: Position, directions: Vec<Direction>) {
-        let room_name = pos.room_name();
-        let direction_order = self.direction_order;
-        let map = self
-            .maps
-            .entry(room_name)
-            .or_insert_with(|| FlowField::new_with_direction_order(direction_order));
+        let map = self.get_or_create_room_map(pos.room_name());
         map.set_directions(pos.x(), pos.y(), directions);
     }
 
     /// Adds a direction to the list of valid directions at a given position across rooms
     pub fn add_direction(&mut self, pos: Position, direction: Direction) {
-        let room_name = pos.room_name();
-        let direction_order = self.direction_order;
-        let map = self
-            .maps
-            .entry(room_name)
-            .or_insert_with(|| FlowField::new_with_direction_order(direction_order));
+        let map = self.get_or_create_room_map(pos.room_name());
         map.add_direction(pos.x(), pos.y(), direction);
     }
 
     pub fn set_terminal(&mut self, pos: Position) {
-        self.terminals.insert(pos);
+        self.terminals
+            .get_or_insert_with(pos.room_name(), TileSet::new)
+            .insert(pos.xy());
     }
 
     pub fn is_terminal(&self, pos: Position) -> bool {
-        self.terminals.contains(&pos)
+        self.terminals
+            .get(pos.room_name())
+            .is_some_and(|tiles| tiles.contains(pos.xy()))
     }
 }
 
@@ -130,14 +121,14 @@ impl MultiroomFlowField {
     /// Gets the list of rooms in the flow field
     #[wasm_bindgen(js_name = getRooms)]
     pub fn js_get_rooms(&self) -> Vec<u16> {
-        self.maps.keys().map(|k| k.packed_repr()).collect()
+        self.maps.rooms().map(|k| k.packed_repr()).collect()
     }
 
     /// Gets the FlowField for a given room
     #[wasm_bindgen(js_name = getRoom)]
     pub fn js_get_room(&self, room_name: u16) -> Option<FlowField> {
         let room_name = RoomName::from_packed(room_name);
-        self.maps.get(&room_name).cloned()
+        self.maps.get(room_name).cloned()
     }
 
     /// Gets the list of valid directions at a given position (JavaScript)
@@ -0,0 +1,79 @@
+use screeps::constants::extra::ROOM_AREA;
+use screeps::{linear_index_to_xy, xy_to_linear_index, Position, RoomName};
+use wasm_bindgen::prelude::*;
+
+use super::RoomSlab;
+
+/// Marks tiles that don't have a label.
+pub const NO_LABEL: u32 = u32::MAX;
+
+/// Maps a `u32` label (such as the origin that owns a tile) onto tiles across
+/// multiple rooms. Unlabeled tiles read as `NO_LABEL`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct MultiroomLabelMap {
+    maps: RoomSlab<Box<[u32; ROOM_AREA]>>,
+}
+
+impl MultiroomLabelMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the label at a given position, or `NO_LABEL`
+    pub fn get(&self, pos: Position) -> u32 {
+        self.maps
+            .get(pos.room_name())
+            .map(|map| map[xy_to_linear_index(pos.xy())])
+            .unwrap_or(NO_LABEL)
+    }
+
+    /// Sets the label at a given position
+    pub fn set(&mut self, pos: Position, label: u32) {
+        let map = self
+            .maps
+            .get_or_insert_with(pos.room_name(), || Box::new([NO_LABEL; ROOM_AREA]));
+        map[xy_to_linear_index(pos.xy())] = label;
+    }
+
+    /// Gets the list of rooms in the map
+    pub fn rooms(&self) -> Vec<RoomName> {
+        self.maps.rooms().collect()
+    }
+
+    /// Every labeled position, room by room.
+    pub fn labeled_positions(&self) -> impl Iterator<Item = (Position, u32)> + '_ {
+        self.maps.iter().flat_map(|(room_name, map)| {
+            map.iter()
+                .enumerate()
+                .filter(|(_, label)| **label != NO_LABEL)
+                .map(move |(index, label)| {
+                    let xy = linear_index_to_xy(index);
+                    (Position::new(xy.x, xy.y, room_name), *label)
+                })
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl MultiroomLabelMap {
+    /// Gets the label at a given position, or `0xFFFFFFFF` if the tile is unlabeled
+    #[wasm_bindgen(js_name = get)]
+    pub fn js_get(&self, packed_pos: u32) -> u32 {
+        self.get(Position::from_packed(packed_pos))
+    }
+
+    /// Gets the list of rooms in the map
+    #[wasm_bindgen(js_name = get_rooms)]
+    pub fn js_get_rooms(&self) -> Vec<u16> {
+        self.rooms().iter().map(|r| r.packed_repr()).collect()
+    }
+
+    /// Gets the labels for a given room as a flat array, indexed like a cost matrix
+    #[wasm_bindgen(js_name = get_room)]
+    pub fn js_get_room(&self, room_name: u16) -> Option<Vec<u32>> {
+        self.maps
+            .get(RoomName::from_packed(room_name))
+            .map(|map| map.to_vec())
+    }
+}
@@ -1,66 +1,74 @@
 use screeps::{Direction, Position, RoomName};
-use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
 use super::mono_flow_field::MonoFlowField;
+use super::RoomSlab;
+use super::TileSet;
 
 /// Maps monodirectional flow field values across multiple rooms, storing a MonoFlowField for each room
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct MultiroomMonoFlowField {
-    maps: HashMap<RoomName, MonoFlowField>,
-    terminals: HashSet<Position>,
+    maps: RoomSlab<MonoFlowField>,
+    // Tiles where path extraction stops: the origins of the distance map it was built from.
+    terminals: RoomSlab<TileSet>,
 }
 
 impl MultiroomMonoFlowField {
     /// Creates a new empty multiroom monodirectional flow field
     pub fn new() -> Self {
         MultiroomMonoFlowField {
-            maps: HashMap::new(),
-            terminals: HashSet::new(),
+            maps: RoomSlab::new(),
+            terminals: RoomSlab::new(),
+        }
+    }
+
+    /// Wraps per-room flow fields that were built elsewhere, such as a search's parents.
+    pub fn from_room_maps(maps: RoomSlab<MonoFlowField>) -> Self {
+        MultiroomMonoFlowField {
+            maps,
+            terminals: RoomSlab::new(),
         }
     }
 
     /// Gets the direction at a given position
     pub fn get(&self, pos: Position) -> Option<Direction> {
         self.maps
-            .get(&pos.room_name())
+            .get(pos.room_name())
             .and_then(|map| map.get(pos.xy()))
     }
 
     /// Sets the direction at a given position
     pub fn set(&mut self, pos: Position, direction: Option<Direction>) {
-        let room_name = pos.room_name();
-        let map = self
-            .maps
-            .entry(room_name)
-            .or_insert_with(MonoFlowField::new);
+        let map = self.get_or_create_room_map(pos.room_name());
         map.set(pos.xy(), direction);
     }
 
     /// Returns whether the flow field contains data for a given room
     pub fn contains_room(&self, room_name: RoomName) -> bool {
-        self.maps.contains_key(&room_name)
+        self.maps.contains(room_name)
     }
 
     /// Gets a reference to the MonoFlowField for a given room, if it exists
     pub fn get_room_map(&self, room_name: RoomName) -> Option<&MonoFlowField> {
-        self.maps.get(&room_name)
+        self.maps.get(room_name)
     }
 
     /// Gets a mutable reference to the MonoFlowField for a given room, creating it if it doesn't exist
     pub fn get_or_create_room_map(&mut self, room_name: RoomName) -> &mut MonoFlowField {
-        self.maps
-            .entry(room_name)
-            .or_insert_with(MonoFlowField::new)
+        self.maps.get_or_insert_with(room_name, MonoFlowField::new)
     }
 
     pub fn set_terminal(&mut self, pos: Position) {
-        self.terminals.insert(pos);
+        self.terminals
+            .get_or_insert_with(pos.room_name(), TileSet::new)
+            .insert(pos.xy());
     }
 
     pub fn is_terminal(&self, pos: Position) -> bool {
-        self.terminals.contains(&pos)
+        self.terminals
+            .get(pos.room_name())
+            .is_some_and(|tiles| tiles.contains(pos.xy()))
     }
 }
 
@@ -89,14 +97,14 @@ impl MultiroomMonoFlowField {
     /// Gets the list of rooms in the flow field
     #[wasm_bindgen(js_name = getRooms)]
     pub fn js_get_rooms(&self) -> Vec<u16> {
-        self.maps.keys().map(|k| k.packed_repr()).collect()
+        self.maps.rooms().map(|k| k.packed_repr()).collect()
     }
 
     /// Gets the MonoFlowField for a given room
     #[wasm_bindgen(js_name = getRoom)]
     pub fn js_get_room(&self, room_name: u16) -> Option<MonoFlowField> {
         let room_name = RoomName::from_packed(room_name);
-        self.maps.get(&room_name).cloned()
+        self.maps.get(room_name).cloned()
     }
 }
 
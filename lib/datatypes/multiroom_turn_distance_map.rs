@@ -0,0 +1,73 @@
+use screeps::{Direction, Position};
+use wasm_bindgen::prelude::*;
+
+use super::MultiroomDistanceMap;
+
+/// Distances from a search that charges for changing direction, kept separately for
+/// each direction a tile can be entered from. Where a path goes next depends on the
+/// direction it arrived in, so unlike a plain distance map, the best path from a tile
+/// can't be read from its neighbors' distances alone.
+///
+/// Directions are the search's, from the start outward: a creep heading back to the
+/// start leaves a tile in the opposite direction. Start tiles are kept under no
+/// direction.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct MultiroomTurnDistanceMap {
+    turn_penalty: usize,
+    // Indexed by direction, with the start tiles at 0.
+    maps: [MultiroomDistanceMap; 9],
+}
+
+impl MultiroomTurnDistanceMap {
+    /// Creates a new empty turn distance map for searches charging `turn_penalty`
+    /// per change of direction.
+    pub fn new(turn_penalty: usize) -> Self {
+        MultiroomTurnDistanceMap {
+            turn_penalty,
+            maps: Default::default(),
+        }
+    }
+
+    /// The extra cost charged each time a path changes direction.
+    pub fn turn_penalty(&self) -> usize {
+        self.turn_penalty
+    }
+
+    /// Gets the distance to a position entered in `direction`, or to a start
+    /// position with no direction.
+    pub fn get(&self, pos: Position, direction: Option<Direction>) -> usize {
+        self.maps[direction.map_or(0, |direction| direction as usize)].get(pos)
+    }
+
+    /// Sets the distance to a position entered in `direction`.
+    pub fn set(&mut self, pos: Position, direction: Option<Direction>, value: usize) {
+        self.maps[direction.map_or(0, |direction| direction as usize)].set(pos, value);
+    }
+
+    /// The extra cost of entering a tile in `direction` after entering the previous
+    /// one in `previous`. Leaving a start tile is free.
+    pub fn turn_cost(&self, previous: Option<Direction>, direction: Direction) -> usize {
+        match previous {
+            Some(previous) if previous != direction => self.turn_penalty,
+            _ => 0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl MultiroomTurnDistanceMap {
+    /// The extra cost charged each time a path changes direction.
+    #[wasm_bindgen(getter, js_name = turn_penalty)]
+    pub fn js_turn_penalty(&self) -> usize {
+        self.turn_penalty
+    }
+
+    /// Gets the distance to a position entered in `direction`, or to a start
+    /// position if `direction` is undefined.
+    #[wasm_bindgen(js_name = get)]
+    pub fn js_get(&self, packed_pos: u32, direction: Option<Direction>) -> usize {
+        let pos = Position::from_packed(packed_pos);
+        self.get(pos, direction)
+    }
+}
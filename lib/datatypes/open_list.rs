@@ -0,0 +1,197 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// How many consecutive keys get their own bucket. Keys further past the lowest
+/// bucket wait in an overflow heap until the buckets catch up.
+const WINDOW: usize = 4096;
+
+/// The open list shared by the searches: states bucketed by key (usually the
+/// f_score), popped lowest key first and last in, first out within a key.
+///
+/// Path costs are small integers, so a bucket per key is much faster than a binary
+/// heap. To keep memory bounded when costs run high (lots of 254-cost tiles, or a
+/// large `max_path_cost`), only `WINDOW` buckets are kept; keys beyond them go to a
+/// heap and are moved into buckets once the search gets there.
+///
+/// Keys pushed below the lowest bucket (possible with weighted or inconsistent
+/// heuristics once the window has moved past them) wait in a second heap and are
+/// popped before anything in the buckets, keeping their own key.
+pub struct OpenList<T> {
+    buckets: Vec<Vec<T>>,
+    base: usize,
+    cursor: usize,
+    overflow: BinaryHeap<HeapEntry<T>>,
+    underflow: BinaryHeap<HeapEntry<T>>,
+}
+
+struct HeapEntry<T> {
+    key: usize,
+    item: T,
+}
+
+impl<T> PartialEq for HeapEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for HeapEntry<T> {}
+
+impl<T> PartialOrd for HeapEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for HeapEntry<T> {
+    // Reversed, so the heap pops the lowest key first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.cmp(&self.key)
+    }
+}
+
+impl<T> Default for OpenList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OpenList<T> {
+    pub fn new() -> Self {
+        Self {
+            buckets: vec![Vec::new()],
+            base: 0,
+            cursor: 0,
+            overflow: BinaryHeap::new(),
+            underflow: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, key: usize, item: T) {
+        if key < self.base {
+            self.underflow.push(HeapEntry { key, item });
+            return;
+        }
+        let offset = key - self.base;
+        if offset >= WINDOW {
+            self.overflow.push(HeapEntry { key, item });
+            return;
+        }
+        if offset >= self.buckets.len() {
+            self.buckets.resize_with(offset + 1, Vec::new);
+        }
+        self.buckets[offset].push(item);
+        self.cursor = self.cursor.min(offset);
+    }
+
+    /// The lowest key in the list, or `None` if it's empty.
+    pub fn peek_key(&mut self) -> Option<usize> {
+        if let Some(lowest) = self.underflow.peek() {
+            return Some(lowest.key);
+        }
+        loop {
+            while self.cursor < self.buckets.len() {
+                if !self.buckets[self.cursor].is_empty() {
+                    return Some(self.base + self.cursor);
+                }
+                self.cursor += 1;
+            }
+            if !self.rebase() {
+                return None;
+            }
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.pop_with_key().map(|(_, item)| item)
+    }
+
+    /// Pops the next state along with the key it was pushed with.
+    pub fn pop_with_key(&mut self) -> Option<(usize, T)> {
+        if let Some(HeapEntry { key, item }) = self.underflow.pop() {
+            return Some((key, item));
+        }
+        let key = self.peek_key()?;
+        self.buckets[self.cursor].pop().map(|item| (key, item))
+    }
+
+    /// Every state still in the list, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets
+            .iter()
+            .flatten()
+            .chain(self.overflow.iter().map(|entry| &entry.item))
+            .chain(self.underflow.iter().map(|entry| &entry.item))
+    }
+
+    /// Once the buckets run dry, moves the window up to the lowest overflowed key.
+    fn rebase(&mut self) -> bool {
+        let Some(lowest) = self.overflow.peek() else {
+            return false;
+        };
+        self.base = lowest.key;
+        self.cursor = 0;
+        self.buckets.truncate(1);
+        while self
+            .overflow
+            .peek()
+            .is_some_and(|entry| entry.key - self.base < WINDOW)
+        {
+            let Some(HeapEntry { key, item }) = self.overflow.pop() else {
+                break;
+            };
+            let offset = key - self.base;
+            if offset >= self.buckets.len() {
+                self.buckets.resize_with(offset + 1, Vec::new);
+            }
+            self.buckets[offset].push(item);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pops_lowest_key_first_and_last_in_first_out() {
+        let mut open = OpenList::new();
+        open.push(3, 'a');
+        open.push(1, 'b');
+        open.push(3, 'c');
+        open.push(2, 'd');
+
+        assert_eq!(open.peek_key(), Some(1));
+        let popped: Vec<char> = std::iter::from_fn(|| open.pop()).collect();
+        assert_eq!(popped, vec!['b', 'd', 'c', 'a']);
+        assert_eq!(open.peek_key(), None);
+    }
+
+    #[test]
+    fn keys_past_the_window_overflow_and_come_back_in_order() {
+        let mut open = OpenList::new();
+        open.push(5, 5);
+        open.push(1_000_000, 1_000_000);
+        open.push(WINDOW * 3, WINDOW * 3);
+        open.push(WINDOW * 3 + 1, WINDOW * 3 + 1);
+
+        assert!(open.buckets.len() <= WINDOW);
+        assert_eq!(open.pop(), Some(5));
+        // Pushed below the current bucket: popped next.
+        open.push(2, 2);
+        assert_eq!(open.pop(), Some(2));
+        assert_eq!(open.peek_key(), Some(WINDOW * 3));
+        // Pushed below the window after it moved: popped next, with its own key.
+        open.push(7, 7);
+        open.push(3, 3);
+        assert_eq!(open.peek_key(), Some(3));
+        assert_eq!(open.pop_with_key(), Some((3, 3)));
+        assert_eq!(open.pop_with_key(), Some((7, 7)));
+        assert_eq!(open.pop(), Some(WINDOW * 3));
+        assert_eq!(open.pop(), Some(WINDOW * 3 + 1));
+        assert_eq!(open.pop(), Some(1_000_000));
+        assert_eq!(open.pop(), None);
+        assert_eq!(open.iter().count(), 0);
+    }
+}
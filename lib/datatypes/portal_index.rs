@@ -3,19 +3,28 @@ use screeps::{xy_to_linear_index, Position, RoomCoordinate, RoomName};
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::mem::size_of;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+use super::RoomSlab;
+use super::TileSet;
+
 const DEFAULT_DISTANCE_CACHE_ROOM_LIMIT: usize = 512;
 
 #[derive(Clone, Debug)]
 struct PortalRoomSummary {
     portals: Vec<Position>,
+    // Rules out most tiles before searching `exits`.
+    entrances: TileSet,
+    // (linear tile index, exit), sorted by tile.
+    exits: Vec<(u16, Position)>,
 }
 
+/// Portal entrances and exits, indexed by room so that searches can check every tile
+/// they step onto without hashing.
 #[derive(Debug)]
 pub struct PortalIndex {
-    exit_by_entry: HashMap<Position, Position>,
-    rooms: HashMap<RoomName, PortalRoomSummary>,
+    rooms: RoomSlab<PortalRoomSummary>,
     nearest_endpoint_room_map_cache: RefCell<RoomMapLruCache>,
 }
 
@@ -27,13 +36,12 @@ struct RoomMapLruCache {
 }
 
 thread_local! {
-    static CONFIGURED_PORTALS: RefCell<PortalIndex> = RefCell::new(PortalIndex::default());
+    static CONFIGURED_PORTALS: RefCell<Rc<PortalIndex>> = RefCell::new(Rc::new(PortalIndex::default()));
 }
 
 impl Clone for PortalIndex {
     fn clone(&self) -> Self {
         Self {
-            exit_by_entry: self.exit_by_entry.clone(),
             rooms: self.rooms.clone(),
             nearest_endpoint_room_map_cache: RefCell::new(RoomMapLruCache::new(
                 self.distance_cache_room_limit(),
@@ -51,8 +59,7 @@ impl Default for PortalIndex {
 impl PortalIndex {
     pub fn with_distance_cache_room_limit(distance_cache_room_limit: usize) -> Self {
         Self {
-            exit_by_entry: HashMap::new(),
-            rooms: HashMap::new(),
+            rooms: RoomSlab::new(),
             nearest_endpoint_room_map_cache: RefCell::new(RoomMapLruCache::new(
                 distance_cache_room_limit,
             )),
@@ -81,7 +88,7 @@ impl PortalIndex {
         PortalIndexDebugInfo {
             cached_distance_maps: self.nearest_endpoint_room_map_cache.borrow().len(),
             max_cached_distance_maps: self.distance_cache_room_limit(),
-            cached_portals: self.exit_by_entry.len(),
+            cached_portals: self.rooms.values().map(|summary| summary.exits.len()).sum(),
             portal_room_summaries: self.rooms.len(),
             total_size_bytes: self.approximate_memory_size_bytes(),
         }
@@ -106,12 +113,25 @@ impl PortalIndex {
         self.clear_distance_caches();
     }
 
+    #[inline]
     pub fn exit(&self, entry: Position) -> Option<Position> {
-        self.exit_by_entry.get(&entry).copied()
+        if self.rooms.is_empty() {
+            return None;
+        }
+        self.rooms.get(entry.room_name())?.exit(entry)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.exit_by_entry.is_empty()
+        self.rooms.is_empty()
+    }
+
+    /// The portal endpoints in a room. Portals are indexed in both directions, so
+    /// each of these is also an entrance.
+    pub fn portals_in_room(&self, room: RoomName) -> &[Position] {
+        self.rooms
+            .get(room)
+            .map(|summary| summary.portals.as_slice())
+            .unwrap_or(&[])
     }
 
     pub fn nearest_endpoint_range(&self, position: Position) -> usize {
@@ -122,7 +142,7 @@ impl PortalIndex {
         let mut best = usize::MAX;
         for radius in 0..=u8::MAX as i32 {
             self.for_each_room_at_range(position.room_name(), radius, |room| {
-                if let Some(summary) = self.rooms.get(&room) {
+                if let Some(summary) = self.rooms.get(room) {
                     for portal in &summary.portals {
                         best = best.min(position.get_range_to(*portal) as usize);
                     }
@@ -196,7 +216,7 @@ impl PortalIndex {
         let mut candidates = Vec::new();
         for radius in 0..=u8::MAX as i32 {
             self.for_each_room_at_range(room, radius, |candidate_room| {
-                if let Some(summary) = self.rooms.get(&candidate_room) {
+                if let Some(summary) = self.rooms.get(candidate_room) {
                     candidates.extend(summary.portals.iter().copied());
                 }
             });
@@ -217,7 +237,9 @@ impl PortalIndex {
     }
 
     fn add_directed(&mut self, from: Position, to: Position) {
-        self.exit_by_entry.insert(from, to);
+        self.rooms
+            .get_or_insert_with(from.room_name(), PortalRoomSummary::new)
+            .add_exit(from, to);
     }
 
     fn clear_distance_caches(&mut self) {
@@ -226,11 +248,8 @@ impl PortalIndex {
 
     fn add_position(&mut self, position: Position) {
         self.rooms
-            .entry(position.room_name())
-            .and_modify(|summary| {
-                summary.add_position(position);
-            })
-            .or_insert_with(|| PortalRoomSummary::from_position(position));
+            .get_or_insert_with(position.room_name(), PortalRoomSummary::new)
+            .add_position(position);
     }
 
     fn for_each_room_at_range(&self, center: RoomName, radius: i32, mut f: impl FnMut(RoomName)) {
@@ -268,8 +287,7 @@ impl PortalIndex {
 
     fn approximate_memory_size_bytes(&self) -> usize {
         size_of::<Self>()
-            + self.exit_by_entry.capacity() * size_of::<(Position, Position)>()
-            + self.rooms.capacity() * size_of::<(RoomName, PortalRoomSummary)>()
+            + self.rooms.len() * size_of::<(RoomName, (u16, usize), PortalRoomSummary)>()
             + self
                 .rooms
                 .values()
@@ -359,9 +377,33 @@ impl RoomMapLruCache {
 }
 
 impl PortalRoomSummary {
-    fn from_position(position: Position) -> Self {
+    fn new() -> Self {
         Self {
-            portals: vec![position],
+            portals: Vec::new(),
+            entrances: TileSet::new(),
+            exits: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn exit(&self, entry: Position) -> Option<Position> {
+        if !self.entrances.contains(entry.xy()) {
+            return None;
+        }
+        let index = xy_to_linear_index(entry.xy()) as u16;
+        self.exits
+            .binary_search_by_key(&index, |(tile, _)| *tile)
+            .ok()
+            .map(|found| self.exits[found].1)
+    }
+
+    /// Maps `entry` to `exit`, replacing any exit it already had.
+    fn add_exit(&mut self, entry: Position, exit: Position) {
+        self.entrances.insert(entry.xy());
+        let index = xy_to_linear_index(entry.xy()) as u16;
+        match self.exits.binary_search_by_key(&index, |(tile, _)| *tile) {
+            Ok(found) => self.exits[found].1 = exit,
+            Err(insert_at) => self.exits.insert(insert_at, (index, exit)),
         }
     }
 
@@ -374,7 +416,8 @@ impl PortalRoomSummary {
     }
 
     fn approximate_memory_size_bytes(&self) -> usize {
-        size_of::<Self>() + self.portals.capacity() * size_of::<Position>()
+        self.portals.capacity() * size_of::<Position>()
+            + self.exits.capacity() * size_of::<(u16, Position)>()
     }
 }
 
@@ -427,6 +470,13 @@ pub fn with_configured_portal_index<R>(f: impl FnOnce(&PortalIndex) -> R) -> R {
     CONFIGURED_PORTALS.with(|portals| f(&portals.borrow()))
 }
 
+/// A shared handle to the configured portals, for values that outlive a single call.
+/// Later `set_portals` calls replace the configured index rather than changing it, so
+/// the handle keeps the portals it was taken with.
+pub fn configured_portal_index() -> Rc<PortalIndex> {
+    CONFIGURED_PORTALS.with(|portals| Rc::clone(&portals.borrow()))
+}
+
 #[wasm_bindgen]
 pub fn set_portals(packed_pairs: Vec<u32>) {
     if !packed_pairs.len().is_multiple_of(2) {
@@ -436,19 +486,17 @@ pub fn set_portals(packed_pairs: Vec<u32>) {
     CONFIGURED_PORTALS.with(|portals| {
         let mut portals = portals.borrow_mut();
         let distance_cache_room_limit = portals.distance_cache_room_limit();
-        *portals = PortalIndex::from_packed_pairs_with_cache_limit(
+        *portals = Rc::new(PortalIndex::from_packed_pairs_with_cache_limit(
             &packed_pairs,
             distance_cache_room_limit,
-        );
+        ));
     });
 }
 
 #[wasm_bindgen]
 pub fn set_portal_distance_cache_room_limit(room_limit: usize) {
     CONFIGURED_PORTALS.with(|portals| {
-        portals
-            .borrow_mut()
-            .set_distance_cache_room_limit(room_limit);
+        Rc::make_mut(&mut portals.borrow_mut()).set_distance_cache_room_limit(room_limit);
     });
 }
 
@@ -457,7 +505,9 @@ pub fn clear_portals() {
     CONFIGURED_PORTALS.with(|portals| {
         let mut portals = portals.borrow_mut();
         let distance_cache_room_limit = portals.distance_cache_room_limit();
-        *portals = PortalIndex::with_distance_cache_room_limit(distance_cache_room_limit);
+        *portals = Rc::new(PortalIndex::with_distance_cache_room_limit(
+            distance_cache_room_limit,
+        ));
     });
 }
 
@@ -571,6 +621,20 @@ mod tests {
         assert_eq!(index.exit(b), Some(a));
     }
 
+    #[test]
+    fn exits_are_indexed_per_room() {
+        let mut index = PortalIndex::default();
+        index.add_bidirectional(pos("W1N1", 10, 20), pos("W2N2", 30, 40));
+        index.add_bidirectional(pos("W1N1", 11, 20), pos("W3N3", 5, 5));
+        index.add_bidirectional(pos("W1N1", 10, 20), pos("W4N4", 1, 1));
+
+        assert_eq!(index.exit(pos("W1N1", 10, 20)), Some(pos("W4N4", 1, 1)));
+        assert_eq!(index.exit(pos("W1N1", 11, 20)), Some(pos("W3N3", 5, 5)));
+        assert_eq!(index.exit(pos("W1N1", 12, 20)), None);
+        assert_eq!(index.exit(pos("W5N5", 10, 20)), None);
+        assert_eq!(index.portals_in_room(room("W1N1")).len(), 2);
+    }
+
     #[test]
     fn nearest_endpoint_does_not_stop_at_current_room_if_adjacent_room_is_closer() {
         let mut index = PortalIndex::default();
@@ -624,4 +688,16 @@ mod tests {
 
         assert_eq!(index.cached_endpoint_room_count(), 0);
     }
+
+    #[test]
+    fn configured_portal_handles_keep_their_portals() {
+        let (a, b) = (pos("W1N1", 10, 10), pos("W3N1", 20, 20));
+        set_portals(vec![a.packed_repr(), b.packed_repr()]);
+        let portals = configured_portal_index();
+        assert!(Rc::ptr_eq(&portals, &configured_portal_index()));
+
+        clear_portals();
+        assert_eq!(portals.exit(a), Some(b));
+        assert!(with_configured_portal_index(|portals| portals.is_empty()));
+    }
 }
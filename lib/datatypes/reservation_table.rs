@@ -0,0 +1,113 @@
+use screeps::Position;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Records which agent will occupy each tile at each tick, so that several creeps
+/// can plan paths that don't collide (cooperative pathfinding).
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct ReservationTable {
+    reservations: HashMap<(Position, u32), u32>,
+}
+
+impl ReservationTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves `position` at `tick` for `agent`. Returns false (and leaves the
+    /// reservation alone) if another agent already holds it.
+    pub fn reserve(&mut self, position: Position, tick: u32, agent: u32) -> bool {
+        match self.reservations.get(&(position, tick)) {
+            Some(owner) if *owner != agent => false,
+            _ => {
+                self.reservations.insert((position, tick), agent);
+                true
+            }
+        }
+    }
+
+    pub fn reserved_by(&self, position: Position, tick: u32) -> Option<u32> {
+        self.reservations.get(&(position, tick)).copied()
+    }
+
+    /// Whether another agent holds `position` at `tick`.
+    pub fn is_blocked(&self, position: Position, tick: u32, agent: u32) -> bool {
+        self.reserved_by(position, tick)
+            .is_some_and(|owner| owner != agent)
+    }
+
+    /// Whether `agent` can't move from `from` at `tick` to `to` at `tick + 1`: either
+    /// `to` is taken, or another agent is moving the opposite way (the two creeps
+    /// would have to swap places).
+    pub fn is_move_blocked(&self, from: Position, to: Position, tick: u32, agent: u32) -> bool {
+        if self.is_blocked(to, tick + 1, agent) {
+            return true;
+        }
+        match self.reserved_by(to, tick) {
+            Some(owner) if owner != agent => self.reserved_by(from, tick + 1) == Some(owner),
+            _ => false,
+        }
+    }
+
+    /// Drops every reservation held by `agent`, e.g. before it replans.
+    pub fn release_agent(&mut self, agent: u32) {
+        self.reservations.retain(|_, owner| *owner != agent);
+    }
+
+    /// Drops every reservation before `tick`.
+    pub fn release_before(&mut self, tick: u32) {
+        self.reservations
+            .retain(|(_, reserved_tick), _| *reserved_tick >= tick);
+    }
+
+    pub fn clear(&mut self) {
+        self.reservations.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.reservations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reservations.is_empty()
+    }
+}
+
+#[wasm_bindgen]
+impl ReservationTable {
+    #[wasm_bindgen(constructor)]
+    pub fn js_new() -> Self {
+        Self::new()
+    }
+
+    #[wasm_bindgen(js_name = reserve)]
+    pub fn js_reserve(&mut self, packed_position: u32, tick: u32, agent: u32) -> bool {
+        self.reserve(Position::from_packed(packed_position), tick, agent)
+    }
+
+    #[wasm_bindgen(js_name = reservedBy)]
+    pub fn js_reserved_by(&self, packed_position: u32, tick: u32) -> Option<u32> {
+        self.reserved_by(Position::from_packed(packed_position), tick)
+    }
+
+    #[wasm_bindgen(js_name = releaseAgent)]
+    pub fn js_release_agent(&mut self, agent: u32) {
+        self.release_agent(agent);
+    }
+
+    #[wasm_bindgen(js_name = releaseBefore)]
+    pub fn js_release_before(&mut self, tick: u32) {
+        self.release_before(tick);
+    }
+
+    #[wasm_bindgen(js_name = clear)]
+    pub fn js_clear(&mut self) {
+        self.clear();
+    }
+
+    #[wasm_bindgen(getter, js_name = size)]
+    pub fn js_size(&self) -> usize {
+        self.len()
+    }
+}
@@ -1,16 +1,24 @@
 use crate::datatypes::ClockworkCostMatrix;
 use crate::datatypes::DistanceMap;
 use crate::datatypes::MultiroomDistanceMap;
+use crate::datatypes::MultiroomMonoFlowField;
+use crate::datatypes::RoomSlab;
+use screeps::Direction;
+use screeps::Position;
 use screeps::RoomName;
-use std::collections::HashMap;
+use screeps::RoomXY;
 use std::ops::Fn;
 use std::ops::Index;
 use std::ops::IndexMut;
 
+use super::mono_flow_field::MonoFlowField;
+
 #[derive(Clone)]
 pub struct RoomData {
     pub cost_matrix: Option<ClockworkCostMatrix>,
     pub distance_map: DistanceMap,
+    // Only kept when the cache is recording parents, see `RoomDataCache::with_parents`.
+    pub parents: Option<MonoFlowField>,
     pub room_name: RoomName,
 }
 
@@ -18,44 +26,132 @@ pub struct RoomDataCache<F>
 where
     F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
 {
-    room_data: Vec<RoomData>,
-    room_map: HashMap<RoomName, usize>,
+    room_data: RoomSlab<RoomData>,
     cost_matrix_creator: F,
     rooms_available: usize,
+    landing_tile_costs: bool,
+    record_parents: bool,
 }
 
 impl<F> RoomDataCache<F>
 where
     F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
 {
+    /// Creates an empty cache that loads at most `max_rooms` rooms with a cost matrix.
+    ///
+    /// Room crossings charge the landing tile by default, which changes the costs
+    /// searches report compared with earlier versions; use `with_landing_tile_costs`
+    /// to opt back out.
     pub fn new(max_rooms: usize, cost_matrix_creator: F) -> Self {
         Self {
-            room_data: vec![],
-            room_map: HashMap::new(),
+            room_data: RoomSlab::new(),
             cost_matrix_creator,
             rooms_available: max_rooms,
+            landing_tile_costs: true,
+            record_parents: false,
+        }
+    }
+
+    /// Whether to keep a parent direction for every tile the search reaches, see
+    /// `set_parent`. Off by default.
+    pub fn with_parents(mut self, record_parents: bool) -> Self {
+        self.record_parents = record_parents;
+        self
+    }
+
+    /// Records how a tile was reached: `step` is the tile stepped onto in the room
+    /// with `room_key`, and `direction` points back from it to the tile the step came
+    /// from. A room crossing is recorded on the edge tile stepped onto rather than the
+    /// tile landed on in the next room, so the parents read like a
+    /// `MultiroomMonoFlowField` leading back to the start.
+    #[inline]
+    pub fn set_parent(&mut self, room_key: usize, step: RoomXY, direction: Direction) {
+        if let Some(parents) = &mut self.room_data[room_key].parents {
+            parents.set(step, Some(direction));
+        }
+    }
+
+    /// Moves the recorded parents out of the cache, if it was recording them.
+    pub fn take_parents(&mut self) -> Option<MultiroomMonoFlowField> {
+        if !self.record_parents {
+            return None;
+        }
+        let mut maps = RoomSlab::new();
+        for key in 0..self.room_data.len() {
+            if let Some(parents) = self.room_data[key].parents.take() {
+                maps.insert(self.room_data.room_name(key), parents);
+            }
+        }
+        Some(MultiroomMonoFlowField::from_room_maps(maps))
+    }
+
+    /// Whether room crossings charge the tile they land on in the next room, see
+    /// `crossing_cost`. On by default; turning it off restores the old behavior of
+    /// only charging the exit tile in the room being left, and ignoring whether the
+    /// landing tile is blocked in the next room's matrix.
+    pub fn with_landing_tile_costs(mut self, landing_tile_costs: bool) -> Self {
+        self.landing_tile_costs = landing_tile_costs;
+        self
+    }
+
+    /// The cost of a step onto a room edge that lands on `landing` in another room,
+    /// given `exit_cost`, the exit tile's cost in the room being left. Both tiles are
+    /// the same border crossing, so it costs whichever is higher, and a landing tile
+    /// marked 255 in its own room's matrix blocks it. Returns `None` if the crossing
+    /// is blocked, including when the other room has no cost matrix.
+    #[inline]
+    pub fn crossing_cost(
+        &self,
+        exit_cost: u8,
+        landing_room_key: usize,
+        landing: Position,
+    ) -> Option<u8> {
+        let cost_matrix = self.room_data[landing_room_key].cost_matrix.as_ref()?;
+        if !self.landing_tile_costs {
+            return Some(exit_cost);
+        }
+        match cost_matrix.get(landing.xy()) {
+            255 => None,
+            landing_cost => Some(exit_cost.max(landing_cost)),
         }
     }
 
     pub fn get_room_key(&mut self, room: RoomName) -> Option<usize> {
-        if let Some(room_key) = self.room_map.get(&room) {
-            return Some(*room_key);
+        if let Some(room_key) = self.room_data.key(room) {
+            return Some(room_key);
         }
         if self.rooms_available == 0 {
             return None;
         }
-        self.room_data.push(RoomData {
-            cost_matrix: (self.cost_matrix_creator)(room),
-            distance_map: DistanceMap::new(),
-            room_name: room,
-        });
-        let key = self.room_data.len() - 1;
-        self.room_map.insert(room, key);
+        let key = self.room_data.insert(
+            room,
+            RoomData {
+                cost_matrix: (self.cost_matrix_creator)(room),
+                distance_map: DistanceMap::new(),
+                parents: self.record_parents.then(MonoFlowField::new),
+                room_name: room,
+            },
+        );
         if self.room_data[key].cost_matrix.is_some() {
             self.rooms_available -= 1;
         }
         Some(key)
     }
+
+    /// Gets the key for a room only if it has already been loaded, without
+    /// calling the cost matrix callback or counting against `max_rooms`.
+    pub fn get_loaded_room_key(&self, room: RoomName) -> Option<usize> {
+        self.room_data.key(room)
+    }
+
+    /// Copies the current distance maps out of the cache, leaving it intact.
+    pub fn to_distance_map(&self) -> MultiroomDistanceMap {
+        let mut maps = RoomSlab::new();
+        for room_data in self.room_data.values() {
+            maps.insert(room_data.room_name, room_data.distance_map.clone());
+        }
+        MultiroomDistanceMap { maps }
+    }
 }
 
 impl<F> Index<usize> for RoomDataCache<F>
@@ -83,10 +179,87 @@ where
     F: Fn(RoomName) -> Option<ClockworkCostMatrix>,
 {
     fn from(cached_room_data: RoomDataCache<F>) -> Self {
-        let mut maps = HashMap::new();
-        for room_data in cached_room_data.room_data {
-            maps.insert(room_data.room_name, room_data.distance_map);
+        MultiroomDistanceMap {
+            maps: cached_room_data
+                .room_data
+                .map(|room_data| room_data.distance_map),
         }
-        MultiroomDistanceMap { maps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::distance_map::astar::astar_search;
+    use crate::algorithms::distance_map::goals::GoalTracker;
+    use crate::test_fixtures::{callback, pos, room};
+    use std::collections::HashMap;
+
+    #[test]
+    fn room_crossings_pay_for_the_landing_tile() {
+        let mut w2n1 = ClockworkCostMatrix::new(Some(5));
+        w2n1.set(pos("W2N1", 49, 30).xy(), 255);
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W2N1"), w2n1),
+        ]);
+        let search = |landing_tile_costs: bool| {
+            astar_search(
+                vec![pos("W1N1", 1, 25)],
+                RoomDataCache::new(2, callback(matrices.clone()))
+                    .with_landing_tile_costs(landing_tile_costs),
+                usize::MAX,
+                usize::MAX,
+                |_| 0,
+                1.0,
+                None,
+                GoalTracker::new(None, None),
+            )
+            .distance_map()
+        };
+
+        let distance_map = search(true);
+        assert_eq!(distance_map.get(pos("W2N1", 49, 25)), 5);
+        assert_eq!(distance_map.get(pos("W2N1", 49, 30)), usize::MAX);
+
+        let distance_map = search(false);
+        assert_eq!(distance_map.get(pos("W2N1", 49, 25)), 1);
+        assert_eq!(distance_map.get(pos("W2N1", 49, 30)), 5);
+    }
+
+    #[test]
+    fn legacy_crossings_only_charge_the_exit_tile() {
+        let mut w2n1 = ClockworkCostMatrix::new(Some(5));
+        w2n1.set(pos("W2N1", 49, 30).xy(), 255);
+        let matrices = HashMap::from([
+            (room("W1N1"), ClockworkCostMatrix::new(Some(1))),
+            (room("W2N1"), w2n1),
+        ]);
+
+        let mut cache = RoomDataCache::new(2, callback(matrices.clone()));
+        let landing_room = cache.get_room_key(room("W2N1")).unwrap();
+        assert_eq!(
+            cache.crossing_cost(1, landing_room, pos("W2N1", 49, 25)),
+            Some(5)
+        );
+        assert_eq!(
+            cache.crossing_cost(1, landing_room, pos("W2N1", 49, 30)),
+            None
+        );
+
+        let mut cache = RoomDataCache::new(2, callback(matrices)).with_landing_tile_costs(false);
+        let landing_room = cache.get_room_key(room("W2N1")).unwrap();
+        assert_eq!(
+            cache.crossing_cost(1, landing_room, pos("W2N1", 49, 25)),
+            Some(1)
+        );
+        assert_eq!(
+            cache.crossing_cost(1, landing_room, pos("W2N1", 49, 30)),
+            Some(1)
+        );
+        assert_eq!(
+            cache.crossing_cost(7, landing_room, pos("W2N1", 49, 30)),
+            Some(7)
+        );
     }
 }
@@ -0,0 +1,180 @@
+use screeps::RoomName;
+use std::ops::{Index, IndexMut};
+
+/// Per-room values stored densely in insertion order, with a small sorted lookup
+/// from room name to slot.
+///
+/// Searches rarely touch more than a few dozen rooms, so a binary search over packed
+/// room names beats hashing, and callers that resolve a room once can index its slot
+/// directly for every tile after that.
+#[derive(Debug, Clone)]
+pub struct RoomSlab<T> {
+    rooms: Vec<RoomName>,
+    values: Vec<T>,
+    // (packed room name, slot), sorted by room name.
+    lookup: Vec<(u16, usize)>,
+}
+
+impl<T> Default for RoomSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RoomSlab<T> {
+    pub fn new() -> Self {
+        Self {
+            rooms: Vec::new(),
+            values: Vec::new(),
+            lookup: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The slot holding `room`'s value, if it has one.
+    #[inline]
+    pub fn key(&self, room: RoomName) -> Option<usize> {
+        let packed = room.packed_repr();
+        self.lookup
+            .binary_search_by_key(&packed, |(room, _)| *room)
+            .ok()
+            .map(|index| self.lookup[index].1)
+    }
+
+    pub fn contains(&self, room: RoomName) -> bool {
+        self.key(room).is_some()
+    }
+
+    /// Stores `value` for `room`, replacing any previous value, and returns its slot.
+    pub fn insert(&mut self, room: RoomName, value: T) -> usize {
+        let packed = room.packed_repr();
+        match self.lookup.binary_search_by_key(&packed, |(room, _)| *room) {
+            Ok(index) => {
+                let key = self.lookup[index].1;
+                self.values[key] = value;
+                key
+            }
+            Err(index) => {
+                let key = self.values.len();
+                self.rooms.push(room);
+                self.values.push(value);
+                self.lookup.insert(index, (packed, key));
+                key
+            }
+        }
+    }
+
+    pub fn get(&self, room: RoomName) -> Option<&T> {
+        self.key(room).map(|key| &self.values[key])
+    }
+
+    pub fn get_or_insert_with(&mut self, room: RoomName, create: impl FnOnce() -> T) -> &mut T {
+        let key = match self.key(room) {
+            Some(key) => key,
+            None => self.insert(room, create()),
+        };
+        &mut self.values[key]
+    }
+
+    /// The room stored in a slot.
+    pub fn room_name(&self, key: usize) -> RoomName {
+        self.rooms[key]
+    }
+
+    /// Rooms in the order they were added.
+    pub fn rooms(&self) -> impl Iterator<Item = RoomName> + '_ {
+        self.rooms.iter().copied()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.values.iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (RoomName, &T)> {
+        self.rooms.iter().copied().zip(self.values.iter())
+    }
+
+    /// Keeps only the rooms for which `keep` returns true. Slots are renumbered.
+    pub fn retain(&mut self, mut keep: impl FnMut(RoomName, &T) -> bool) {
+        let rooms = std::mem::take(&mut self.rooms);
+        let values = std::mem::take(&mut self.values);
+        self.lookup.clear();
+        for (room, value) in rooms.into_iter().zip(values) {
+            if keep(room, &value) {
+                self.insert(room, value);
+            }
+        }
+    }
+
+    /// Converts every value, keeping each room in the same slot.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> RoomSlab<U> {
+        RoomSlab {
+            rooms: self.rooms,
+            values: self.values.into_iter().map(f).collect(),
+            lookup: self.lookup,
+        }
+    }
+}
+
+impl<T> Index<usize> for RoomSlab<T> {
+    type Output = T;
+
+    fn index(&self, key: usize) -> &T {
+        &self.values[key]
+    }
+}
+
+impl<T> IndexMut<usize> for RoomSlab<T> {
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        &mut self.values[key]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::room;
+
+    #[test]
+    fn looks_up_rooms_by_name_and_slot() {
+        let mut slab = RoomSlab::new();
+        let w5 = slab.insert(room("W5N5"), 'a');
+        let e1 = slab.insert(room("E1S1"), 'b');
+        let w1 = slab.insert(room("W1N1"), 'c');
+
+        assert_eq!(slab.key(room("E1S1")), Some(e1));
+        assert_eq!(slab.get(room("W5N5")), Some(&'a'));
+        assert_eq!(slab[w1], 'c');
+        assert_eq!(slab.room_name(e1), room("E1S1"));
+        assert_eq!(slab.get(room("W2N2")), None);
+
+        // Replacing keeps the slot.
+        assert_eq!(slab.insert(room("W5N5"), 'd'), w5);
+        assert_eq!(slab.len(), 3);
+        assert_eq!(
+            slab.rooms().collect::<Vec<_>>(),
+            vec![room("W5N5"), room("E1S1"), room("W1N1")]
+        );
+    }
+
+    #[test]
+    fn retain_renumbers_slots() {
+        let mut slab = RoomSlab::new();
+        for (index, name) in ["W1N1", "W2N1", "W3N1"].iter().enumerate() {
+            slab.insert(room(name), index);
+        }
+        slab.retain(|_, value| *value != 1);
+
+        assert_eq!(slab.len(), 2);
+        assert_eq!(slab.get(room("W2N1")), None);
+        let key = slab.key(room("W3N1")).unwrap();
+        assert_eq!(slab[key], 2);
+    }
+}
@@ -0,0 +1,35 @@
+use screeps::constants::extra::ROOM_AREA;
+use screeps::{xy_to_linear_index, RoomXY};
+
+const WORDS: usize = ROOM_AREA.div_ceil(64);
+
+/// A set of tiles in one room, stored as one bit per tile, for per-tile membership
+/// checks in hot loops that shouldn't hash.
+#[derive(Debug, Clone)]
+pub struct TileSet {
+    words: [u64; WORDS],
+}
+
+impl Default for TileSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TileSet {
+    pub fn new() -> Self {
+        Self { words: [0; WORDS] }
+    }
+
+    #[inline]
+    pub fn insert(&mut self, xy: RoomXY) {
+        let index = xy_to_linear_index(xy);
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    #[inline]
+    pub fn contains(&self, xy: RoomXY) -> bool {
+        let index = xy_to_linear_index(xy);
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+}
@@ -1,18 +1,32 @@
 mod algorithms;
 mod datatypes;
 mod helpers;
+#[cfg(test)]
+mod test_fixtures;
 mod utils;
 
 #[cfg(feature = "bench")]
 pub mod bench_support {
     pub use crate::algorithms::distance_map::astar::astar_multiroom_distance_map;
     pub use crate::algorithms::distance_map::astar::astar_portal_multiroom_distance_map;
+    pub use crate::algorithms::distance_map::astar::weighted_astar_multiroom_distance_map;
+    pub use crate::algorithms::distance_map::astar::weighted_astar_portal_multiroom_distance_map;
     pub use crate::algorithms::distance_map::breadth_first_search::bfs_multiroom_distance_map;
     pub use crate::algorithms::distance_map::breadth_first_search::bfs_portal_multiroom_distance_map;
     pub use crate::algorithms::distance_map::dijkstra::dijkstra_multiroom_distance_map;
     pub use crate::algorithms::distance_map::dijkstra::dijkstra_portal_multiroom_distance_map;
+    pub use crate::algorithms::distance_map::flee::astar_flee_multiroom_distance_map;
+    pub use crate::algorithms::distance_map::flee::astar_flee_portal_multiroom_distance_map;
+    pub use crate::algorithms::distance_map::flee::dijkstra_flee_multiroom_distance_map;
+    pub use crate::algorithms::distance_map::goals::Goal;
     pub use crate::algorithms::distance_map::heuristics::base_heuristic_with_range;
     pub use crate::algorithms::distance_map::heuristics::closest_portal_heuristic_cached_with_range;
+    pub use crate::algorithms::distance_map::heuristics::flee_heuristic;
+    pub use crate::algorithms::distance_map::heuristics::landmark_heuristic_with_range;
+    pub use crate::algorithms::distance_map::incremental::IncrementalDijkstra;
+    pub use crate::algorithms::distance_map::landmarks::build_landmark_index;
+    pub use crate::algorithms::distance_map::landmarks::select_landmarks;
+    pub use crate::algorithms::distance_map::seeds::Seed;
     pub use crate::algorithms::flow_field::multiroom_flow_field::multiroom_flow_field;
     pub use crate::algorithms::flow_field::multiroom_mono_flow_field::multiroom_mono_flow_field;
     pub use crate::algorithms::map::DirectionOrder;
@@ -20,11 +34,24 @@ pub mod bench_support {
     pub use crate::algorithms::path::to_multiroom_distance_map_origin::path_to_multiroom_distance_map_origin_with_portals;
     pub use crate::algorithms::path::to_multiroom_flow_field_origin::path_to_multiroom_flow_field_origin;
     pub use crate::algorithms::path::to_multiroom_mono_flow_field_origin::path_to_multiroom_mono_flow_field_origin;
+    pub use crate::algorithms::pathfinding::bidirectional_astar::bidirectional_astar_multiroom_path;
+    pub use crate::algorithms::pathfinding::hierarchical::hierarchical_multiroom_path;
+    pub use crate::algorithms::pathfinding::jump_point_search::jps_multiroom_path;
+    pub use crate::algorithms::pathfinding::jump_point_search::jps_portal_multiroom_path;
+    pub use crate::algorithms::pathfinding::space_time_astar::space_time_astar_multiroom_path;
+    pub use crate::algorithms::pathfinding::space_time_astar::space_time_astar_portal_multiroom_path;
+    pub use crate::algorithms::pathfinding::travel_time::travel_time_multiroom_path;
+    pub use crate::algorithms::pathfinding::travel_time::travel_time_portal_multiroom_path;
+    pub use crate::algorithms::pathfinding::travel_time::CreepBody;
+    pub use crate::algorithms::pathfinding::PathResult;
     pub use crate::datatypes::ClockworkCostMatrix;
+    pub use crate::datatypes::ExitGraph;
+    pub use crate::datatypes::LandmarkIndex;
     pub use crate::datatypes::MultiroomDistanceMap;
     pub use crate::datatypes::MultiroomFlowField;
     pub use crate::datatypes::MultiroomMonoFlowField;
     pub use crate::datatypes::PortalIndex;
+    pub use crate::datatypes::ReservationTable;
 }
 
 use screeps::Position;
@@ -0,0 +1,58 @@
+//! Helpers shared by the unit tests: position shorthands, cost matrix callbacks and
+//! small deterministic terrain generators.
+
+use crate::datatypes::{ClockworkCostMatrix, Path};
+use screeps::{Position, RoomCoordinate, RoomName, RoomXY};
+use std::collections::HashMap;
+
+pub fn room(name: &str) -> RoomName {
+    name.parse().unwrap()
+}
+
+pub fn pos(room_name: &str, x: u8, y: u8) -> Position {
+    Position::new(
+        RoomCoordinate::new(x).unwrap(),
+        RoomCoordinate::new(y).unwrap(),
+        room(room_name),
+    )
+}
+
+pub fn xy(x: u8, y: u8) -> RoomXY {
+    RoomXY::new(
+        RoomCoordinate::new(x).unwrap(),
+        RoomCoordinate::new(y).unwrap(),
+    )
+}
+
+/// A cost matrix callback over the given rooms; any other room is unsearchable.
+pub fn callback(
+    matrices: HashMap<RoomName, ClockworkCostMatrix>,
+) -> impl Fn(RoomName) -> Option<ClockworkCostMatrix> {
+    move |room| matrices.get(&room).cloned()
+}
+
+pub fn positions(path: &Path) -> Vec<Position> {
+    (0..path.len()).map(|i| *path.get(i).unwrap()).collect()
+}
+
+/// A matrix filled in by a small deterministic generator. Each tile, in column
+/// order, draws the next pseudo-random roll; `cost` turns the tile and its roll into
+/// the tile's cost, or `None` to leave it at `default`.
+pub fn random_matrix(
+    seed: u32,
+    default: u8,
+    mut cost: impl FnMut(RoomXY, u32) -> Option<u8>,
+) -> ClockworkCostMatrix {
+    let mut matrix = ClockworkCostMatrix::new(Some(default));
+    let mut state = seed;
+    for x in 0..50 {
+        for y in 0..50 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let tile = xy(x, y);
+            if let Some(cost) = cost(tile, state >> 16) {
+                matrix.set(tile, cost);
+            }
+        }
+    }
+    matrix
+}
@@ -3,31 +3,56 @@ import 'fastestsmallesttextencoderdecoder-encodeinto/EncoderDecoderTogether.min.
 
 import {
   type InitOutput,
+  choose_landmarks,
+  clear_landmarks,
   clear_portals,
   ClockworkCostMatrix,
+  debug_landmark_index,
   debug_portal_index,
   DirectionOrder,
   initSync,
+  rebuild_landmarks,
+  seededDirectionOrder as seeded_direction_order,
+  set_landmark_room_budget,
+  set_landmarks,
   set_portal_distance_cache_room_limit,
   set_portals,
   version
 } from './wasm/screeps_clockwork';
-import { packPortalPairs, type PortalPair } from './utils/packedArrays';
+import { fromPacked, fromPackedRoomNameCached } from './utils/fromPacked';
+import { packPortalPairs, packPositions, type PortalPair } from './utils/packedArrays';
 export { ClockworkCostMatrix, DirectionOrder };
 export type { PortalPair };
 
 export * from './wrappers/astarDistanceMap';
+export * from './wrappers/astarSearch';
 export * from './wrappers/bfsDistanceMap';
+export * from './wrappers/bidirectionalAstarPath';
 export * from './wrappers/dijkstraDistanceMap';
 export * from './wrappers/flowField';
 export * from './wrappers/getRange';
 export * from './wrappers/getTerrainCostMatrix';
+export * from './wrappers/goalMask';
+export * from './wrappers/hierarchicalPath';
+export * from './wrappers/incrementalDijkstra';
+export * from './wrappers/jpsPath';
 export * from './wrappers/monoFlowField';
 export * from './wrappers/multiroomFlowField';
 export * from './wrappers/multiroomMonoFlowField';
 export * from './wrappers/path';
+export * from './wrappers/roomRoute';
+export * from './wrappers/spaceTimePath';
+export * from './wrappers/travelTimePath';
+export * from './wrappers/voronoi';
+export type { ClockworkCompactDistanceMap } from './wrappers/compactDistanceMap';
 export type { ClockworkDistanceMap } from './wrappers/distanceMap';
+export type { ClockworkMultiroomCompactDistanceMap } from './wrappers/multiroomCompactDistanceMap';
 export type { ClockworkMultiroomDistanceMap } from './wrappers/multiroomDistanceMap';
+export type { ClockworkMultiroomLabelMap } from './wrappers/multiroomLabelMap';
+export type { ClockworkMultiroomLazyFlowField } from './wrappers/multiroomLazyFlowField';
+export type { ClockworkMultiroomTurnDistanceMap } from './wrappers/multiroomTurnDistanceMap';
+export type { ClockworkParentTree } from './wrappers/parentTree';
+export type { SearchTarget } from './wrappers/searchResult';
 
 declare namespace WebAssembly {
   class Module {
@@ -50,6 +75,21 @@ export interface ClockworkInitializeOptions {
   portalDistanceCacheRoomLimit?: number;
 }
 
+export interface LandmarkOptions {
+  costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+  maxRooms: number;
+  usePortals?: boolean;
+}
+
+export interface LandmarkIndexDebugInfo {
+  landmarks: RoomPosition[];
+  storedRooms: number;
+  roomBudget: number;
+  usesPortals: boolean;
+  totalSizeBytes: number;
+  totalSize: string;
+}
+
 export interface PortalIndexDebugInfo {
   cachedDistanceMaps: number;
   maxCachedDistanceMaps: number;
@@ -173,6 +213,91 @@ export function debugPortalIndex(): PortalIndexDebugInfo {
   };
 }
 
+/**
+ * Build distance maps from the given landmark positions and use them to tighten
+ * the A* heuristic (ALT). Build them from terrain-only cost matrices, or the lowest
+ * costs your searches will use, so the bounds stay admissible. With `usePortals`,
+ * distances are measured through the configured portals and the landmarks are also
+ * used by portal searches.
+ */
+export function setLandmarks(
+  landmarks: RoomPosition[],
+  { costMatrixCallback, maxRooms, usePortals }: LandmarkOptions
+): void {
+  set_landmarks(
+    packPositions(landmarks),
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    usePortals
+  );
+}
+
+/**
+ * Choose up to `count` landmarks spread out around `seed` and configure them as
+ * with `setLandmarks`. Returns the chosen landmark positions.
+ */
+export function chooseLandmarks(
+  seed: RoomPosition,
+  count: number,
+  { costMatrixCallback, maxRooms, usePortals }: LandmarkOptions
+): RoomPosition[] {
+  const chosen = choose_landmarks(
+    seed.__packedPos,
+    count,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    usePortals
+  );
+  return [...chosen].map(pos => fromPacked(pos));
+}
+
+/**
+ * Rebuild the configured landmarks' distance maps, e.g. after the cost matrices
+ * they were built from have changed.
+ */
+export function rebuildLandmarks({ costMatrixCallback, maxRooms }: Omit<LandmarkOptions, 'usePortals'>): void {
+  rebuild_landmarks((room: number) => costMatrixCallback(fromPackedRoomNameCached(room)), maxRooms);
+}
+
+/**
+ * Remove all configured landmarks while preserving the room budget.
+ */
+export function clearLandmarks(): void {
+  clear_landmarks();
+}
+
+/**
+ * Cap the total number of rooms stored across all landmarks. Lowering the budget
+ * drops the most recently added landmarks until the stored rooms fit.
+ */
+export function setLandmarkRoomBudget(roomBudget: number): void {
+  set_landmark_room_budget(roomBudget);
+}
+
+/**
+ * Return debug counters for the configured landmarks. `totalSize` is an approximate
+ * in-memory size formatted for logs; `totalSizeBytes` preserves the raw byte estimate.
+ */
+export function debugLandmarkIndex(): LandmarkIndexDebugInfo {
+  const info = debug_landmark_index() as Omit<LandmarkIndexDebugInfo, 'landmarks' | 'totalSize'> & {
+    landmarks: number[];
+  };
+  return {
+    ...info,
+    landmarks: info.landmarks.map(pos => fromPacked(pos)),
+    totalSize: formatBytes(info.totalSizeBytes)
+  };
+}
+
+/**
+ * The shuffled direction order for `seed`, to pass as `customDirections` to flow
+ * fields and paths so that different creeps spread out over equally short routes.
+ * The same seed always gives the same order.
+ */
+export function seededDirectionOrder(seed: number): DirectionConstant[] {
+  return seeded_direction_order(seed);
+}
+
 function formatBytes(bytes: number): string {
   const units = ['B', 'KiB', 'MiB', 'GiB'];
   let value = bytes;
@@ -1,10 +1,15 @@
+import { packRoomName } from './fromPacked';
+
 interface PackedDestination {
   pos: RoomPosition;
   range: number;
+  minRange?: number;
 }
 
 export type PortalPair = readonly [RoomPosition, RoomPosition];
 
+const MAX_PACKED_RANGE = 0xffff;
+
 export function packPositions(positions: RoomPosition[]): Uint32Array {
   const packed = new Uint32Array(positions.length);
   for (let i = 0; i < positions.length; i++) {
@@ -13,6 +18,8 @@ export function packPositions(positions: RoomPosition[]): Uint32Array {
   return packed;
 }
 
+export function packDestinations(destinations: PackedDestination[]): Uint32Array;
+export function packDestinations(destinations: PackedDestination[] | undefined): Uint32Array | undefined;
 export function packDestinations(destinations: PackedDestination[] | undefined): Uint32Array | undefined {
   if (!destinations) {
     return undefined;
@@ -21,8 +28,30 @@ export function packDestinations(destinations: PackedDestination[] | undefined):
   const packed = new Uint32Array(destinations.length * 2);
   for (let i = 0; i < destinations.length; i++) {
     const offset = i * 2;
+    const { range, minRange = 0 } = destinations[i];
+    if (!isPackableRange(range) || !isPackableRange(minRange) || minRange > range) {
+      throw new Error(`Invalid destination range: ${minRange}..${range}`);
+    }
     packed[offset] = destinations[i].pos.__packedPos;
-    packed[offset + 1] = destinations[i].range;
+    // The minimum range goes in the high 16 bits, so plain ranges still mean "within range"
+    packed[offset + 1] = ((minRange << 16) | range) >>> 0;
+  }
+  return packed;
+}
+
+/** Ranges are packed into 16 bits each, see `packDestinations`. */
+function isPackableRange(range: number): boolean {
+  return Number.isInteger(range) && range >= 0 && range <= MAX_PACKED_RANGE;
+}
+
+export function packRooms(rooms: string[] | undefined): Uint16Array | undefined {
+  if (!rooms) {
+    return undefined;
+  }
+
+  const packed = new Uint16Array(rooms.length);
+  for (let i = 0; i < rooms.length; i++) {
+    packed[i] = packRoomName(rooms[i]);
   }
   return packed;
 }
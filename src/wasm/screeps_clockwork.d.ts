@@ -1,6 +1,40 @@
 /* tslint:disable */
 /* eslint-disable */
 
+/**
+ * A handle to an A* distance-map search that can be advanced across several ticks.
+ */
+export class AstarSearchHandle {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Stops the search and releases its state without producing a result.
+     */
+    cancel(): void;
+    /**
+     * Returns the search result, releasing the search state. If the search has not
+     * finished, the result contains the partial distance map explored so far.
+     */
+    finalize(): SearchResult;
+    /**
+     * Sets up a resumable A* search. Arguments match `js_astar_multiroom_distance_map`,
+     * except that the ops budget is passed to each call to `step`.
+     */
+    constructor(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null);
+    /**
+     * Advances the search by roughly `ops` tiles. Returns true if the search is finished.
+     */
+    step(ops: number): boolean;
+    /**
+     * Whether the search has finished (or was cancelled).
+     */
+    readonly finished: boolean;
+    /**
+     * The number of tiles opened so far.
+     */
+    readonly ops: number;
+}
+
 /**
  * A wrapper around the `LocalCostMatrix` type from the Screeps API.
  * Instances can be passed between WASM and JS as a pointer, using the
@@ -40,6 +74,46 @@ export enum Color {
     White = 10,
 }
 
+/**
+ * A `DistanceMap` stored as `u16`s, 5 KB per room in wasm32 instead of 10 KB.
+ * Unreachable tiles hold `COMPACT_UNREACHABLE` and read back as `usize::MAX`, so
+ * converting back to a `DistanceMap` is lossless. Distances of 65535 or more don't fit.
+ */
+export class CompactDistanceMap {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Gets the distance value at a given position.
+     */
+    get(x: number, y: number): number;
+    /**
+     * Converts back to a full `DistanceMap`.
+     */
+    toDistanceMap(): DistanceMap;
+    /**
+     * A `Uint16Array` view of the distances, indexed like a cost matrix, without
+     * copying. Unreachable tiles are 65535. The view is only valid until the map is
+     * freed or WASM memory grows (which any call into WASM might do), so read it
+     * right away.
+     */
+    view(): Uint16Array;
+}
+
+/**
+ * The parts of a creep's body that matter for movement.
+ */
+export class CreepBody {
+    free(): void;
+    [Symbol.dispose](): void;
+    constructor(move_parts: number, loaded_carry_parts: number, empty_carry_parts: number, other_parts: number, move_boost?: number | null);
+    readonly empty_carry_parts: number;
+    readonly loaded_carry_parts: number;
+    readonly move_boost: number;
+    readonly move_parts: number;
+    readonly other_parts: number;
+}
+
 /**
  * Translates the `DENSITY_*` constants.
  */
@@ -109,6 +183,33 @@ export enum ExitDirection {
     Left = 7,
 }
 
+/**
+ * A cache of room abstractions for hierarchical pathfinding, kept between searches.
+ * Entries are checked against a fingerprint of the room's current cost matrix whenever
+ * they're used and rebuilt if it has changed, so callers only need to invalidate rooms explicitly
+ * to free memory.
+ */
+export class ExitGraph {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Drops all cached room abstractions
+     */
+    clear(): void;
+    /**
+     * Drops the cached abstraction for a room
+     */
+    invalidate_room(room_name: number): void;
+    /**
+     * Creates a new, empty exit graph cache (JavaScript constructor)
+     */
+    constructor();
+    /**
+     * The number of rooms currently cached
+     */
+    readonly room_count: number;
+}
+
 /**
  * Translates `FIND_*` constants for interal API calls
  *
@@ -191,6 +292,62 @@ export class FlowField {
     setDirections(x: number, y: number, directions: any[]): void;
 }
 
+/**
+ * A set of goal tiles across multiple rooms, stored as one bit per tile. Searches
+ * given a mask stop at the first masked tile they settle, for goals that don't fit
+ * a position and range ("the nearest free tile near the controller").
+ */
+export class GoalMask {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Marks a packed position as a goal.
+     */
+    add(packed_position: number): void;
+    /**
+     * Marks every tile with a non-zero value in the cost matrix as a goal.
+     */
+    addRoom(room_name: number, matrix: ClockworkCostMatrix): void;
+    has(packed_position: number): boolean;
+    constructor();
+}
+
+/**
+ * A handle to a distance map that can be repaired in place when cost matrices change.
+ */
+export class IncrementalDijkstraHandle {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Gets the distance at a packed position.
+     */
+    get(packed_pos: number): number;
+    /**
+     * Builds the initial distance map. Rooms are loaded from `get_cost_matrix` as the
+     * search reaches them; later changes are applied with `update_costs` or
+     * `update_cost_matrix`.
+     */
+    constructor(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_path_cost: number);
+    /**
+     * Replaces a room's cost matrix, repairing the tiles that changed. Returns the
+     * number of tiles updated.
+     */
+    update_cost_matrix(room_name: number, cost_matrix: ClockworkCostMatrix): number;
+    /**
+     * Applies changed tile costs, given as a flat list of packed position and cost
+     * pairs. Returns the number of tiles updated.
+     */
+    update_costs(changes: Uint32Array): number;
+    /**
+     * Copies out the current distance map.
+     */
+    readonly distance_map: MultiroomDistanceMap;
+    /**
+     * The total number of tiles updated, including the initial search.
+     */
+    readonly ops: number;
+}
+
 /**
  * A flow field is a 50x50 grid (representing a room), representing viable directions
  * to travel to reach a particular target (or targets). A mono flow field only stores
@@ -211,6 +368,37 @@ export class MonoFlowField {
     set(x: number, y: number, value?: Direction | null): void;
 }
 
+/**
+ * Maps distance values across multiple rooms with `u16` storage, see
+ * `CompactDistanceMap`. Reads the same as a `MultiroomDistanceMap`.
+ */
+export class MultiroomCompactDistanceMap {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Gets the distance value at a given position
+     */
+    get(packed_pos: number): number;
+    /**
+     * Gets the CompactDistanceMap for a given room
+     */
+    get_room(room_name: number): CompactDistanceMap | undefined;
+    /**
+     * Gets the list of rooms in the map
+     */
+    get_rooms(): Uint16Array;
+    /**
+     * Converts back to a full `MultiroomDistanceMap`.
+     */
+    toDistanceMap(): MultiroomDistanceMap;
+    /**
+     * A zero-copy `Uint16Array` view of a room's distances, with the same caveats
+     * as `CompactDistanceMap.view`.
+     */
+    view_room(room_name: number): Uint16Array | undefined;
+}
+
 /**
  * Maps distance values across multiple rooms, storing a DistanceMap for each room
  */
@@ -237,6 +425,11 @@ export class MultiroomDistanceMap {
      * Sets the distance value at a given position
      */
     set(packed_pos: number, value: number): void;
+    /**
+     * Converts to `u16` storage, see `MultiroomCompactDistanceMap`. Throws if a
+     * distance is 65535 or more.
+     */
+    toCompact(): MultiroomCompactDistanceMap;
 }
 
 /**
@@ -279,6 +472,83 @@ export class MultiroomFlowField {
     setDirections(packed_pos: number, directions: any[]): void;
 }
 
+/**
+ * Maps a `u32` label (such as the origin that owns a tile) onto tiles across
+ * multiple rooms. Unlabeled tiles read as `NO_LABEL`.
+ */
+export class MultiroomLabelMap {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Gets the label at a given position, or `0xFFFFFFFF` if the tile is unlabeled
+     */
+    get(packed_pos: number): number;
+    /**
+     * Gets the labels for a given room as a flat array, indexed like a cost matrix
+     */
+    get_room(room_name: number): Uint32Array | undefined;
+    /**
+     * Gets the list of rooms in the map
+     */
+    get_rooms(): Uint16Array;
+}
+
+/**
+ * A flow field that works out each tile's directions from a distance map when it's
+ * asked for them, instead of building every room up front. A creep following a
+ * route only pays for the tiles it actually stands on.
+ *
+ * Reads match `multiroomFlowField` (or `multiroomPortalFlowField` with portals),
+ * and values written through `set`, `setDirections` or `addDirection` take
+ * precedence over computed ones.
+ */
+export class MultiroomLazyFlowField {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Adds a direction to the list of valid directions at a given position (JavaScript)
+     */
+    addDirection(packed_pos: number, direction: Direction): void;
+    /**
+     * Gets the flow field value at a given position
+     */
+    get(packed_pos: number): number;
+    /**
+     * Gets the list of valid directions at a given position (JavaScript)
+     */
+    getDirections(packed_pos: number): any[];
+    /**
+     * Gets the first valid direction at a given position (JavaScript)
+     */
+    getFirstDirection(packed_pos: number): Direction | undefined;
+    /**
+     * Gets the FlowField for a given room, computing every tile in it
+     */
+    getRoom(room_name: number): FlowField | undefined;
+    /**
+     * Gets the list of rooms in the flow field
+     */
+    getRooms(): Uint16Array;
+    /**
+     * Creates a lazy flow field over the given distance map (JavaScript constructor).
+     * The field takes the distance map over instead of copying it, so the JavaScript
+     * object passed in can't be used afterwards. With `portals`, the currently
+     * configured portals are followed, and later `set_portals` calls don't change them.
+     * `custom_directions`, if given, breaks ties in that order instead of
+     * `direction_order`.
+     */
+    constructor(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, portals?: boolean | null, memoize?: boolean | null, custom_directions?: any[] | null);
+    /**
+     * Sets the flow field value at a given position
+     */
+    set(packed_pos: number, value: number): void;
+    /**
+     * Sets the list of valid directions at a given position (JavaScript)
+     */
+    setDirections(packed_pos: number, directions: any[]): void;
+}
+
 /**
  * Maps monodirectional flow field values across multiple rooms, storing a MonoFlowField for each room
  */
@@ -307,6 +577,31 @@ export class MultiroomMonoFlowField {
     set(packed_pos: number, direction?: Direction | null): void;
 }
 
+/**
+ * Distances from a search that charges for changing direction, kept separately for
+ * each direction a tile can be entered from. Where a path goes next depends on the
+ * direction it arrived in, so unlike a plain distance map, the best path from a tile
+ * can't be read from its neighbors' distances alone.
+ *
+ * Directions are the search's, from the start outward: a creep heading back to the
+ * start leaves a tile in the opposite direction. Start tiles are kept under no
+ * direction.
+ */
+export class MultiroomTurnDistanceMap {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Gets the distance to a position entered in `direction`, or to a start
+     * position if `direction` is undefined.
+     */
+    get(packed_pos: number, direction?: Direction | null): number;
+    /**
+     * The extra cost charged each time a path changes direction.
+     */
+    readonly turn_penalty: number;
+}
+
 /**
  * Translates the `EFFECT_*` constants, which are natural effect types
  */
@@ -336,6 +631,22 @@ export class Path {
     to_array_reversed(): Uint32Array;
 }
 
+/**
+ * Searches that plan a single route return the path itself rather than a distance
+ * map. The path runs from the start position to the tile where the search stopped,
+ * including both tiles of any room edge or portal crossing. If no path was found,
+ * `path` is empty and `cost` is `usize::MAX`.
+ */
+export class PathResult {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    readonly cost: number;
+    readonly found: boolean;
+    readonly ops: number;
+    readonly path: Path;
+}
+
 /**
  * Translates the `PWR_*` constants, which are types of powers used by power
  * creeps
@@ -361,6 +672,22 @@ export enum PowerType {
     OperateFactory = 19,
 }
 
+/**
+ * Records which agent will occupy each tile at each tick, so that several creeps
+ * can plan paths that don't collide (cooperative pathfinding).
+ */
+export class ReservationTable {
+    free(): void;
+    [Symbol.dispose](): void;
+    clear(): void;
+    constructor();
+    releaseAgent(agent: number): void;
+    releaseBefore(tick: number): void;
+    reserve(packed_position: number, tick: number, agent: number): boolean;
+    reservedBy(packed_position: number, tick: number): number | undefined;
+    readonly size: number;
+}
+
 export class SearchGoal {
     private constructor();
     free(): void;
@@ -373,18 +700,44 @@ export class SearchGoal {
  * A distance map search returns both the distance map (filled out
  * with all tiles explored) and the targets found. These aren't necessarily
  * the same positions specified as targets - if the target range is 5, then
- * this is the first position in range 5 of the target. If multiple targets
- * are specified, and you care about matching the found target with one of
- * the original targets, you can iterate through your list and figure out the
- * ones that are in range of the found target(s).
+ * this is the first position in range 5 of the target. `targets` records,
+ * for each found target, its path cost and the indices of the destinations it
+ * satisfied.
  */
 export class SearchResult {
     private constructor();
     free(): void;
     [Symbol.dispose](): void;
+    /**
+     * The distance map with `u16` storage. Throws if a distance is 65535 or more,
+     * which a `max_path_cost` below that rules out.
+     */
+    readonly compact_distance_map: MultiroomCompactDistanceMap;
     readonly distance_map: MultiroomDistanceMap;
     readonly found_targets: Uint32Array;
     readonly ops: number;
+    /**
+     * The parent tree as a `MultiroomMonoFlowField`, or undefined if the search
+     * wasn't asked to record it.
+     */
+    readonly parents: MultiroomMonoFlowField | undefined;
+    /**
+     * The found targets cost at most this many times their optimal cost, measured
+     * against the cheapest path the search left unexplored. 1 means they're known to
+     * be optimal, or that nothing was found.
+     */
+    readonly suboptimality_bound: number;
+    /**
+     * Each tile where goals were reached, as `{ position, cost, destinations, allOf,
+     * origin }` objects. `destinations` holds indices into `all_of_destinations` if
+     * `allOf` is set, or `any_of_destinations` otherwise. `origin` is the label of
+     * the start the path came from, if starts were labeled.
+     */
+    readonly targets: Array<any>;
+    /**
+     * The per-direction distances, or undefined if the search didn't charge for turns.
+     */
+    readonly turn_distance_map: MultiroomTurnDistanceMap | undefined;
 }
 
 /**
@@ -396,8 +749,35 @@ export enum Terrain {
     Swamp = 2,
 }
 
+/**
+ * A distance map from several origins, plus the origin that owns each tile.
+ */
+export class VoronoiResult {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    boundaryTiles(): Uint32Array;
+    /**
+     * The packed positions owned by the origin with this label.
+     */
+    territory(label: number): Uint32Array;
+    readonly distance_map: MultiroomDistanceMap;
+    readonly labels: MultiroomLabelMap;
+    readonly ops: number;
+}
+
+/**
+ * Chooses up to `count` landmarks around `seed` and makes them the configured
+ * landmarks. Returns the chosen landmark positions.
+ */
+export function choose_landmarks(seed_packed: number, count: number, get_cost_matrix: Function, max_rooms: number, use_portals?: boolean | null): Uint32Array;
+
+export function clear_landmarks(): void;
+
 export function clear_portals(): void;
 
+export function debug_landmark_index(): any;
+
 export function debug_portal_index(): any;
 
 /**
@@ -407,9 +787,54 @@ export function get_range(packed_pos_1: number, packed_pos_2: number): number;
 
 export function get_terrain_cost_matrix(room_name: number, plain_cost?: number | null, swamp_cost?: number | null, wall_cost?: number | null): ClockworkCostMatrix;
 
-export function js_astar_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null): SearchResult;
+/**
+ * WASM wrapper for the A* flee search. `flee_from` is a flat list of packed
+ * position and range pairs. `allowed_rooms`, if given, keeps the search to those
+ * rooms, and `landing_tile_costs` (default true) charges the tile a room crossing
+ * lands on, like `js_astar_multiroom_distance_map`. The other flee entry points take
+ * the same trailing options.
+ */
+export function js_astar_flee_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, flee_from: Uint32Array, allowed_rooms?: Uint16Array | null, landing_tile_costs?: boolean | null): SearchResult;
+
+export function js_astar_flee_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, flee_from: Uint32Array, allowed_rooms?: Uint16Array | null, landing_tile_costs?: boolean | null): SearchResult;
+
+/**
+ * `heuristic_weight` defaults to 1 (plain A*). Higher weights behave like PathFinder's
+ * `heuristicWeight`; a weight of `1 + ε` keeps paths within (1 + ε) of optimal.
+ * Weights below 1, NaN and infinity throw.
+ * `allowed_rooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `js_find_room_route`.
+ * If landmarks are configured (see `set_landmarks`), their ALT bound tightens the
+ * range heuristic. `start_costs` and `start_labels`, if given, are per-start head
+ * starts and labels (see `Seed`). `landing_tile_costs` (default true) charges the tile
+ * a room crossing lands on in the next room, see `RoomDataCache::crossing_cost`.
+ * `record_parents` keeps the search's parent tree, see `SearchResult.parents`.
+ * `turn_penalty`, if given, is charged each time a path changes direction, and the
+ * result keeps a `turn_distance_map` instead of a parent tree, so asking for both
+ * throws. The portal variant takes the same trailing options, minus `turn_penalty`.
+ */
+export function js_astar_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null, heuristic_weight?: number | null, allowed_rooms?: Uint16Array | null, start_costs?: Uint32Array | null, start_labels?: Uint32Array | null, landing_tile_costs?: boolean | null, record_parents?: boolean | null, turn_penalty?: number | null): SearchResult;
+
+/**
+ * Searches to the nearest tile in `goal_mask`. If given, `bound_destinations` are
+ * goals (packed like `any_of_destinations`) that every masked tile satisfies, such as
+ * the range the mask was built around; their range heuristic guides the search.
+ */
+export function js_astar_multiroom_distance_map_to_goal_mask(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, goal_mask: GoalMask, bound_destinations?: Uint32Array | null, allowed_rooms?: Uint16Array | null): SearchResult;
+
+/**
+ * Portal-aware variant of `js_astar_multiroom_distance_map`, using the configured
+ * portals (see `set_portals`). `landing_tile_costs` and `record_parents` work the
+ * same way; steps onto a portal record their parent on the portal tile stepped onto,
+ * see `path_to_parent_tree_origin_with_portals`.
+ */
+export function js_astar_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null, heuristic_weight?: number | null, allowed_rooms?: Uint16Array | null, start_costs?: Uint32Array | null, start_labels?: Uint32Array | null, landing_tile_costs?: boolean | null, record_parents?: boolean | null): SearchResult;
 
-export function js_astar_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null): SearchResult;
+/**
+ * Portal-aware variant of `js_astar_multiroom_distance_map_to_goal_mask`, using the
+ * configured portals (see `set_portals`).
+ */
+export function js_astar_portal_multiroom_distance_map_to_goal_mask(start_packed: Uint32Array, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, goal_mask: GoalMask, bound_destinations?: Uint32Array | null, allowed_rooms?: Uint16Array | null): SearchResult;
 
 /**
  * WASM wrapper for the BFS multiroom distance map function.
@@ -423,43 +848,150 @@ export function js_astar_portal_multiroom_distance_map(start_packed: Uint32Array
  * * `max_path_cost` - Maximum distance in tiles to explore
  * * `any_of_destinations` - Array of packed positions to trigger early exit when any are reached
  * * `all_of_destinations` - Array of packed positions to trigger early exit when all are reached
+ * * `allowed_rooms` - Optional rooms to keep the search to, such as the rooms of a route from `js_find_room_route`
+ * * `start_costs` - Optional head start (initial cost) for each start position
+ * * `start_labels` - Optional label for each start position, reported with found targets
+ * * `record_parents` - Whether to keep the search's parent tree, see `SearchResult.parents`
  *
  * # Returns
  * A `MultiroomDistanceMap` containing the distances from the start positions
  */
-export function js_bfs_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null): SearchResult;
+export function js_bfs_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null, allowed_rooms?: Uint16Array | null, start_costs?: Uint32Array | null, start_labels?: Uint32Array | null, record_parents?: boolean | null): SearchResult;
 
-export function js_bfs_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null): SearchResult;
+export function js_bfs_multiroom_distance_map_to_goal_mask(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, goal_mask: GoalMask, allowed_rooms?: Uint16Array | null): SearchResult;
 
-export function js_dijkstra_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null): SearchResult;
+/**
+ * Portal-aware variant of `js_bfs_multiroom_distance_map`, using the configured
+ * portals (see `set_portals`).
+ */
+export function js_bfs_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null, allowed_rooms?: Uint16Array | null, start_costs?: Uint32Array | null, start_labels?: Uint32Array | null, record_parents?: boolean | null): SearchResult;
 
-export function js_dijkstra_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null): SearchResult;
+export function js_bidirectional_astar_multiroom_path(start_packed: number, goal_packed: number, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number): PathResult;
 
-export function js_path_to_multiroom_distance_map_origin(start: number, distance_map: MultiroomDistanceMap, direction_order: DirectionOrder): Path;
+export function js_dijkstra_flee_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, flee_from: Uint32Array, allowed_rooms?: Uint16Array | null, landing_tile_costs?: boolean | null): SearchResult;
 
-export function js_path_to_multiroom_distance_map_origin_with_portals(start: number, distance_map: MultiroomDistanceMap, direction_order: DirectionOrder): Path;
+export function js_dijkstra_flee_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, flee_from: Uint32Array, allowed_rooms?: Uint16Array | null, landing_tile_costs?: boolean | null): SearchResult;
+
+/**
+ * `allowed_rooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `js_find_room_route`.
+ * `landing_tile_costs` (default true) charges the tile a room crossing lands on in the
+ * next room, see `RoomDataCache::crossing_cost`. `record_parents` keeps the search's
+ * parent tree, see `SearchResult.parents`. `turn_penalty`, if given, is charged each
+ * time a path changes direction, and the result keeps a `turn_distance_map` instead
+ * of a parent tree, so asking for both throws. The portal variant takes the same
+ * trailing options, minus `turn_penalty`.
+ */
+export function js_dijkstra_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null, allowed_rooms?: Uint16Array | null, landing_tile_costs?: boolean | null, record_parents?: boolean | null, turn_penalty?: number | null): SearchResult;
+
+export function js_dijkstra_multiroom_distance_map_to_goal_mask(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, goal_mask: GoalMask, allowed_rooms?: Uint16Array | null): SearchResult;
+
+/**
+ * `landing_tile_costs` and `record_parents` work as in
+ * `js_dijkstra_multiroom_distance_map`.
+ */
+export function js_dijkstra_portal_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, any_of_destinations?: Uint32Array | null, all_of_destinations?: Uint32Array | null, allowed_rooms?: Uint16Array | null, landing_tile_costs?: boolean | null, record_parents?: boolean | null): SearchResult;
+
+export function js_find_portal_room_route(from: number, to: number, room_cost: Function | null | undefined, max_rooms: number): Uint16Array | undefined;
+
+export function js_find_room_route(from: number, to: number, room_cost: Function | null | undefined, max_rooms: number): Uint16Array | undefined;
+
+export function js_hierarchical_multiroom_path(exit_graph: ExitGraph, start_packed: number, goal_packed: number, range: number, get_cost_matrix: Function, max_rooms: number, max_ops: number): PathResult;
+
+export function js_hierarchical_portal_multiroom_path(exit_graph: ExitGraph, start_packed: number, goal_packed: number, range: number, get_cost_matrix: Function, max_rooms: number, max_ops: number): PathResult;
+
+export function js_jps_multiroom_path(start_packed: number, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, destinations: Uint32Array): PathResult;
+
+export function js_jps_portal_multiroom_path(start_packed: number, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, destinations: Uint32Array): PathResult;
+
+export function js_path_to_multiroom_distance_map_origin(start: number, distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, custom_directions?: any[] | null): Path;
+
+export function js_path_to_multiroom_distance_map_origin_with_portals(start: number, distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, custom_directions?: any[] | null): Path;
 
 export function js_path_to_multiroom_flow_field_origin(start: number, flow_field: MultiroomFlowField): Path;
 
 export function js_path_to_multiroom_flow_field_origin_with_portals(start: number, flow_field: MultiroomFlowField): Path;
 
+export function js_path_to_multiroom_flow_field_origin_with_secondary_cost(start: number, flow_field: MultiroomFlowField, get_secondary_cost: Function): Path;
+
 export function js_path_to_multiroom_mono_flow_field_origin(start: number, flow_field: MultiroomMonoFlowField): Path;
 
 export function js_path_to_multiroom_mono_flow_field_origin_with_portals(start: number, flow_field: MultiroomMonoFlowField): Path;
 
+export function js_path_to_parent_tree_origin(target: number, parents: MultiroomMonoFlowField): Path;
+
+export function js_path_to_parent_tree_origin_with_portals(target: number, parents: MultiroomMonoFlowField): Path;
+
+export function js_path_to_turn_distance_map_origin(target: number, turn_distance_map: MultiroomTurnDistanceMap): Path;
+
+export function js_space_time_astar_multiroom_path(start_packed: number, start_tick: number, agent: number, reservations: ReservationTable, window: number, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, destinations: Uint32Array): PathResult;
+
+export function js_space_time_astar_portal_multiroom_path(start_packed: number, start_tick: number, agent: number, reservations: ReservationTable, window: number, get_cost_matrix: Function, max_rooms: number, max_ops: number, max_path_cost: number, destinations: Uint32Array): PathResult;
+
 /**
- * Creates a flow field for the given distance map.
+ * `get_fatigue_matrix` returns matrices of fatigue per part: 1 for roads, 2 for
+ * plains, 10 for swamps, 255 for impassable tiles.
  */
-export function multiroomFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder): MultiroomFlowField;
+export function js_travel_time_multiroom_path(start_packed: number, body: CreepBody, get_fatigue_matrix: Function, max_rooms: number, max_ops: number, max_ticks: number, destinations: Uint32Array): PathResult;
+
+export function js_travel_time_portal_multiroom_path(start_packed: number, body: CreepBody, get_fatigue_matrix: Function, max_rooms: number, max_ops: number, max_ticks: number, destinations: Uint32Array): PathResult;
 
 /**
- * Creates a monodirectional flow field for the given distance map.
+ * WASM wrapper for `voronoi_multiroom_distance_map`. `start_costs` and
+ * `start_labels`, if given, are per-start head starts and labels.
  */
-export function multiroomMonoFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder): MultiroomMonoFlowField;
+export function js_voronoi_multiroom_distance_map(start_packed: Uint32Array, get_cost_matrix: Function, max_ops: number, max_rooms: number, max_path_cost: number, start_costs?: Uint32Array | null, start_labels?: Uint32Array | null): VoronoiResult;
 
-export function multiroomPortalFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder): MultiroomFlowField;
+/**
+ * Creates a flow field for the given distance map. `custom_directions`, if given,
+ * breaks ties in that order instead of `direction_order` (see `seededDirectionOrder`).
+ */
+export function multiroomFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, custom_directions?: any[] | null): MultiroomFlowField;
 
-export function multiroomPortalMonoFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder): MultiroomMonoFlowField;
+export function multiroomFlowFieldWithSecondaryCost(distance_map: MultiroomDistanceMap, get_secondary_cost: Function, direction_order: DirectionOrder, custom_directions?: any[] | null): MultiroomFlowField;
+
+/**
+ * Creates a monodirectional flow field for the given distance map. `custom_directions`, if given,
+ * breaks ties in that order instead of `direction_order` (see `seededDirectionOrder`).
+ */
+export function multiroomMonoFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, custom_directions?: any[] | null): MultiroomMonoFlowField;
+
+export function multiroomMonoFlowFieldWithSecondaryCost(distance_map: MultiroomDistanceMap, get_secondary_cost: Function, direction_order: DirectionOrder, custom_directions?: any[] | null): MultiroomMonoFlowField;
+
+/**
+ * Portal-aware variant of `multiroomFlowField`, using the configured portals (see
+ * `set_portals`).
+ */
+export function multiroomPortalFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, custom_directions?: any[] | null): MultiroomFlowField;
+
+/**
+ * Portal-aware variant of `multiroomMonoFlowField`, using the configured portals (see
+ * `set_portals`).
+ */
+export function multiroomPortalMonoFlowField(distance_map: MultiroomDistanceMap, direction_order: DirectionOrder, custom_directions?: any[] | null): MultiroomMonoFlowField;
+
+/**
+ * Rebuilds the configured landmarks' distance maps, e.g. after the cost matrices
+ * they were built from have changed.
+ */
+export function rebuild_landmarks(get_cost_matrix: Function, max_rooms: number): void;
+
+/**
+ * The shuffled direction order for `seed`, to pass as the custom direction order of
+ * flow fields and paths. The same seed always gives the same order.
+ */
+export function seededDirectionOrder(seed: number): any[];
+
+export function set_landmark_room_budget(room_budget: number): void;
+
+/**
+ * Builds landmarks at the given positions and makes them the configured landmarks
+ * used by the A* entry points. Pass terrain-only matrices (or the lowest costs
+ * searches will use) so the bounds stay admissible. With `use_portals`, distances
+ * are measured through the configured portals, and the landmarks can also be used
+ * by portal searches.
+ */
+export function set_landmarks(packed_landmarks: Uint32Array, get_cost_matrix: Function, max_rooms: number, use_portals?: boolean | null): void;
 
 export function set_portal_distance_cache_room_limit(room_limit: number): void;
 
@@ -471,13 +1003,54 @@ export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembl
 
 export interface InitOutput {
     readonly memory: WebAssembly.Memory;
-    readonly js_astar_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
-    readonly js_astar_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
-    readonly __wbg_clockworkcostmatrix_free: (a: number, b: number) => void;
+    readonly js_astar_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number, l: number, m: number, n: number, o: number, p: number, q: number, r: number, s: number, t: number, u: number) => number;
+    readonly js_astar_multiroom_distance_map_to_goal_mask: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_astar_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number, l: number, m: number, n: number, o: number, p: number, q: number, r: number, s: number, t: number) => number;
+    readonly js_astar_portal_multiroom_distance_map_to_goal_mask: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly __wbg_incrementaldijkstrahandle_free: (a: number, b: number) => void;
+    readonly choose_landmarks: (a: number, b: number, c: number, d: number, e: number, f: number) => void;
+    readonly incrementaldijkstrahandle_distance_map: (a: number) => number;
+    readonly incrementaldijkstrahandle_get: (a: number, b: number) => number;
+    readonly incrementaldijkstrahandle_new: (a: number, b: number, c: number, d: number, e: number) => number;
+    readonly incrementaldijkstrahandle_ops: (a: number) => number;
+    readonly incrementaldijkstrahandle_update_cost_matrix: (a: number, b: number, c: number) => number;
+    readonly incrementaldijkstrahandle_update_costs: (a: number, b: number, c: number) => number;
+    readonly multiroomFlowFieldWithSecondaryCost: (a: number, b: number, c: number, d: number, e: number) => number;
+    readonly multiroomMonoFlowFieldWithSecondaryCost: (a: number, b: number, c: number, d: number, e: number) => number;
+    readonly rebuild_landmarks: (a: number, b: number) => void;
+    readonly set_landmarks: (a: number, b: number, c: number, d: number, e: number) => void;
+    readonly __wbg_goalmask_free: (a: number, b: number) => void;
+    readonly __wbg_multiroommonoflowfield_free: (a: number, b: number) => void;
+    readonly clear_portals: () => void;
+    readonly debug_portal_index: () => number;
+    readonly goalmask_add: (a: number, b: number) => void;
+    readonly goalmask_addRoom: (a: number, b: number, c: number) => void;
+    readonly goalmask_has: (a: number, b: number) => number;
+    readonly goalmask_js_new: () => number;
+    readonly multiroommonoflowfield_get: (a: number, b: number) => number;
+    readonly multiroommonoflowfield_getRoom: (a: number, b: number) => number;
+    readonly multiroommonoflowfield_getRooms: (a: number, b: number) => void;
+    readonly multiroommonoflowfield_js_new: () => number;
+    readonly multiroommonoflowfield_set: (a: number, b: number, c: number) => void;
+    readonly set_portal_distance_cache_room_limit: (a: number) => void;
+    readonly set_portals: (a: number, b: number) => void;
+    readonly js_bfs_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number, l: number, m: number, n: number, o: number, p: number, q: number) => number;
+    readonly js_bfs_multiroom_distance_map_to_goal_mask: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number) => number;
+    readonly js_bfs_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number, l: number, m: number, n: number, o: number, p: number, q: number) => number;
+    readonly js_dijkstra_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number, l: number, m: number, n: number, o: number) => number;
+    readonly js_dijkstra_multiroom_distance_map_to_goal_mask: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number) => number;
+    readonly js_dijkstra_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number, l: number, m: number, n: number) => number;
+    readonly __wbg_distancemap_free: (a: number, b: number) => void;
+    readonly __wbg_multiroomcompactdistancemap_free: (a: number, b: number) => void;
     readonly __wbg_multiroomflowfield_free: (a: number, b: number) => void;
-    readonly clockworkcostmatrix_get: (a: number, b: number, c: number) => number;
-    readonly clockworkcostmatrix_new: (a: number) => number;
-    readonly clockworkcostmatrix_set: (a: number, b: number, c: number, d: number) => void;
+    readonly distancemap_get: (a: number, b: number, c: number) => number;
+    readonly distancemap_set: (a: number, b: number, c: number, d: number) => void;
+    readonly distancemap_toArray: (a: number, b: number) => void;
+    readonly multiroomcompactdistancemap_get: (a: number, b: number) => number;
+    readonly multiroomcompactdistancemap_get_room: (a: number, b: number) => number;
+    readonly multiroomcompactdistancemap_get_rooms: (a: number, b: number) => void;
+    readonly multiroomcompactdistancemap_toDistanceMap: (a: number) => number;
+    readonly multiroomcompactdistancemap_view_room: (a: number, b: number) => number;
     readonly multiroomflowfield_addDirection: (a: number, b: number, c: number) => void;
     readonly multiroomflowfield_get: (a: number, b: number) => number;
     readonly multiroomflowfield_getDirections: (a: number, b: number, c: number) => void;
@@ -486,63 +1059,138 @@ export interface InitOutput {
     readonly multiroomflowfield_js_new: () => number;
     readonly multiroomflowfield_set: (a: number, b: number, c: number) => void;
     readonly multiroomflowfield_setDirections: (a: number, b: number, c: number, d: number) => void;
-    readonly multiroomFlowField: (a: number, b: number) => number;
-    readonly multiroomMonoFlowField: (a: number, b: number) => number;
-    readonly multiroomPortalFlowField: (a: number, b: number) => number;
-    readonly multiroomPortalMonoFlowField: (a: number, b: number) => number;
-    readonly __wbg_flowfield_free: (a: number, b: number) => void;
-    readonly __wbg_monoflowfield_free: (a: number, b: number) => void;
-    readonly flowfield_addDirection: (a: number, b: number, c: number, d: number) => void;
-    readonly flowfield_get: (a: number, b: number, c: number) => number;
-    readonly flowfield_getDirections: (a: number, b: number, c: number, d: number) => void;
-    readonly flowfield_set: (a: number, b: number, c: number, d: number) => void;
-    readonly flowfield_setDirections: (a: number, b: number, c: number, d: number, e: number) => void;
-    readonly monoflowfield_get: (a: number, b: number, c: number) => number;
-    readonly monoflowfield_set: (a: number, b: number, c: number, d: number) => void;
-    readonly js_dijkstra_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
-    readonly js_dijkstra_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
-    readonly js_path_to_multiroom_distance_map_origin: (a: number, b: number, c: number, d: number) => void;
-    readonly js_path_to_multiroom_distance_map_origin_with_portals: (a: number, b: number, c: number, d: number) => void;
+    readonly __wbg_compactdistancemap_free: (a: number, b: number) => void;
+    readonly __wbg_multiroomdistancemap_free: (a: number, b: number) => void;
+    readonly __wbg_multiroomturndistancemap_free: (a: number, b: number) => void;
+    readonly compactdistancemap_get: (a: number, b: number, c: number) => number;
+    readonly compactdistancemap_toDistanceMap: (a: number) => number;
+    readonly compactdistancemap_view: (a: number) => number;
+    readonly multiroomFlowField: (a: number, b: number, c: number, d: number) => number;
+    readonly multiroomMonoFlowField: (a: number, b: number, c: number, d: number) => number;
+    readonly multiroomPortalFlowField: (a: number, b: number, c: number, d: number) => number;
+    readonly multiroomPortalMonoFlowField: (a: number, b: number, c: number, d: number) => number;
+    readonly multiroomdistancemap_get: (a: number, b: number) => number;
+    readonly multiroomdistancemap_get_room: (a: number, b: number) => number;
+    readonly multiroomdistancemap_get_rooms: (a: number, b: number) => void;
+    readonly multiroomdistancemap_js_new: () => number;
+    readonly multiroomdistancemap_set: (a: number, b: number, c: number) => void;
+    readonly multiroomdistancemap_toCompact: (a: number) => number;
+    readonly multiroomturndistancemap_get: (a: number, b: number, c: number) => number;
+    readonly multiroomturndistancemap_turn_penalty: (a: number) => number;
+    readonly get_terrain_cost_matrix: (a: number, b: number, c: number, d: number) => number;
+    readonly js_bidirectional_astar_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number) => number;
+    readonly js_hierarchical_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number) => number;
+    readonly js_hierarchical_portal_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number) => number;
     readonly js_path_to_multiroom_flow_field_origin: (a: number, b: number, c: number) => void;
     readonly js_path_to_multiroom_flow_field_origin_with_portals: (a: number, b: number, c: number) => void;
+    readonly js_path_to_multiroom_flow_field_origin_with_secondary_cost: (a: number, b: number, c: number, d: number) => void;
     readonly js_path_to_multiroom_mono_flow_field_origin: (a: number, b: number, c: number) => void;
     readonly js_path_to_multiroom_mono_flow_field_origin_with_portals: (a: number, b: number, c: number) => void;
+    readonly __wbg_creepbody_free: (a: number, b: number) => void;
+    readonly __wbg_monoflowfield_free: (a: number, b: number) => void;
+    readonly __wbg_multiroomlazyflowfield_free: (a: number, b: number) => void;
+    readonly creepbody_empty_carry_parts: (a: number) => number;
+    readonly creepbody_js_new: (a: number, b: number, c: number, d: number, e: number) => number;
+    readonly creepbody_loaded_carry_parts: (a: number) => number;
+    readonly creepbody_move_boost: (a: number) => number;
+    readonly creepbody_move_parts: (a: number) => number;
+    readonly creepbody_other_parts: (a: number) => number;
+    readonly js_jps_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number) => number;
+    readonly js_jps_portal_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number) => number;
+    readonly js_travel_time_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number) => number;
+    readonly js_travel_time_portal_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number) => number;
+    readonly monoflowfield_get: (a: number, b: number, c: number) => number;
+    readonly monoflowfield_set: (a: number, b: number, c: number, d: number) => void;
+    readonly multiroomlazyflowfield_addDirection: (a: number, b: number, c: number) => void;
+    readonly multiroomlazyflowfield_get: (a: number, b: number) => number;
+    readonly multiroomlazyflowfield_getDirections: (a: number, b: number, c: number) => void;
+    readonly multiroomlazyflowfield_getFirstDirection: (a: number, b: number) => number;
+    readonly multiroomlazyflowfield_getRoom: (a: number, b: number) => number;
+    readonly multiroomlazyflowfield_getRooms: (a: number, b: number) => void;
+    readonly multiroomlazyflowfield_js_new: (a: number, b: number, c: number, d: number, e: number, f: number) => number;
+    readonly multiroomlazyflowfield_set: (a: number, b: number, c: number) => void;
+    readonly multiroomlazyflowfield_setDirections: (a: number, b: number, c: number, d: number) => void;
+    readonly seededDirectionOrder: (a: number, b: number) => void;
+    readonly __wbg_astarsearchhandle_free: (a: number, b: number) => void;
+    readonly __wbg_pathresult_free: (a: number, b: number) => void;
     readonly __wbg_searchresult_free: (a: number, b: number) => void;
+    readonly __wbg_voronoiresult_free: (a: number, b: number) => void;
+    readonly astarsearchhandle_cancel: (a: number) => void;
+    readonly astarsearchhandle_finalize: (a: number) => number;
+    readonly astarsearchhandle_finished: (a: number) => number;
+    readonly astarsearchhandle_new: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number) => number;
+    readonly astarsearchhandle_ops: (a: number) => number;
+    readonly astarsearchhandle_step: (a: number, b: number) => number;
+    readonly js_astar_flee_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_astar_flee_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_dijkstra_flee_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_dijkstra_flee_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_path_to_parent_tree_origin: (a: number, b: number) => number;
+    readonly js_path_to_parent_tree_origin_with_portals: (a: number, b: number) => number;
+    readonly js_voronoi_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
+    readonly pathresult_cost: (a: number) => number;
+    readonly pathresult_found: (a: number) => number;
+    readonly pathresult_ops: (a: number) => number;
+    readonly pathresult_path: (a: number) => number;
+    readonly searchresult_compact_distance_map: (a: number) => number;
     readonly searchresult_distance_map: (a: number) => number;
     readonly searchresult_found_targets: (a: number, b: number) => void;
     readonly searchresult_ops: (a: number) => number;
-    readonly __wbg_distancemap_free: (a: number, b: number) => void;
-    readonly __wbg_multiroomdistancemap_free: (a: number, b: number) => void;
-    readonly distancemap_get: (a: number, b: number, c: number) => number;
-    readonly distancemap_set: (a: number, b: number, c: number, d: number) => void;
-    readonly distancemap_toArray: (a: number, b: number) => void;
-    readonly get_terrain_cost_matrix: (a: number, b: number, c: number, d: number) => number;
-    readonly multiroomdistancemap_get: (a: number, b: number) => number;
-    readonly multiroomdistancemap_get_room: (a: number, b: number) => number;
-    readonly multiroomdistancemap_get_rooms: (a: number, b: number) => void;
-    readonly multiroomdistancemap_js_new: () => number;
-    readonly multiroomdistancemap_set: (a: number, b: number, c: number) => void;
-    readonly clear_portals: () => void;
-    readonly debug_portal_index: () => number;
-    readonly set_portal_distance_cache_room_limit: (a: number) => void;
-    readonly set_portals: (a: number, b: number) => void;
-    readonly js_bfs_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
-    readonly js_bfs_portal_multiroom_distance_map: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number) => number;
-    readonly __wbg_multiroommonoflowfield_free: (a: number, b: number) => void;
+    readonly searchresult_parents: (a: number) => number;
+    readonly searchresult_suboptimality_bound: (a: number) => number;
+    readonly searchresult_targets: (a: number) => number;
+    readonly searchresult_turn_distance_map: (a: number) => number;
+    readonly voronoiresult_boundaryTiles: (a: number, b: number) => void;
+    readonly voronoiresult_distance_map: (a: number) => number;
+    readonly voronoiresult_labels: (a: number) => number;
+    readonly voronoiresult_ops: (a: number) => number;
+    readonly voronoiresult_territory: (a: number, b: number, c: number) => void;
+    readonly __wbg_clockworkcostmatrix_free: (a: number, b: number) => void;
+    readonly __wbg_exitgraph_free: (a: number, b: number) => void;
+    readonly __wbg_multiroomlabelmap_free: (a: number, b: number) => void;
     readonly __wbg_path_free: (a: number, b: number) => void;
+    readonly __wbg_reservationtable_free: (a: number, b: number) => void;
+    readonly clockworkcostmatrix_get: (a: number, b: number, c: number) => number;
+    readonly clockworkcostmatrix_new: (a: number) => number;
+    readonly clockworkcostmatrix_set: (a: number, b: number, c: number, d: number) => void;
+    readonly exitgraph_clear: (a: number) => void;
+    readonly exitgraph_invalidate_room: (a: number, b: number) => void;
+    readonly exitgraph_js_new: () => number;
+    readonly exitgraph_js_room_count: (a: number) => number;
     readonly get_range: (a: number, b: number) => number;
-    readonly multiroommonoflowfield_get: (a: number, b: number) => number;
-    readonly multiroommonoflowfield_getRoom: (a: number, b: number) => number;
-    readonly multiroommonoflowfield_getRooms: (a: number, b: number) => void;
-    readonly multiroommonoflowfield_js_new: () => number;
-    readonly multiroommonoflowfield_set: (a: number, b: number, c: number) => void;
+    readonly multiroomlabelmap_get: (a: number, b: number) => number;
+    readonly multiroomlabelmap_get_room: (a: number, b: number, c: number) => void;
+    readonly multiroomlabelmap_get_rooms: (a: number, b: number) => void;
     readonly path_add: (a: number, b: number) => void;
     readonly path_find_next_index: (a: number, b: number) => number;
     readonly path_get: (a: number, b: number) => number;
     readonly path_len: (a: number) => number;
     readonly path_to_array: (a: number, b: number) => void;
     readonly path_to_array_reversed: (a: number, b: number) => void;
+    readonly reservationtable_clear: (a: number) => void;
+    readonly reservationtable_releaseAgent: (a: number, b: number) => void;
+    readonly reservationtable_releaseBefore: (a: number, b: number) => void;
+    readonly reservationtable_reserve: (a: number, b: number, c: number, d: number) => number;
+    readonly reservationtable_reservedBy: (a: number, b: number, c: number) => number;
     readonly version: (a: number) => void;
+    readonly reservationtable_size: (a: number) => number;
+    readonly reservationtable_js_new: () => number;
+    readonly __wbg_flowfield_free: (a: number, b: number) => void;
+    readonly flowfield_addDirection: (a: number, b: number, c: number, d: number) => void;
+    readonly flowfield_get: (a: number, b: number, c: number) => number;
+    readonly flowfield_getDirections: (a: number, b: number, c: number, d: number) => void;
+    readonly flowfield_set: (a: number, b: number, c: number, d: number) => void;
+    readonly flowfield_setDirections: (a: number, b: number, c: number, d: number, e: number) => void;
+    readonly js_path_to_multiroom_distance_map_origin: (a: number, b: number, c: number, d: number, e: number, f: number) => void;
+    readonly js_path_to_multiroom_distance_map_origin_with_portals: (a: number, b: number, c: number, d: number, e: number, f: number) => void;
+    readonly js_path_to_turn_distance_map_origin: (a: number, b: number, c: number) => void;
+    readonly js_space_time_astar_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_space_time_astar_portal_multiroom_path: (a: number, b: number, c: number, d: number, e: number, f: number, g: number, h: number, i: number, j: number, k: number) => number;
+    readonly js_find_portal_room_route: (a: number, b: number, c: number, d: number, e: number) => void;
+    readonly js_find_room_route: (a: number, b: number, c: number, d: number, e: number) => void;
+    readonly clear_landmarks: () => void;
+    readonly debug_landmark_index: () => number;
+    readonly set_landmark_room_budget: (a: number) => void;
     readonly __wbg_searchgoal_free: (a: number, b: number) => void;
     readonly searchgoal_pos: (a: number) => number;
     readonly searchgoal_range: (a: number) => number;
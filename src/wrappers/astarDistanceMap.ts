@@ -1,11 +1,16 @@
 import { MAX_USIZE } from '../utils/constants';
 import { fromPackedRoomNameCached } from '../utils/fromPacked';
-import { packDestinations, packPositions } from '../utils/packedArrays';
+import { packDestinations, packPositions, packRooms } from '../utils/packedArrays';
 import {
   ClockworkCostMatrix,
+  js_astar_flee_multiroom_distance_map,
+  js_astar_flee_portal_multiroom_distance_map,
   js_astar_multiroom_distance_map,
-  js_astar_portal_multiroom_distance_map
+  js_astar_multiroom_distance_map_to_goal_mask,
+  js_astar_portal_multiroom_distance_map,
+  js_astar_portal_multiroom_distance_map_to_goal_mask
 } from '../wasm/screeps_clockwork';
+import { ClockworkGoalMask } from './goalMask';
 import { fromPackedSearchResult } from './searchResult';
 
 /**
@@ -18,6 +23,33 @@ import { fromPackedSearchResult } from './searchResult';
  * - `maxRooms`: The maximum number of rooms to explore.
  * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
  *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `heuristicWeight` defaults to 1 (plain A*). Higher weights behave like PathFinder's
+ * `heuristicWeight`; a weight of `1 + ε` keeps paths within (1 + ε) of optimal, and
+ * the result's `suboptimalityBound` reports how close the found targets actually are.
+ * Weights below 1, `NaN` and `Infinity` throw.
+ *
+ * `startCosts` and `startLabels`, if given, hold a head start (initial cost) and a
+ * label for each start position. A target's `origin` is the label of the start its
+ * path came from.
+ *
+ * `landingTileCosts` (default true) makes a room crossing cost the higher of the exit
+ * tile and the tile it lands on in the next room, and a landing tile marked 255 blocks
+ * the crossing. Set it to false to charge only the exit tile.
+ *
+ * `recordParents` keeps the search's parent tree, returned as `parents`. Walking it
+ * back with `pathToOrigin` follows the exact path the search took to each tile.
+ *
+ * `turnPenalty`, if given, is charged each time a path changes direction, so paths
+ * prefer straight lines. The result then has a `turnDistanceMap` to walk back with
+ * instead of a parent tree, and asking for both throws.
+ *
+ * Destinations with a `minRange` are only reached at a range between `minRange`
+ * and `range`, like a ranged attacker keeping its distance. Ranges must be integers
+ * from 0 to 65535, with `minRange` no higher than `range`, or the search throws.
+ *
  * At least one of these limits must be set.
  *
  * @param start - The starting positions.
@@ -32,14 +64,28 @@ export function astarMultiroomDistanceMap(
     maxOps = MAX_USIZE,
     maxPathCost = MAX_USIZE,
     anyOfDestinations,
-    allOfDestinations
+    allOfDestinations,
+    heuristicWeight,
+    allowedRooms,
+    startCosts,
+    startLabels,
+    landingTileCosts,
+    recordParents,
+    turnPenalty
   }: {
     costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
     maxRooms?: number;
     maxOps?: number;
     maxPathCost?: number;
-    anyOfDestinations?: { pos: RoomPosition; range: number }[];
-    allOfDestinations?: { pos: RoomPosition; range: number }[];
+    anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    heuristicWeight?: number;
+    allowedRooms?: string[];
+    startCosts?: number[];
+    startLabels?: number[];
+    landingTileCosts?: boolean;
+    recordParents?: boolean;
+    turnPenalty?: number;
   }
 ) {
   if ([maxRooms, maxOps, maxPathCost].every(n => n === MAX_USIZE) && !anyOfDestinations && !allOfDestinations) {
@@ -56,7 +102,14 @@ export function astarMultiroomDistanceMap(
     maxOps,
     maxPathCost,
     packDestinations(anyOfDestinations),
-    packDestinations(allOfDestinations)
+    packDestinations(allOfDestinations),
+    heuristicWeight,
+    packRooms(allowedRooms),
+    startCosts && Uint32Array.from(startCosts),
+    startLabels && Uint32Array.from(startLabels),
+    landingTileCosts,
+    recordParents,
+    turnPenalty
   );
 
   return fromPackedSearchResult(result);
@@ -74,6 +127,29 @@ export function astarMultiroomDistanceMap(
  * - `maxRooms`: The maximum number of rooms to explore.
  * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
  *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `heuristicWeight` defaults to 1 (plain A*). Higher weights behave like PathFinder's
+ * `heuristicWeight`; a weight of `1 + ε` keeps paths within (1 + ε) of optimal, and
+ * the result's `suboptimalityBound` reports how close the found targets actually are.
+ * Weights below 1, `NaN` and `Infinity` throw.
+ *
+ * `startCosts` and `startLabels`, if given, hold a head start (initial cost) and a
+ * label for each start position. A target's `origin` is the label of the start its
+ * path came from.
+ *
+ * `landingTileCosts` (default true) makes a room crossing cost the higher of the exit
+ * tile and the tile it lands on in the next room, and a landing tile marked 255 blocks
+ * the crossing. Set it to false to charge only the exit tile.
+ *
+ * `recordParents` keeps the search's parent tree, returned as `parents`. Walking it
+ * back with `pathToOrigin` follows the exact path the search took to each tile.
+ *
+ * Destinations with a `minRange` are only reached at a range between `minRange`
+ * and `range`, like a ranged attacker keeping its distance. Ranges must be integers
+ * from 0 to 65535, with `minRange` no higher than `range`, or the search throws.
+ *
  * At least one of these limits or destination lists must be set.
  *
  * @param start - The starting positions.
@@ -88,14 +164,26 @@ export function astarPortalMultiroomDistanceMap(
     maxOps = MAX_USIZE,
     maxPathCost = MAX_USIZE,
     anyOfDestinations,
-    allOfDestinations
+    allOfDestinations,
+    heuristicWeight,
+    allowedRooms,
+    startCosts,
+    startLabels,
+    landingTileCosts,
+    recordParents
   }: {
     costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
     maxRooms?: number;
     maxOps?: number;
     maxPathCost?: number;
-    anyOfDestinations?: { pos: RoomPosition; range: number }[];
-    allOfDestinations?: { pos: RoomPosition; range: number }[];
+    anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    heuristicWeight?: number;
+    allowedRooms?: string[];
+    startCosts?: number[];
+    startLabels?: number[];
+    landingTileCosts?: boolean;
+    recordParents?: boolean;
   }
 ) {
   if ([maxRooms, maxOps, maxPathCost].every(n => n === MAX_USIZE) && !anyOfDestinations && !allOfDestinations) {
@@ -112,7 +200,208 @@ export function astarPortalMultiroomDistanceMap(
     maxOps,
     maxPathCost,
     packDestinations(anyOfDestinations),
-    packDestinations(allOfDestinations)
+    packDestinations(allOfDestinations),
+    heuristicWeight,
+    packRooms(allowedRooms),
+    startCosts && Uint32Array.from(startCosts),
+    startLabels && Uint32Array.from(startLabels),
+    landingTileCosts,
+    recordParents
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Search away from the given threats, like PathFinder's `flee: true`, using A* to
+ * head toward safety.
+ *
+ * The search stops at the cheapest tile where every threat in `fleeFrom` is at least
+ * its range away. That tile is returned in `foundTargets`, and the distance map is
+ * rooted at the start positions, so `pathToOrigin` from it walks back to the start.
+ *
+ * This calculates a distance map across multiple rooms, with a few configurable limits:
+ * - `maxOps`: The maximum number of pathfinding operations to perform.
+ * - `maxRooms`: The maximum number of rooms to explore.
+ * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
+ *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `landingTileCosts` (default true) makes a room crossing cost the higher of the exit
+ * tile and the tile it lands on in the next room, and a landing tile marked 255 blocks
+ * the crossing. Set it to false to charge only the exit tile.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function astarFleeMultiroomDistanceMap(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    fleeFrom,
+    allowedRooms,
+    landingTileCosts
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+    fleeFrom: { pos: RoomPosition; range: number }[];
+    allowedRooms?: string[];
+    landingTileCosts?: boolean;
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_astar_flee_multiroom_distance_map(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    packDestinations(fleeFrom),
+    packRooms(allowedRooms),
+    landingTileCosts
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Portal-aware variant of `astarFleeMultiroomDistanceMap`. Stepping onto a portal
+ * entrance lands on its paired exit, as in the other portal searches.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function astarFleePortalMultiroomDistanceMap(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    fleeFrom,
+    allowedRooms,
+    landingTileCosts
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+    fleeFrom: { pos: RoomPosition; range: number }[];
+    allowedRooms?: string[];
+    landingTileCosts?: boolean;
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_astar_flee_portal_multiroom_distance_map(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    packDestinations(fleeFrom),
+    packRooms(allowedRooms),
+    landingTileCosts
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Create a distance map for the given start positions using A* guided by `boundDestinations`, stopping at
+ * the nearest tile in `goalMask`. The tile is returned in `foundTargets`.
+ *
+ * `boundDestinations`, if given, are destinations that every masked tile is within
+ * range of, such as the range the mask was built around. Their range heuristic
+ * guides the search.
+ *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function astarMultiroomDistanceMapToGoalMask(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    goalMask,
+    boundDestinations,
+    allowedRooms
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+    goalMask: ClockworkGoalMask;
+    boundDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allowedRooms?: string[];
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_astar_multiroom_distance_map_to_goal_mask(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    goalMask.handle,
+    packDestinations(boundDestinations),
+    packRooms(allowedRooms)
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Portal-aware variant of `astarMultiroomDistanceMapToGoalMask`: stepping onto a
+ * portal entrance lands on its paired exit.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function astarPortalMultiroomDistanceMapToGoalMask(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    goalMask,
+    boundDestinations,
+    allowedRooms
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+    goalMask: ClockworkGoalMask;
+    boundDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allowedRooms?: string[];
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_astar_portal_multiroom_distance_map_to_goal_mask(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    goalMask.handle,
+    packDestinations(boundDestinations),
+    packRooms(allowedRooms)
   );
 
   return fromPackedSearchResult(result);
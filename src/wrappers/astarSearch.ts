@@ -0,0 +1,87 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached } from '../utils/fromPacked';
+import { packDestinations, packPositions } from '../utils/packedArrays';
+import { AstarSearchHandle, ClockworkCostMatrix } from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { fromPackedSearchResult } from './searchResult';
+
+/**
+ * An A* distance-map search that can be advanced a few ops at a time, so a large
+ * search can be spread across several ticks.
+ *
+ * Takes the same options as `astarMultiroomDistanceMap`, except that the ops budget
+ * is passed to each call to `step`. The cost matrix callback is kept until the
+ * search is finalized or cancelled; call `free` once you are done with the handle.
+ */
+export class ClockworkAstarSearch {
+  private _search: AstarSearchHandle | undefined;
+
+  constructor(
+    start: RoomPosition[],
+    {
+      costMatrixCallback,
+      maxRooms = MAX_USIZE,
+      maxPathCost = MAX_USIZE,
+      anyOfDestinations,
+      allOfDestinations
+    }: {
+      costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+      maxRooms?: number;
+      maxPathCost?: number;
+      anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+      allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    }
+  ) {
+    this._search = new AstarSearchHandle(
+      packPositions(start),
+      (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+      maxRooms,
+      maxPathCost,
+      packDestinations(anyOfDestinations),
+      packDestinations(allOfDestinations)
+    );
+  }
+
+  /**
+   * Frees the underlying WASM search allocation.
+   */
+  free(): void {
+    this._search = freeHandle(this._search);
+  }
+
+  /**
+   * Advance the search by roughly `ops` tiles. Returns true if the search is finished.
+   */
+  step(ops: number): boolean {
+    return assertNotFreed(this._search, 'ClockworkAstarSearch').step(ops);
+  }
+
+  /**
+   * Whether the search has finished (or was cancelled).
+   */
+  get finished(): boolean {
+    return assertNotFreed(this._search, 'ClockworkAstarSearch').finished;
+  }
+
+  /**
+   * The number of tiles opened so far.
+   */
+  get ops(): number {
+    return assertNotFreed(this._search, 'ClockworkAstarSearch').ops;
+  }
+
+  /**
+   * Stop the search and release its state without producing a result.
+   */
+  cancel(): void {
+    assertNotFreed(this._search, 'ClockworkAstarSearch').cancel();
+  }
+
+  /**
+   * Return the search result and release the search. If the search has not
+   * finished, the result contains the partial distance map explored so far.
+   */
+  finalize() {
+    return fromPackedSearchResult(assertNotFreed(this._search, 'ClockworkAstarSearch').finalize());
+  }
+}
@@ -1,11 +1,13 @@
 import { MAX_USIZE } from '../utils/constants';
 import { fromPackedRoomNameCached } from '../utils/fromPacked';
-import { packDestinations, packPositions } from '../utils/packedArrays';
+import { packDestinations, packPositions, packRooms } from '../utils/packedArrays';
 import {
   ClockworkCostMatrix,
   js_bfs_multiroom_distance_map,
+  js_bfs_multiroom_distance_map_to_goal_mask,
   js_bfs_portal_multiroom_distance_map
 } from '../wasm/screeps_clockwork';
+import { ClockworkGoalMask } from './goalMask';
 import { fromPackedSearchResult } from './searchResult';
 
 /**
@@ -18,6 +20,20 @@ import { fromPackedSearchResult } from './searchResult';
  * - `maxRooms`: The maximum number of rooms to explore.
  * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
  *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `startCosts` and `startLabels`, if given, hold a head start (initial cost) and a
+ * label for each start position. A target's `origin` is the label of the start its
+ * path came from.
+ *
+ * `recordParents` keeps the search's parent tree, returned as `parents`. Walking it
+ * back with `pathToOrigin` follows the exact path the search took to each tile.
+ *
+ * Destinations with a `minRange` are only reached at a range between `minRange`
+ * and `range`, like a ranged attacker keeping its distance. Ranges must be integers
+ * from 0 to 65535, with `minRange` no higher than `range`, or the search throws.
+ *
  * At least one of these limits must be set.
  *
  * @param start - The starting positions.
@@ -32,14 +48,22 @@ export function bfsMultiroomDistanceMap(
     maxRooms = MAX_USIZE,
     maxPathCost = MAX_USIZE,
     anyOfDestinations,
-    allOfDestinations
+    allOfDestinations,
+    allowedRooms,
+    startCosts,
+    startLabels,
+    recordParents
   }: {
     costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
     maxOps?: number;
     maxRooms?: number;
     maxPathCost?: number;
-    anyOfDestinations?: { pos: RoomPosition; range: number }[];
-    allOfDestinations?: { pos: RoomPosition; range: number }[];
+    anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allowedRooms?: string[];
+    startCosts?: number[];
+    startLabels?: number[];
+    recordParents?: boolean;
   }
 ) {
   if ([maxOps, maxRooms, maxPathCost].every(n => n === MAX_USIZE) && !anyOfDestinations && !allOfDestinations) {
@@ -56,7 +80,11 @@ export function bfsMultiroomDistanceMap(
     maxRooms,
     maxPathCost,
     packDestinations(anyOfDestinations),
-    packDestinations(allOfDestinations)
+    packDestinations(allOfDestinations),
+    packRooms(allowedRooms),
+    startCosts && Uint32Array.from(startCosts),
+    startLabels && Uint32Array.from(startLabels),
+    recordParents
   );
 
   return fromPackedSearchResult(result);
@@ -75,6 +103,20 @@ export function bfsMultiroomDistanceMap(
  * - `maxRooms`: The maximum number of rooms to explore.
  * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
  *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `startCosts` and `startLabels`, if given, hold a head start (initial cost) and a
+ * label for each start position. A target's `origin` is the label of the start its
+ * path came from.
+ *
+ * `recordParents` keeps the search's parent tree, returned as `parents`. Walking it
+ * back with `pathToOrigin` follows the exact path the search took to each tile.
+ *
+ * Destinations with a `minRange` are only reached at a range between `minRange`
+ * and `range`, like a ranged attacker keeping its distance. Ranges must be integers
+ * from 0 to 65535, with `minRange` no higher than `range`, or the search throws.
+ *
  * At least one of these limits or destination lists must be set.
  *
  * @param start - The starting positions.
@@ -89,14 +131,22 @@ export function bfsPortalMultiroomDistanceMap(
     maxRooms = MAX_USIZE,
     maxPathCost = MAX_USIZE,
     anyOfDestinations,
-    allOfDestinations
+    allOfDestinations,
+    allowedRooms,
+    startCosts,
+    startLabels,
+    recordParents
   }: {
     costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
     maxOps?: number;
     maxRooms?: number;
     maxPathCost?: number;
-    anyOfDestinations?: { pos: RoomPosition; range: number }[];
-    allOfDestinations?: { pos: RoomPosition; range: number }[];
+    anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allowedRooms?: string[];
+    startCosts?: number[];
+    startLabels?: number[];
+    recordParents?: boolean;
   }
 ) {
   if ([maxOps, maxRooms, maxPathCost].every(n => n === MAX_USIZE) && !anyOfDestinations && !allOfDestinations) {
@@ -113,7 +163,54 @@ export function bfsPortalMultiroomDistanceMap(
     maxRooms,
     maxPathCost,
     packDestinations(anyOfDestinations),
-    packDestinations(allOfDestinations)
+    packDestinations(allOfDestinations),
+    packRooms(allowedRooms),
+    startCosts && Uint32Array.from(startCosts),
+    startLabels && Uint32Array.from(startLabels),
+    recordParents
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Create a distance map for the given start positions using a breadth-first search, stopping at
+ * the nearest tile in `goalMask`. The tile is returned in `foundTargets`.
+ *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function bfsMultiroomDistanceMapToGoalMask(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxOps = MAX_USIZE,
+    maxRooms = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    goalMask,
+    allowedRooms
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxOps?: number;
+    maxRooms?: number;
+    maxPathCost?: number;
+    goalMask: ClockworkGoalMask;
+    allowedRooms?: string[];
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_bfs_multiroom_distance_map_to_goal_mask(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxOps,
+    maxRooms,
+    maxPathCost,
+    goalMask.handle,
+    packRooms(allowedRooms)
   );
 
   return fromPackedSearchResult(result);
@@ -0,0 +1,43 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached } from '../utils/fromPacked';
+import { ClockworkCostMatrix, js_bidirectional_astar_multiroom_path } from '../wasm/screeps_clockwork';
+import { fromPackedPathResult } from './pathResult';
+
+/**
+ * Find a path between two positions with bidirectional A*, searching forward from
+ * the start and backward from the goal until the two searches meet. This usually
+ * opens far fewer tiles than a one-directional search on long paths.
+ *
+ * The search can be limited with `maxRooms`, `maxOps`, and `maxPathCost`.
+ *
+ * @param start - The starting position.
+ * @param goal - The goal position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether the goal was reached, and the ops used.
+ */
+export function bidirectionalAstarMultiroomPath(
+  start: RoomPosition,
+  goal: RoomPosition,
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+  }
+) {
+  const result = js_bidirectional_astar_multiroom_path(
+    start.__packedPos,
+    goal.__packedPos,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost
+  );
+
+  return fromPackedPathResult(result);
+}
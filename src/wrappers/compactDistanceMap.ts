@@ -0,0 +1,45 @@
+import { CompactDistanceMap } from '../wasm/screeps_clockwork';
+import { ClockworkDistanceMap } from './distanceMap';
+import { assertNotFreed, freeHandle } from './freeable';
+
+/**
+ * A distance map for a single room, stored as 16-bit values to halve its memory.
+ * Unreachable tiles read back as `0xFFFFFFFF`, like a `ClockworkDistanceMap`.
+ */
+export class ClockworkCompactDistanceMap {
+  private _map: CompactDistanceMap | undefined;
+
+  constructor(map: CompactDistanceMap) {
+    this._map = map;
+  }
+
+  /**
+   * Frees the underlying WASM distance map allocation.
+   */
+  free(): void {
+    this._map = freeHandle(this._map);
+  }
+
+  /**
+   * Gets the distance value at a given position.
+   */
+  get(x: number, y: number): number {
+    return assertNotFreed(this._map, 'ClockworkCompactDistanceMap').get(x, y);
+  }
+
+  /**
+   * A view of the distances, indexed like a cost matrix, without copying.
+   * Unreachable tiles are 65535. The view is only valid until the map is freed or
+   * WASM memory grows (which any call into WASM might do), so read it right away.
+   */
+  view(): Uint16Array {
+    return assertNotFreed(this._map, 'ClockworkCompactDistanceMap').view();
+  }
+
+  /**
+   * Converts back to a full distance map.
+   */
+  toDistanceMap(): ClockworkDistanceMap {
+    return new ClockworkDistanceMap(assertNotFreed(this._map, 'ClockworkCompactDistanceMap').toDistanceMap());
+  }
+}
@@ -1,11 +1,15 @@
 import { MAX_USIZE } from '../utils/constants';
 import { fromPackedRoomNameCached } from '../utils/fromPacked';
-import { packDestinations, packPositions } from '../utils/packedArrays';
+import { packDestinations, packPositions, packRooms } from '../utils/packedArrays';
 import {
   ClockworkCostMatrix,
+  js_dijkstra_flee_multiroom_distance_map,
+  js_dijkstra_flee_portal_multiroom_distance_map,
   js_dijkstra_multiroom_distance_map,
+  js_dijkstra_multiroom_distance_map_to_goal_mask,
   js_dijkstra_portal_multiroom_distance_map
 } from '../wasm/screeps_clockwork';
+import { ClockworkGoalMask } from './goalMask';
 import { fromPackedSearchResult } from './searchResult';
 
 /**
@@ -17,6 +21,24 @@ import { fromPackedSearchResult } from './searchResult';
  * - `maxRooms`: The maximum number of rooms to explore.
  * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
  *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `landingTileCosts` (default true) makes a room crossing cost the higher of the exit
+ * tile and the tile it lands on in the next room, and a landing tile marked 255 blocks
+ * the crossing. Set it to false to charge only the exit tile.
+ *
+ * `recordParents` keeps the search's parent tree, returned as `parents`. Walking it
+ * back with `pathToOrigin` follows the exact path the search took to each tile.
+ *
+ * `turnPenalty`, if given, is charged each time a path changes direction, so paths
+ * prefer straight lines. The result then has a `turnDistanceMap` to walk back with
+ * instead of a parent tree, and asking for both throws.
+ *
+ * Destinations with a `minRange` are only reached at a range between `minRange`
+ * and `range`, like a ranged attacker keeping its distance. Ranges must be integers
+ * from 0 to 65535, with `minRange` no higher than `range`, or the search throws.
+ *
  * At least one of these limits must be set.
  *
  * @param start - The starting positions.
@@ -31,14 +53,22 @@ export function dijkstraMultiroomDistanceMap(
     maxRooms = MAX_USIZE,
     maxPathCost = MAX_USIZE,
     anyOfDestinations,
-    allOfDestinations
+    allOfDestinations,
+    allowedRooms,
+    landingTileCosts,
+    recordParents,
+    turnPenalty
   }: {
     costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
     maxOps?: number;
     maxRooms?: number;
     maxPathCost?: number;
-    anyOfDestinations?: { pos: RoomPosition; range: number }[];
-    allOfDestinations?: { pos: RoomPosition; range: number }[];
+    anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allowedRooms?: string[];
+    landingTileCosts?: boolean;
+    recordParents?: boolean;
+    turnPenalty?: number;
   }
 ) {
   if ([maxOps, maxRooms, maxPathCost].every(n => n === MAX_USIZE) && !anyOfDestinations && !allOfDestinations) {
@@ -55,7 +85,11 @@ export function dijkstraMultiroomDistanceMap(
     maxRooms,
     maxPathCost,
     packDestinations(anyOfDestinations),
-    packDestinations(allOfDestinations)
+    packDestinations(allOfDestinations),
+    packRooms(allowedRooms),
+    landingTileCosts,
+    recordParents,
+    turnPenalty
   );
 
   return fromPackedSearchResult(result);
@@ -73,6 +107,20 @@ export function dijkstraMultiroomDistanceMap(
  * - `maxRooms`: The maximum number of rooms to explore.
  * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
  *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `landingTileCosts` (default true) makes a room crossing cost the higher of the exit
+ * tile and the tile it lands on in the next room, and a landing tile marked 255 blocks
+ * the crossing. Set it to false to charge only the exit tile.
+ *
+ * `recordParents` keeps the search's parent tree, returned as `parents`. Walking it
+ * back with `pathToOrigin` follows the exact path the search took to each tile.
+ *
+ * Destinations with a `minRange` are only reached at a range between `minRange`
+ * and `range`, like a ranged attacker keeping its distance. Ranges must be integers
+ * from 0 to 65535, with `minRange` no higher than `range`, or the search throws.
+ *
  * At least one of these limits or destination lists must be set.
  *
  * @param start - The starting positions.
@@ -87,14 +135,20 @@ export function dijkstraPortalMultiroomDistanceMap(
     maxRooms = MAX_USIZE,
     maxPathCost = MAX_USIZE,
     anyOfDestinations,
-    allOfDestinations
+    allOfDestinations,
+    allowedRooms,
+    landingTileCosts,
+    recordParents
   }: {
     costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
     maxOps?: number;
     maxRooms?: number;
     maxPathCost?: number;
-    anyOfDestinations?: { pos: RoomPosition; range: number }[];
-    allOfDestinations?: { pos: RoomPosition; range: number }[];
+    anyOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allOfDestinations?: { pos: RoomPosition; range: number; minRange?: number }[];
+    allowedRooms?: string[];
+    landingTileCosts?: boolean;
+    recordParents?: boolean;
   }
 ) {
   if ([maxOps, maxRooms, maxPathCost].every(n => n === MAX_USIZE) && !anyOfDestinations && !allOfDestinations) {
@@ -111,7 +165,149 @@ export function dijkstraPortalMultiroomDistanceMap(
     maxRooms,
     maxPathCost,
     packDestinations(anyOfDestinations),
-    packDestinations(allOfDestinations)
+    packDestinations(allOfDestinations),
+    packRooms(allowedRooms),
+    landingTileCosts,
+    recordParents
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Search away from the given threats, like PathFinder's `flee: true`, using Dijkstra's
+ * algorithm.
+ *
+ * The search stops at the cheapest tile where every threat in `fleeFrom` is at least
+ * its range away. That tile is returned in `foundTargets`, and the distance map is
+ * rooted at the start positions, so `pathToOrigin` from it walks back to the start.
+ *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * `landingTileCosts` (default true) makes a room crossing cost the higher of the exit
+ * tile and the tile it lands on in the next room, and a landing tile marked 255 blocks
+ * the crossing. Set it to false to charge only the exit tile.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function dijkstraFleeMultiroomDistanceMap(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxOps = MAX_USIZE,
+    maxRooms = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    fleeFrom,
+    allowedRooms,
+    landingTileCosts
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxOps?: number;
+    maxRooms?: number;
+    maxPathCost?: number;
+    fleeFrom: { pos: RoomPosition; range: number }[];
+    allowedRooms?: string[];
+    landingTileCosts?: boolean;
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_dijkstra_flee_multiroom_distance_map(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxOps,
+    maxRooms,
+    maxPathCost,
+    packDestinations(fleeFrom),
+    packRooms(allowedRooms),
+    landingTileCosts
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Portal-aware variant of `dijkstraFleeMultiroomDistanceMap`.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function dijkstraFleePortalMultiroomDistanceMap(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxOps = MAX_USIZE,
+    maxRooms = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    fleeFrom,
+    allowedRooms,
+    landingTileCosts
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxOps?: number;
+    maxRooms?: number;
+    maxPathCost?: number;
+    fleeFrom: { pos: RoomPosition; range: number }[];
+    allowedRooms?: string[];
+    landingTileCosts?: boolean;
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_dijkstra_flee_portal_multiroom_distance_map(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxOps,
+    maxRooms,
+    maxPathCost,
+    packDestinations(fleeFrom),
+    packRooms(allowedRooms),
+    landingTileCosts
+  );
+
+  return fromPackedSearchResult(result);
+}
+
+/**
+ * Create a distance map for the given start positions using Dijkstra's algorithm, stopping at
+ * the nearest tile in `goalMask`. The tile is returned in `foundTargets`.
+ *
+ * `allowedRooms`, if given, keeps the search to those rooms, such as the rooms of a
+ * route from `findRoomRoute`.
+ *
+ * @param start - The starting positions.
+ * @param options - The options for the distance map.
+ * @returns A multi-room distance map.
+ */
+export function dijkstraMultiroomDistanceMapToGoalMask(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxOps = MAX_USIZE,
+    maxRooms = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    goalMask,
+    allowedRooms
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxOps?: number;
+    maxRooms?: number;
+    maxPathCost?: number;
+    goalMask: ClockworkGoalMask;
+    allowedRooms?: string[];
+  }
+) {
+  const startPacked = packPositions(start);
+  const result = js_dijkstra_multiroom_distance_map_to_goal_mask(
+    startPacked,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxOps,
+    maxRooms,
+    maxPathCost,
+    goalMask.handle,
+    packRooms(allowedRooms)
   );
 
   return fromPackedSearchResult(result);
@@ -0,0 +1,51 @@
+import { packRoomName } from '../utils/fromPacked';
+import { ClockworkCostMatrix, GoalMask } from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+
+/**
+ * A set of goal tiles across multiple rooms. Searches given a mask stop at the
+ * first masked tile they settle, for goals that don't fit a position and range
+ * ("the nearest free tile near the controller").
+ */
+export class ClockworkGoalMask {
+  private _mask: GoalMask | undefined;
+
+  constructor() {
+    this._mask = new GoalMask();
+  }
+
+  /**
+   * Frees the underlying WASM goal mask allocation.
+   */
+  free(): void {
+    this._mask = freeHandle(this._mask);
+  }
+
+  /**
+   * Mark a position as a goal.
+   */
+  add(pos: RoomPosition): void {
+    assertNotFreed(this._mask, 'ClockworkGoalMask').add(pos.__packedPos);
+  }
+
+  /**
+   * Mark every tile with a non-zero value in the cost matrix as a goal.
+   */
+  addRoom(room: string, matrix: ClockworkCostMatrix): void {
+    assertNotFreed(this._mask, 'ClockworkGoalMask').addRoom(packRoomName(room), matrix);
+  }
+
+  /**
+   * Whether a position is a goal.
+   */
+  has(pos: RoomPosition): boolean {
+    return assertNotFreed(this._mask, 'ClockworkGoalMask').has(pos.__packedPos);
+  }
+
+  /**
+   * The underlying WASM goal mask, for passing to the search functions.
+   */
+  get handle(): GoalMask {
+    return assertNotFreed(this._mask, 'ClockworkGoalMask');
+  }
+}
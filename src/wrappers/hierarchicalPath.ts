@@ -0,0 +1,141 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached, packRoomName } from '../utils/fromPacked';
+import {
+  ClockworkCostMatrix,
+  ExitGraph,
+  js_hierarchical_multiroom_path,
+  js_hierarchical_portal_multiroom_path
+} from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { fromPackedPathResult } from './pathResult';
+
+/**
+ * A cache of room abstractions for hierarchical pathfinding, kept between searches.
+ * Entries are rebuilt automatically when a room's cost matrix changes, so rooms only
+ * need to be invalidated explicitly to free memory.
+ */
+export class ClockworkExitGraph {
+  private _graph: ExitGraph | undefined;
+
+  constructor() {
+    this._graph = new ExitGraph();
+  }
+
+  /**
+   * Frees the underlying WASM exit graph allocation.
+   */
+  free(): void {
+    this._graph = freeHandle(this._graph);
+  }
+
+  /**
+   * Drop all cached room abstractions.
+   */
+  clear(): void {
+    assertNotFreed(this._graph, 'ClockworkExitGraph').clear();
+  }
+
+  /**
+   * Drop the cached abstraction for a room.
+   */
+  invalidateRoom(room: string): void {
+    assertNotFreed(this._graph, 'ClockworkExitGraph').invalidate_room(packRoomName(room));
+  }
+
+  /**
+   * The number of rooms currently cached.
+   */
+  get roomCount(): number {
+    return assertNotFreed(this._graph, 'ClockworkExitGraph').room_count;
+  }
+
+  /**
+   * The underlying WASM exit graph, for passing to the search functions.
+   */
+  get handle(): ExitGraph {
+    return assertNotFreed(this._graph, 'ClockworkExitGraph');
+  }
+}
+
+/**
+ * Plan a path across many rooms on an abstract graph instead of tile by tile. Each
+ * room is reduced to its exits and the costs between them, which are cached in
+ * `exitGraph` between searches. The abstract route is then refined into a concrete
+ * path through only the rooms along it.
+ *
+ * Crossing each stretch of room edge at a single tile makes the result slightly
+ * suboptimal compared to a full tile-level search.
+ *
+ * @param exitGraph - The room abstraction cache to use and fill.
+ * @param start - The starting position.
+ * @param goal - The goal position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether the goal was reached, and the ops used.
+ */
+export function hierarchicalMultiroomPath(
+  exitGraph: ClockworkExitGraph,
+  start: RoomPosition,
+  goal: RoomPosition,
+  {
+    costMatrixCallback,
+    range = 0,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    range?: number;
+    maxRooms?: number;
+    maxOps?: number;
+  }
+) {
+  const result = js_hierarchical_multiroom_path(
+    exitGraph.handle,
+    start.__packedPos,
+    goal.__packedPos,
+    range,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps
+  );
+
+  return fromPackedPathResult(result);
+}
+
+/**
+ * Portal-aware variant of `hierarchicalMultiroomPath`: portal entrances are exits of
+ * their room, leading to the paired portal exit.
+ *
+ * @param exitGraph - The room abstraction cache to use and fill.
+ * @param start - The starting position.
+ * @param goal - The goal position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether the goal was reached, and the ops used.
+ */
+export function hierarchicalPortalMultiroomPath(
+  exitGraph: ClockworkExitGraph,
+  start: RoomPosition,
+  goal: RoomPosition,
+  {
+    costMatrixCallback,
+    range = 0,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    range?: number;
+    maxRooms?: number;
+    maxOps?: number;
+  }
+) {
+  const result = js_hierarchical_portal_multiroom_path(
+    exitGraph.handle,
+    start.__packedPos,
+    goal.__packedPos,
+    range,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps
+  );
+
+  return fromPackedPathResult(result);
+}
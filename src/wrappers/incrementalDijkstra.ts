@@ -0,0 +1,93 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached, packRoomName } from '../utils/fromPacked';
+import { packPositions } from '../utils/packedArrays';
+import { ClockworkCostMatrix, IncrementalDijkstraHandle } from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { ClockworkMultiroomDistanceMap } from './multiroomDistanceMap';
+
+/**
+ * A Dijkstra distance map that is repaired in place when tile costs change,
+ * instead of being rebuilt from scratch.
+ *
+ * Rooms are loaded from `costMatrixCallback` as the search reaches them. The
+ * search is bounded by `maxRooms` and `maxPathCost`, at least one of which must be set.
+ */
+export class ClockworkIncrementalDijkstra {
+  private _search: IncrementalDijkstraHandle | undefined;
+
+  constructor(
+    start: RoomPosition[],
+    {
+      costMatrixCallback,
+      maxRooms = MAX_USIZE,
+      maxPathCost = MAX_USIZE
+    }: {
+      costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+      maxRooms?: number;
+      maxPathCost?: number;
+    }
+  ) {
+    if ([maxRooms, maxPathCost].every(n => n === MAX_USIZE)) {
+      throw new Error('At least one of maxRooms or maxPathCost must be set');
+    }
+
+    this._search = new IncrementalDijkstraHandle(
+      packPositions(start),
+      (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+      maxRooms,
+      maxPathCost
+    );
+  }
+
+  /**
+   * Frees the underlying WASM search allocation.
+   */
+  free(): void {
+    this._search = freeHandle(this._search);
+  }
+
+  /**
+   * Get the current distance for a given position.
+   */
+  get(pos: RoomPosition): number {
+    return assertNotFreed(this._search, 'ClockworkIncrementalDijkstra').get(pos.__packedPos);
+  }
+
+  /**
+   * Apply changed tile costs, repairing the distances they affect. Returns the
+   * number of tiles updated.
+   */
+  updateCosts(changes: { pos: RoomPosition; cost: number }[]): number {
+    const packed = new Uint32Array(changes.length * 2);
+    for (let i = 0; i < changes.length; i++) {
+      packed[i * 2] = changes[i].pos.__packedPos;
+      packed[i * 2 + 1] = changes[i].cost;
+    }
+    return assertNotFreed(this._search, 'ClockworkIncrementalDijkstra').update_costs(packed);
+  }
+
+  /**
+   * Replace a room's cost matrix, repairing the tiles that changed. Returns the
+   * number of tiles updated.
+   */
+  updateCostMatrix(room: string, costMatrix: ClockworkCostMatrix): number {
+    return assertNotFreed(this._search, 'ClockworkIncrementalDijkstra').update_cost_matrix(
+      packRoomName(room),
+      costMatrix
+    );
+  }
+
+  /**
+   * A copy of the current distance map.
+   */
+  get distanceMap(): ClockworkMultiroomDistanceMap {
+    return new ClockworkMultiroomDistanceMap(assertNotFreed(this._search, 'ClockworkIncrementalDijkstra').distance_map);
+  }
+
+  /**
+   * The total number of tiles updated, including the initial search.
+   */
+  get ops(): number {
+    return assertNotFreed(this._search, 'ClockworkIncrementalDijkstra').ops;
+  }
+}
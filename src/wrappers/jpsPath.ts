@@ -0,0 +1,81 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached } from '../utils/fromPacked';
+import { packDestinations } from '../utils/packedArrays';
+import { ClockworkCostMatrix, js_jps_multiroom_path, js_jps_portal_multiroom_path } from '../wasm/screeps_clockwork';
+import { fromPackedPathResult } from './pathResult';
+
+/**
+ * Find a path from the start position to any of the destinations with jump point
+ * search. Every passable tile costs 1 to enter, so cost matrix values only matter
+ * as obstacles (255).
+ *
+ * The search can be limited with `maxRooms`, `maxOps`, and `maxPathCost`. Ops count
+ * tiles scanned while jumping, rather than tiles settled.
+ *
+ * @param start - The starting position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether a destination was reached, and the ops used.
+ */
+export function jpsMultiroomPath(
+  start: RoomPosition,
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    destinations
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+    destinations: { pos: RoomPosition; range: number }[];
+  }
+) {
+  const result = js_jps_multiroom_path(
+    start.__packedPos,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    packDestinations(destinations)
+  );
+
+  return fromPackedPathResult(result);
+}
+
+/**
+ * Portal-aware variant of `jpsMultiroomPath`: stepping onto a portal entrance lands
+ * on its paired exit.
+ *
+ * @param start - The starting position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether a destination was reached, and the ops used.
+ */
+export function jpsPortalMultiroomPath(
+  start: RoomPosition,
+  {
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    destinations
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxRooms?: number;
+    maxOps?: number;
+    maxPathCost?: number;
+    destinations: { pos: RoomPosition; range: number }[];
+  }
+) {
+  const result = js_jps_portal_multiroom_path(
+    start.__packedPos,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    packDestinations(destinations)
+  );
+
+  return fromPackedPathResult(result);
+}
@@ -0,0 +1,66 @@
+import { fromPackedRoomName, packRoomName } from '../utils/fromPacked';
+import { MultiroomCompactDistanceMap } from '../wasm/screeps_clockwork';
+import { ClockworkCompactDistanceMap } from './compactDistanceMap';
+import { assertNotFreed, freeHandle } from './freeable';
+import { ClockworkMultiroomDistanceMap } from './multiroomDistanceMap';
+
+/**
+ * A distance map that covers multiple rooms, stored as 16-bit values to halve its
+ * memory. Created with `ClockworkMultiroomDistanceMap.toCompact`, and reads the
+ * same as the map it was created from.
+ */
+export class ClockworkMultiroomCompactDistanceMap {
+  private _map: MultiroomCompactDistanceMap | undefined;
+
+  constructor(map: MultiroomCompactDistanceMap) {
+    this._map = map;
+  }
+
+  /**
+   * Frees the underlying WASM distance map allocation.
+   */
+  free(): void {
+    this._map = freeHandle(this._map);
+  }
+
+  /**
+   * Get the stored value for a given position.
+   */
+  get(pos: RoomPosition): number {
+    return assertNotFreed(this._map, 'ClockworkMultiroomCompactDistanceMap').get(pos.__packedPos);
+  }
+
+  /**
+   * Get the compact distance map for a given room.
+   */
+  getRoom(room: string): ClockworkCompactDistanceMap | undefined {
+    const map = assertNotFreed(this._map, 'ClockworkMultiroomCompactDistanceMap').get_room(packRoomName(room));
+    return map ? new ClockworkCompactDistanceMap(map) : undefined;
+  }
+
+  /**
+   * List all the rooms covered by this distance map.
+   */
+  getRooms(): string[] {
+    return [...assertNotFreed(this._map, 'ClockworkMultiroomCompactDistanceMap').get_rooms()].map(room =>
+      fromPackedRoomName(room)
+    );
+  }
+
+  /**
+   * A view of a room's distances without copying, with the same caveats as
+   * `ClockworkCompactDistanceMap.view`.
+   */
+  viewRoom(room: string): Uint16Array | undefined {
+    return assertNotFreed(this._map, 'ClockworkMultiroomCompactDistanceMap').view_room(packRoomName(room));
+  }
+
+  /**
+   * Converts back to a full multiroom distance map.
+   */
+  toDistanceMap(): ClockworkMultiroomDistanceMap {
+    return new ClockworkMultiroomDistanceMap(
+      assertNotFreed(this._map, 'ClockworkMultiroomCompactDistanceMap').toDistanceMap()
+    );
+  }
+}
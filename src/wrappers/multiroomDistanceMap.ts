@@ -1,22 +1,38 @@
-import { fromPackedRoomName, packRoomName } from '../utils/fromPacked';
+import { fromPackedRoomName, fromPackedRoomNameCached, packRoomName } from '../utils/fromPacked';
 import {
+  ClockworkCostMatrix,
   DirectionOrder,
   js_path_to_multiroom_distance_map_origin,
   js_path_to_multiroom_distance_map_origin_with_portals,
   MultiroomDistanceMap,
+  MultiroomLazyFlowField,
   multiroomFlowField,
+  multiroomFlowFieldWithSecondaryCost,
   multiroomMonoFlowField,
+  multiroomMonoFlowFieldWithSecondaryCost,
   multiroomPortalFlowField,
   multiroomPortalMonoFlowField
 } from '../wasm/screeps_clockwork';
 import { ClockworkDistanceMap } from './distanceMap';
+import { ClockworkMultiroomCompactDistanceMap } from './multiroomCompactDistanceMap';
 import { ClockworkMultiroomFlowField } from './multiroomFlowField';
+import { ClockworkMultiroomLazyFlowField } from './multiroomLazyFlowField';
 import { ClockworkMultiroomMonoFlowField } from './multiroomMonoFlowField';
 import { ClockworkPath } from './path';
 import { assertNotFreed, freeHandle } from './freeable';
 
 export interface DirectionOrderOptions {
   directionOrder?: DirectionOrder;
+  /**
+   * Breaks ties in this order instead of `directionOrder`, such as an order from
+   * `seededDirectionOrder`.
+   */
+  customDirections?: DirectionConstant[];
+}
+
+export interface LazyFlowFieldOptions extends DirectionOrderOptions {
+  portals?: boolean;
+  memoize?: boolean;
 }
 
 const DEFAULT_DIRECTION_ORDER = DirectionOrder.CardinalFirst;
@@ -70,6 +86,17 @@ export class ClockworkMultiroomDistanceMap {
     );
   }
 
+  /**
+   * Convert to a compact distance map, which stores distances as 16-bit values to
+   * halve their memory. Throws if a distance is 65535 or more, which a `maxPathCost`
+   * below that rules out.
+   */
+  toCompact(): ClockworkMultiroomCompactDistanceMap {
+    return new ClockworkMultiroomCompactDistanceMap(
+      assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap').toCompact()
+    );
+  }
+
   /**
    * Path to the origin from a given position.
    * Pass `DirectionOrder.DiagonalFirst` to prefer diagonal steps when multiple neighbors are equally close.
@@ -79,7 +106,8 @@ export class ClockworkMultiroomDistanceMap {
       js_path_to_multiroom_distance_map_origin(
         start.__packedPos,
         assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
-        options.directionOrder ?? DEFAULT_DIRECTION_ORDER
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
       )
     );
   }
@@ -93,7 +121,8 @@ export class ClockworkMultiroomDistanceMap {
       js_path_to_multiroom_distance_map_origin_with_portals(
         start.__packedPos,
         assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
-        options.directionOrder ?? DEFAULT_DIRECTION_ORDER
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
       )
     );
   }
@@ -106,7 +135,8 @@ export class ClockworkMultiroomDistanceMap {
     return new ClockworkMultiroomFlowField(
       multiroomFlowField(
         assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
-        options.directionOrder ?? DEFAULT_DIRECTION_ORDER
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
       )
     );
   }
@@ -119,7 +149,27 @@ export class ClockworkMultiroomDistanceMap {
     return new ClockworkMultiroomFlowField(
       multiroomPortalFlowField(
         assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
-        options.directionOrder ?? DEFAULT_DIRECTION_ORDER
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
+      )
+    );
+  }
+
+  /**
+   * Flow field for this distance map that only keeps the directions leading to paths
+   * that are shortest first, and cheapest by `secondaryCostCallback` second, such as
+   * preferring roads. Rooms without a secondary cost matrix cost nothing.
+   */
+  toFlowFieldWithSecondaryCost(
+    secondaryCostCallback: (room: string) => ClockworkCostMatrix | undefined,
+    options: DirectionOrderOptions = {}
+  ): ClockworkMultiroomFlowField {
+    return new ClockworkMultiroomFlowField(
+      multiroomFlowFieldWithSecondaryCost(
+        assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
+        (room: number) => secondaryCostCallback(fromPackedRoomNameCached(room)),
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
       )
     );
   }
@@ -132,7 +182,8 @@ export class ClockworkMultiroomDistanceMap {
     return new ClockworkMultiroomMonoFlowField(
       multiroomMonoFlowField(
         assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
-        options.directionOrder ?? DEFAULT_DIRECTION_ORDER
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
       )
     );
   }
@@ -145,7 +196,47 @@ export class ClockworkMultiroomDistanceMap {
     return new ClockworkMultiroomMonoFlowField(
       multiroomPortalMonoFlowField(
         assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
-        options.directionOrder ?? DEFAULT_DIRECTION_ORDER
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
+      )
+    );
+  }
+  /**
+   * Mono-directional flow field for this distance map, following paths that are
+   * shortest first and cheapest by `secondaryCostCallback` second.
+   */
+  toMonoFlowFieldWithSecondaryCost(
+    secondaryCostCallback: (room: string) => ClockworkCostMatrix | undefined,
+    options: DirectionOrderOptions = {}
+  ): ClockworkMultiroomMonoFlowField {
+    return new ClockworkMultiroomMonoFlowField(
+      multiroomMonoFlowFieldWithSecondaryCost(
+        assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap'),
+        (room: number) => secondaryCostCallback(fromPackedRoomNameCached(room)),
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.customDirections
+      )
+    );
+  }
+
+  /**
+   * Lazy flow field for this distance map, which works out each tile's directions
+   * only when they're read. Pass `portals: true` to follow the currently configured
+   * portals, and `memoize: true` to keep computed tiles.
+   *
+   * The flow field takes this distance map over, so it can't be used afterwards;
+   * freeing the flow field frees the distance map too.
+   */
+  toLazyFlowField(options: LazyFlowFieldOptions = {}): ClockworkMultiroomLazyFlowField {
+    const map = assertNotFreed(this._map, 'ClockworkMultiroomDistanceMap');
+    this._map = undefined;
+    return new ClockworkMultiroomLazyFlowField(
+      new MultiroomLazyFlowField(
+        map,
+        options.directionOrder ?? DEFAULT_DIRECTION_ORDER,
+        options.portals,
+        options.memoize,
+        options.customDirections
       )
     );
   }
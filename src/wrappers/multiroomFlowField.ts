@@ -1,8 +1,10 @@
-import { fromPackedRoomName, packRoomName } from '../utils/fromPacked';
+import { fromPackedRoomName, fromPackedRoomNameCached, packRoomName } from '../utils/fromPacked';
 import {
+  ClockworkCostMatrix,
   MultiroomFlowField,
   js_path_to_multiroom_flow_field_origin,
-  js_path_to_multiroom_flow_field_origin_with_portals
+  js_path_to_multiroom_flow_field_origin_with_portals,
+  js_path_to_multiroom_flow_field_origin_with_secondary_cost
 } from '../wasm/screeps_clockwork';
 import { ClockworkFlowField } from './flowField';
 import { assertNotFreed, freeHandle } from './freeable';
@@ -102,4 +104,21 @@ export class ClockworkMultiroomFlowField {
       )
     );
   }
+  /**
+   * Find a path from a given position to the origin of the flow field, choosing
+   * between the field's directions by `secondaryCostCallback`, such as preferring
+   * roads. Ties still go to the field's direction order.
+   */
+  pathToOriginWithSecondaryCost(
+    start: RoomPosition,
+    secondaryCostCallback: (room: string) => ClockworkCostMatrix | undefined
+  ): ClockworkPath {
+    return new ClockworkPath(
+      js_path_to_multiroom_flow_field_origin_with_secondary_cost(
+        start.__packedPos,
+        assertNotFreed(this._flowField, 'ClockworkMultiroomFlowField'),
+        (room: number) => secondaryCostCallback(fromPackedRoomNameCached(room))
+      )
+    );
+  }
 }
@@ -0,0 +1,46 @@
+import { fromPackedRoomName, packRoomName } from '../utils/fromPacked';
+import { MultiroomLabelMap } from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+
+/**
+ * A label (such as the origin that owns a tile) for tiles across multiple rooms.
+ * Typically returned by `voronoiMultiroomDistanceMap` rather than created directly.
+ * Unlabeled tiles read as `0xFFFFFFFF`.
+ */
+export class ClockworkMultiroomLabelMap {
+  private _map: MultiroomLabelMap | undefined;
+
+  constructor(map: MultiroomLabelMap) {
+    this._map = map;
+  }
+
+  /**
+   * Frees the underlying WASM label map allocation.
+   */
+  free(): void {
+    this._map = freeHandle(this._map);
+  }
+
+  /**
+   * Get the label for a given position.
+   */
+  get(pos: RoomPosition): number {
+    return assertNotFreed(this._map, 'ClockworkMultiroomLabelMap').get(pos.__packedPos);
+  }
+
+  /**
+   * Get the labels for a given room as a flat array, indexed like a cost matrix.
+   */
+  getRoom(room: string): Uint32Array | undefined {
+    return assertNotFreed(this._map, 'ClockworkMultiroomLabelMap').get_room(packRoomName(room));
+  }
+
+  /**
+   * List all the rooms covered by this label map.
+   */
+  getRooms(): string[] {
+    return [...assertNotFreed(this._map, 'ClockworkMultiroomLabelMap').get_rooms()].map(room =>
+      fromPackedRoomName(room)
+    );
+  }
+}
@@ -0,0 +1,91 @@
+import { fromPackedRoomName, packRoomName } from '../utils/fromPacked';
+import { MultiroomLazyFlowField } from '../wasm/screeps_clockwork';
+import { ClockworkFlowField } from './flowField';
+import { assertNotFreed, freeHandle } from './freeable';
+
+/**
+ * A flow field that spans multiple rooms and works out each tile's directions from
+ * its distance map only when they're read, so a creep following a route only pays
+ * for the tiles it actually stands on. Typically created with
+ * `ClockworkMultiroomDistanceMap.toLazyFlowField`.
+ */
+export class ClockworkMultiroomLazyFlowField {
+  private _flowField: MultiroomLazyFlowField | undefined;
+
+  constructor(flowField: MultiroomLazyFlowField) {
+    this._flowField = flowField;
+  }
+
+  /**
+   * Frees the underlying WASM lazy flow field allocation, including its distance map.
+   */
+  free(): void {
+    this._flowField = freeHandle(this._flowField);
+  }
+
+  /**
+   * Get the flow field value at a given position.
+   */
+  get(pos: RoomPosition): number {
+    return assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').get(pos.__packedPos);
+  }
+
+  /**
+   * Set the flow field value at a given position. Set values take precedence over
+   * computed ones.
+   */
+  set(pos: RoomPosition, value: number): void {
+    assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').set(pos.__packedPos, value);
+  }
+
+  /**
+   * Get the list of valid directions at a given position.
+   */
+  getDirections(pos: RoomPosition): DirectionConstant[] {
+    return assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').getDirections(pos.__packedPos);
+  }
+
+  /**
+   * Get the first valid direction at a given position.
+   */
+  getFirstDirection(pos: RoomPosition): DirectionConstant | null {
+    return (
+      assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').getFirstDirection(pos.__packedPos) ?? null
+    );
+  }
+
+  /**
+   * Set the list of valid directions at a given position.
+   */
+  setDirections(pos: RoomPosition, directions: DirectionConstant[]): void {
+    assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').setDirections(pos.__packedPos, directions);
+  }
+
+  /**
+   * Add a direction to the list of valid directions at a given position.
+   */
+  addDirection(pos: RoomPosition, direction: DirectionConstant): void {
+    assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').addDirection(pos.__packedPos, direction);
+  }
+
+  /**
+   * Get the list of rooms in the flow field.
+   */
+  getRooms(): string[] {
+    const rooms = [];
+    for (const packedRoomName of assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').getRooms()) {
+      rooms.push(fromPackedRoomName(packedRoomName));
+    }
+    return rooms;
+  }
+
+  /**
+   * Get the flow field for a given room, computing every tile in it.
+   */
+  getRoom(roomName: string): ClockworkFlowField | null {
+    const flowField = assertNotFreed(this._flowField, 'ClockworkMultiroomLazyFlowField').getRoom(
+      packRoomName(roomName)
+    );
+    return flowField ? new ClockworkFlowField(flowField) : null;
+  }
+}
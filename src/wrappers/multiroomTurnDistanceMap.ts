@@ -0,0 +1,51 @@
+import { MultiroomTurnDistanceMap, js_path_to_turn_distance_map_origin } from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { ClockworkPath } from './path';
+
+/**
+ * Distances from a search with a `turnPenalty`, kept separately for each direction
+ * a tile can be entered from. Returned as a search result's `turnDistanceMap`
+ * rather than created directly.
+ */
+export class ClockworkMultiroomTurnDistanceMap {
+  private _map: MultiroomTurnDistanceMap | undefined;
+
+  constructor(map: MultiroomTurnDistanceMap) {
+    this._map = map;
+  }
+
+  /**
+   * Frees the underlying WASM turn distance map allocation.
+   */
+  free(): void {
+    this._map = freeHandle(this._map);
+  }
+
+  /**
+   * Get the distance to a position entered moving in `direction`, away from the
+   * start, or to a start position if `direction` is omitted.
+   */
+  get(pos: RoomPosition, direction?: DirectionConstant): number {
+    return assertNotFreed(this._map, 'ClockworkMultiroomTurnDistanceMap').get(pos.__packedPos, direction);
+  }
+
+  /**
+   * The extra cost charged each time a path changes direction.
+   */
+  get turnPenalty(): number {
+    return assertNotFreed(this._map, 'ClockworkMultiroomTurnDistanceMap').turn_penalty;
+  }
+
+  /**
+   * Find the path the search charged for from its start to a given position. The
+   * path runs from `target` back to the start.
+   */
+  pathToOrigin(target: RoomPosition): ClockworkPath {
+    return new ClockworkPath(
+      js_path_to_turn_distance_map_origin(
+        target.__packedPos,
+        assertNotFreed(this._map, 'ClockworkMultiroomTurnDistanceMap')
+      )
+    );
+  }
+}
@@ -0,0 +1,57 @@
+import {
+  MultiroomMonoFlowField,
+  js_path_to_parent_tree_origin,
+  js_path_to_parent_tree_origin_with_portals
+} from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { ClockworkPath } from './path';
+
+/**
+ * The parent tree recorded by a search with `recordParents`. Each tile the search
+ * reached points back toward the start it was reached from, so walking it can't
+ * cycle or stall the way descending a distance map can.
+ */
+export class ClockworkParentTree {
+  private _parents: MultiroomMonoFlowField | undefined;
+
+  constructor(parents: MultiroomMonoFlowField) {
+    this._parents = parents;
+  }
+
+  /**
+   * Frees the underlying WASM parent tree allocation.
+   */
+  free(): void {
+    this._parents = freeHandle(this._parents);
+  }
+
+  /**
+   * Get the direction from a given position toward its parent, or null for a
+   * start position or a tile the search didn't reach.
+   */
+  get(pos: RoomPosition): DirectionConstant | null {
+    return assertNotFreed(this._parents, 'ClockworkParentTree').get(pos.__packedPos) ?? null;
+  }
+
+  /**
+   * Find the path the search took from its start to a given position. The path
+   * runs from `target` back to the start.
+   */
+  pathToOrigin(target: RoomPosition): ClockworkPath {
+    return new ClockworkPath(
+      js_path_to_parent_tree_origin(target.__packedPos, assertNotFreed(this._parents, 'ClockworkParentTree'))
+    );
+  }
+
+  /**
+   * Like `pathToOrigin`, for the parent tree of a portal-aware search.
+   */
+  pathToOriginWithPortals(target: RoomPosition): ClockworkPath {
+    return new ClockworkPath(
+      js_path_to_parent_tree_origin_with_portals(
+        target.__packedPos,
+        assertNotFreed(this._parents, 'ClockworkParentTree')
+      )
+    );
+  }
+}
@@ -0,0 +1,20 @@
+import { PathResult } from '../wasm/screeps_clockwork';
+import { ClockworkPath } from './path';
+
+export function fromPackedPathResult(result: PathResult) {
+  try {
+    const path = new ClockworkPath(result.path);
+    const cost = result.cost;
+    const found = result.found;
+    const ops = result.ops;
+
+    return {
+      path,
+      cost,
+      found,
+      ops
+    };
+  } finally {
+    result.free();
+  }
+}
@@ -0,0 +1,58 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomName, fromPackedRoomNameCached, packRoomName } from '../utils/fromPacked';
+import { js_find_portal_room_route, js_find_room_route } from '../wasm/screeps_clockwork';
+
+/**
+ * Returns the cost of entering `roomName` from `fromRoomName`: `Infinity` to
+ * forbid the room, or `undefined` to use the default cost of 1.
+ */
+export type RoomRouteCallback = (roomName: string, fromRoomName: string) => number | undefined;
+
+function packRouteCallback(roomCallback: RoomRouteCallback | undefined) {
+  if (!roomCallback) {
+    return undefined;
+  }
+  return (room: number, from: number) => roomCallback(fromPackedRoomNameCached(room), fromPackedRoomNameCached(from));
+}
+
+/**
+ * Find the cheapest sequence of rooms from one room to another, like
+ * `Game.map.findRoute`. Pass the route to a search's `allowedRooms` to keep
+ * the search to those rooms.
+ *
+ * @param from - The starting room.
+ * @param to - The destination room.
+ * @param options - `roomCallback` works like `findRoute`'s `routeCallback`; at most `maxRooms` rooms are explored.
+ * @returns The rooms in order, starting with `from` and ending with `to`, or `undefined` if no route was found.
+ */
+export function findRoomRoute(
+  from: string,
+  to: string,
+  { roomCallback, maxRooms = MAX_USIZE }: { roomCallback?: RoomRouteCallback; maxRooms?: number } = {}
+): string[] | undefined {
+  const route = js_find_room_route(packRoomName(from), packRoomName(to), packRouteCallback(roomCallback), maxRooms);
+  return route ? [...route].map(room => fromPackedRoomName(room)) : undefined;
+}
+
+/**
+ * Portal-aware variant of `findRoomRoute`: rooms with configured portals also
+ * lead to the rooms their portals exit into.
+ *
+ * @param from - The starting room.
+ * @param to - The destination room.
+ * @param options - `roomCallback` works like `findRoute`'s `routeCallback`; at most `maxRooms` rooms are explored.
+ * @returns The rooms in order, starting with `from` and ending with `to`, or `undefined` if no route was found.
+ */
+export function findPortalRoomRoute(
+  from: string,
+  to: string,
+  { roomCallback, maxRooms = MAX_USIZE }: { roomCallback?: RoomRouteCallback; maxRooms?: number } = {}
+): string[] | undefined {
+  const route = js_find_portal_room_route(
+    packRoomName(from),
+    packRoomName(to),
+    packRouteCallback(roomCallback),
+    maxRooms
+  );
+  return route ? [...route].map(room => fromPackedRoomName(room)) : undefined;
+}
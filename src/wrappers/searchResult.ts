@@ -1,6 +1,30 @@
 import { fromPacked } from '../utils/fromPacked';
 import { SearchResult } from '../wasm/screeps_clockwork';
 import { ClockworkMultiroomDistanceMap } from './multiroomDistanceMap';
+import { ClockworkMultiroomTurnDistanceMap } from './multiroomTurnDistanceMap';
+import { ClockworkParentTree } from './parentTree';
+
+/**
+ * A tile where a search reached one or more of its destinations. `destinations`
+ * holds indices into `allOfDestinations` if `allOf` is set, or `anyOfDestinations`
+ * otherwise. `origin` is the label of the start the path came from, if starts
+ * were labeled.
+ */
+export interface SearchTarget {
+  pos: RoomPosition;
+  cost: number;
+  destinations: number[];
+  allOf: boolean;
+  origin?: number;
+}
+
+interface PackedSearchTarget {
+  position: number;
+  cost: number;
+  destinations: number[];
+  allOf: boolean;
+  origin?: number;
+}
 
 export function fromPackedSearchResult(result: SearchResult) {
   try {
@@ -8,13 +32,25 @@ export function fromPackedSearchResult(result: SearchResult) {
       acc.push(fromPacked(pos));
       return acc;
     }, [] as RoomPosition[]);
+    const targets = (result.targets as PackedSearchTarget[]).map(
+      ({ position, ...target }): SearchTarget => ({ pos: fromPacked(position), ...target })
+    );
     const distanceMap = new ClockworkMultiroomDistanceMap(result.distance_map);
     const ops = result.ops;
+    const suboptimalityBound = result.suboptimality_bound;
+    const packedParents = result.parents;
+    const parents = packedParents && new ClockworkParentTree(packedParents);
+    const packedTurnDistanceMap = result.turn_distance_map;
+    const turnDistanceMap = packedTurnDistanceMap && new ClockworkMultiroomTurnDistanceMap(packedTurnDistanceMap);
 
     return {
       distanceMap,
       foundTargets,
-      ops
+      targets,
+      ops,
+      suboptimalityBound,
+      parents,
+      turnDistanceMap
     };
   } finally {
     result.free();
@@ -0,0 +1,177 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached } from '../utils/fromPacked';
+import { packDestinations } from '../utils/packedArrays';
+import {
+  ClockworkCostMatrix,
+  js_space_time_astar_multiroom_path,
+  js_space_time_astar_portal_multiroom_path,
+  ReservationTable
+} from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { fromPackedPathResult } from './pathResult';
+
+/**
+ * Records which agent will occupy each tile at each tick, so that several creeps
+ * can plan paths that don't collide. Agents are identified by any number the
+ * caller chooses, and ticks are usually `Game.time`.
+ */
+export class ClockworkReservationTable {
+  private _table: ReservationTable | undefined;
+
+  constructor() {
+    this._table = new ReservationTable();
+  }
+
+  /**
+   * Frees the underlying WASM reservation table allocation.
+   */
+  free(): void {
+    this._table = freeHandle(this._table);
+  }
+
+  /**
+   * Reserve `pos` at `tick` for `agent`. Returns false (and leaves the reservation
+   * alone) if another agent already holds it.
+   */
+  reserve(pos: RoomPosition, tick: number, agent: number): boolean {
+    return assertNotFreed(this._table, 'ClockworkReservationTable').reserve(pos.__packedPos, tick, agent);
+  }
+
+  /**
+   * The agent holding `pos` at `tick`, if any.
+   */
+  reservedBy(pos: RoomPosition, tick: number): number | undefined {
+    return assertNotFreed(this._table, 'ClockworkReservationTable').reservedBy(pos.__packedPos, tick);
+  }
+
+  /**
+   * Drop every reservation held by `agent`, e.g. when the creep dies.
+   */
+  releaseAgent(agent: number): void {
+    assertNotFreed(this._table, 'ClockworkReservationTable').releaseAgent(agent);
+  }
+
+  /**
+   * Drop every reservation before `tick`.
+   */
+  releaseBefore(tick: number): void {
+    assertNotFreed(this._table, 'ClockworkReservationTable').releaseBefore(tick);
+  }
+
+  /**
+   * Drop every reservation.
+   */
+  clear(): void {
+    assertNotFreed(this._table, 'ClockworkReservationTable').clear();
+  }
+
+  /**
+   * The number of reservations held.
+   */
+  get size(): number {
+    return assertNotFreed(this._table, 'ClockworkReservationTable').size;
+  }
+
+  /**
+   * The underlying WASM reservation table, for passing to the search functions.
+   */
+  get handle(): ReservationTable {
+    return assertNotFreed(this._table, 'ClockworkReservationTable');
+  }
+}
+
+interface SpaceTimeOptions {
+  agent: number;
+  startTick: number;
+  reservations: ClockworkReservationTable;
+  window: number;
+  costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+  maxRooms?: number;
+  maxOps?: number;
+  maxPathCost?: number;
+  destinations: { pos: RoomPosition; range: number }[];
+}
+
+/**
+ * Find a path for `agent` that avoids tiles other agents have reserved, waiting in
+ * place when that's cheaper than going around. Each move or wait takes one tick,
+ * starting at `startTick`; waiting costs 1.
+ *
+ * Reservations are only respected for `window` ticks, after which the search carries
+ * on as plain A*. The agent's previous reservations are released before searching,
+ * and the found path is written back to `reservations` for the window.
+ *
+ * Waits appear in the path as repeated positions, so the path's index is the tick
+ * offset from `startTick` (room edge and portal crossings add their step tile).
+ *
+ * @param start - The starting position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether a destination was reached, and the ops used.
+ */
+export function spaceTimeAstarMultiroomPath(
+  start: RoomPosition,
+  {
+    agent,
+    startTick,
+    reservations,
+    window,
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    destinations
+  }: SpaceTimeOptions
+) {
+  const result = js_space_time_astar_multiroom_path(
+    start.__packedPos,
+    startTick,
+    agent,
+    reservations.handle,
+    window,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    packDestinations(destinations)
+  );
+
+  return fromPackedPathResult(result);
+}
+
+/**
+ * Portal-aware variant of `spaceTimeAstarMultiroomPath`: stepping onto a portal
+ * entrance lands on its paired exit.
+ *
+ * @param start - The starting position.
+ * @param options - The options for the search.
+ * @returns The path, its cost, whether a destination was reached, and the ops used.
+ */
+export function spaceTimeAstarPortalMultiroomPath(
+  start: RoomPosition,
+  {
+    agent,
+    startTick,
+    reservations,
+    window,
+    costMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    destinations
+  }: SpaceTimeOptions
+) {
+  const result = js_space_time_astar_portal_multiroom_path(
+    start.__packedPos,
+    startTick,
+    agent,
+    reservations.handle,
+    window,
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxRooms,
+    maxOps,
+    maxPathCost,
+    packDestinations(destinations)
+  );
+
+  return fromPackedPathResult(result);
+}
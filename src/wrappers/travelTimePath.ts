@@ -0,0 +1,118 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPackedRoomNameCached } from '../utils/fromPacked';
+import { packDestinations } from '../utils/packedArrays';
+import {
+  ClockworkCostMatrix,
+  CreepBody,
+  js_travel_time_multiroom_path,
+  js_travel_time_portal_multiroom_path
+} from '../wasm/screeps_clockwork';
+import { fromPackedPathResult } from './pathResult';
+
+/**
+ * The parts of a creep's body that matter for movement. `moveBoost` is the boost
+ * tier of the MOVE parts: 0 (the default) for unboosted, or 1-3.
+ */
+export interface CreepBodyParts {
+  moveParts: number;
+  loadedCarryParts?: number;
+  emptyCarryParts?: number;
+  otherParts?: number;
+  moveBoost?: number;
+}
+
+interface TravelTimeOptions {
+  body: CreepBodyParts;
+  fatigueMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+  maxRooms?: number;
+  maxOps?: number;
+  maxTicks?: number;
+  destinations: { pos: RoomPosition; range: number }[];
+}
+
+function toCreepBody({
+  moveParts,
+  loadedCarryParts = 0,
+  emptyCarryParts = 0,
+  otherParts = 0,
+  moveBoost
+}: CreepBodyParts) {
+  return new CreepBody(moveParts, loadedCarryParts, emptyCarryParts, otherParts, moveBoost);
+}
+
+/**
+ * Find the path that gets a creep with the given body to any of the destinations
+ * in the fewest ticks.
+ *
+ * `fatigueMatrixCallback` returns, for each tile, the fatigue each part generates
+ * when moving onto it: 1 for roads, 2 for plains, 10 for swamps, or 255 if
+ * impassable. The result's `cost` is the number of ticks until the creep arrives,
+ * assuming it starts with no fatigue.
+ *
+ * @param start - The starting position.
+ * @param options - The options for the search.
+ * @returns The path, its cost in ticks, whether a destination was reached, and the ops used.
+ */
+export function travelTimeMultiroomPath(
+  start: RoomPosition,
+  {
+    body,
+    fatigueMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxTicks = MAX_USIZE,
+    destinations
+  }: TravelTimeOptions
+) {
+  const creepBody = toCreepBody(body);
+  try {
+    const result = js_travel_time_multiroom_path(
+      start.__packedPos,
+      creepBody,
+      (room: number) => fatigueMatrixCallback(fromPackedRoomNameCached(room)),
+      maxRooms,
+      maxOps,
+      maxTicks,
+      packDestinations(destinations)
+    );
+    return fromPackedPathResult(result);
+  } finally {
+    creepBody.free();
+  }
+}
+
+/**
+ * Portal-aware variant of `travelTimeMultiroomPath`: stepping onto a portal
+ * entrance lands on its paired exit.
+ *
+ * @param start - The starting position.
+ * @param options - The options for the search.
+ * @returns The path, its cost in ticks, whether a destination was reached, and the ops used.
+ */
+export function travelTimePortalMultiroomPath(
+  start: RoomPosition,
+  {
+    body,
+    fatigueMatrixCallback,
+    maxRooms = MAX_USIZE,
+    maxOps = MAX_USIZE,
+    maxTicks = MAX_USIZE,
+    destinations
+  }: TravelTimeOptions
+) {
+  const creepBody = toCreepBody(body);
+  try {
+    const result = js_travel_time_portal_multiroom_path(
+      start.__packedPos,
+      creepBody,
+      (room: number) => fatigueMatrixCallback(fromPackedRoomNameCached(room)),
+      maxRooms,
+      maxOps,
+      maxTicks,
+      packDestinations(destinations)
+    );
+    return fromPackedPathResult(result);
+  } finally {
+    creepBody.free();
+  }
+}
@@ -0,0 +1,115 @@
+import { MAX_USIZE } from '../utils/constants';
+import { fromPacked, fromPackedRoomNameCached } from '../utils/fromPacked';
+import { packPositions } from '../utils/packedArrays';
+import { ClockworkCostMatrix, js_voronoi_multiroom_distance_map, VoronoiResult } from '../wasm/screeps_clockwork';
+import { assertNotFreed, freeHandle } from './freeable';
+import { ClockworkMultiroomDistanceMap } from './multiroomDistanceMap';
+import { ClockworkMultiroomLabelMap } from './multiroomLabelMap';
+
+/**
+ * A distance map from several origins, plus the origin that owns each tile.
+ * Returned by `voronoiMultiroomDistanceMap`.
+ */
+export class ClockworkVoronoiResult {
+  private _result: VoronoiResult | undefined;
+
+  constructor(result: VoronoiResult) {
+    this._result = result;
+  }
+
+  /**
+   * Frees the underlying WASM result allocation.
+   */
+  free(): void {
+    this._result = freeHandle(this._result);
+  }
+
+  /**
+   * A copy of the distance to the nearest origin for each tile.
+   */
+  get distanceMap(): ClockworkMultiroomDistanceMap {
+    return new ClockworkMultiroomDistanceMap(assertNotFreed(this._result, 'ClockworkVoronoiResult').distance_map);
+  }
+
+  /**
+   * A copy of the label of the origin that owns each tile.
+   */
+  get labels(): ClockworkMultiroomLabelMap {
+    return new ClockworkMultiroomLabelMap(assertNotFreed(this._result, 'ClockworkVoronoiResult').labels);
+  }
+
+  /**
+   * The number of tiles explored.
+   */
+  get ops(): number {
+    return assertNotFreed(this._result, 'ClockworkVoronoiResult').ops;
+  }
+
+  /**
+   * The positions owned by the origin with this label.
+   */
+  territory(label: number): RoomPosition[] {
+    return [...assertNotFreed(this._result, 'ClockworkVoronoiResult').territory(label)].map(pos => fromPacked(pos));
+  }
+
+  /**
+   * Owned tiles next to a tile owned by a different origin. Both sides of a border
+   * are included.
+   */
+  boundaryTiles(): RoomPosition[] {
+    return [...assertNotFreed(this._result, 'ClockworkVoronoiResult').boundaryTiles()].map(pos => fromPacked(pos));
+  }
+}
+
+/**
+ * Create a distance map from several origins with Dijkstra's algorithm, labeling
+ * each tile with the origin it's closest to. Origins are labeled by their index
+ * in `start` unless `startLabels` is given; ties go to the lowest label.
+ *
+ * This calculates a distance map across multiple rooms, with a few configurable limits:
+ * - `maxOps`: The maximum number of pathfinding operations to perform.
+ * - `maxRooms`: The maximum number of rooms to explore.
+ * - `maxPathCost`: Don't explore tiles with a greater path cost than this.
+ *
+ * `startCosts`, if given, holds a head start (initial cost) for each origin.
+ *
+ * At least one of these limits must be set.
+ *
+ * @param start - The origins.
+ * @param options - The options for the distance map.
+ * @returns The distance map and the label of the origin that owns each tile.
+ */
+export function voronoiMultiroomDistanceMap(
+  start: RoomPosition[],
+  {
+    costMatrixCallback,
+    maxOps = MAX_USIZE,
+    maxRooms = MAX_USIZE,
+    maxPathCost = MAX_USIZE,
+    startCosts,
+    startLabels
+  }: {
+    costMatrixCallback: (room: string) => ClockworkCostMatrix | undefined;
+    maxOps?: number;
+    maxRooms?: number;
+    maxPathCost?: number;
+    startCosts?: number[];
+    startLabels?: number[];
+  }
+) {
+  if ([maxOps, maxRooms, maxPathCost].every(n => n === MAX_USIZE)) {
+    throw new Error('At least one of maxOps, maxRooms, or maxPathCost must be set');
+  }
+
+  const result = js_voronoi_multiroom_distance_map(
+    packPositions(start),
+    (room: number) => costMatrixCallback(fromPackedRoomNameCached(room)),
+    maxOps,
+    maxRooms,
+    maxPathCost,
+    startCosts && Uint32Array.from(startCosts),
+    startLabels && Uint32Array.from(startLabels)
+  );
+
+  return new ClockworkVoronoiResult(result);
+}
@@ -387,4 +387,61 @@ describe('astarMultiroomDistanceMap', () => {
 
     expect(searchResult.foundTargets.every(pos => pos.roomName === 'W1N2')).toBe(true);
   });
+
+  it('should bound the cost of a weighted search', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    for (let y = 5; y < 45; y++) {
+      costMatrix.set(25, y, 255);
+    }
+    const target = new RoomPosition(30, 25, 'W1N1');
+    const weighted = astarMultiroomDistanceMap([new RoomPosition(20, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }],
+      heuristicWeight: 1.5
+    });
+    const optimal = astarMultiroomDistanceMap([new RoomPosition(20, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }]
+    });
+
+    expect(optimal.suboptimalityBound).toBe(1);
+    expect(weighted.suboptimalityBound).toBeLessThan(1.5 + Number.EPSILON);
+    expect(weighted.distanceMap.get(target)).toBeLessThan(optimal.distanceMap.get(target) * 1.5 + 1);
+  });
+
+  it('should reject heuristic weights that bound nothing', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    for (const heuristicWeight of [0.5, NaN, Infinity]) {
+      expect(() =>
+        astarMultiroomDistanceMap([new RoomPosition(20, 25, 'W1N1')], {
+          costMatrixCallback: () => costMatrix,
+          maxRooms: 1,
+          anyOfDestinations: [{ pos: new RoomPosition(30, 25, 'W1N1'), range: 0 }],
+          heuristicWeight
+        })
+      ).toThrow(/^Invalid heuristic weight/);
+    }
+  });
+
+  it('should report which destinations each target satisfied', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const near = new RoomPosition(15, 10, 'W1N1');
+    const far = new RoomPosition(40, 40, 'W1N1');
+    const { targets } = astarMultiroomDistanceMap([new RoomPosition(10, 10, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [
+        { pos: far, range: 0 },
+        { pos: near, range: 1 }
+      ]
+    });
+
+    expect(targets).toHaveLength(1);
+    expect(targets[0].pos.getRangeTo(near)).toBe(1);
+    expect(targets[0].cost).toBe(4);
+    expect(targets[0].destinations).toEqual([1]);
+    expect(targets[0].allOf).toBe(false);
+  });
 });
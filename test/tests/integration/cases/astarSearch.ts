@@ -0,0 +1,45 @@
+import { ClockworkAstarSearch, ClockworkCostMatrix, astarMultiroomDistanceMap } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+const start = new RoomPosition(5, 5, 'W1N1');
+const target = new RoomPosition(45, 45, 'W1N1');
+
+describe('ClockworkAstarSearch', () => {
+  it('should match a one-shot search when stepped to completion', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const search = new ClockworkAstarSearch([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }]
+    });
+    let steps = 0;
+    while (!search.step(10)) {
+      steps++;
+    }
+    expect(steps).toBeGreaterThan(0);
+    expect(search.finished).toBe(true);
+    const stepped = search.finalize();
+    search.free();
+
+    const oneShot = astarMultiroomDistanceMap([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }]
+    });
+    expect(stepped.distanceMap.get(target)).toBe(oneShot.distanceMap.get(target));
+    expect(stepped.foundTargets).toHaveLength(1);
+  });
+
+  it('should throw when finalized after being cancelled', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const search = new ClockworkAstarSearch([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }]
+    });
+    search.cancel();
+    expect(search.finished).toBe(true);
+    expect(() => search.finalize()).toThrow('Search has already been finalized or cancelled');
+    search.free();
+  });
+});
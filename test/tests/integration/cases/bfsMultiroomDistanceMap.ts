@@ -165,4 +165,20 @@ describe('bfsMultiroomDistanceMap', () => {
 
     expect(searchResult.foundTargets.every(pos => pos.roomName === 'W1N2')).toBe(true);
   });
+
+  it('should report the labeled start each target was reached from', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const target = new RoomPosition(40, 25, 'W1N1');
+    const { targets } = bfsMultiroomDistanceMap([new RoomPosition(10, 25, 'W1N1'), new RoomPosition(30, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }],
+      startCosts: [0, 25],
+      startLabels: [7, 8]
+    });
+
+    expect(targets).toHaveLength(1);
+    expect(targets[0].origin).toBe(7);
+    expect(targets[0].cost).toBe(30);
+  });
 });
@@ -0,0 +1,30 @@
+import {
+  ClockworkCostMatrix,
+  bidirectionalAstarMultiroomPath,
+  dijkstraMultiroomDistanceMap
+} from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('bidirectionalAstarMultiroomPath', () => {
+  it('should find an optimal path around an obstacle', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    for (let y = 5; y < 45; y++) {
+      costMatrix.set(25, y, 255);
+    }
+    const start = new RoomPosition(20, 25, 'W1N1');
+    const goal = new RoomPosition(30, 25, 'W1N1');
+    const { path, cost, found } = bidirectionalAstarMultiroomPath(start, goal, {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    });
+    const reference = dijkstraMultiroomDistanceMap([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    }).distanceMap;
+    expect(found).toBe(true);
+    expect(cost).toBe(reference.get(goal));
+    expect(path.get(0).isEqualTo(start)).toBe(true);
+    expect(path.get(path.length - 1).isEqualTo(goal)).toBe(true);
+    path.free();
+  });
+});
@@ -0,0 +1,27 @@
+import { ClockworkCostMatrix, dijkstraMultiroomDistanceMap } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+const UNREACHABLE = 0xffffffff;
+
+describe('ClockworkMultiroomCompactDistanceMap', () => {
+  it('should read the same as the distance map it was created from', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const distanceMap = dijkstraMultiroomDistanceMap([new RoomPosition(25, 25, 'W1N1')], {
+      costMatrixCallback: roomName => (roomName === 'W1N1' ? costMatrix : undefined),
+      maxRooms: 1
+    }).distanceMap;
+    const compact = distanceMap.toCompact();
+
+    expect(compact.get(new RoomPosition(30, 30, 'W1N1'))).toBe(distanceMap.get(new RoomPosition(30, 30, 'W1N1')));
+    expect(compact.get(new RoomPosition(25, 25, 'W1N2'))).toBe(UNREACHABLE);
+    expect(compact.getRooms()).toEqual(['W1N1']);
+    expect(compact.viewRoom('W1N1')![30 * 50 + 30]).toBe(5);
+
+    const roundTrip = compact.toDistanceMap();
+    expect(roundTrip.get(new RoomPosition(1, 1, 'W1N1'))).toBe(24);
+
+    roundTrip.free();
+    compact.free();
+    distanceMap.free();
+  });
+});
@@ -162,4 +162,104 @@ describe('dijkstraMultiroomDistanceMap', () => {
 
     expect(searchResult.foundTargets.every(pos => pos.roomName === 'W1N2')).toBe(true);
   });
+
+  it('should stop between the minimum and maximum range of a destination', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const target = new RoomPosition(25, 25, 'W1N1');
+    const { foundTargets } = dijkstraMultiroomDistanceMap([new RoomPosition(26, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 3, minRange: 3 }]
+    });
+    expect(foundTargets).toHaveLength(1);
+    expect(foundTargets[0].getRangeTo(target)).toBe(3);
+  });
+
+  it('should reject destination ranges that cannot be packed', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const target = new RoomPosition(25, 25, 'W1N1');
+    for (const destination of [
+      { pos: target, range: 2, minRange: 3 },
+      { pos: target, range: 0x10000 },
+      { pos: target, range: -1 },
+      { pos: target, range: 1.5 }
+    ]) {
+      expect(() =>
+        dijkstraMultiroomDistanceMap([new RoomPosition(26, 25, 'W1N1')], {
+          costMatrixCallback: () => costMatrix,
+          maxRooms: 1,
+          anyOfDestinations: [destination]
+        })
+      ).toThrow(/^Invalid destination range/);
+    }
+  });
+
+  it('should charge the tile a room crossing lands on', () => {
+    const plains = new ClockworkCostMatrix(1);
+    // W1N2 is north of W1N1, so leaving W1N1 from y = 0 lands on y = 49
+    const swampEdge = new ClockworkCostMatrix(1);
+    for (let x = 0; x < 50; x++) {
+      swampEdge.set(x, 49, 5);
+    }
+    const options = {
+      costMatrixCallback: (roomName: string) => (roomName === 'W1N2' ? swampEdge : plains),
+      maxOps: 10000,
+      allowedRooms: ['W1N1', 'W1N2']
+    };
+    const start = [new RoomPosition(25, 1, 'W1N1')];
+    const landing = new RoomPosition(25, 49, 'W1N2');
+
+    const charged = dijkstraMultiroomDistanceMap(start, options).distanceMap;
+    const uncharged = dijkstraMultiroomDistanceMap(start, { ...options, landingTileCosts: false }).distanceMap;
+    expect(charged.get(landing)).toBe(5);
+    expect(uncharged.get(landing)).toBe(1);
+  });
+
+  it('should walk the recorded parent tree back to the start', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const start = new RoomPosition(10, 25, 'W1N1');
+    const target = new RoomPosition(20, 25, 'W1N1');
+    const options = {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }]
+    };
+
+    expect(dijkstraMultiroomDistanceMap([start], options).parents).toBeUndefined();
+
+    const { parents } = dijkstraMultiroomDistanceMap([start], { ...options, recordParents: true });
+    expect(parents).toBeDefined();
+    const path = parents!.pathToOrigin(target);
+    expect(path.length).toBe(11);
+    expect(path.get(0).isEqualTo(target)).toBe(true);
+    expect(path.get(10).isEqualTo(start)).toBe(true);
+    expect(parents!.get(start)).toBeNull();
+    path.free();
+    parents!.free();
+  });
+
+  it('should charge for turns and walk the turn distance map back to the start', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const start = new RoomPosition(10, 25, 'W1N1');
+    const target = new RoomPosition(20, 30, 'W1N1');
+    const options = {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      anyOfDestinations: [{ pos: target, range: 0 }],
+      turnPenalty: 3
+    };
+
+    const { turnDistanceMap, parents } = dijkstraMultiroomDistanceMap([start], options);
+    expect(parents).toBeUndefined();
+    expect(turnDistanceMap).toBeDefined();
+    expect(turnDistanceMap!.turnPenalty).toBe(3);
+    expect(turnDistanceMap!.get(start)).toBe(0);
+    const path = turnDistanceMap!.pathToOrigin(target);
+    expect(path.get(0).isEqualTo(target)).toBe(true);
+    expect(path.get(path.length - 1).isEqualTo(start)).toBe(true);
+    path.free();
+    turnDistanceMap!.free();
+
+    expect(() => dijkstraMultiroomDistanceMap([start], { ...options, recordParents: true })).toThrow();
+  });
 });
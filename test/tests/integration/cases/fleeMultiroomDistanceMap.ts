@@ -0,0 +1,55 @@
+import {
+  ClockworkCostMatrix,
+  astarFleeMultiroomDistanceMap,
+  dijkstraFleeMultiroomDistanceMap
+} from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+const threat = new RoomPosition(25, 25, 'W1N1');
+const start = new RoomPosition(26, 25, 'W1N1');
+
+describe('fleeMultiroomDistanceMap', () => {
+  it('should stop at the nearest tile out of range of every threat', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const { distanceMap, foundTargets } = astarFleeMultiroomDistanceMap([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      fleeFrom: [{ pos: threat, range: 5 }]
+    });
+    expect(foundTargets).toHaveLength(1);
+    expect(foundTargets[0].getRangeTo(threat)).toBe(5);
+    expect(distanceMap.get(foundTargets[0])).toBe(4);
+  });
+
+  it('should match A* with Dijkstra', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const astar = astarFleeMultiroomDistanceMap([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      fleeFrom: [{ pos: threat, range: 5 }]
+    });
+    const dijkstra = dijkstraFleeMultiroomDistanceMap([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      fleeFrom: [{ pos: threat, range: 5 }]
+    });
+    expect(dijkstra.distanceMap.get(dijkstra.foundTargets[0])).toBe(astar.distanceMap.get(astar.foundTargets[0]));
+  });
+
+  it('should stay inside the allowed rooms', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const options = {
+      costMatrixCallback: () => costMatrix,
+      fleeFrom: [{ pos: new RoomPosition(10, 25, 'W1N1'), range: 12 }]
+    };
+    const nearEdge = [new RoomPosition(2, 25, 'W1N1')];
+
+    // Crossing west into W2N1 is the quickest way out of range
+    const anywhere = dijkstraFleeMultiroomDistanceMap(nearEdge, options);
+    const restricted = dijkstraFleeMultiroomDistanceMap(nearEdge, { ...options, allowedRooms: ['W1N1'] });
+    expect(anywhere.foundTargets[0].roomName).toBe('W2N1');
+    expect(anywhere.distanceMap.get(anywhere.foundTargets[0])).toBe(3);
+    expect(restricted.foundTargets[0].roomName).toBe('W1N1');
+    expect(restricted.distanceMap.get(restricted.foundTargets[0])).toBe(12);
+  });
+});
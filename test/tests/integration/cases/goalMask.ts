@@ -0,0 +1,46 @@
+import {
+  ClockworkCostMatrix,
+  ClockworkGoalMask,
+  astarMultiroomDistanceMapToGoalMask,
+  dijkstraMultiroomDistanceMapToGoalMask
+} from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('goal mask searches', () => {
+  it('should stop at the nearest masked tile', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const goalMask = new ClockworkGoalMask();
+    const near = new RoomPosition(30, 25, 'W1N1');
+    goalMask.add(near);
+    goalMask.add(new RoomPosition(45, 25, 'W1N1'));
+    expect(goalMask.has(near)).toBe(true);
+
+    const { foundTargets } = dijkstraMultiroomDistanceMapToGoalMask([new RoomPosition(25, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      goalMask
+    });
+    expect(foundTargets).toHaveLength(1);
+    expect(foundTargets[0].isEqualTo(near)).toBe(true);
+    goalMask.free();
+  });
+
+  it('should mark every non-zero tile of a matrix', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const mask = new ClockworkCostMatrix(0);
+    const controllerRing = new RoomPosition(10, 12, 'W1N1');
+    mask.set(controllerRing.x, controllerRing.y, 1);
+    const goalMask = new ClockworkGoalMask();
+    goalMask.addRoom('W1N1', mask);
+
+    const { foundTargets } = astarMultiroomDistanceMapToGoalMask([new RoomPosition(40, 40, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      goalMask,
+      boundDestinations: [{ pos: new RoomPosition(10, 10, 'W1N1'), range: 2 }]
+    });
+    expect(foundTargets).toHaveLength(1);
+    expect(foundTargets[0].isEqualTo(controllerRing)).toBe(true);
+    goalMask.free();
+  });
+});
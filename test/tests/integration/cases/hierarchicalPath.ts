@@ -0,0 +1,24 @@
+import { ClockworkCostMatrix, ClockworkExitGraph, hierarchicalMultiroomPath } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('hierarchicalMultiroomPath', () => {
+  it('should find a path across rooms and cache their abstractions', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const exitGraph = new ClockworkExitGraph();
+    const start = new RoomPosition(25, 25, 'W1N1');
+    const goal = new RoomPosition(25, 25, 'W3N1');
+    const { path, found } = hierarchicalMultiroomPath(exitGraph, start, goal, {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 9
+    });
+    expect(found).toBe(true);
+    expect(path.get(0).isEqualTo(start)).toBe(true);
+    expect(path.get(path.length - 1).isEqualTo(goal)).toBe(true);
+    expect(exitGraph.roomCount).toBeGreaterThan(0);
+
+    exitGraph.clear();
+    expect(exitGraph.roomCount).toBe(0);
+    path.free();
+    exitGraph.free();
+  });
+});
@@ -0,0 +1,31 @@
+import { ClockworkCostMatrix, ClockworkIncrementalDijkstra, dijkstraMultiroomDistanceMap } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+const start = new RoomPosition(25, 25, 'W1N1');
+
+describe('ClockworkIncrementalDijkstra', () => {
+  it('should match a fresh search after a cost change', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const search = new ClockworkIncrementalDijkstra([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    });
+    expect(search.get(new RoomPosition(30, 25, 'W1N1'))).toBe(5);
+
+    const wall: { pos: RoomPosition; cost: number }[] = [];
+    for (let y = 10; y <= 40; y++) {
+      wall.push({ pos: new RoomPosition(27, y, 'W1N1'), cost: 255 });
+      costMatrix.set(27, y, 255);
+    }
+    expect(search.updateCosts(wall)).toBeGreaterThan(0);
+
+    const fresh = dijkstraMultiroomDistanceMap([start], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    }).distanceMap;
+    for (const pos of [new RoomPosition(30, 25, 'W1N1'), new RoomPosition(45, 45, 'W1N1')]) {
+      expect(search.get(pos)).toBe(fresh.get(pos));
+    }
+    search.free();
+  });
+});
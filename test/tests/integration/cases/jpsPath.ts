@@ -0,0 +1,35 @@
+import { ClockworkCostMatrix, jpsMultiroomPath } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('jpsMultiroomPath', () => {
+  it('should find a straight path in an empty room', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const start = new RoomPosition(5, 5, 'W1N1');
+    const goal = new RoomPosition(15, 5, 'W1N1');
+    const { path, cost, found } = jpsMultiroomPath(start, {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      destinations: [{ pos: goal, range: 0 }]
+    });
+    expect(found).toBe(true);
+    expect(cost).toBe(10);
+    expect(path.length).toBe(11);
+    expect(path.get(path.length - 1).isEqualTo(goal)).toBe(true);
+    path.free();
+  });
+
+  it('should report an empty path when no destination is reachable', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    for (let y = 0; y < 50; y++) {
+      costMatrix.set(10, y, 255);
+    }
+    const { path, found } = jpsMultiroomPath(new RoomPosition(5, 5, 'W1N1'), {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      destinations: [{ pos: new RoomPosition(15, 5, 'W1N1'), range: 0 }]
+    });
+    expect(found).toBe(false);
+    expect(path.length).toBe(0);
+    path.free();
+  });
+});
@@ -0,0 +1,44 @@
+import {
+  ClockworkCostMatrix,
+  astarMultiroomDistanceMap,
+  chooseLandmarks,
+  clearLandmarks,
+  debugLandmarkIndex,
+  setLandmarks
+} from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('landmarks', () => {
+  it('should configure and clear landmarks', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const landmark = new RoomPosition(5, 5, 'W1N1');
+    setLandmarks([landmark], { costMatrixCallback: () => costMatrix, maxRooms: 4 });
+
+    const info = debugLandmarkIndex();
+    expect(info.landmarks).toHaveLength(1);
+    expect(info.landmarks[0].isEqualTo(landmark)).toBe(true);
+    expect(info.storedRooms).toBeGreaterThan(0);
+
+    clearLandmarks();
+    expect(debugLandmarkIndex().landmarks).toHaveLength(0);
+  });
+
+  it('should not change distances found with A*', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const start = new RoomPosition(10, 10, 'W1N1');
+    const target = new RoomPosition(40, 40, 'W2N1');
+    const options = {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 4,
+      anyOfDestinations: [{ pos: target, range: 0 }]
+    };
+    const without = astarMultiroomDistanceMap([start], options).distanceMap.get(target);
+
+    const chosen = chooseLandmarks(start, 2, { costMatrixCallback: () => costMatrix, maxRooms: 4 });
+    expect(chosen.length).toBeGreaterThan(0);
+    const withLandmarks = astarMultiroomDistanceMap([start], options).distanceMap.get(target);
+    clearLandmarks();
+
+    expect(withLandmarks).toBe(without);
+  });
+});
@@ -1,4 +1,4 @@
-import { bfsMultiroomDistanceMap, ClockworkCostMatrix, DirectionOrder } from '../../../../src/index';
+import { bfsMultiroomDistanceMap, ClockworkCostMatrix, DirectionOrder, seededDirectionOrder } from '../../../../src/index';
 import { describe, expect, it } from '../../helpers';
 
 describe('bfsMultiroomFlowField', () => {
@@ -34,4 +34,35 @@ describe('bfsMultiroomFlowField', () => {
     expect(defaultFlowField.getDirections(position)).toEqual([LEFT, TOP_LEFT]);
     expect(diagonalFlowField.getDirections(position)).toEqual([TOP_LEFT, LEFT]);
   });
+
+  it('should break ties by secondary cost', () => {
+    const costMatrix = new ClockworkCostMatrix();
+    const secondaryCosts = new ClockworkCostMatrix();
+    secondaryCosts.set(26, 26, 10);
+    const flowField = distanceMapWithEmptyRoom(costMatrix).toFlowFieldWithSecondaryCost(() => secondaryCosts);
+    const monoFlowField = distanceMapWithEmptyRoom(costMatrix).toMonoFlowFieldWithSecondaryCost(() => secondaryCosts);
+    const position = new RoomPosition(27, 26, 'W1N1');
+
+    expect(flowField.getDirections(position)).toEqual([TOP_LEFT]);
+    expect(monoFlowField.get(position)).toBe(TOP_LEFT);
+  });
+
+  it('should order tied directions by a custom direction order', () => {
+    const costMatrix = new ClockworkCostMatrix();
+    const flowField = distanceMapWithEmptyRoom(costMatrix).toFlowField({
+      customDirections: [TOP_LEFT, TOP, TOP_RIGHT, RIGHT, BOTTOM_RIGHT, BOTTOM, BOTTOM_LEFT, LEFT]
+    });
+
+    expect(flowField.getDirections(new RoomPosition(27, 26, 'W1N1'))).toEqual([TOP_LEFT, LEFT]);
+  });
+
+  it('should give the same seeded direction order for the same seed', () => {
+    const order = seededDirectionOrder(42);
+
+    expect(order).toHaveLength(8);
+    expect(seededDirectionOrder(42)).toEqual(order);
+    for (const direction of [TOP, TOP_RIGHT, RIGHT, BOTTOM_RIGHT, BOTTOM, BOTTOM_LEFT, LEFT, TOP_LEFT]) {
+      expect(order).toContain(direction);
+    }
+  });
 });
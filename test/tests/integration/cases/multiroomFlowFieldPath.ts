@@ -21,4 +21,19 @@ describe('multiroomFlowFieldPath', () => {
     expect(path[path.length - 1].isEqualTo(new RoomPosition(25, 25, 'W2N2'))).toBeTruthy();
     expect(path.length).toBe(53);
   }, 15);
+
+  it('should follow the cheapest directions by secondary cost', () => {
+    const costMatrix = new ClockworkCostMatrix();
+    const secondaryCosts = new ClockworkCostMatrix();
+    secondaryCosts.set(26, 26, 10);
+    const flowField = bfsMultiroomDistanceMap([new RoomPosition(25, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    }).distanceMap.toFlowField();
+    const start = new RoomPosition(27, 26, 'W1N1');
+    const path = flowField.pathToOriginWithSecondaryCost(start, () => secondaryCosts).toArray();
+
+    expect(path.some(pos => pos.isEqualTo(new RoomPosition(26, 26, 'W1N1')))).toBe(false);
+    expect(path.some(pos => pos.isEqualTo(new RoomPosition(26, 25, 'W1N1')))).toBe(true);
+  });
 });
@@ -0,0 +1,48 @@
+import { bfsMultiroomDistanceMap, ClockworkCostMatrix } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('multiroomLazyFlowField', () => {
+  function distanceMapWithEmptyRoom(costMatrix: ClockworkCostMatrix) {
+    return bfsMultiroomDistanceMap([new RoomPosition(25, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    }).distanceMap;
+  }
+
+  it('should match the eager flow field', () => {
+    const costMatrix = new ClockworkCostMatrix();
+    const flowField = distanceMapWithEmptyRoom(costMatrix).toFlowField();
+    const lazyFlowField = distanceMapWithEmptyRoom(costMatrix).toLazyFlowField({ memoize: true });
+
+    for (const position of [
+      new RoomPosition(25, 25, 'W1N1'),
+      new RoomPosition(26, 25, 'W1N1'),
+      new RoomPosition(27, 26, 'W1N1'),
+      new RoomPosition(0, 0, 'W1N1')
+    ]) {
+      expect(lazyFlowField.getDirections(position)).toEqual(flowField.getDirections(position));
+    }
+    expect(lazyFlowField.getFirstDirection(new RoomPosition(26, 25, 'W1N1'))).toBe(LEFT);
+    flowField.free();
+    lazyFlowField.free();
+  });
+
+  it('should take the distance map over', () => {
+    const costMatrix = new ClockworkCostMatrix();
+    const distanceMap = distanceMapWithEmptyRoom(costMatrix);
+    const lazyFlowField = distanceMap.toLazyFlowField();
+
+    expect(() => distanceMap.get(new RoomPosition(25, 25, 'W1N1'))).toThrow();
+    lazyFlowField.free();
+  });
+
+  it('should prefer set directions over computed ones', () => {
+    const costMatrix = new ClockworkCostMatrix();
+    const lazyFlowField = distanceMapWithEmptyRoom(costMatrix).toLazyFlowField();
+    const position = new RoomPosition(26, 25, 'W1N1');
+
+    lazyFlowField.setDirections(position, [TOP]);
+    expect(lazyFlowField.getDirections(position)).toEqual([TOP]);
+    lazyFlowField.free();
+  });
+});
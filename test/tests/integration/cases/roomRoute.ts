@@ -0,0 +1,30 @@
+import { ClockworkCostMatrix, dijkstraMultiroomDistanceMap, findRoomRoute } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+const UNREACHABLE = 0xffffffff;
+
+describe('findRoomRoute', () => {
+  it('should find a straight route between rooms', () => {
+    expect(findRoomRoute('W1N1', 'W3N1')).toEqual(['W1N1', 'W2N1', 'W3N1']);
+  });
+
+  it('should avoid blocked rooms', () => {
+    const route = findRoomRoute('W1N1', 'W3N1', {
+      roomCallback: roomName => (roomName === 'W2N1' ? Infinity : undefined)
+    });
+    expect(route).toBeDefined();
+    expect(route!.includes('W2N1')).toBe(false);
+  });
+
+  it('should keep a search to the rooms of a route', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const allowedRooms = findRoomRoute('W1N1', 'W2N1')!;
+    const distanceMap = dijkstraMultiroomDistanceMap([new RoomPosition(25, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 4,
+      allowedRooms
+    }).distanceMap;
+    expect(distanceMap.get(new RoomPosition(25, 25, 'W2N1'))).toBeLessThan(UNREACHABLE);
+    expect(distanceMap.get(new RoomPosition(25, 25, 'W1N2'))).toBe(UNREACHABLE);
+  });
+});
@@ -0,0 +1,31 @@
+import { ClockworkCostMatrix, ClockworkReservationTable, spaceTimeAstarMultiroomPath } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('spaceTimeAstarMultiroomPath', () => {
+  it('should wait for a tile reserved by another agent', () => {
+    // A one-tile-wide corridor along y = 10
+    const costMatrix = new ClockworkCostMatrix(255);
+    for (let x = 10; x <= 20; x++) {
+      costMatrix.set(x, 10, 1);
+    }
+    const reservations = new ClockworkReservationTable();
+    expect(reservations.reserve(new RoomPosition(11, 10, 'W1N1'), 1, 1)).toBe(true);
+    expect(reservations.reservedBy(new RoomPosition(11, 10, 'W1N1'), 1)).toBe(1);
+
+    const { path, found } = spaceTimeAstarMultiroomPath(new RoomPosition(10, 10, 'W1N1'), {
+      agent: 2,
+      startTick: 0,
+      reservations,
+      window: 10,
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1,
+      destinations: [{ pos: new RoomPosition(15, 10, 'W1N1'), range: 0 }]
+    });
+    expect(found).toBe(true);
+    expect(path.get(1).isEqualTo(new RoomPosition(10, 10, 'W1N1'))).toBe(true);
+    expect(reservations.reservedBy(new RoomPosition(12, 10, 'W1N1'), 3)).toBe(2);
+
+    path.free();
+    reservations.free();
+  });
+});
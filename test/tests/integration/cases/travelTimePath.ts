@@ -0,0 +1,33 @@
+import { ClockworkCostMatrix, travelTimeMultiroomPath } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('travelTimeMultiroomPath', () => {
+  it('should take a move per tick on plains with enough move parts', () => {
+    const plains = new ClockworkCostMatrix(2);
+    const start = new RoomPosition(10, 25, 'W1N1');
+    const goal = new RoomPosition(20, 25, 'W1N1');
+    const { path, cost, found } = travelTimeMultiroomPath(start, {
+      body: { moveParts: 1, otherParts: 1 },
+      fatigueMatrixCallback: () => plains,
+      maxRooms: 1,
+      destinations: [{ pos: goal, range: 0 }]
+    });
+    expect(found).toBe(true);
+    expect(cost).toBe(10);
+    path.free();
+  });
+
+  it('should be slowed down by fatigue', () => {
+    const plains = new ClockworkCostMatrix(2);
+    const start = new RoomPosition(10, 25, 'W1N1');
+    const goal = new RoomPosition(20, 25, 'W1N1');
+    const { path, cost } = travelTimeMultiroomPath(start, {
+      body: { moveParts: 1, otherParts: 3 },
+      fatigueMatrixCallback: () => plains,
+      maxRooms: 1,
+      destinations: [{ pos: goal, range: 0 }]
+    });
+    expect(cost).toBeGreaterThan(10);
+    path.free();
+  });
+});
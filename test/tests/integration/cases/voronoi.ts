@@ -0,0 +1,23 @@
+import { ClockworkCostMatrix, voronoiMultiroomDistanceMap } from '../../../../src/index';
+import { describe, expect, it } from '../../helpers';
+
+describe('voronoiMultiroomDistanceMap', () => {
+  it('should split a room between its origins', () => {
+    const costMatrix = new ClockworkCostMatrix(1);
+    const result = voronoiMultiroomDistanceMap([new RoomPosition(10, 25, 'W1N1'), new RoomPosition(40, 25, 'W1N1')], {
+      costMatrixCallback: () => costMatrix,
+      maxRooms: 1
+    });
+    const labels = result.labels;
+    expect(labels.get(new RoomPosition(15, 25, 'W1N1'))).toBe(0);
+    expect(labels.get(new RoomPosition(35, 25, 'W1N1'))).toBe(1);
+    expect(result.distanceMap.get(new RoomPosition(35, 25, 'W1N1'))).toBe(5);
+
+    const territory = result.territory(0);
+    expect(territory.every(pos => pos.x <= 25)).toBe(true);
+    expect(result.boundaryTiles().every(pos => pos.x === 25 || pos.x === 26)).toBe(true);
+
+    labels.free();
+    result.free();
+  });
+});
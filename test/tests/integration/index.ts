@@ -5,13 +5,27 @@
  */
 
 import './cases/astarMultiroomDistanceMap';
+import './cases/astarSearch';
 import './cases/bfsMultiroomDistanceMap';
+import './cases/bidirectionalAstarPath';
 import './cases/clockworkCostMatrix';
+import './cases/compactDistanceMap';
 import './cases/dijkstraMultiroomDistanceMap';
+import './cases/fleeMultiroomDistanceMap';
 import './cases/getRange';
+import './cases/goalMask';
+import './cases/hierarchicalPath';
+import './cases/incrementalDijkstra';
+import './cases/jpsPath';
+import './cases/landmarks';
 import './cases/multiroomDistanceMapPath';
 import './cases/multiroomFlowField';
 import './cases/multiroomFlowFieldPath';
+import './cases/multiroomLazyFlowField';
 import './cases/multiroomMonoFlowField';
 import './cases/multiroomMonoFlowFieldPath';
 import './cases/portalMultiroomDistanceMap';
+import './cases/roomRoute';
+import './cases/spaceTimePath';
+import './cases/travelTimePath';
+import './cases/voronoi';